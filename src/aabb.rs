@@ -0,0 +1,65 @@
+use cgmath::Point3;
+
+/// Axis-aligned bounding box in world space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Point3<f32>,
+    pub max: Point3<f32>,
+}
+
+impl Aabb {
+    /// Box of a standing body, centered horizontally on `feet` and extending up by `height`
+    pub fn from_feet(feet: Point3<f32>, half_width: f32, height: f32) -> Self {
+        Self {
+            min: Point3::new(feet.x - half_width, feet.y, feet.z - half_width),
+            max: Point3::new(feet.x + half_width, feet.y + height, feet.z + half_width),
+        }
+    }
+
+    /// The unit cube filled by the block at `pos`
+    pub fn block(pos: [i32; 3]) -> Self {
+        let min = Point3::new(pos[0] as f32, pos[1] as f32, pos[2] as f32);
+        Self {
+            min,
+            max: Point3::new(min.x + 1.0, min.y + 1.0, min.z + 1.0),
+        }
+    }
+
+    /// True if the boxes share volume. Boxes that only touch don't intersect,
+    /// so a body resting on a block isn't considered inside it.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        self.min.x < other.max.x
+            && self.max.x > other.min.x
+            && self.min.y < other.max.y
+            && self.max.y > other.min.y
+            && self.min.z < other.max.z
+            && self.max.z > other.min.z
+    }
+}
+
+/// Whether a block placed at `pos` would embed any of the given bodies
+/// (the player, dropped items, ...)
+pub fn block_overlaps_any(pos: [i32; 3], occupants: &[Aabb]) -> bool {
+    let block = Aabb::block(pos);
+    occupants.iter().any(|occupant| occupant.intersects(&block))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placing_onto_dropped_item_cell_is_rejected() {
+        // Small item lying on top of the block at (3, 9, 5)
+        let item = Aabb {
+            min: Point3::new(3.4, 10.0, 5.4),
+            max: Point3::new(3.65, 10.25, 5.65),
+        };
+
+        assert!(block_overlaps_any([3, 10, 5], &[item]));
+        // The block it rests on and the cell above it are free
+        assert!(!block_overlaps_any([3, 9, 5], &[item]));
+        assert!(!block_overlaps_any([3, 11, 5], &[item]));
+        assert!(!block_overlaps_any([4, 10, 5], &[item]));
+    }
+}
@@ -1,4 +1,5 @@
 use crate::blocks::BlockType;
+use crate::light::DAY_SKY;
 use noise::{NoiseFn, Perlin};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -14,6 +15,7 @@ pub enum Biome {
     Tundra,
     Forest,
     Swamp,
+    Ocean,
 }
 
 /// Configuration for biome-specific terrain generation
@@ -46,8 +48,36 @@ pub struct BiomeConfig {
     pub tree_density: f64,
     /// Probability per chunk for house structure placement (0.0 = never, higher = more frequent)
     pub house_chance: f64,
+    /// Probability per structure spot of a village instead, rolled before trees and houses
+    #[serde(default)]
+    pub village_chance: f64,
+
+    // Biome transitions
+    /// Width in blocks of the band where surface blocks dither into the neighboring biome
+    #[serde(default = "default_transition_band")]
+    pub transition_band: i32,
+
+    // Atmosphere
+    /// Midday sky color (RGB 0-1); darkened at night and warmed at sunrise and sunset
+    #[serde(default = "default_sky_color")]
+    pub sky_color: [f32; 3],
+    /// Midday color distant terrain fades into toward the render distance
+    #[serde(default = "default_sky_color")]
+    pub fog_color: [f32; 3],
 }
 
+fn default_transition_band() -> i32 {
+    6
+}
+
+fn default_sky_color() -> [f32; 3] {
+    DAY_SKY
+}
+
+/// Humidity above which the selector picks ocean. High enough that only the cores of wet
+/// regions become sea, ringed by swamp, but they still span around a hundred blocks.
+const OCEAN_HUMIDITY: f64 = 0.6;
+
 /// Selects biomes based on environmental factors
 pub struct BiomeSelector {
     temperature_noise: Perlin,
@@ -70,9 +100,16 @@ impl BiomeSelector {
         let humidity = self
             .humidity_noise
             .get([world_x as f64 * 0.004, world_z as f64 * 0.004]);
+        Self::biome_for_climate(temp, humidity)
+    }
 
+    /// Biome for a temperature and humidity, both from -1 to 1
+    pub fn biome_for_climate(temperature: f64, humidity: f64) -> Biome {
         // 2D biome grid based on temperature and humidity
-        match (temp, humidity) {
+        match (temperature, humidity) {
+            // Open sea in the wettest regions, whatever the temperature
+            (_, h) if h > OCEAN_HUMIDITY => Biome::Ocean,
+
             // Very cold regions
             (t, h) if t < -0.4 && h < 0.0 => Biome::Tundra,
             (t, _) if t < -0.2 => Biome::Mountain,
@@ -93,6 +130,16 @@ impl BiomeSelector {
 }
 
 impl Biome {
+    pub const ALL: [Biome; 7] = [
+        Biome::Plains,
+        Biome::Desert,
+        Biome::Mountain,
+        Biome::Tundra,
+        Biome::Forest,
+        Biome::Swamp,
+        Biome::Ocean,
+    ];
+
     /// Get configuration parameters for this biome
     pub fn get_config(&self) -> BiomeConfig {
         match self {
@@ -107,6 +154,10 @@ impl Biome {
                 humidity: 0.0,
                 tree_density: 0.005, // Sparse trees
                 house_chance: 0.001, // Rare settlements
+                village_chance: 0.0,
+                transition_band: 6,
+                sky_color: DAY_SKY,
+                fog_color: DAY_SKY,
             },
 
             Biome::Desert => BiomeConfig {
@@ -120,6 +171,10 @@ impl Biome {
                 humidity: -0.8,
                 tree_density: 0.0001, // Almost no trees
                 house_chance: 0.002,  // Occasional oasis settlements
+                village_chance: 0.01,
+                transition_band: 6,
+                sky_color: [0.85, 0.78, 0.6],
+                fog_color: [0.88, 0.8, 0.64],
             },
 
             Biome::Plains => BiomeConfig {
@@ -133,6 +188,10 @@ impl Biome {
                 humidity: 0.0,
                 tree_density: 0.015, // Moderate tree coverage
                 house_chance: 0.008, // Common settlements
+                village_chance: 0.05,
+                transition_band: 6,
+                sky_color: DAY_SKY,
+                fog_color: DAY_SKY,
             },
 
             Biome::Forest => BiomeConfig {
@@ -146,6 +205,10 @@ impl Biome {
                 humidity: 0.2,
                 tree_density: 0.08,  // Dense forest
                 house_chance: 0.003, // Rare clearings
+                village_chance: 0.01,
+                transition_band: 6,
+                sky_color: DAY_SKY,
+                fog_color: DAY_SKY,
             },
 
             Biome::Tundra => BiomeConfig {
@@ -159,6 +222,10 @@ impl Biome {
                 humidity: -0.2,
                 tree_density: 0.002,  // Very sparse trees
                 house_chance: 0.0005, // Extremely rare settlements
+                village_chance: 0.0,
+                transition_band: 6,
+                sky_color: [0.72, 0.85, 0.95],
+                fog_color: [0.82, 0.9, 0.97],
             },
 
             Biome::Swamp => BiomeConfig {
@@ -172,6 +239,27 @@ impl Biome {
                 humidity: 0.8,
                 tree_density: 0.04,  // Moderate tree coverage
                 house_chance: 0.001, // Rare stilted settlements
+                village_chance: 0.0,
+                transition_band: 6,
+                sky_color: [0.46, 0.55, 0.42],
+                fog_color: [0.4, 0.48, 0.34],
+            },
+
+            Biome::Ocean => BiomeConfig {
+                base_height: 1, // Floor well below sea level so the columns fill with water
+                frequency: 0.01,
+                amplitude: 2.0, // Gentle swells in the seafloor
+                surface_block: BlockType::Sand,
+                subsurface_block: BlockType::Sand,
+                stone_block: BlockType::Stone,
+                temperature: 0.0,
+                humidity: 1.0,
+                tree_density: 0.0,
+                house_chance: 0.0,
+                village_chance: 0.0,
+                transition_band: 6,
+                sky_color: DAY_SKY,
+                fog_color: DAY_SKY,
             },
         }
     }
@@ -185,6 +273,7 @@ impl Biome {
             Biome::Tundra => "Tundra",
             Biome::Forest => "Forest",
             Biome::Swamp => "Swamp",
+            Biome::Ocean => "Ocean",
         }
     }
 }
@@ -194,6 +283,12 @@ pub struct BiomeManager {
     configs: HashMap<Biome, BiomeConfig>,
 }
 
+impl Default for BiomeManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BiomeManager {
     /// Create a new BiomeManager with default configs
     pub fn new() -> Self {
@@ -208,14 +303,7 @@ impl BiomeManager {
         let configs: HashMap<Biome, BiomeConfig> = toml::from_str(&content)?;
 
         // Ensure all biomes are present
-        for biome in [
-            Biome::Plains,
-            Biome::Desert,
-            Biome::Mountain,
-            Biome::Tundra,
-            Biome::Forest,
-            Biome::Swamp,
-        ] {
+        for biome in Biome::ALL {
             if !configs.contains_key(&biome) {
                 return Err(format!("Missing configuration for biome: {:?}", biome).into());
             }
@@ -233,14 +321,7 @@ impl BiomeManager {
         let new_configs: HashMap<Biome, BiomeConfig> = toml::from_str(&content)?;
 
         // Ensure all biomes are present
-        for biome in [
-            Biome::Plains,
-            Biome::Desert,
-            Biome::Mountain,
-            Biome::Tundra,
-            Biome::Forest,
-            Biome::Swamp,
-        ] {
+        for biome in Biome::ALL {
             if !new_configs.contains_key(&biome) {
                 return Err(format!("Missing configuration for biome: {:?}", biome).into());
             }
@@ -263,14 +344,7 @@ impl BiomeManager {
     fn load_default_configs() -> HashMap<Biome, BiomeConfig> {
         let mut configs = HashMap::new();
 
-        for biome in [
-            Biome::Plains,
-            Biome::Desert,
-            Biome::Mountain,
-            Biome::Tundra,
-            Biome::Forest,
-            Biome::Swamp,
-        ] {
+        for biome in Biome::ALL {
             configs.insert(biome, biome.get_config());
         }
 
@@ -0,0 +1,217 @@
+use crate::blocks::{BlockRegistry, BlockType};
+use crate::chunk::{ChunkBlocks, ChunkPos, CHUNK_SIZE, WORLD_HEIGHT};
+use std::collections::HashMap;
+
+/// Light level of a block with an emission of 1.0. Each step away from it loses one level,
+/// so light reaches at most this many blocks minus one.
+pub const MAX_LIGHT_LEVEL: u8 = 15;
+
+/// A light-emitting block at a world position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LightSource {
+    pub position: [i32; 3],
+    pub level: u8,
+}
+
+/// Light level a block with the given emission (0.0-1.0) gives off
+pub fn light_level(emission: f32) -> u8 {
+    (emission.clamp(0.0, 1.0) * MAX_LIGHT_LEVEL as f32).round() as u8
+}
+
+/// Block light of one chunk, a level from 0 to `MAX_LIGHT_LEVEL` per voxel, kept next to
+/// its `ChunkBlocks` together with the chunk's own light sources
+#[derive(Debug, Clone)]
+pub struct BlockLight {
+    levels: Box<[u8]>,
+    sources: Vec<LightSource>,
+}
+
+impl BlockLight {
+    /// Unlit chunk light holding the light sources found in `chunk_blocks`
+    pub fn new(chunk_pos: ChunkPos, chunk_blocks: &ChunkBlocks, registry: &BlockRegistry) -> Self {
+        let mut block_light = Self {
+            levels: vec![0; CHUNK_SIZE * CHUNK_SIZE * WORLD_HEIGHT].into_boxed_slice(),
+            sources: Vec::new(),
+        };
+        block_light.refresh_sources(chunk_pos, chunk_blocks, registry);
+        block_light
+    }
+
+    /// Same layout as `ChunkBlocks`, columns contiguous
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        debug_assert!(x < CHUNK_SIZE && y < WORLD_HEIGHT && z < CHUNK_SIZE);
+        (x * CHUNK_SIZE + z) * WORLD_HEIGHT + y
+    }
+
+    /// Light level at chunk-local coordinates
+    pub fn get(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.levels[Self::index(x, y, z)]
+    }
+
+    /// Light-emitting blocks in this chunk
+    pub fn sources(&self) -> &[LightSource] {
+        &self.sources
+    }
+
+    /// Look for light sources in `chunk_blocks` again after it was edited. Levels are
+    /// left alone until the next `propagate`.
+    pub fn refresh_sources(
+        &mut self,
+        chunk_pos: ChunkPos,
+        chunk_blocks: &ChunkBlocks,
+        registry: &BlockRegistry,
+    ) {
+        self.sources.clear();
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for y in 0..WORLD_HEIGHT {
+                    let block_type = chunk_blocks.get(x, y, z);
+                    if block_type == BlockType::Air {
+                        continue;
+                    }
+                    let level = light_level(registry.emission(block_type));
+                    if level > 0 {
+                        self.sources.push(LightSource {
+                            position: [
+                                chunk_pos.x * CHUNK_SIZE as i32 + x as i32,
+                                y as i32,
+                                chunk_pos.z * CHUNK_SIZE as i32 + z as i32,
+                            ],
+                            level,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Recompute this chunk's levels by flood-filling light from `sources` (those of this
+    /// chunk and any others close enough to reach it). Light loses a level per step and
+    /// only passes through air and transparent blocks; `block_at` looks up world
+    /// positions, with `None` (not loaded) stopping the light.
+    /// Returns whether any level in the chunk changed.
+    pub fn propagate(
+        &mut self,
+        chunk_pos: ChunkPos,
+        sources: &[LightSource],
+        registry: &BlockRegistry,
+        block_at: impl Fn(i32, i32, i32) -> Option<BlockType>,
+    ) -> bool {
+        if sources.is_empty() && self.levels.iter().all(|&level| level == 0) {
+            return false;
+        }
+
+        let reached = flood_fill(sources, |x, y, z| {
+            block_at(x, y, z).is_some_and(|block_type| {
+                block_type == BlockType::Air || registry.is_transparent(block_type)
+            })
+        });
+
+        let mut levels = vec![0; self.levels.len()].into_boxed_slice();
+        let origin_x = chunk_pos.x * CHUNK_SIZE as i32;
+        let origin_z = chunk_pos.z * CHUNK_SIZE as i32;
+        for ([x, y, z], level) in reached {
+            let (local_x, local_z) = (x - origin_x, z - origin_z);
+            if (0..CHUNK_SIZE as i32).contains(&local_x)
+                && (0..CHUNK_SIZE as i32).contains(&local_z)
+            {
+                levels[Self::index(local_x as usize, y as usize, local_z as usize)] = level;
+            }
+        }
+
+        let changed = levels != self.levels;
+        self.levels = levels;
+        changed
+    }
+}
+
+/// Breadth-first spread of light from every source at once, brightest first so each
+/// position is settled at its highest level before spreading on from it.
+/// Returns the level of every position reached, sources included.
+fn flood_fill(
+    sources: &[LightSource],
+    passes_light: impl Fn(i32, i32, i32) -> bool,
+) -> HashMap<[i32; 3], u8> {
+    let mut reached: HashMap<[i32; 3], u8> = HashMap::new();
+    // One queue per level
+    let mut queues: Vec<Vec<[i32; 3]>> = vec![Vec::new(); MAX_LIGHT_LEVEL as usize + 1];
+    for source in sources {
+        let level = source.level.min(MAX_LIGHT_LEVEL);
+        if reached
+            .get(&source.position)
+            .is_none_or(|&known| known < level)
+        {
+            reached.insert(source.position, level);
+            queues[level as usize].push(source.position);
+        }
+    }
+
+    for level in (2..=MAX_LIGHT_LEVEL).rev() {
+        let queue = std::mem::take(&mut queues[level as usize]);
+        for [x, y, z] in queue {
+            // Already brightened by a stronger source
+            if reached[&[x, y, z]] != level {
+                continue;
+            }
+            for [dx, dy, dz] in [
+                [1, 0, 0],
+                [-1, 0, 0],
+                [0, 1, 0],
+                [0, -1, 0],
+                [0, 0, 1],
+                [0, 0, -1],
+            ] {
+                let neighbor = [x + dx, y + dy, z + dz];
+                if !(0..WORLD_HEIGHT as i32).contains(&neighbor[1])
+                    || reached
+                        .get(&neighbor)
+                        .is_some_and(|&known| known >= level - 1)
+                    || !passes_light(neighbor[0], neighbor[1], neighbor[2])
+                {
+                    continue;
+                }
+                reached.insert(neighbor, level - 1);
+                queues[level as usize - 1].push(neighbor);
+            }
+        }
+    }
+
+    reached
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_light_fades_with_distance_and_bends_around_walls() {
+        // A wall at x = 2 from y = 0 to 14 across z = -20..20, with a torch in front of it
+        let is_wall = |x: i32, y: i32, z: i32| x == 2 && y <= 14 && (-20..20).contains(&z);
+        let torch = LightSource {
+            position: [0, 10, 0],
+            level: MAX_LIGHT_LEVEL,
+        };
+        let reached = flood_fill(&[torch], |x, y, z| !is_wall(x, y, z));
+        let level = |position: [i32; 3]| reached.get(&position).copied().unwrap_or(0);
+
+        assert_eq!(level([0, 10, 0]), 15);
+        assert_eq!(level([1, 10, 0]), 14);
+        assert_eq!(level([-5, 10, 0]), 10);
+        assert_eq!(level([0, 10, -14]), 1);
+        assert_eq!(level([0, 10, -15]), 0);
+        assert_eq!(level([2, 10, 0]), 0);
+
+        // Behind the wall the light has to go over the top: up 5, across 3, down 5
+        assert_eq!(level([3, 15, 0]), 15 - 8);
+        assert_eq!(level([3, 10, 0]), 15 - 13);
+
+        // A second, weaker torch doesn't dim what the first one lights
+        let candle = LightSource {
+            position: [-3, 10, 0],
+            level: 5,
+        };
+        let both = flood_fill(&[candle, torch], |x, y, z| !is_wall(x, y, z));
+        assert_eq!(both[&[-3, 10, 0]], 12);
+        assert_eq!(both[&[-5, 10, 0]], 10);
+    }
+}
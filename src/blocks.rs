@@ -1,3 +1,4 @@
+use crate::tools::ToolKind;
 use crate::voxel::FaceTextures;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -17,6 +18,10 @@ pub enum BlockType {
     Planks,
     Cobblestone,
     Glass,
+    Coal,
+    Iron,
+    Gold,
+    Glowstone,
 }
 
 /// Texture atlas indices for different block textures
@@ -36,6 +41,58 @@ pub enum TextureId {
     Planks = 11,
     Cobblestone = 12,
     Glass = 13,
+    CoalOre = 14,
+    IronOre = 15,
+    GoldOre = 16,
+    Glowstone = 17,
+}
+
+impl TextureId {
+    /// Every texture, in atlas order
+    pub const ALL: [TextureId; 18] = [
+        TextureId::Stone,
+        TextureId::Dirt,
+        TextureId::GrassTop,
+        TextureId::GrassSide,
+        TextureId::Sand,
+        TextureId::Water,
+        TextureId::WoodTop,
+        TextureId::WoodSide,
+        TextureId::Leaves,
+        TextureId::Snow,
+        TextureId::Bedrock,
+        TextureId::Planks,
+        TextureId::Cobblestone,
+        TextureId::Glass,
+        TextureId::CoalOre,
+        TextureId::IronOre,
+        TextureId::GoldOre,
+        TextureId::Glowstone,
+    ];
+}
+
+/// Name of the file in `textures/` (without extension) a texture is loaded from
+pub fn texture_id_filename(texture_id: TextureId) -> &'static str {
+    match texture_id {
+        TextureId::Stone => "stone",
+        TextureId::Dirt => "dirt",
+        TextureId::GrassTop => "grass_top",
+        TextureId::GrassSide => "grass_side",
+        TextureId::Sand => "sand",
+        TextureId::Water => "water",
+        TextureId::WoodTop => "wood_top",
+        TextureId::WoodSide => "wood_side",
+        TextureId::Leaves => "leaves",
+        TextureId::Snow => "snow",
+        TextureId::Bedrock => "bedrock",
+        TextureId::Planks => "planks",
+        TextureId::Cobblestone => "cobblestone",
+        TextureId::Glass => "glass",
+        TextureId::CoalOre => "coal_ore",
+        TextureId::IronOre => "iron_ore",
+        TextureId::GoldOre => "gold_ore",
+        TextureId::Glowstone => "glowstone",
+    }
 }
 
 /// Material properties for a block type
@@ -47,6 +104,12 @@ pub struct BlockMaterial {
     pub is_solid: bool,
     pub is_transparent: bool,
     pub emission: f32, // For glowing blocks
+    /// Randomly rotate the texture per block to hide tiling (off for directional textures)
+    pub random_rotation: bool,
+    /// Tool kind that mines this block faster, if any
+    pub preferred_tool: Option<ToolKind>,
+    /// Drop down while there is air beneath, like sand
+    pub falls: bool,
 }
 
 /// Registry for all block types and their properties
@@ -54,6 +117,12 @@ pub struct BlockRegistry {
     materials: HashMap<BlockType, BlockMaterial>,
 }
 
+impl Default for BlockRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BlockRegistry {
     pub fn new() -> Self {
         let mut registry = Self {
@@ -83,6 +152,53 @@ impl BlockRegistry {
             .unwrap_or(FaceTextures::all_same(TextureId::Stone as u32)) // Stone for missing blocks
     }
 
+    /// Check if a block's texture should be randomly rotated per position
+    pub fn has_random_rotation(&self, block_type: BlockType) -> bool {
+        self.materials
+            .get(&block_type)
+            .map(|m| m.random_rotation)
+            .unwrap_or(false)
+    }
+
+    /// How long a block takes to break by hand, relative to other blocks
+    pub fn hardness(&self, block_type: BlockType) -> f32 {
+        self.materials
+            .get(&block_type)
+            .map(|m| m.hardness)
+            .unwrap_or(0.0)
+    }
+
+    /// Tool kind suited to mining a block, if any
+    pub fn preferred_tool(&self, block_type: BlockType) -> Option<ToolKind> {
+        self.materials
+            .get(&block_type)
+            .and_then(|m| m.preferred_tool)
+    }
+
+    /// Check if blocks behind this one can be seen through it
+    pub fn is_transparent(&self, block_type: BlockType) -> bool {
+        self.materials
+            .get(&block_type)
+            .map(|m| m.is_transparent)
+            .unwrap_or(false)
+    }
+
+    /// Check if a block drops down when there is air beneath it
+    pub fn falls(&self, block_type: BlockType) -> bool {
+        self.materials
+            .get(&block_type)
+            .map(|m| m.falls)
+            .unwrap_or(false)
+    }
+
+    /// Light a block gives off, 0.0-1.0. Its faces are lit at least this brightly.
+    pub fn emission(&self, block_type: BlockType) -> f32 {
+        self.materials
+            .get(&block_type)
+            .map(|m| m.emission)
+            .unwrap_or(0.0)
+    }
+
     /// Check if a block is solid
     pub fn is_solid(&self, block_type: BlockType) -> bool {
         self.materials
@@ -103,6 +219,9 @@ impl BlockRegistry {
                 is_solid: false,
                 is_transparent: true,
                 emission: 0.0,
+                random_rotation: false,
+                preferred_tool: None,
+                falls: false,
             },
         );
 
@@ -116,6 +235,9 @@ impl BlockRegistry {
                 is_solid: true,
                 is_transparent: false,
                 emission: 0.0,
+                random_rotation: true,
+                preferred_tool: Some(ToolKind::Pickaxe),
+                falls: false,
             },
         );
 
@@ -129,6 +251,9 @@ impl BlockRegistry {
                 is_solid: true,
                 is_transparent: false,
                 emission: 0.0,
+                random_rotation: true,
+                preferred_tool: Some(ToolKind::Shovel),
+                falls: false,
             },
         );
 
@@ -149,6 +274,9 @@ impl BlockRegistry {
                 is_solid: true,
                 is_transparent: false,
                 emission: 0.0,
+                random_rotation: false,
+                preferred_tool: Some(ToolKind::Shovel),
+                falls: false,
             },
         );
 
@@ -162,6 +290,9 @@ impl BlockRegistry {
                 is_solid: true,
                 is_transparent: false,
                 emission: 0.0,
+                random_rotation: true,
+                preferred_tool: Some(ToolKind::Shovel),
+                falls: true,
             },
         );
 
@@ -175,6 +306,9 @@ impl BlockRegistry {
                 is_solid: false,
                 is_transparent: true,
                 emission: 0.0,
+                random_rotation: false,
+                preferred_tool: None,
+                falls: false,
             },
         );
 
@@ -195,6 +329,9 @@ impl BlockRegistry {
                 is_solid: true,
                 is_transparent: false,
                 emission: 0.0,
+                random_rotation: false,
+                preferred_tool: Some(ToolKind::Axe),
+                falls: false,
             },
         );
 
@@ -208,6 +345,9 @@ impl BlockRegistry {
                 is_solid: true,
                 is_transparent: true,
                 emission: 0.0,
+                random_rotation: false,
+                preferred_tool: None,
+                falls: false,
             },
         );
 
@@ -221,6 +361,9 @@ impl BlockRegistry {
                 is_solid: true,
                 is_transparent: false,
                 emission: 0.0,
+                random_rotation: false,
+                preferred_tool: Some(ToolKind::Shovel),
+                falls: false,
             },
         );
 
@@ -234,6 +377,9 @@ impl BlockRegistry {
                 is_solid: true,
                 is_transparent: false,
                 emission: 0.0,
+                random_rotation: false,
+                preferred_tool: Some(ToolKind::Axe),
+                falls: false,
             },
         );
 
@@ -247,6 +393,9 @@ impl BlockRegistry {
                 is_solid: true,
                 is_transparent: false,
                 emission: 0.0,
+                random_rotation: false,
+                preferred_tool: Some(ToolKind::Pickaxe),
+                falls: false,
             },
         );
 
@@ -260,24 +409,82 @@ impl BlockRegistry {
                 is_solid: true,
                 is_transparent: true,
                 emission: 0.0,
+                random_rotation: false,
+                preferred_tool: None,
+                falls: false,
             },
         );
+
+        // Glowstone - glows at full brightness, even at night
+        self.register(
+            BlockType::Glowstone,
+            BlockMaterial {
+                name: "Glowstone",
+                textures: FaceTextures::all_same(TextureId::Glowstone as u32),
+                hardness: 0.3,
+                is_solid: true,
+                is_transparent: false,
+                emission: 1.0,
+                random_rotation: true,
+                preferred_tool: None,
+                falls: false,
+            },
+        );
+
+        // Ores - found in clusters in the stone layer
+        for (block_type, name, texture_id, hardness) in [
+            (BlockType::Coal, "Coal Ore", TextureId::CoalOre, 3.0),
+            (BlockType::Iron, "Iron Ore", TextureId::IronOre, 3.5),
+            (BlockType::Gold, "Gold Ore", TextureId::GoldOre, 3.5),
+        ] {
+            self.register(
+                block_type,
+                BlockMaterial {
+                    name,
+                    textures: FaceTextures::all_same(texture_id as u32),
+                    hardness,
+                    is_solid: true,
+                    is_transparent: false,
+                    emission: 0.0,
+                    random_rotation: true,
+                    preferred_tool: Some(ToolKind::Pickaxe),
+                    falls: false,
+                },
+            );
+        }
     }
 }
 
-use std::sync::OnceLock;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-/// Global block registry instance
-static BLOCK_REGISTRY: OnceLock<BlockRegistry> = OnceLock::new();
-
-/// Initialize the global block registry
-pub fn init_block_registry() {
-    BLOCK_REGISTRY.get_or_init(BlockRegistry::new);
-}
+    #[test]
+    fn test_registries_are_isolated() {
+        let mut custom = BlockRegistry::new();
+        custom.register(
+            BlockType::Stone,
+            BlockMaterial {
+                name: "Marble",
+                textures: FaceTextures::all_same(TextureId::Snow as u32),
+                hardness: 2.0,
+                is_solid: true,
+                is_transparent: false,
+                emission: 0.0,
+                random_rotation: false,
+                preferred_tool: None,
+                falls: false,
+            },
+        );
+        let default = BlockRegistry::new();
 
-/// Get reference to the global block registry
-pub fn get_block_registry() -> &'static BlockRegistry {
-    BLOCK_REGISTRY
-        .get()
-        .expect("Block registry not initialized")
+        assert_eq!(
+            custom.get_textures(BlockType::Stone).top,
+            TextureId::Snow as u32
+        );
+        assert_eq!(
+            default.get_textures(BlockType::Stone).top,
+            TextureId::Stone as u32
+        );
+    }
 }
@@ -1,9 +1,12 @@
+use crate::key_bindings::{Action, KeyBindings};
+use crate::voxel_query::{find_ground_level, VoxelQuery};
 use bytemuck::{Pod, Zeroable};
 use cgmath::*;
-use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
 use wgpu::util::DeviceExt;
 use winit::event::*;
-use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::keyboard::PhysicalKey;
 
 #[rustfmt::skip]
 pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
@@ -13,6 +16,28 @@ pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
     0.0, 0.0, 0.5, 1.0,
 );
 
+/// Two Space presses closer together than this toggle flying
+const DOUBLE_TAP_WINDOW: Duration = Duration::from_millis(300);
+/// Horizontal speed multiplier while flying
+const FLY_SPEED_MULTIPLIER: f32 = 2.5;
+/// How far behind the player's eye the third-person camera sits
+const THIRD_PERSON_DISTANCE: f32 = 4.0;
+/// Gap kept between a pulled-in third-person camera and the block behind it
+const THIRD_PERSON_WALL_MARGIN: f32 = 0.2;
+/// Eye height a player who fell out of the world is put back at when the ground
+/// below isn't loaded, same as the initial spawn
+const FALLBACK_SPAWN_HEIGHT: f32 = 64.0;
+
+/// Where the view is rendered from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewMode {
+    /// From the player's eye
+    #[default]
+    First,
+    /// From behind the player, looking the same way
+    Third,
+}
+
 pub struct Camera {
     pub position: Point3<f32>,
     yaw: Rad<f32>,
@@ -21,6 +46,8 @@ pub struct Camera {
     fovy: Rad<f32>,
     znear: f32,
     zfar: f32,
+    // Distance the rendered eye is pulled back along the view direction (third person)
+    eye_offset: f32,
 }
 
 impl Camera {
@@ -33,38 +60,117 @@ impl Camera {
             fovy: Rad(45.0_f32.to_radians()),
             znear: 0.1,
             zfar: 100.0,
+            eye_offset: 0.0,
         }
     }
 
-    pub fn calc_matrix(&self) -> Matrix4<f32> {
+    /// Unit vector the camera looks along
+    fn forward(&self) -> Vector3<f32> {
         let (sin_pitch, cos_pitch) = self.pitch.0.sin_cos();
         let (sin_yaw, cos_yaw) = self.yaw.0.sin_cos();
+        Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw)
+    }
+
+    /// Where the view is rendered from: the player's eye, or behind it in third person
+    pub fn eye_position(&self) -> Point3<f32> {
+        self.position - self.forward() * self.eye_offset
+    }
+
+    /// Distance behind the player's eye for the third-person camera, pulled in so it
+    /// stays in front of any block in the way
+    fn third_person_offset(&self, world: &dyn VoxelQuery) -> f32 {
+        let ray = crate::raycast::Ray::new(self.position, -self.forward());
+        match crate::raycast::raycast_blocks(ray, THIRD_PERSON_DISTANCE, world) {
+            Some(hit) => (hit.distance - THIRD_PERSON_WALL_MARGIN).max(0.0),
+            None => THIRD_PERSON_DISTANCE,
+        }
+    }
 
-        let target =
-            self.position + Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw);
+    pub fn calc_matrix(&self) -> Matrix4<f32> {
+        let eye = self.eye_position();
+        let target = eye + self.forward();
 
-        let view = Matrix4::look_at_rh(self.position, target, Vector3::unit_y());
+        let view = Matrix4::look_at_rh(eye, target, Vector3::unit_y());
         let proj = perspective(self.fovy, self.aspect, self.znear, self.zfar);
 
         OPENGL_TO_WGPU_MATRIX * proj * view
     }
+
+    pub fn depth_params(&self) -> DepthParams {
+        DepthParams {
+            near: self.znear,
+            far: self.zfar,
+        }
+    }
+
+    /// Whether the eye the view is rendered from is inside a water block
+    pub fn is_underwater(&self, world: &dyn VoxelQuery) -> bool {
+        let eye = self.eye_position();
+        let [x, y, z] = [eye.x, eye.y, eye.z].map(|c| c.floor());
+        world.get_block(x as i32, y as i32, z as i32) == crate::blocks::BlockType::Water
+    }
+}
+
+/// What a shader needs to turn a depth buffer value back into view distance.
+///
+/// `perspective` produces OpenGL's -1..1 clip Z and `OPENGL_TO_WGPU_MATRIX` remaps it to
+/// wgpu's 0..1, which works out to `depth = a + b / distance` (see `projection_constants`).
+/// Depth is far from linear: most of the 0..1 range is spent close to the near plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthParams {
+    pub near: f32,
+    pub far: f32,
+}
+
+impl DepthParams {
+    /// `(a, b)` in `depth = a + b / distance`, with distance along the view direction
+    pub fn projection_constants(&self) -> (f32, f32) {
+        let range = self.far - self.near;
+        (self.far / range, -self.far * self.near / range)
+    }
+
+    /// Distance along the view direction for a 0..1 depth value.
+    /// Mirrors `linearize_depth` in shader.wgsl.
+    pub fn linearize_depth(&self, depth: f32) -> f32 {
+        self.near * self.far / (self.far - depth * (self.far - self.near))
+    }
+}
+
+/// Serializable snapshot of where the player is and where they are looking
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraState {
+    pub position: [f32; 3],
+    /// Yaw in radians
+    pub yaw: f32,
+    /// Pitch in radians
+    pub pitch: f32,
 }
 
 #[repr(C)]
 #[derive(Debug, Copy, Clone, Pod, Zeroable)]
 struct CameraUniform {
     view_proj: [[f32; 4]; 4],
+    /// Seconds since the game started, for shader animations like the water waves
+    time: f32,
+    _padding: [f32; 3],
+    /// Player position (w unused), which fog distances are measured from
+    position: [f32; 4],
 }
 
 impl CameraUniform {
     fn new() -> Self {
         Self {
             view_proj: Matrix4::identity().into(),
+            time: 0.0,
+            _padding: [0.0; 3],
+            position: [0.0; 4],
         }
     }
 
     fn update_view_proj(&mut self, camera: &Camera) {
         self.view_proj = camera.calc_matrix().into();
+        let position = camera.position;
+        self.position = [position.x, position.y, position.z, 1.0];
     }
 }
 
@@ -82,6 +188,18 @@ pub struct CameraController {
     sensitivity: f32,
     left_mouse_pressed: bool,
     right_mouse_pressed: bool,
+    left_mouse_held: bool,
+    is_descend_pressed: bool,
+    // Fly through blocks without gravity, e.g. for photo mode
+    free_fly: bool,
+    // Player flight: no gravity, Space/Shift rise and sink, blocks still collide
+    fly_mode: bool,
+    // Let fly mode pass through blocks
+    noclip: bool,
+    // When Space was last tapped, for double-tap detection
+    last_jump_tap: Option<Instant>,
+    // See `set_auto_jump`
+    auto_jump: bool,
     // Physics properties
     velocity_y: f32,
     is_grounded: bool,
@@ -107,6 +225,13 @@ impl CameraController {
             sensitivity,
             left_mouse_pressed: false,
             right_mouse_pressed: false,
+            left_mouse_held: false,
+            is_descend_pressed: false,
+            free_fly: false,
+            fly_mode: false,
+            noclip: false,
+            last_jump_tap: None,
+            auto_jump: false,
             velocity_y: 0.0,
             is_grounded: false,
             jump_speed: 8.0,
@@ -116,54 +241,78 @@ impl CameraController {
         }
     }
 
-    pub fn process_window_events(&mut self, event: &WindowEvent) -> bool {
+    pub fn process_window_events(
+        &mut self,
+        event: &WindowEvent,
+        key_bindings: &KeyBindings,
+    ) -> bool {
         match event {
             WindowEvent::KeyboardInput {
                 event:
                     KeyEvent {
                         state,
                         physical_key: PhysicalKey::Code(keycode),
+                        repeat,
                         ..
                     },
                 ..
             } => {
                 let is_pressed = *state == ElementState::Pressed;
-                match keycode {
-                    KeyCode::KeyW | KeyCode::ArrowUp => {
+                match key_bindings.action(*keycode) {
+                    Some(Action::Forward) => {
                         self.is_forward_pressed = is_pressed;
                         true
                     }
-                    KeyCode::KeyA | KeyCode::ArrowLeft => {
+                    Some(Action::Left) => {
                         self.is_left_pressed = is_pressed;
                         true
                     }
-                    KeyCode::KeyS | KeyCode::ArrowDown => {
+                    Some(Action::Back) => {
                         self.is_backward_pressed = is_pressed;
                         true
                     }
-                    KeyCode::KeyD | KeyCode::ArrowRight => {
+                    Some(Action::Right) => {
                         self.is_right_pressed = is_pressed;
                         true
                     }
-                    KeyCode::Space => {
+                    Some(Action::Jump) => {
                         // Only register jump on key press, not hold
                         if is_pressed && !self.is_jump_pressed {
                             self.is_jump_pressed = true;
                         } else if !is_pressed {
                             self.is_jump_pressed = false;
                         }
+
+                        // Double-tapping jump toggles flying (held-key repeats don't count)
+                        if is_pressed && !*repeat && !self.free_fly {
+                            let now = Instant::now();
+                            let double_tap = self
+                                .last_jump_tap
+                                .is_some_and(|last| now.duration_since(last) < DOUBLE_TAP_WINDOW);
+                            if double_tap {
+                                self.toggle_fly();
+                                self.last_jump_tap = None;
+                            } else {
+                                self.last_jump_tap = Some(now);
+                            }
+                        }
                         true
                     }
-                    KeyCode::ControlLeft | KeyCode::ControlRight => {
+                    Some(Action::Run) => {
                         self.is_running = is_pressed;
                         true
                     }
+                    Some(Action::Descend) => {
+                        self.is_descend_pressed = is_pressed;
+                        true
+                    }
                     _ => false,
                 }
             }
             WindowEvent::MouseInput { state, button, .. } => match button {
                 MouseButton::Left => {
                     self.left_mouse_pressed = *state == ElementState::Pressed;
+                    self.left_mouse_held = *state == ElementState::Pressed;
                     true
                 }
                 MouseButton::Right => {
@@ -187,14 +336,16 @@ impl CameraController {
         }
     }
 
-    pub fn update_camera(
-        &mut self,
-        camera: &mut Camera,
-        dt: Duration,
-        world: &crate::world::World,
-    ) {
+    pub fn update_camera(&mut self, camera: &mut Camera, dt: Duration, world: &dyn VoxelQuery) {
         let dt = dt.as_secs_f32();
 
+        // Fell out of the world, e.g. through terrain that wasn't loaded yet
+        if camera.position.y < 0.0 && !self.free_fly {
+            let spawn = self.safe_spawn(camera.position.x, camera.position.z, world);
+            self.teleport(camera, spawn);
+            return;
+        }
+
         // Handle mouse look
         camera.yaw += Rad(self.mouse_dx * self.sensitivity * dt);
         camera.pitch -= Rad(self.mouse_dy * self.sensitivity * dt);
@@ -235,28 +386,89 @@ impl CameraController {
             horizontal_movement = horizontal_movement.normalize() * current_speed * dt;
         }
 
+        if self.free_fly {
+            // Space rises, Shift sinks; no gravity or collision
+            let mut vertical = 0.0;
+            if self.is_jump_pressed {
+                vertical += 1.0;
+            }
+            if self.is_descend_pressed {
+                vertical -= 1.0;
+            }
+            let current_speed = if self.is_running {
+                self.run_speed
+            } else {
+                self.speed
+            };
+            camera.position += horizontal_movement;
+            camera.position.y += vertical * current_speed * dt;
+            return;
+        }
+
+        if self.fly_mode {
+            // Space rises, Shift sinks, and nothing pulls the player down
+            let mut vertical = 0.0;
+            if self.is_jump_pressed {
+                vertical += 1.0;
+            }
+            if self.is_descend_pressed {
+                vertical -= 1.0;
+            }
+            let horizontal_movement = horizontal_movement * FLY_SPEED_MULTIPLIER;
+            let movement = Vector3::new(
+                horizontal_movement.x,
+                vertical * self.speed * dt,
+                horizontal_movement.z,
+            );
+
+            // Move one axis at a time so flying into a wall slides along it
+            for axis in 0..3 {
+                let mut new_position = camera.position;
+                new_position[axis] += movement[axis];
+                if self.noclip || !self.check_collision(new_position, world) {
+                    camera.position = new_position;
+                }
+            }
+            return;
+        }
+
         // Apply horizontal movement with collision detection
         let new_x = camera.position.x + horizontal_movement.x;
         let new_z = camera.position.z + horizontal_movement.z;
 
         // Check X movement collision
-        if !self.check_collision(
-            Point3::new(new_x, camera.position.y, camera.position.z),
-            world,
-        ) {
+        let mut blocked = false;
+        let target = Point3::new(new_x, camera.position.y, camera.position.z);
+        if !self.check_collision(target, world) {
             camera.position.x = new_x;
+        } else if !self.try_step_up(camera, target, world) {
+            blocked = true;
         }
 
         // Check Z movement collision
-        if !self.check_collision(
-            Point3::new(camera.position.x, camera.position.y, new_z),
-            world,
-        ) {
+        let target = Point3::new(camera.position.x, camera.position.y, new_z);
+        if !self.check_collision(target, world) {
             camera.position.z = new_z;
+        } else if !self.try_step_up(camera, target, world) {
+            blocked = true;
         }
 
+        // Auto-jump: walked into an obstacle that's only one block high, with room to jump
+        let auto_jump = self.auto_jump
+            && blocked
+            && self.is_grounded
+            && !self.check_collision(
+                Point3::new(
+                    camera.position.x,
+                    camera.position.y + 1.0,
+                    camera.position.z,
+                ),
+                world,
+            )
+            && !self.check_collision(Point3::new(new_x, camera.position.y + 1.0, new_z), world);
+
         // Handle jumping
-        if self.is_jump_pressed && self.is_grounded {
+        if (self.is_jump_pressed || auto_jump) && self.is_grounded {
             self.velocity_y = self.jump_speed;
             self.is_grounded = false;
             self.is_jump_pressed = false; // Consume the jump input
@@ -287,7 +499,29 @@ impl CameraController {
         }
     }
 
-    fn check_collision(&self, eye_position: Point3<f32>, world: &crate::world::World) -> bool {
+    /// Climb onto a 1-block ledge blocking a move to `target`: if there's head room above
+    /// both the player and the ledge, move there one block higher. Auto-jump handles
+    /// ledges instead when it's on.
+    fn try_step_up(
+        &self,
+        camera: &mut Camera,
+        target: Point3<f32>,
+        world: &dyn VoxelQuery,
+    ) -> bool {
+        if self.auto_jump || !self.is_grounded {
+            return false;
+        }
+        let raised = target + Vector3::unit_y();
+        if self.check_collision(camera.position + Vector3::unit_y(), world)
+            || self.check_collision(raised, world)
+        {
+            return false;
+        }
+        camera.position = raised;
+        true
+    }
+
+    fn check_collision(&self, eye_position: Point3<f32>, world: &dyn VoxelQuery) -> bool {
         // Convert eye position to feet position
         let feet_position = Point3::new(
             eye_position.x,
@@ -304,7 +538,7 @@ impl CameraController {
 
         // Check blocks at player position for both feet and head levels
         for y in feet_y..=head_y {
-            if world.is_block_solid(player_x, y, player_z) {
+            if world.is_solid(player_x, y, player_z) {
                 return true;
             }
         }
@@ -329,15 +563,74 @@ impl CameraController {
         }
     }
 
+    /// Whether the left button is currently held down (not consumed by clicks)
+    pub fn is_left_mouse_held(&self) -> bool {
+        self.left_mouse_held
+    }
+
     pub fn reset_mouse_deltas(&mut self) {
         self.mouse_dx = 0.0;
         self.mouse_dy = 0.0;
     }
+
+    /// Drop any vertical momentum, e.g. after teleporting the camera
+    pub fn reset_velocity(&mut self) {
+        self.velocity_y = 0.0;
+        self.is_grounded = false;
+    }
+
+    /// Move the camera to `position` with no momentum or pending mouse movement
+    pub fn teleport(&mut self, camera: &mut Camera, position: Point3<f32>) {
+        camera.position = position;
+        self.reset_velocity();
+        self.reset_mouse_deltas();
+    }
+
+    /// Eye position standing on the ground at x/z, or at a fixed height when the
+    /// column isn't loaded
+    fn safe_spawn(&self, x: f32, z: f32, world: &dyn VoxelQuery) -> Point3<f32> {
+        let y = find_ground_level(world, x.floor() as i32, z.floor() as i32)
+            .map_or(FALLBACK_SPAWN_HEIGHT, |ground| {
+                ground as f32 + self.eye_height
+            });
+        Point3::new(x, y, z)
+    }
+
+    pub fn set_free_fly(&mut self, free_fly: bool) {
+        self.free_fly = free_fly;
+        self.reset_velocity();
+    }
+
+    /// Jump automatically when walking into a 1-block step with room above it. While on,
+    /// it takes over from stepping up onto ledges.
+    pub fn set_auto_jump(&mut self, auto_jump: bool) {
+        self.auto_jump = auto_jump;
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+        self.run_speed = speed * 2.0;
+    }
+
+    pub fn set_sensitivity(&mut self, sensitivity: f32) {
+        self.sensitivity = sensitivity;
+    }
+
+    /// Start or stop flying. Landing from a flight starts with no vertical momentum.
+    pub fn toggle_fly(&mut self) {
+        self.fly_mode = !self.fly_mode;
+        self.reset_velocity();
+    }
+
+    pub fn toggle_noclip(&mut self) {
+        self.noclip = !self.noclip;
+    }
 }
 
 pub struct CameraSystem {
     camera: Camera,
     controller: CameraController,
+    view_mode: ViewMode,
     uniform: CameraUniform,
     buffer: wgpu::Buffer,
     pub bind_group: wgpu::BindGroup,
@@ -383,6 +676,7 @@ impl CameraSystem {
         Self {
             camera,
             controller,
+            view_mode: ViewMode::default(),
             uniform,
             buffer,
             bind_group,
@@ -390,19 +684,32 @@ impl CameraSystem {
         }
     }
 
-    pub fn process_window_events(&mut self, event: &WindowEvent) -> bool {
-        self.controller.process_window_events(event)
+    pub fn process_window_events(
+        &mut self,
+        event: &WindowEvent,
+        key_bindings: &KeyBindings,
+    ) -> bool {
+        self.controller.process_window_events(event, key_bindings)
     }
 
     pub fn process_device_events(&mut self, event: &DeviceEvent) -> bool {
         self.controller.process_device_events(event)
     }
 
-    pub fn update(&mut self, dt: Duration, world: &crate::world::World) {
+    pub fn update(&mut self, dt: Duration, world: &dyn VoxelQuery) {
         self.controller.update_camera(&mut self.camera, dt, world);
+        self.camera.eye_offset = match self.view_mode {
+            ViewMode::First => 0.0,
+            ViewMode::Third => self.camera.third_person_offset(world),
+        };
         self.uniform.update_view_proj(&self.camera);
     }
 
+    /// Animation time passed to the shaders with the next `update_buffer`
+    pub fn set_time(&mut self, time: f32) {
+        self.uniform.time = time;
+    }
+
     pub fn update_buffer(&self, queue: &wgpu::Queue) {
         queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.uniform]));
     }
@@ -411,6 +718,16 @@ impl CameraSystem {
         self.camera.position
     }
 
+    pub fn is_underwater(&self, world: &dyn VoxelQuery) -> bool {
+        self.camera.is_underwater(world)
+    }
+
+    /// Match the projection to a new window size so the world doesn't stretch
+    pub fn set_aspect(&mut self, width: u32, height: u32) {
+        self.camera.aspect = width as f32 / height as f32;
+        self.uniform.update_view_proj(&self.camera);
+    }
+
     pub fn get_yaw(&self) -> f32 {
         self.camera.yaw.0
     }
@@ -427,7 +744,357 @@ impl CameraSystem {
         self.controller.was_right_mouse_clicked()
     }
 
+    pub fn is_left_mouse_held(&self) -> bool {
+        self.controller.is_left_mouse_held()
+    }
+
     pub fn reset_mouse_deltas(&mut self) {
         self.controller.reset_mouse_deltas()
     }
+
+    /// Detach the camera from player physics so it flies freely
+    pub fn set_free_fly(&mut self, free_fly: bool) {
+        self.controller.set_free_fly(free_fly);
+    }
+
+    /// Current view-projection matrix, e.g. for frustum culling
+    pub fn view_proj(&self) -> Matrix4<f32> {
+        self.camera.calc_matrix()
+    }
+
+    /// Near/far planes for reconstructing linear depth in shaders
+    pub fn depth_params(&self) -> DepthParams {
+        self.camera.depth_params()
+    }
+
+    /// See `CameraController::set_auto_jump`
+    pub fn set_auto_jump(&mut self, auto_jump: bool) {
+        self.controller.set_auto_jump(auto_jump);
+    }
+
+    /// Walking speed in blocks per second; running stays twice as fast
+    pub fn set_speed(&mut self, speed: f32) {
+        self.controller.set_speed(speed);
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.controller.speed
+    }
+
+    /// Mouse look sensitivity, used from the next `update`
+    pub fn set_sensitivity(&mut self, sensitivity: f32) {
+        self.controller.set_sensitivity(sensitivity);
+    }
+
+    pub fn sensitivity(&self) -> f32 {
+        self.controller.sensitivity
+    }
+
+    /// Render from the player's eye or from behind the player. Takes effect on the
+    /// next `update`.
+    pub fn set_view_mode(&mut self, view_mode: ViewMode) {
+        self.view_mode = view_mode;
+    }
+
+    pub fn view_mode(&self) -> ViewMode {
+        self.view_mode
+    }
+
+    /// Switch between walking and flying. Returns whether the player is now flying.
+    pub fn toggle_fly(&mut self) -> bool {
+        self.controller.toggle_fly();
+        self.controller.fly_mode
+    }
+
+    /// Let flight pass through blocks. Returns whether noclip is now on.
+    pub fn toggle_noclip(&mut self) -> bool {
+        self.controller.toggle_noclip();
+        self.controller.noclip
+    }
+
+    pub fn fov_degrees(&self) -> f32 {
+        self.camera.fovy.0.to_degrees()
+    }
+
+    /// Change the vertical field of view, clamped to 20-110 degrees
+    pub fn set_fov_degrees(&mut self, degrees: f32) {
+        self.camera.fovy = Rad(degrees.clamp(20.0, 110.0).to_radians());
+        self.uniform.update_view_proj(&self.camera);
+    }
+
+    /// Snapshot the camera position and orientation for saving
+    pub fn camera_state(&self) -> CameraState {
+        CameraState {
+            position: self.camera.position.into(),
+            yaw: self.camera.yaw.0,
+            pitch: self.camera.pitch.0,
+        }
+    }
+
+    /// Move the player, dropping any fall speed and pending mouse movement
+    pub fn teleport(&mut self, position: Point3<f32>) {
+        self.controller.teleport(&mut self.camera, position);
+        self.uniform.update_view_proj(&self.camera);
+    }
+
+    /// Move the camera back to a saved snapshot
+    pub fn restore_camera_state(&mut self, state: &CameraState) {
+        self.camera.position = Point3::from(state.position);
+        self.camera.yaw = Rad(state.yaw);
+        self.camera.pitch = Rad(state.pitch);
+        self.controller.reset_velocity();
+        self.uniform.update_view_proj(&self.camera);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voxel_query::BlockMap;
+    use crate::world::World;
+
+    #[test]
+    fn test_third_person_camera_is_pulled_in_front_of_walls() {
+        let mut world = World::new();
+        // Yaw 0 looks down +X, so the third-person camera sits toward -X
+        let mut camera = Camera::new(Point3::new(0.5, 62.5, 0.5), Deg(0.0), Deg(0.0), 1.0);
+        assert_eq!(camera.third_person_offset(&world), THIRD_PERSON_DISTANCE);
+
+        world.set_block_for_test(-2, 62, 0, crate::blocks::BlockType::Stone);
+        camera.eye_offset = camera.third_person_offset(&world);
+        // The wall's face is at x = -1, 1.5 blocks behind the eye
+        assert!((camera.eye_offset - (1.5 - THIRD_PERSON_WALL_MARGIN)).abs() < 1e-4);
+        assert!((camera.eye_position().x - (-1.0 + THIRD_PERSON_WALL_MARGIN)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_falling_out_of_the_world_respawns_on_the_ground() {
+        let mut world = World::new();
+        world.set_block_for_test(3, 20, 5, crate::blocks::BlockType::Stone);
+        let mut camera = Camera::new(Point3::new(3.5, -0.5, 5.5), Deg(0.0), Deg(0.0), 1.0);
+        let mut controller = CameraController::new(4.0, 0.5);
+        controller.velocity_y = -50.0;
+
+        controller.update_camera(&mut camera, Duration::from_millis(16), &world);
+        assert_eq!(camera.position, Point3::new(3.5, 21.0 + 1.6, 5.5));
+        assert_eq!(controller.velocity_y, 0.0);
+
+        // Over a column that isn't loaded, fall back to the spawn height
+        camera.position = Point3::new(100.5, -2.0, 100.5);
+        controller.update_camera(&mut camera, Duration::from_millis(16), &world);
+        assert_eq!(camera.position.y, FALLBACK_SPAWN_HEIGHT);
+    }
+
+    /// A 5x5 stone floor whose top is at y = 60
+    fn floor() -> BlockMap {
+        let mut map = BlockMap::new();
+        map.fill([-2, 59, -2], [2, 59, 2], crate::blocks::BlockType::Stone);
+        map
+    }
+
+    /// Step the controller in 16ms frames, returning the highest feet height reached
+    fn simulate(
+        controller: &mut CameraController,
+        camera: &mut Camera,
+        map: &BlockMap,
+        frames: usize,
+    ) -> f32 {
+        let mut highest = f32::MIN;
+        for _ in 0..frames {
+            controller.update_camera(camera, Duration::from_millis(16), map);
+            highest = highest.max(camera.position.y - controller.eye_height);
+        }
+        highest
+    }
+
+    #[test]
+    fn test_falling_player_lands_on_the_floor() {
+        let map = floor();
+        let mut camera = Camera::new(Point3::new(0.5, 66.0, 0.5), Deg(0.0), Deg(0.0), 1.0);
+        let mut controller = CameraController::new(4.0, 0.5);
+
+        simulate(&mut controller, &mut camera, &map, 120);
+
+        let feet = camera.position.y - controller.eye_height;
+        assert!(controller.is_grounded);
+        assert_eq!(controller.velocity_y, 0.0);
+        // Stops within one frame's fall of the floor, never inside it
+        assert!((60.0..60.5).contains(&feet), "feet at {}", feet);
+    }
+
+    #[test]
+    fn test_jump_rises_about_a_block_and_lands_again() {
+        let map = floor();
+        let mut camera = Camera::new(Point3::new(0.5, 61.6, 0.5), Deg(0.0), Deg(0.0), 1.0);
+        let mut controller = CameraController::new(4.0, 0.5);
+        simulate(&mut controller, &mut camera, &map, 10);
+        assert!(controller.is_grounded);
+        let start = camera.position.y - controller.eye_height;
+
+        controller.is_jump_pressed = true;
+        let highest = simulate(&mut controller, &mut camera, &map, 120);
+
+        // jump_speed^2 / (2 * gravity) = 1.28 blocks, less a little for the frame steps
+        let rise = highest - start;
+        assert!((1.1..1.3).contains(&rise), "rose {}", rise);
+        assert!(controller.is_grounded);
+        let feet = camera.position.y - controller.eye_height;
+        assert!((feet - start).abs() < 0.5, "landed at {}", feet);
+    }
+
+    #[test]
+    fn test_ceiling_cuts_a_jump_short() {
+        let mut map = floor();
+        // Two blocks above the floor: only 0.2 blocks of room over the player's head
+        map.set(0, 62, 0, crate::blocks::BlockType::Stone);
+        let mut camera = Camera::new(Point3::new(0.5, 61.6, 0.5), Deg(0.0), Deg(0.0), 1.0);
+        let mut controller = CameraController::new(4.0, 0.5);
+        simulate(&mut controller, &mut camera, &map, 10);
+        let start = camera.position.y - controller.eye_height;
+
+        controller.is_jump_pressed = true;
+        let highest = simulate(&mut controller, &mut camera, &map, 120);
+
+        assert!(highest - start < 0.2 + 1e-4, "rose {}", highest - start);
+        assert!(controller.is_grounded);
+    }
+
+    #[test]
+    fn test_free_fly_ignores_gravity_and_blocks() {
+        let mut world = World::new();
+        world.set_block_for_test(0, 60, 0, crate::blocks::BlockType::Stone);
+        let mut camera = Camera::new(Point3::new(0.5, 62.0, 0.5), Deg(0.0), Deg(-90.0), 1.0);
+        let mut controller = CameraController::new(4.0, 0.5);
+        controller.set_free_fly(true);
+
+        // Hovers in place without input
+        controller.update_camera(&mut camera, Duration::from_millis(500), &world);
+        assert_eq!(camera.position.y, 62.0);
+
+        // Sinks straight through the stone below
+        controller.is_descend_pressed = true;
+        controller.update_camera(&mut camera, Duration::from_secs(1), &world);
+        assert!((camera.position.y - 58.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_fly_mode_hovers_but_still_collides() {
+        let mut world = World::new();
+        world.set_block_for_test(0, 64, 0, crate::blocks::BlockType::Stone);
+        let mut camera = Camera::new(Point3::new(0.5, 62.0, 0.5), Deg(0.0), Deg(90.0), 1.0);
+        let mut controller = CameraController::new(4.0, 0.5);
+        controller.toggle_fly();
+
+        // No gravity
+        controller.update_camera(&mut camera, Duration::from_millis(500), &world);
+        assert_eq!(camera.position.y, 62.0);
+
+        // Rising is stopped by the stone overhead
+        controller.is_jump_pressed = true;
+        controller.update_camera(&mut camera, Duration::from_secs(1), &world);
+        assert_eq!(camera.position.y, 62.0);
+
+        // Unless noclip is on too
+        controller.toggle_noclip();
+        controller.update_camera(&mut camera, Duration::from_secs(1), &world);
+        assert!((camera.position.y - 66.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_step_up_climbs_stairs_but_not_walls() {
+        // Floor along +X with a staircase of single steps starting at x = 2, up to a
+        // platform 3 blocks high
+        let mut world = World::new();
+        for x in 0..10 {
+            world.set_block_for_test(x, 59, 0, crate::blocks::BlockType::Stone);
+        }
+        for x in 2..10 {
+            let height = (x - 1).min(3);
+            for y in 60..60 + height {
+                world.set_block_for_test(x, y, 0, crate::blocks::BlockType::Stone);
+            }
+        }
+
+        let walk = |world: &World| {
+            let mut camera = Camera::new(Point3::new(0.5, 61.6, 0.5), Deg(0.0), Deg(0.0), 1.0);
+            let mut controller = CameraController::new(4.0, 0.5);
+            controller.is_forward_pressed = true;
+            for _ in 0..120 {
+                controller.update_camera(&mut camera, Duration::from_millis(16), world);
+            }
+            camera.position
+        };
+
+        let position = walk(&world);
+        assert!(position.x > 5.0);
+        assert!((position.y - 64.6).abs() < 0.1);
+
+        // A 2-block wall at the bottom of the stairs stops the player
+        world.set_block_for_test(2, 61, 0, crate::blocks::BlockType::Stone);
+        let position = walk(&world);
+        assert!(position.x < 2.0);
+        assert!((position.y - 61.6).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_auto_jump_over_one_block_wall() {
+        let mut world = World::new();
+        // Floor, with a 1-block wall one step ahead in +X
+        for x in 0..4 {
+            world.set_block_for_test(x, 59, 0, crate::blocks::BlockType::Stone);
+        }
+        world.set_block_for_test(1, 60, 0, crate::blocks::BlockType::Stone);
+
+        for auto_jump in [true, false] {
+            // Standing on the floor, facing +X, right against the wall
+            let mut camera = Camera::new(Point3::new(0.95, 61.6, 0.5), Deg(0.0), Deg(0.0), 1.0);
+            let mut controller = CameraController::new(4.0, 0.5);
+            controller.set_auto_jump(auto_jump);
+            controller.is_grounded = true;
+            controller.is_forward_pressed = true;
+
+            controller.update_camera(&mut camera, Duration::from_millis(16), &world);
+
+            assert_eq!(controller.velocity_y > 0.0, auto_jump);
+        }
+    }
+
+    #[test]
+    fn test_underwater_only_when_the_eye_is_in_water() {
+        let mut world = World::new();
+        world.set_block_for_test(0, 60, 0, crate::blocks::BlockType::Water);
+
+        let camera_at = |y: f32| Camera::new(Point3::new(0.5, y, 0.5), Deg(0.0), Deg(0.0), 1.0);
+        assert!(camera_at(60.5).is_underwater(&world));
+        assert!(!camera_at(61.2).is_underwater(&world));
+        assert!(!camera_at(59.9).is_underwater(&world));
+    }
+
+    #[test]
+    fn test_linearize_depth_recovers_view_distance() {
+        let camera = Camera::new(Point3::new(3.0, 70.0, -8.0), Deg(30.0), Deg(-10.0), 1.6);
+        let params = camera.depth_params();
+        let view_proj = camera.calc_matrix();
+
+        let (sin_pitch, cos_pitch) = camera.pitch.0.sin_cos();
+        let (sin_yaw, cos_yaw) = camera.yaw.0.sin_cos();
+        let forward = Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw);
+
+        for distance in [params.near, 1.0, 12.5, 60.0, params.far] {
+            let point = camera.position + forward * distance;
+            let clip = view_proj * point.to_homogeneous();
+            let depth = clip.z / clip.w;
+
+            assert!((0.0..=1.0 + 1e-5).contains(&depth));
+            let (a, b) = params.projection_constants();
+            assert!((a + b / distance - depth).abs() < 1e-4);
+            let linear = params.linearize_depth(depth);
+            assert!(
+                (linear - distance).abs() < distance * 1e-3,
+                "{} -> {}",
+                distance,
+                linear
+            );
+        }
+    }
 }
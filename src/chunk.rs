@@ -1,54 +1,274 @@
 use crate::biome::Biome;
 use crate::biome::BiomeManager;
-use crate::blocks::{get_block_registry, BlockType};
+use crate::block_light::MAX_LIGHT_LEVEL;
+use crate::blocks::{BlockRegistry, BlockType, TextureId};
+use crate::structure_file::FileStructure;
 use crate::structures::{PlacedStructure, StructureGenerator};
 use crate::terrain::Terrain;
-use crate::voxel::{create_cube_indices_selective, create_cube_vertices_selective, Vertex};
+use crate::terrain_modifier::TerrainModifier;
+use crate::voxel::{
+    ao_factor, create_cube_indices_selective, create_cube_vertices_selective, face_ao_levels,
+    smooth_normals, texture_rotation_at, wide_ambient_occlusion, FaceTextures, Vertex,
+    FACE_NORMALS,
+};
+use std::collections::HashMap;
 
 pub const CHUNK_SIZE: usize = 16;
 pub const WORLD_HEIGHT: usize = 255; // Maximum world height for building
 pub const TERRAIN_MAX_HEIGHT: usize = 128; // Maximum natural terrain height
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+/// Share of the sunlight that gets past each opaque block above a face
+const SKYLIGHT_PER_BLOCK: f32 = 0.7;
+/// Opaque blocks overhead past which a face counts as buried (about 0.3% of the sunlight
+/// gets through), so faces deep underground still merge
+const MAX_SKY_DEPTH: u8 = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ChunkPos {
     pub x: i32,
     pub z: i32,
 }
 
+/// Options that change how chunk meshes are built
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MeshOptions {
+    /// Show plain dirt instead of the green overlay on grass sides that are covered
+    /// from above (a block on top, or an overhang right above the side)
+    pub smart_grass_sides: bool,
+    /// Merge neighboring faces with the same texture into larger quads (fewer vertices)
+    pub greedy: bool,
+    /// Average normals across the faces meeting at each corner so lighting rounds off
+    /// over hills. The geometry stays the same; off by default for flat shading.
+    pub smooth_normals: bool,
+    /// Also darken corners by the blocks two steps out (`wide_ambient_occlusion`) for
+    /// softer contact shadows. Too slow for every edit, so it is only used for a one-off
+    /// bake of the loaded chunks; faces are never merged with it.
+    pub high_quality_ao: bool,
+}
+
+/// Textures for a grass block with `MeshOptions::smart_grass_sides`.
+/// `is_solid_at` takes an offset from the grass block.
+pub fn grass_face_textures(
+    mut textures: FaceTextures,
+    is_solid_at: impl Fn(i32, i32, i32) -> bool,
+) -> FaceTextures {
+    let dirt = TextureId::Dirt as u32;
+    let covered = is_solid_at(0, 1, 0);
+
+    // Same face order as create_cube_vertices_selective
+    if covered || is_solid_at(0, 1, 1) {
+        textures.front = dirt;
+    }
+    if covered || is_solid_at(0, 1, -1) {
+        textures.back = dirt;
+    }
+    if covered || is_solid_at(-1, 1, 0) {
+        textures.left = dirt;
+    }
+    if covered || is_solid_at(1, 1, 0) {
+        textures.right = dirt;
+    }
+
+    textures
+}
+
 /// Raw chunk data that can be generated concurrently
+#[derive(Default)]
 pub struct ChunkData {
     pub vertices: Vec<Vertex>,
     pub indices: Vec<u32>,
+    /// Indices of opaque faces, which come first. The rest belong to transparent faces.
+    pub num_opaque_indices: u32,
+}
+
+impl ChunkData {
+    /// Append quads given as four vertices each, in `create_cube_vertices_selective` order
+    fn push_quads(&mut self, quads: Vec<Vertex>) {
+        let vertex_offset = self.vertices.len() as u32;
+        let faces: Vec<usize> = (0..quads.len() / 4).collect();
+        self.indices
+            .extend(create_cube_indices_selective(&faces, vertex_offset));
+        self.vertices.extend(quads);
+    }
 }
 
 pub struct Chunk {
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
-    pub num_indices: u32,
+    /// Opaque faces come first in the index buffer, followed by the transparent ones
+    pub num_opaque_indices: u32,
+    pub num_transparent_indices: u32,
+    pub num_vertices: u32,
+    /// Sizes of the GPU buffers, for the world's memory estimate
+    pub vertex_bytes: u64,
+    pub index_bytes: u64,
+}
+
+/// Block data for one chunk, heap-allocated so larger chunk sizes don't overflow the stack
+#[derive(Clone)]
+pub struct ChunkBlocks {
+    blocks: Box<[BlockType]>,
+    /// Blocks placed by structures, keyed by index, with the terrain block each one replaced
+    structure_blocks: HashMap<usize, BlockType>,
 }
 
-pub type ChunkBlocks = [[[BlockType; WORLD_HEIGHT]; CHUNK_SIZE]; CHUNK_SIZE];
+impl ChunkBlocks {
+    /// Create a chunk filled with air
+    pub fn new() -> Self {
+        Self {
+            blocks: vec![BlockType::Air; CHUNK_SIZE * CHUNK_SIZE * WORLD_HEIGHT].into_boxed_slice(),
+            structure_blocks: HashMap::new(),
+        }
+    }
+
+    /// Index into the flat storage; columns are contiguous so Y loops stay cache friendly
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        debug_assert!(x < CHUNK_SIZE && y < WORLD_HEIGHT && z < CHUNK_SIZE);
+        (x * CHUNK_SIZE + z) * WORLD_HEIGHT + y
+    }
+
+    /// Get the block at chunk-local coordinates
+    pub fn get(&self, x: usize, y: usize, z: usize) -> BlockType {
+        self.blocks[Self::index(x, y, z)]
+    }
+
+    /// Set the block at chunk-local coordinates
+    pub fn set(&mut self, x: usize, y: usize, z: usize, block_type: BlockType) {
+        self.blocks[Self::index(x, y, z)] = block_type;
+    }
+
+    /// Place a structure block, remembering the terrain block underneath so it can be restored
+    pub fn set_structure_block(&mut self, x: usize, y: usize, z: usize, block_type: BlockType) {
+        let index = Self::index(x, y, z);
+        let terrain_block = self.blocks[index];
+        self.structure_blocks.entry(index).or_insert(terrain_block);
+        self.blocks[index] = block_type;
+    }
+
+    /// Whether the block at chunk-local coordinates was placed by a structure
+    pub fn is_structure_block(&self, x: usize, y: usize, z: usize) -> bool {
+        self.structure_blocks.contains_key(&Self::index(x, y, z))
+    }
+
+    /// Put back the terrain blocks that structures replaced
+    pub fn clear_structure_blocks(&mut self) {
+        for (index, terrain_block) in self.structure_blocks.drain() {
+            self.blocks[index] = terrain_block;
+        }
+    }
+}
+
+impl Default for ChunkBlocks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 /// Orchestrates chunk generation by combining terrain and structures
 pub struct ChunkGenerator {
     structure_generator: StructureGenerator,
+    modifiers: Vec<Box<dyn TerrainModifier>>,
+    mesh_options: MeshOptions,
 }
 
 impl ChunkGenerator {
     pub fn new(seed: u32) -> Self {
         Self {
             structure_generator: StructureGenerator::new(seed),
+            modifiers: Vec::new(),
+            mesh_options: MeshOptions::default(),
         }
     }
 
+    pub fn mesh_options(&self) -> MeshOptions {
+        self.mesh_options
+    }
+
+    /// Change how meshes are built for chunks generated from now on
+    pub fn set_mesh_options(&mut self, mesh_options: MeshOptions) {
+        self.mesh_options = mesh_options;
+    }
+
+    /// Add a structure loaded from a file to the ones placed in new chunks
+    pub fn register_structure(&mut self, structure: FileStructure) {
+        self.structure_generator.add_file_structure(structure);
+    }
+
+    /// Register a custom terrain feature, applied after terrain and before structures
+    pub fn register_modifier(&mut self, modifier: Box<dyn TerrainModifier>) {
+        self.modifiers.push(modifier);
+    }
+
     /// Generate a complete chunk with terrain and structures
     pub fn generate_chunk(
         &self,
         chunk_pos: ChunkPos,
         terrain: &Terrain,
         biome_manager: &BiomeManager,
+        registry: &BlockRegistry,
     ) -> (ChunkData, ChunkBlocks) {
-        // Generate height and biome maps for structure generation
+        let chunk_blocks = self.generate_chunk_blocks(chunk_pos, terrain, biome_manager);
+
+        // Neighboring chunks aren't generated yet, so predict their terrain from the height
+        // map and sea level. Anything above it (structures, modifiers) counts as air and
+        // keeps the face.
+        let neighbor_heights = border_column_heights(chunk_pos, terrain, biome_manager);
+        let chunk_data = mesh_chunk_blocks(
+            chunk_pos,
+            &chunk_blocks,
+            registry,
+            self.mesh_options,
+            |world_x, y, world_z| {
+                let filled_height = *neighbor_heights.get(&(world_x, world_z))?;
+                Some(predicted_terrain_block(
+                    filled_height,
+                    terrain.sea_level(),
+                    y,
+                ))
+            },
+            |_, _, _| 0,
+        );
+        (chunk_data, chunk_blocks)
+    }
+
+    /// Generate a chunk's terrain and structure blocks without meshing them
+    pub fn generate_chunk_blocks(
+        &self,
+        chunk_pos: ChunkPos,
+        terrain: &Terrain,
+        biome_manager: &BiomeManager,
+    ) -> ChunkBlocks {
+        let structures = self.generate_structures(chunk_pos, terrain, biome_manager);
+
+        // Generate terrain and structures combined
+        self.generate_blocks_with_structures(chunk_pos, &structures, terrain, biome_manager)
+    }
+
+    /// Swap a chunk's structure blocks for freshly generated ones, leaving terrain untouched
+    pub fn regenerate_structures(
+        &self,
+        chunk_pos: ChunkPos,
+        chunk_blocks: &mut ChunkBlocks,
+        terrain: &Terrain,
+        biome_manager: &BiomeManager,
+    ) {
+        chunk_blocks.clear_structure_blocks();
+        let structures = self.generate_structures(chunk_pos, terrain, biome_manager);
+        place_structures(chunk_pos, &structures, chunk_blocks);
+    }
+
+    fn generate_structures(
+        &self,
+        chunk_pos: ChunkPos,
+        terrain: &Terrain,
+        biome_manager: &BiomeManager,
+    ) -> Vec<PlacedStructure> {
+        if !terrain.has_structures() {
+            return Vec::new();
+        }
+
+        // Generate surface height and biome maps for structure generation. Structures
+        // stand on the first free block, so use the height the terrain is actually filled to.
         let mut height_values = [[0usize; CHUNK_SIZE]; CHUNK_SIZE];
         let mut biome_map = [[Biome::Plains; CHUNK_SIZE]; CHUNK_SIZE];
 
@@ -57,7 +277,7 @@ impl ChunkGenerator {
                 let world_x = chunk_pos.x * CHUNK_SIZE as i32 + x as i32;
                 let world_z = chunk_pos.z * CHUNK_SIZE as i32 + z as i32;
 
-                let height = terrain.height_at(world_x, world_z, biome_manager);
+                let height = terrain.filled_height_at(world_x, world_z, biome_manager);
                 let biome = terrain.biome_at(world_x, world_z);
 
                 height_values[x][z] = height;
@@ -65,31 +285,23 @@ impl ChunkGenerator {
             }
         }
 
-        // Generate structures for this chunk
-        let structures = self.structure_generator.generate_structures_for_chunk(
+        self.structure_generator.generate_structures_for_chunk(
             chunk_pos.x,
             chunk_pos.z,
             &height_values,
             &biome_map,
             terrain,
             biome_manager,
-        );
-
-        // Generate chunk data with terrain and structures combined
-        self.generate_chunk_data(chunk_pos, &structures, terrain, biome_manager)
+        )
     }
 
-    fn generate_chunk_data(
+    fn generate_blocks_with_structures(
         &self,
         chunk_pos: ChunkPos,
         structures: &[PlacedStructure],
         terrain: &Terrain,
         biome_manager: &BiomeManager,
-    ) -> (ChunkData, ChunkBlocks) {
-        let mut vertices = Vec::new();
-        let mut indices: Vec<u32> = Vec::new();
-        let registry = get_block_registry();
-
+    ) -> ChunkBlocks {
         // Pre-generate block data for the entire chunk to enable face culling
         let mut chunk_blocks;
 
@@ -121,39 +333,518 @@ impl ChunkGenerator {
         chunk_blocks =
             terrain.generate_terrain_blocks(chunk_pos, &height_values, &biome_map, biome_manager);
 
-        // Place structure blocks into the chunk
-        for structure in structures {
-            for block in &structure.blocks {
-                let block_x = structure.world_x + block.relative_pos.0;
-                let block_y = structure.world_y + block.relative_pos.1;
-                let block_z = structure.world_z + block.relative_pos.2;
-
-                // Check if this block is within the current chunk
-                let local_x = block_x - (chunk_pos.x * CHUNK_SIZE as i32);
-                let local_z = block_z - (chunk_pos.z * CHUNK_SIZE as i32);
-
-                if local_x >= 0
-                    && local_x < CHUNK_SIZE as i32
-                    && local_z >= 0
-                    && local_z < CHUNK_SIZE as i32
-                    && block_y >= 0
-                    && block_y < WORLD_HEIGHT as i32
+        // Let registered modifiers add their own features
+        let origin_x = chunk_pos.x * CHUNK_SIZE as i32;
+        let origin_z = chunk_pos.z * CHUNK_SIZE as i32;
+        for modifier in &self.modifiers {
+            modifier.modify(
+                &mut chunk_blocks,
+                origin_x,
+                origin_z,
+                terrain,
+                biome_manager,
+            );
+        }
+
+        // Place structure blocks last, so the blocks they remember replacing (and put back
+        // when structures are regenerated) include the modifiers' features
+        place_structures(chunk_pos, structures, &mut chunk_blocks);
+
+        chunk_blocks
+    }
+}
+
+/// Faces of a block that aren't hidden, in `create_cube_vertices_selective` order.
+/// `block_at(dx, dy, dz)` gives the block at that offset, or None where it's unknown.
+/// A face shows if the block behind it is air, unknown, or a different block that can be
+/// seen through. Faces between two blocks of the same type (water against water, glass
+/// against glass) are culled.
+pub fn visible_faces(
+    block_type: BlockType,
+    registry: &BlockRegistry,
+    block_at: impl Fn(i32, i32, i32) -> Option<BlockType>,
+) -> Vec<usize> {
+    (0..FACE_NORMALS.len())
+        .filter(|&face| {
+            let [dx, dy, dz] = FACE_NORMALS[face];
+            block_at(dx, dy, dz).is_none_or(|adj_block| {
+                adj_block == BlockType::Air
+                    || (adj_block != block_type && registry.is_transparent(adj_block))
+            })
+        })
+        .collect()
+}
+
+/// Block a column with the given terrain fill height is expected to have at height `y`
+/// before anything is built on it: terrain below the fill height, then water up to sea
+/// level and air above
+pub fn predicted_terrain_block(filled_height: usize, sea_level: usize, y: i32) -> BlockType {
+    if (y as usize) < filled_height {
+        BlockType::Stone
+    } else if (y as usize) < sea_level {
+        BlockType::Water
+    } else {
+        BlockType::Air
+    }
+}
+
+/// Terrain fill height of each column just outside the chunk's four sides, keyed by world (x, z)
+pub fn border_column_heights(
+    chunk_pos: ChunkPos,
+    terrain: &Terrain,
+    biome_manager: &BiomeManager,
+) -> HashMap<(i32, i32), usize> {
+    let origin_x = chunk_pos.x * CHUNK_SIZE as i32;
+    let origin_z = chunk_pos.z * CHUNK_SIZE as i32;
+    let size = CHUNK_SIZE as i32;
+
+    let mut columns = Vec::with_capacity(CHUNK_SIZE * 4);
+    for i in 0..size {
+        columns.push((origin_x - 1, origin_z + i));
+        columns.push((origin_x + size, origin_z + i));
+        columns.push((origin_x + i, origin_z - 1));
+        columns.push((origin_x + i, origin_z + size));
+    }
+
+    columns
+        .into_iter()
+        .map(|(x, z)| ((x, z), terrain.filled_height_at(x, z, biome_manager)))
+        .collect()
+}
+
+/// Build the mesh for a chunk, culling faces hidden by a neighboring block.
+///
+/// `neighbor_block` looks up world positions outside this chunk; `None` (e.g. the
+/// neighboring chunk isn't loaded) keeps the face so the chunk edge is never left open.
+/// `block_light` gives the block light level at a world position; each face takes the
+/// level of the cell it faces.
+/// With `MeshOptions::greedy` set this builds the same surface as `mesh_chunk_blocks_greedy`.
+pub fn mesh_chunk_blocks(
+    chunk_pos: ChunkPos,
+    chunk_blocks: &ChunkBlocks,
+    registry: &BlockRegistry,
+    mesh_options: MeshOptions,
+    neighbor_block: impl Fn(i32, i32, i32) -> Option<BlockType>,
+    block_light: impl Fn(i32, i32, i32) -> u8,
+) -> ChunkData {
+    if mesh_options.greedy {
+        return mesh_chunk_blocks_greedy(
+            chunk_pos,
+            chunk_blocks,
+            registry,
+            mesh_options,
+            neighbor_block,
+            block_light,
+        );
+    }
+
+    let mut vertices = Vec::new();
+    let mut indices: Vec<u32> = Vec::new();
+    let mut num_opaque_indices = 0;
+
+    for_each_visible_block(
+        chunk_pos,
+        chunk_blocks,
+        registry,
+        mesh_options,
+        neighbor_block,
+        block_light,
+        |world_pos,
+         textures,
+         faces_to_render,
+         texture_rotation,
+         transparent,
+         emission,
+         face_light,
+         is_solid| {
+            let vertex_offset = vertices.len() as u32;
+            let mut block_vertices = create_cube_vertices_selective(
+                world_pos[0],
+                world_pos[1],
+                world_pos[2],
+                &textures,
+                faces_to_render,
+                texture_rotation.unwrap_or(0),
+                is_solid,
+            );
+            if mesh_options.high_quality_ao {
+                wide_ambient_occlusion(&mut block_vertices, world_pos, is_solid);
+            }
+            if mesh_options.smooth_normals {
+                smooth_normals(&mut block_vertices, world_pos, is_solid);
+            }
+            set_emission(&mut block_vertices, emission);
+            set_face_light(&mut block_vertices, faces_to_render, face_light);
+            vertices.extend(block_vertices);
+            indices.extend(create_cube_indices_selective(
+                faces_to_render,
+                vertex_offset,
+            ));
+            if !transparent {
+                num_opaque_indices = indices.len() as u32;
+            }
+        },
+    );
+
+    ChunkData {
+        vertices,
+        indices,
+        num_opaque_indices,
+    }
+}
+
+/// Like `mesh_chunk_blocks`, but merges neighboring coplanar faces with the same texture
+/// ambient occlusion and block light into larger quads whose UVs repeat the tile once
+/// per block.
+/// Faces of glowing blocks and blocks with random texture rotation, water faces, all
+/// faces with `MeshOptions::high_quality_ao`, and faces whose
+/// corners are shaded unevenly or (with `MeshOptions::smooth_normals`) get bent normals,
+/// are left as one quad per block. Transparent faces still come after all opaque ones.
+pub fn mesh_chunk_blocks_greedy(
+    chunk_pos: ChunkPos,
+    chunk_blocks: &ChunkBlocks,
+    registry: &BlockRegistry,
+    mesh_options: MeshOptions,
+    neighbor_block: impl Fn(i32, i32, i32) -> Option<BlockType>,
+    block_light: impl Fn(i32, i32, i32) -> u8,
+) -> ChunkData {
+    const DIMS: [usize; 3] = [CHUNK_SIZE, WORLD_HEIGHT, CHUNK_SIZE];
+    let cell_index = |p: [usize; 3]| (p[0] * WORLD_HEIGHT + p[1]) * CHUNK_SIZE + p[2];
+
+    // Opaque and transparent quads, joined at the end
+    let mut meshes = [ChunkData::default(), ChunkData::default()];
+
+    // Texture, ambient occlusion and light level of every mergeable visible face, one mask per
+    // face direction, with the masks of transparent blocks after the opaque ones
+    let mut face_masks = vec![vec![None; DIMS.iter().product()]; 12];
+
+    for_each_visible_block(
+        chunk_pos,
+        chunk_blocks,
+        registry,
+        mesh_options,
+        neighbor_block,
+        block_light,
+        |world_pos,
+         textures,
+         faces_to_render,
+         texture_rotation,
+         transparent,
+         emission,
+         face_light,
+         is_solid| {
+            let mesh = &mut meshes[transparent as usize];
+            // Glowing blocks are rare, so they keep one quad per face rather than
+            // carrying their emission through the merge
+            if texture_rotation.is_some() || emission > 0.0 || mesh_options.high_quality_ao {
+                let mut vertices = create_cube_vertices_selective(
+                    world_pos[0],
+                    world_pos[1],
+                    world_pos[2],
+                    &textures,
+                    faces_to_render,
+                    texture_rotation.unwrap_or(0),
+                    is_solid,
+                );
+                if mesh_options.high_quality_ao {
+                    wide_ambient_occlusion(&mut vertices, world_pos, is_solid);
+                }
+                if mesh_options.smooth_normals {
+                    smooth_normals(&mut vertices, world_pos, is_solid);
+                }
+                set_emission(&mut vertices, emission);
+                set_face_light(&mut vertices, faces_to_render, face_light);
+                mesh.push_quads(vertices);
+                return;
+            }
+
+            let local = [
+                (world_pos[0] as i32 - chunk_pos.x * CHUNK_SIZE as i32) as usize,
+                world_pos[1] as usize,
+                (world_pos[2] as i32 - chunk_pos.z * CHUNK_SIZE as i32) as usize,
+            ];
+            for &face in faces_to_render {
+                let ao_levels = face_ao_levels(face, is_solid);
+                let single_face = || {
+                    let mut vertices = create_cube_vertices_selective(
+                        world_pos[0],
+                        world_pos[1],
+                        world_pos[2],
+                        &textures,
+                        &[face],
+                        0,
+                        is_solid,
+                    );
+                    set_face_light(&mut vertices, &[face], face_light);
+                    vertices
+                };
+                // The shader ripples water per vertex, so a merged surface would only
+                // move at its outer corners
+                if ao_levels.iter().any(|&level| level != ao_levels[0])
+                    || face_texture(&textures, face) == TextureId::Water as u32
+                {
+                    mesh.push_quads(single_face());
+                    continue;
+                }
+                if mesh_options.smooth_normals {
+                    // Faces on a ledge or in a dip get bent normals; keep them separate
+                    let mut vertices = single_face();
+                    let flat_normal = vertices[0].normal;
+                    smooth_normals(&mut vertices, world_pos, is_solid);
+                    if vertices.iter().any(|vertex| vertex.normal != flat_normal) {
+                        mesh.push_quads(vertices);
+                        continue;
+                    }
+                }
+                face_masks[transparent as usize * 6 + face][cell_index(local)] = Some((
+                    face_texture(&textures, face),
+                    ao_levels[0],
+                    face_light[face],
+                ));
+            }
+        },
+    );
+
+    for (mask_index, mask) in face_masks.iter_mut().enumerate() {
+        let face = mask_index % 6;
+        let mesh = &mut meshes[mask_index / 6];
+        let (normal_axis, u_axis, v_axis) = FACE_AXES[face];
+
+        for layer in 0..DIMS[normal_axis] {
+            let cell = |u: usize, v: usize| {
+                let mut p = [0; 3];
+                p[normal_axis] = layer;
+                p[u_axis] = u;
+                p[v_axis] = v;
+                p
+            };
+
+            for v in 0..DIMS[v_axis] {
+                for u in 0..DIMS[u_axis] {
+                    let Some(key) = mask[cell_index(cell(u, v))] else {
+                        continue;
+                    };
+
+                    // Grow along u, then add whole rows along v while they match
+                    let mut width = 1;
+                    while u + width < DIMS[u_axis]
+                        && mask[cell_index(cell(u + width, v))] == Some(key)
+                    {
+                        width += 1;
+                    }
+                    let mut height = 1;
+                    while v + height < DIMS[v_axis]
+                        && (u..u + width)
+                            .all(|row_u| mask[cell_index(cell(row_u, v + height))] == Some(key))
+                    {
+                        height += 1;
+                    }
+
+                    for dv in 0..height {
+                        for du in 0..width {
+                            mask[cell_index(cell(u + du, v + dv))] = None;
+                        }
+                    }
+
+                    let local = cell(u, v);
+                    let origin = [
+                        (chunk_pos.x * CHUNK_SIZE as i32 + local[0] as i32) as f32,
+                        local[1] as f32,
+                        (chunk_pos.z * CHUNK_SIZE as i32 + local[2] as i32) as f32,
+                    ];
+                    let (texture_id, ao_level, face_light) = key;
+                    mesh.push_quads(merged_face_vertices(
+                        origin,
+                        face,
+                        texture_id,
+                        ao_level,
+                        face_light,
+                        [width, height],
+                    ));
+                }
+            }
+        }
+    }
+
+    let [mut opaque, transparent] = meshes;
+    opaque.num_opaque_indices = opaque.indices.len() as u32;
+    opaque.push_quads(transparent.vertices);
+    opaque
+}
+
+/// Give every vertex of a block the block's emission
+fn set_emission(vertices: &mut [Vertex], emission: f32) {
+    for vertex in vertices {
+        vertex.emission = emission;
+    }
+}
+
+/// Light reaching a face, taken from the cell in front of it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct FaceLight {
+    /// Block light level, see `block_light`
+    block_light: u8,
+    /// Opaque blocks stacked above the cell, up to `MAX_SKY_DEPTH`
+    sky_depth: u8,
+}
+
+impl FaceLight {
+    fn apply(self, vertex: &mut Vertex) {
+        vertex.block_light = self.block_light as f32 / MAX_LIGHT_LEVEL as f32;
+        vertex.skylight = SKYLIGHT_PER_BLOCK.powi(self.sky_depth as i32);
+    }
+}
+
+/// Give the four vertices of each of `faces` the light in front of that face
+fn set_face_light(vertices: &mut [Vertex], faces: &[usize], face_light: [FaceLight; 6]) {
+    for (face_vertices, &face) in vertices.chunks_mut(4).zip(faces) {
+        for vertex in face_vertices {
+            face_light[face].apply(vertex);
+        }
+    }
+}
+
+/// For every cell of a chunk and of the columns bordering it, how many opaque blocks are
+/// above it, capped at `MAX_SKY_DEPTH`. Indexed by `sky_depth_index`.
+fn sky_depths(
+    registry: &BlockRegistry,
+    block_at: impl Fn(i32, i32, i32) -> Option<BlockType>,
+) -> Vec<u8> {
+    let mut depths = vec![0; (CHUNK_SIZE + 2) * (CHUNK_SIZE + 2) * WORLD_HEIGHT];
+    for x in -1..=CHUNK_SIZE as i32 {
+        for z in -1..=CHUNK_SIZE as i32 {
+            let mut depth = 0;
+            for y in (0..WORLD_HEIGHT as i32).rev() {
+                depths[sky_depth_index(x, y, z)] = depth;
+                // Unloaded neighbors count as open sky
+                if block_at(x, y, z).is_some_and(|block_type| !registry.is_transparent(block_type))
                 {
-                    // Place structure blocks
-                    chunk_blocks[local_x as usize][local_z as usize][block_y as usize] =
-                        block.block_type;
+                    depth = (depth + 1).min(MAX_SKY_DEPTH);
                 }
             }
         }
+    }
+    depths
+}
+
+/// Index into `sky_depths` of chunk-local (x, y, z), with x and z from -1 to `CHUNK_SIZE`
+fn sky_depth_index(x: i32, y: i32, z: i32) -> usize {
+    (((x + 1) as usize * (CHUNK_SIZE + 2)) + (z + 1) as usize) * WORLD_HEIGHT + y as usize
+}
+
+/// For each face direction: the axis along its normal, then the axes its texture's u and v
+/// run along (matching the UV layout in `create_cube_vertices_selective`)
+const FACE_AXES: [(usize, usize, usize); 6] = [
+    (2, 0, 1), // Front (+Z)
+    (2, 0, 1), // Back (-Z)
+    (0, 2, 1), // Left (-X)
+    (0, 2, 1), // Right (+X)
+    (1, 0, 2), // Top (+Y)
+    (1, 0, 2), // Bottom (-Y)
+];
+
+fn face_texture(textures: &FaceTextures, face: usize) -> u32 {
+    [
+        textures.front,
+        textures.back,
+        textures.left,
+        textures.right,
+        textures.top,
+        textures.bottom,
+    ][face]
+}
+
+/// One face of the block at `origin`, stretched to `size` blocks along its u and v axes,
+/// with the same ambient occlusion and light on every corner.
+/// UVs run 0..size so the tile repeats once per block, like the unmerged faces.
+fn merged_face_vertices(
+    origin: [f32; 3],
+    face: usize,
+    texture_id: u32,
+    ao_level: u8,
+    face_light: FaceLight,
+    size: [usize; 2],
+) -> Vec<Vertex> {
+    let (_, u_axis, v_axis) = FACE_AXES[face];
+    let mut vertices = create_cube_vertices_selective(
+        origin[0],
+        origin[1],
+        origin[2],
+        &FaceTextures::all_same(texture_id),
+        &[face],
+        0,
+        |_, _, _| false,
+    );
+
+    for vertex in &mut vertices {
+        vertex.ao = ao_factor(ao_level);
+        face_light.apply(vertex);
+        for (uv_index, (axis, length)) in [(u_axis, size[0]), (v_axis, size[1])]
+            .into_iter()
+            .enumerate()
+        {
+            if vertex.position[axis] > origin[axis] + 0.5 {
+                vertex.position[axis] = origin[axis] + length as f32;
+            }
+            vertex.tex_coords[uv_index] *= length as f32;
+        }
+    }
+
+    vertices
+}
 
-        // Generate vertices with face culling
+/// Run `emit` for every non-air block with at least one visible face, passing its world
+/// position, face textures, visible faces, texture rotation (`None` if the block
+/// doesn't use random rotation), whether it is transparent, its emission, the light in
+/// front of each visible face, and a lookup of which neighbors (by offset) are
+/// opaque, for ambient occlusion. Transparent blocks come after all opaque ones so alpha
+/// blending sees the terrain behind them.
+fn for_each_visible_block(
+    chunk_pos: ChunkPos,
+    chunk_blocks: &ChunkBlocks,
+    registry: &BlockRegistry,
+    mesh_options: MeshOptions,
+    neighbor_block: impl Fn(i32, i32, i32) -> Option<BlockType>,
+    block_light: impl Fn(i32, i32, i32) -> u8,
+    mut emit: impl FnMut(
+        [f32; 3],
+        FaceTextures,
+        &[usize],
+        Option<u8>,
+        bool,
+        f32,
+        [FaceLight; 6],
+        &dyn Fn(i32, i32, i32) -> bool,
+    ),
+) {
+    // Block at a position relative to chunk-local (x, y, z), looking into neighbors if needed
+    let block_at = |x: i32, y: i32, z: i32| -> Option<BlockType> {
+        if !(0..WORLD_HEIGHT as i32).contains(&y) {
+            None
+        } else if (0..CHUNK_SIZE as i32).contains(&x) && (0..CHUNK_SIZE as i32).contains(&z) {
+            Some(chunk_blocks.get(x as usize, y as usize, z as usize))
+        } else {
+            neighbor_block(
+                chunk_pos.x * CHUNK_SIZE as i32 + x,
+                y,
+                chunk_pos.z * CHUNK_SIZE as i32 + z,
+            )
+        }
+    };
+
+    let sky_depths = sky_depths(registry, block_at);
+
+    // Generate vertices with face culling
+    for transparent in [false, true] {
         for x in 0..CHUNK_SIZE {
             for z in 0..CHUNK_SIZE {
                 for y in 0..WORLD_HEIGHT {
-                    let block_type = chunk_blocks[x][z][y];
+                    let block_type = chunk_blocks.get(x, y, z);
 
-                    // Skip air blocks
-                    if block_type == BlockType::Air {
+                    // Skip air blocks, and blocks drawn in the other pass
+                    if block_type == BlockType::Air
+                        || registry.is_transparent(block_type) != transparent
+                    {
                         continue;
                     }
 
@@ -161,77 +852,105 @@ impl ChunkGenerator {
                     let world_z = (chunk_pos.z * CHUNK_SIZE as i32 + z as i32) as f32;
 
                     // Check each face for culling
-                    let mut faces_to_render = Vec::new();
-
-                    // Check each direction for adjacent blocks
-                    let directions = [
-                        (0, 0, 1),  // Front (+Z)
-                        (0, 0, -1), // Back (-Z)
-                        (-1, 0, 0), // Left (-X)
-                        (1, 0, 0),  // Right (+X)
-                        (0, 1, 0),  // Top (+Y)
-                        (0, -1, 0), // Bottom (-Y)
-                    ];
+                    let faces_to_render = visible_faces(block_type, registry, |dx, dy, dz| {
+                        block_at(x as i32 + dx, y as i32 + dy, z as i32 + dz)
+                    });
 
-                    for (i, &(dx, dy, dz)) in directions.iter().enumerate() {
-                        let adj_x = x as i32 + dx;
-                        let adj_y = y as i32 + dy;
-                        let adj_z = z as i32 + dz;
-
-                        let should_render_face = if adj_x < 0
-                            || adj_x >= CHUNK_SIZE as i32
-                            || adj_z < 0
-                            || adj_z >= CHUNK_SIZE as i32
-                            || adj_y < 0
-                            || adj_y >= WORLD_HEIGHT as i32
-                        {
-                            // Face is at chunk boundary, check if there's a block in the neighboring position
-                            if adj_y < 0 || adj_y >= WORLD_HEIGHT as i32 {
-                                // Out of world bounds vertically, always render
-                                true
-                            } else {
-                                // For chunk boundaries, we'll assume render face (can be optimized later)
-                                true
-                            }
-                        } else {
-                            // Check if adjacent block is air (render face) or solid (cull face)
-                            let adj_block =
-                                chunk_blocks[adj_x as usize][adj_z as usize][adj_y as usize];
-                            adj_block == BlockType::Air
-                        };
+                    // Only generate vertices for visible faces
+                    if !faces_to_render.is_empty() {
+                        let mut textures = registry.get_textures(block_type);
+                        if block_type == BlockType::Grass && mesh_options.smart_grass_sides {
+                            textures = grass_face_textures(textures, |dx, dy, dz| {
+                                block_at(x as i32 + dx, y as i32 + dy, z as i32 + dz)
+                                    .is_some_and(|adj_block| registry.is_solid(adj_block))
+                            });
+                        }
+                        let texture_rotation = registry
+                            .has_random_rotation(block_type)
+                            .then(|| texture_rotation_at(world_x as i32, y as i32, world_z as i32));
 
-                        if should_render_face {
-                            faces_to_render.push(i);
+                        let mut face_light = [FaceLight::default(); 6];
+                        for &face in &faces_to_render {
+                            let [dx, dy, dz] = FACE_NORMALS[face];
+                            let (x, y, z) = (x as i32 + dx, y as i32 + dy, z as i32 + dz);
+                            face_light[face] = FaceLight {
+                                block_light: block_light(
+                                    chunk_pos.x * CHUNK_SIZE as i32 + x,
+                                    y,
+                                    chunk_pos.z * CHUNK_SIZE as i32 + z,
+                                ),
+                                // Past the top of the world is open sky, and nothing
+                                // below the bottom is ever seen
+                                sky_depth: if (0..WORLD_HEIGHT as i32).contains(&y) {
+                                    sky_depths[sky_depth_index(x, y, z)]
+                                } else {
+                                    0
+                                },
+                            };
                         }
-                    }
 
-                    // Only generate vertices for visible faces
-                    if !faces_to_render.is_empty() {
-                        let textures = registry.get_textures(block_type);
-
-                        let vertex_offset = vertices.len() as u32;
-                        let cube_vertices = create_cube_vertices_selective(
-                            world_x,
-                            y as f32,
-                            world_z,
-                            &textures,
+                        let is_opaque = |dx: i32, dy: i32, dz: i32| {
+                            block_at(x as i32 + dx, y as i32 + dy, z as i32 + dz)
+                                .is_some_and(|adj_block| !registry.is_transparent(adj_block))
+                        };
+
+                        emit(
+                            [world_x, y as f32, world_z],
+                            textures,
                             &faces_to_render,
+                            texture_rotation,
+                            transparent,
+                            registry.emission(block_type),
+                            face_light,
+                            &is_opaque,
                         );
-                        vertices.extend(cube_vertices);
-
-                        let cube_indices =
-                            create_cube_indices_selective(&faces_to_render, vertex_offset);
-                        indices.extend(cube_indices);
                     }
                 }
             }
         }
+    }
+}
+
+/// Write the parts of `structures` that fall inside this chunk, marking them as structure blocks
+fn place_structures(
+    chunk_pos: ChunkPos,
+    structures: &[PlacedStructure],
+    chunk_blocks: &mut ChunkBlocks,
+) {
+    for structure in structures {
+        for block in &structure.blocks {
+            let block_x = structure.world_x + block.relative_pos.0;
+            let block_y = structure.world_y + block.relative_pos.1;
+            let block_z = structure.world_z + block.relative_pos.2;
 
-        (ChunkData { vertices, indices }, chunk_blocks)
+            // Check if this block is within the current chunk
+            let local_x = block_x - (chunk_pos.x * CHUNK_SIZE as i32);
+            let local_z = block_z - (chunk_pos.z * CHUNK_SIZE as i32);
+
+            if local_x >= 0
+                && local_x < CHUNK_SIZE as i32
+                && local_z >= 0
+                && local_z < CHUNK_SIZE as i32
+                && block_y >= 0
+                && block_y < WORLD_HEIGHT as i32
+            {
+                chunk_blocks.set_structure_block(
+                    local_x as usize,
+                    block_y as usize,
+                    local_z as usize,
+                    block.block_type,
+                );
+            }
+        }
     }
 }
 
 impl Chunk {
+    /// GPU memory held by this chunk's mesh
+    pub fn gpu_bytes(&self) -> u64 {
+        self.vertex_bytes + self.index_bytes
+    }
+
     pub fn from_data(chunk_data: ChunkData, device: &wgpu::Device) -> Self {
         use wgpu::util::DeviceExt;
 
@@ -248,9 +967,348 @@ impl Chunk {
         });
 
         Self {
+            num_opaque_indices: chunk_data.num_opaque_indices,
+            num_transparent_indices: chunk_data.indices.len() as u32
+                - chunk_data.num_opaque_indices,
+            num_vertices: chunk_data.vertices.len() as u32,
+            vertex_bytes: vertex_buffer.size(),
+            index_bytes: index_buffer.size(),
             vertex_buffer,
             index_buffer,
-            num_indices: chunk_data.indices.len() as u32,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structures::StructureType;
+
+    #[test]
+    fn test_covered_grass_sides_show_dirt() {
+        let textures = BlockRegistry::new().get_textures(BlockType::Grass);
+        let grass_side = TextureId::GrassSide as u32;
+        let dirt = TextureId::Dirt as u32;
+
+        // Open above: every side keeps the green overlay
+        let open = grass_face_textures(textures, |_, _, _| false);
+        assert_eq!(open.front, grass_side);
+        assert_eq!(open.right, grass_side);
+
+        // Overhang above the +X side only
+        let overhang = grass_face_textures(textures, |dx, dy, dz| (dx, dy, dz) == (1, 1, 0));
+        assert_eq!(overhang.right, dirt);
+        assert_eq!(overhang.left, grass_side);
+        assert_eq!(overhang.top, TextureId::GrassTop as u32);
+
+        // Block on top: all sides turn to dirt
+        let covered = grass_face_textures(textures, |dx, dy, dz| (dx, dy, dz) == (0, 1, 0));
+        assert_eq!(
+            [covered.front, covered.back, covered.left, covered.right],
+            [dirt; 4]
+        );
+    }
+
+    #[test]
+    fn test_greedy_mesh_matches_naive_mesh_on_flat_plain() {
+        let mut chunk_blocks = ChunkBlocks::new();
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for y in 0..3 {
+                    chunk_blocks.set(x, y, z, BlockType::Planks);
+                }
+                chunk_blocks.set(x, 3, z, BlockType::Grass);
+            }
+        }
+        let chunk_pos = ChunkPos { x: -1, z: 2 };
+        let registry = BlockRegistry::new();
+        let naive = mesh_chunk_blocks(
+            chunk_pos,
+            &chunk_blocks,
+            &registry,
+            MeshOptions::default(),
+            |_, _, _| None,
+            |_, _, _| 0,
+        );
+        let greedy = mesh_chunk_blocks_greedy(
+            chunk_pos,
+            &chunk_blocks,
+            &registry,
+            MeshOptions::default(),
+            |_, _, _| None,
+            |_, _, _| 0,
+        );
+
+        // Top, bottom, a grass strip and a planks strip per side
+        assert_eq!(greedy.vertices.len(), (2 + 4 * 2) * 4);
+        assert!(greedy.vertices.len() * 20 < naive.vertices.len());
+
+        // Every naive face is covered by a greedy quad that shows the same texels: sample
+        // points inside the face and compare wrapped UVs (UVs are affine across a quad)
+        let sub = |a: [f32; 3], b: [f32; 3]| [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+        let dot = |a: [f32; 3], b: [f32; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+        for face in naive.vertices.chunks(4) {
+            let center = [0, 1, 2].map(|i| face.iter().map(|v| v.position[i]).sum::<f32>() / 4.0);
+            let center_uv = [0, 1].map(|i| face.iter().map(|v| v.tex_coords[i]).sum::<f32>() / 4.0);
+
+            for corner in face {
+                let point = [0, 1, 2].map(|i| 0.75 * corner.position[i] + 0.25 * center[i]);
+                let expected = [0, 1].map(|i| 0.75 * corner.tex_coords[i] + 0.25 * center_uv[i]);
+
+                let quad = greedy
+                    .vertices
+                    .chunks(4)
+                    .find(|quad| {
+                        quad[0].normal == corner.normal
+                            && quad[0].texture_id == corner.texture_id
+                            && dot(sub(point, quad[0].position), corner.normal).abs() < 1e-4
+                            && [0, 1, 2].into_iter().all(|i| {
+                                let min =
+                                    quad.iter().map(|v| v.position[i]).fold(f32::MAX, f32::min);
+                                let max =
+                                    quad.iter().map(|v| v.position[i]).fold(f32::MIN, f32::max);
+                                (min..=max).contains(&point[i])
+                            })
+                    })
+                    .expect("naive face not covered by a greedy quad");
+
+                let edge_u = sub(quad[1].position, quad[0].position);
+                let edge_v = sub(quad[3].position, quad[0].position);
+                let offset = sub(point, quad[0].position);
+                let a = dot(offset, edge_u) / dot(edge_u, edge_u);
+                let b = dot(offset, edge_v) / dot(edge_v, edge_v);
+                for (i, expected) in expected.into_iter().enumerate() {
+                    let uv = quad[0].tex_coords[i]
+                        + a * (quad[1].tex_coords[i] - quad[0].tex_coords[i])
+                        + b * (quad[3].tex_coords[i] - quad[0].tex_coords[i]);
+                    assert!((uv.rem_euclid(1.0) - expected).abs() < 1e-4);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_water_culls_against_itself_but_not_the_floor() {
+        // One stone layer under two layers of water, with the same next door
+        let mut chunk_blocks = ChunkBlocks::new();
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                chunk_blocks.set(x, 0, z, BlockType::Stone);
+                chunk_blocks.set(x, 1, z, BlockType::Water);
+                chunk_blocks.set(x, 2, z, BlockType::Water);
+            }
+        }
+        let neighbor_block = |_, y, _| Some(chunk_blocks.get(0, y as usize, 0));
+        let registry = BlockRegistry::new();
+        let water = TextureId::Water as u32;
+
+        for greedy in [false, true] {
+            let mesh_options = MeshOptions {
+                greedy,
+                ..MeshOptions::default()
+            };
+            let chunk_data = mesh_chunk_blocks(
+                ChunkPos { x: 0, z: 0 },
+                &chunk_blocks,
+                &registry,
+                mesh_options,
+                neighbor_block,
+                |_, _, _| 0,
+            );
+
+            // Stone top (seen through the water) and bottom, and the water surface only,
+            // left one quad per block even when greedy so every vertex can ripple
+            let faces: Vec<_> = chunk_data.vertices.chunks(4).collect();
+            let water_faces = faces.iter().filter(|f| f[0].texture_id == water).count();
+            assert_eq!(water_faces, CHUNK_SIZE * CHUNK_SIZE);
+            assert!(faces[..faces.len() - water_faces]
+                .iter()
+                .all(|f| f[0].texture_id != water));
+            assert_eq!(
+                chunk_data.num_opaque_indices as usize,
+                (faces.len() - water_faces) * 6
+            );
+            assert!(faces[faces.len() - water_faces..]
+                .iter()
+                .all(|f| f[0].texture_id == water && f[0].normal == [0.0, 1.0, 0.0]));
+        }
+    }
+
+    #[test]
+    fn test_glowstone_faces_carry_its_emission() {
+        // A glowstone block set into a stone floor, poking out of it
+        let mut chunk_blocks = ChunkBlocks::new();
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                chunk_blocks.set(x, 0, z, BlockType::Stone);
+            }
+        }
+        chunk_blocks.set(5, 1, 5, BlockType::Glowstone);
+        let registry = BlockRegistry::new();
+        let glowstone = TextureId::Glowstone as u32;
+
+        for greedy in [false, true] {
+            let mesh_options = MeshOptions {
+                greedy,
+                ..MeshOptions::default()
+            };
+            let chunk_data = mesh_chunk_blocks(
+                ChunkPos { x: 0, z: 0 },
+                &chunk_blocks,
+                &registry,
+                mesh_options,
+                |_, _, _| None,
+                |_, _, _| 0,
+            );
+
+            // Five exposed sides of the glowstone, fully lit; the stone doesn't glow
+            let glowing: Vec<_> = chunk_data
+                .vertices
+                .iter()
+                .filter(|v| v.texture_id == glowstone)
+                .collect();
+            assert_eq!(glowing.len(), 5 * 4);
+            assert!(glowing.iter().all(|v| v.emission == 1.0));
+            assert!(chunk_data
+                .vertices
+                .iter()
+                .filter(|v| v.texture_id != glowstone)
+                .all(|v| v.emission == 0.0));
+        }
+    }
+
+    #[test]
+    fn test_faces_under_blocks_get_less_skylight() {
+        // A stone floor, with a one block roof over x = 0..4 and a three block one (plus a
+        // glass pane that doesn't count) over x = 8..12
+        let mut chunk_blocks = ChunkBlocks::new();
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                chunk_blocks.set(x, 0, z, BlockType::Stone);
+                if x < 4 {
+                    chunk_blocks.set(x, 5, z, BlockType::Planks);
+                } else if (8..12).contains(&x) {
+                    for y in 5..8 {
+                        chunk_blocks.set(x, y, z, BlockType::Planks);
+                    }
+                    chunk_blocks.set(x, 10, z, BlockType::Glass);
+                }
+            }
+        }
+        let registry = BlockRegistry::new();
+
+        for greedy in [false, true] {
+            let mesh_options = MeshOptions {
+                greedy,
+                ..MeshOptions::default()
+            };
+            let chunk_data = mesh_chunk_blocks(
+                ChunkPos { x: 0, z: 0 },
+                &chunk_blocks,
+                &registry,
+                mesh_options,
+                |_, _, _| None,
+                |_, _, _| 0,
+            );
+
+            // Skylight on the floor's top faces, by x
+            let floor_skylight = |x: f32| {
+                let skylight: Vec<f32> = chunk_data
+                    .vertices
+                    .chunks(4)
+                    .filter(|face| {
+                        face[0].normal == [0.0, 1.0, 0.0]
+                            && face[0].position[1] == 1.0
+                            && face.iter().any(|v| v.position[0] <= x)
+                            && face.iter().any(|v| v.position[0] >= x + 1.0)
+                    })
+                    .flat_map(|face| face.iter().map(|v| v.skylight))
+                    .collect();
+                assert!(!skylight.is_empty());
+                assert!(skylight.iter().all(|&value| value == skylight[0]));
+                skylight[0]
+            };
+            assert_eq!(floor_skylight(1.0), SKYLIGHT_PER_BLOCK);
+            assert_eq!(floor_skylight(5.0), 1.0);
+            assert_eq!(floor_skylight(9.0), SKYLIGHT_PER_BLOCK.powi(3));
+            assert_eq!(floor_skylight(14.0), 1.0);
+
+            // The roofs stay in full sunlight, glass over one of them doesn't count
+            assert!(chunk_data
+                .vertices
+                .iter()
+                .filter(|v| v.normal == [0.0, 1.0, 0.0] && v.position[1] > 1.0)
+                .all(|v| v.skylight == 1.0));
+        }
+    }
+
+    #[test]
+    fn test_trees_at_a_height_step_stand_on_the_ground() {
+        let generator = ChunkGenerator::new(42);
+        let terrain = Terrain::new(42);
+        let biome_manager = BiomeManager::new();
+        let registry = BlockRegistry::new();
+        let surface = |x: i32, z: i32| terrain.filled_height_at(x, z, &biome_manager) as i32;
+
+        let mut trees_checked = 0;
+        for chunk_x in -2..2 {
+            for chunk_z in -2..2 {
+                let chunk_pos = ChunkPos {
+                    x: chunk_x,
+                    z: chunk_z,
+                };
+                let structures = generator.generate_structures(chunk_pos, &terrain, &biome_manager);
+                let chunk_blocks =
+                    generator.generate_chunk_blocks(chunk_pos, &terrain, &biome_manager);
+
+                for tree in structures
+                    .iter()
+                    .filter(|s| matches!(s.structure_type, StructureType::Tree))
+                {
+                    let local_x = tree.world_x - chunk_x * CHUNK_SIZE as i32;
+                    let local_z = tree.world_z - chunk_z * CHUNK_SIZE as i32;
+                    if !(0..CHUNK_SIZE as i32).contains(&local_x)
+                        || !(0..CHUNK_SIZE as i32).contains(&local_z)
+                    {
+                        continue;
+                    }
+                    // Only trunks next to a 1-block height change
+                    let height = surface(tree.world_x, tree.world_z);
+                    let steps = [(1, 0), (-1, 0), (0, 1), (0, -1)].iter().any(|(dx, dz)| {
+                        (surface(tree.world_x + dx, tree.world_z + dz) - height).abs() == 1
+                    });
+                    if !steps {
+                        continue;
+                    }
+
+                    assert_eq!(tree.world_y, height);
+                    let (x, y, z) = (local_x as usize, tree.world_y as usize, local_z as usize);
+                    assert_eq!(chunk_blocks.get(x, y, z), BlockType::Wood);
+                    assert!(registry.is_solid(chunk_blocks.get(x, y - 1, z)));
+                    trees_checked += 1;
+                }
+            }
+        }
+        assert!(trees_checked > 0, "no tree next to a height step");
+    }
+
+    #[test]
+    fn test_generated_chunk_culls_faces_against_neighbor_terrain() {
+        let terrain = Terrain::with_kind(
+            42,
+            crate::terrain::TerrainKind::SuperFlat {
+                layers: vec![(BlockType::Stone, 3), (BlockType::Grass, 1)],
+            },
+        );
+        let (chunk_data, _) = ChunkGenerator::new(42).generate_chunk(
+            ChunkPos { x: 2, z: -3 },
+            &terrain,
+            &BiomeManager::new(),
+            &BlockRegistry::new(),
+        );
+
+        // Neighbors are solid at the same height, so only the top and the world-bottom faces remain
+        let faces = chunk_data.indices.len() / 6;
+        assert_eq!(faces, CHUNK_SIZE * CHUNK_SIZE * 2);
+    }
+}
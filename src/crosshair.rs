@@ -0,0 +1,192 @@
+use crate::ui::pixels_to_ndc;
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+/// Half the length of each crosshair arm, in pixels
+const ARM_LENGTH_PX: f32 = 10.0;
+/// Width of the crosshair lines, in pixels
+const THICKNESS_PX: f32 = 2.0;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct CrosshairVertex {
+    pub position: [f32; 2],
+}
+
+impl CrosshairVertex {
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<CrosshairVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[wgpu::VertexAttribute {
+                offset: 0,
+                shader_location: 0,
+                format: wgpu::VertexFormat::Float32x2,
+            }],
+        }
+    }
+}
+
+/// Plus-shaped marker at the center of the screen for aiming at blocks
+pub struct CrosshairRenderer {
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+}
+
+impl CrosshairRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        window_width: u32,
+        window_height: u32,
+    ) -> Self {
+        let vertices = create_crosshair_vertices(window_width, window_height);
+        let indices = create_crosshair_indices();
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Crosshair Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Crosshair Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Crosshair Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("crosshair.wgsl").into()),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Crosshair Pipeline Layout"),
+                bind_group_layouts: &[],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Crosshair Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[CrosshairVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always, // Overlay everything
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            render_pipeline,
+            vertex_buffer,
+            index_buffer,
+            num_indices: indices.len() as u32,
+        }
+    }
+
+    /// Rebuild the crosshair for a new window size so it keeps its pixel size
+    pub fn update_geometry(&self, queue: &wgpu::Queue, window_width: u32, window_height: u32) {
+        let vertices = create_crosshair_vertices(window_width, window_height);
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+    }
+
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+    }
+}
+
+/// A horizontal and a vertical bar crossing at the screen center, in NDC
+fn create_crosshair_vertices(window_width: u32, window_height: u32) -> Vec<CrosshairVertex> {
+    let center_x = window_width as f32 / 2.0;
+    let center_y = window_height as f32 / 2.0;
+    let half_thickness = THICKNESS_PX / 2.0;
+
+    // (half width, half height) of each bar in pixels
+    [
+        (ARM_LENGTH_PX, half_thickness),
+        (half_thickness, ARM_LENGTH_PX),
+    ]
+    .into_iter()
+    .flat_map(|(half_w, half_h)| {
+        [
+            (-half_w, half_h),
+            (half_w, half_h),
+            (half_w, -half_h),
+            (-half_w, -half_h),
+        ]
+    })
+    .map(|(dx, dy)| CrosshairVertex {
+        position: pixels_to_ndc(center_x + dx, center_y + dy, window_width, window_height),
+    })
+    .collect()
+}
+
+fn create_crosshair_indices() -> Vec<u16> {
+    vec![
+        0, 1, 2, 0, 2, 3, // Horizontal bar
+        4, 5, 6, 4, 6, 7, // Vertical bar
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crosshair_is_centered_and_keeps_its_pixel_size() {
+        for (width, height) in [(1280, 800), (3840, 1080)] {
+            let vertices = create_crosshair_vertices(width, height);
+            let xs = vertices.iter().map(|v| v.position[0]);
+            let ys = vertices.iter().map(|v| v.position[1]);
+            let (min_x, max_x) = (
+                xs.clone().fold(f32::MAX, f32::min),
+                xs.fold(f32::MIN, f32::max),
+            );
+            let (min_y, max_y) = (
+                ys.clone().fold(f32::MAX, f32::min),
+                ys.fold(f32::MIN, f32::max),
+            );
+
+            assert!((min_x + max_x).abs() < 1e-6 && (min_y + max_y).abs() < 1e-6);
+            assert!(((max_x - min_x) * width as f32 / 2.0 - ARM_LENGTH_PX * 2.0).abs() < 1e-3);
+            assert!(((max_y - min_y) * height as f32 / 2.0 - ARM_LENGTH_PX * 2.0).abs() < 1e-3);
+        }
+    }
+}
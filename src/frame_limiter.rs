@@ -0,0 +1,83 @@
+use std::time::{Duration, Instant};
+
+/// Sleeping is only accurate to about a millisecond, so the last stretch is spun
+const SPIN_TAIL: Duration = Duration::from_micros(1500);
+
+/// Caps the frame rate by waiting out the rest of each frame's time budget
+pub struct FrameLimiter {
+    frame_duration: Option<Duration>,
+    last_frame: Instant,
+}
+
+impl FrameLimiter {
+    /// `None` (or 0) disables the cap
+    pub fn new(target_fps: Option<u32>) -> Self {
+        Self {
+            frame_duration: target_fps
+                .filter(|&fps| fps > 0)
+                .map(|fps| Duration::from_secs_f64(1.0 / fps as f64)),
+            last_frame: Instant::now(),
+        }
+    }
+
+    /// Block until a full frame budget has passed since the previous call
+    pub fn wait(&mut self) {
+        let now = Instant::now();
+        let remaining = self.time_to_wait(now);
+        if remaining.is_zero() {
+            return;
+        }
+
+        let deadline = now + remaining;
+        if remaining > SPIN_TAIL {
+            std::thread::sleep(remaining - SPIN_TAIL);
+        }
+        while Instant::now() < deadline {
+            std::hint::spin_loop();
+        }
+    }
+
+    /// How long a frame ending at `now` still has to wait to fill its budget. The next
+    /// frame's budget starts when that wait is over.
+    fn time_to_wait(&mut self, now: Instant) -> Duration {
+        let Some(frame_duration) = self.frame_duration else {
+            self.last_frame = now;
+            return Duration::ZERO;
+        };
+
+        let deadline = self.last_frame + frame_duration;
+        if now >= deadline {
+            // Running behind, don't try to catch up with a burst of short frames
+            self.last_frame = now;
+            return Duration::ZERO;
+        }
+
+        self.last_frame = deadline;
+        deadline - now
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_waits_out_the_rest_of_each_frame() {
+        let ms = Duration::from_millis;
+        let mut limiter = FrameLimiter::new(Some(100));
+        let start = limiter.last_frame;
+
+        // 4 ms of work leaves 6 ms of the 10 ms budget; the next frame starts at 10 ms
+        assert_eq!(limiter.time_to_wait(start + ms(4)), ms(6));
+        assert_eq!(limiter.time_to_wait(start + ms(13)), ms(7));
+        // A slow frame isn't made up for with a short one afterwards
+        assert_eq!(limiter.time_to_wait(start + ms(45)), Duration::ZERO);
+        assert_eq!(limiter.time_to_wait(start + ms(46)), ms(9));
+
+        for uncapped in [None, Some(0)] {
+            let mut limiter = FrameLimiter::new(uncapped);
+            let start = limiter.last_frame;
+            assert_eq!(limiter.time_to_wait(start + ms(1)), Duration::ZERO);
+        }
+    }
+}
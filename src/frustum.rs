@@ -0,0 +1,85 @@
+use crate::aabb::Aabb;
+use cgmath::{InnerSpace, Matrix, Matrix4, Vector3, Vector4};
+
+/// Camera view volume as six inward-facing planes, for skipping off-screen geometry
+#[derive(Debug, Clone, Copy)]
+pub struct Frustum {
+    // (normal, distance) with normal.dot(p) + distance >= 0 for points inside
+    planes: [(Vector3<f32>, f32); 6],
+}
+
+impl Frustum {
+    /// Extract the planes from a view-projection matrix with wgpu's 0..1 clip depth
+    pub fn from_view_proj(view_proj: Matrix4<f32>) -> Self {
+        let row = |i: usize| view_proj.row(i);
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+
+        let plane = |v: Vector4<f32>| {
+            let normal = v.truncate();
+            let length = normal.magnitude();
+            (normal / length, v.w / length)
+        };
+
+        Self {
+            planes: [
+                plane(r3 + r0), // Left
+                plane(r3 - r0), // Right
+                plane(r3 + r1), // Bottom
+                plane(r3 - r1), // Top
+                plane(r2),      // Near (z >= 0)
+                plane(r3 - r2), // Far
+            ],
+        }
+    }
+
+    /// False only when the box is entirely outside one of the planes. Boxes near a
+    /// corner of the frustum may still pass, which is fine for culling.
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|(normal, distance)| {
+            // Corner of the box furthest along the plane normal
+            let corner = Vector3::new(
+                if normal.x >= 0.0 {
+                    aabb.max.x
+                } else {
+                    aabb.min.x
+                },
+                if normal.y >= 0.0 {
+                    aabb.max.y
+                } else {
+                    aabb.min.y
+                },
+                if normal.z >= 0.0 {
+                    aabb.max.z
+                } else {
+                    aabb.min.z
+                },
+            );
+            normal.dot(corner) + distance >= 0.0
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::camera::Camera;
+    use cgmath::{Deg, Point3};
+
+    #[test]
+    fn test_boxes_behind_and_beside_camera_are_culled() {
+        // Yaw 0 looks down +X
+        let camera = Camera::new(Point3::new(0.0, 50.0, 0.0), Deg(0.0), Deg(0.0), 16.0 / 9.0);
+        let frustum = Frustum::from_view_proj(camera.calc_matrix());
+
+        let chunk_at = |x: f32, z: f32| Aabb {
+            min: Point3::new(x, 0.0, z),
+            max: Point3::new(x + 16.0, 255.0, z + 16.0),
+        };
+
+        assert!(frustum.intersects_aabb(&chunk_at(32.0, -8.0)));
+        // The chunk the camera stands in is always visible
+        assert!(frustum.intersects_aabb(&chunk_at(-8.0, -8.0)));
+        assert!(!frustum.intersects_aabb(&chunk_at(-48.0, -8.0)));
+        assert!(!frustum.intersects_aabb(&chunk_at(0.0, 200.0)));
+    }
+}
@@ -0,0 +1,196 @@
+use crate::voxel::{
+    create_cube_indices_selective, create_cube_vertices_selective, FaceTextures, Vertex,
+};
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+/// Opacity of the placement preview
+pub const GHOST_ALPHA: f32 = 0.4;
+
+const ALL_FACES: [usize; 6] = [0, 1, 2, 3, 4, 5];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct GhostUniform {
+    alpha: f32,
+    _padding: [f32; 3], // 16-byte alignment
+}
+
+/// Translucent preview of the block that a click would place, drawn with the terrain shader
+pub struct GhostBlockRenderer {
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    num_indices: u32,
+    visible: bool,
+}
+
+impl GhostBlockRenderer {
+    /// `scene_bind_group_layouts` are the terrain pipeline's camera, light and texture layouts
+    pub fn new(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        shader: &wgpu::ShaderModule,
+        scene_bind_group_layouts: [&wgpu::BindGroupLayout; 3],
+    ) -> Self {
+        let vertices = create_cube_vertices_selective(
+            0.0,
+            0.0,
+            0.0,
+            &FaceTextures::all_same(0),
+            &ALL_FACES,
+            0,
+            |_, _, _| false,
+        );
+        let indices = create_cube_indices_selective(&ALL_FACES, 0);
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ghost Block Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ghost Block Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let uniform = GhostUniform {
+            alpha: GHOST_ALPHA,
+            _padding: [0.0; 3],
+        };
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ghost Block Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[uniform]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("ghost_block_bind_group_layout"),
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+            label: Some("ghost_block_bind_group"),
+        });
+
+        let [camera_layout, light_layout, texture_layout] = scene_bind_group_layouts;
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Ghost Block Pipeline Layout"),
+                bind_group_layouts: &[
+                    camera_layout,
+                    light_layout,
+                    texture_layout,
+                    &bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Ghost Block Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                buffers: &[Vertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: shader,
+                entry_point: "fs_ghost",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false, // See-through, so don't hide what's behind it
+                depth_compare: wgpu::CompareFunction::LessEqual,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            render_pipeline,
+            vertex_buffer,
+            index_buffer,
+            bind_group,
+            num_indices: indices.len() as u32,
+            visible: false,
+        }
+    }
+
+    /// Show the preview at a block position with the given face textures
+    pub fn show(&mut self, queue: &wgpu::Queue, pos: [i32; 3], textures: &FaceTextures) {
+        let vertices = create_cube_vertices_selective(
+            pos[0] as f32,
+            pos[1] as f32,
+            pos[2] as f32,
+            textures,
+            &ALL_FACES,
+            0,
+            |_, _, _| false,
+        );
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        self.visible = true;
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+    }
+
+    /// Draw the preview if it's shown, with the same scene bind groups as the terrain
+    pub fn render<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        scene_bind_groups: [&'a wgpu::BindGroup; 3],
+    ) {
+        if !self.visible {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        for (index, bind_group) in scene_bind_groups.into_iter().enumerate() {
+            render_pass.set_bind_group(index as u32, bind_group, &[]);
+        }
+        render_pass.set_bind_group(3, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+    }
+}
@@ -0,0 +1,518 @@
+use crate::blocks::BlockType;
+use crate::slot_ui::{add_to_slots, SlotUI, SlotVertex, MAX_STACK_SIZE};
+use crate::ui::pixels_to_ndc;
+use crate::ui_atlas::{glyph_uv, sprite_uv, UiSprite};
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+/// Storage grid size, below the hotbar's 10 slots
+pub const STORAGE_COLUMNS: usize = 9;
+pub const STORAGE_ROWS: usize = 3;
+pub const STORAGE_SLOTS: usize = STORAGE_COLUMNS * STORAGE_ROWS;
+
+const SLOT_SIZE_PX: f32 = 60.0;
+const GAP_PX: f32 = 6.0;
+const PANEL_PADDING_PX: f32 = 16.0;
+
+// Marks no picked storage slot in the shader uniform
+const NO_SLOT: u32 = u32::MAX;
+
+/// A slot on the inventory screen: one of the hotbar's or one of the storage grid's
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InventorySlot {
+    Hotbar(usize),
+    Storage(usize),
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct InventoryUniform {
+    picked_slot: u32,
+    atlas_tiles_per_row: u32,
+    _padding: [u32; 2], // 16-byte alignment
+    frame_uv: [f32; 4],
+    selected_frame_uv: [f32; 4],
+    digit_uv: [f32; 4],
+}
+
+impl InventoryUniform {
+    fn new(picked_slot: u32, atlas_tiles_per_row: u32) -> Self {
+        Self {
+            picked_slot,
+            atlas_tiles_per_row,
+            _padding: [0; 2],
+            frame_uv: sprite_uv(UiSprite::SlotFrame),
+            selected_frame_uv: sprite_uv(UiSprite::SlotFrameSelected),
+            digit_uv: glyph_uv('0'),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct StorageData {
+    // Four slots per vec4, 28 entries for the 27 slots
+    texture_ids: [[u32; 4]; 7],
+    counts: [[u32; 4]; 7],
+}
+
+/// Full inventory opened with E: a 3x9 storage grid over the middle of the screen.
+/// Clicking a slot picks its stack up and clicking another moves it there, swapping
+/// or merging with what was there. The hotbar stays visible below and takes part.
+pub struct InventoryScreen {
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    uniform_buffer: wgpu::Buffer,
+    storage_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    atlas_tiles_per_row: u32,
+    num_indices: u32,
+    storage: [Option<(BlockType, u32)>; STORAGE_SLOTS],
+    picked: Option<InventorySlot>,
+    visible: bool,
+}
+
+impl InventoryScreen {
+    /// The slot frames and block icons come from `slot_ui`'s texture bind groups
+    pub fn new(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        slot_ui: &SlotUI,
+        window_width: u32,
+        window_height: u32,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Inventory Screen Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("inventory_screen.wgsl").into()),
+        });
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Inventory Screen Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[InventoryUniform::new(
+                NO_SLOT,
+                slot_ui.atlas_tiles_per_row(),
+            )]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let storage_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Inventory Screen Storage Buffer"),
+            contents: bytemuck::cast_slice(&[StorageData::zeroed()]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let uniform_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[uniform_entry(0), uniform_entry(1)],
+            label: Some("inventory_screen_bind_group_layout"),
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: storage_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("inventory_screen_bind_group"),
+        });
+
+        let texture_bind_group_layout = slot_ui.texture_bind_group_layout();
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Inventory Screen Pipeline Layout"),
+                bind_group_layouts: &[
+                    &bind_group_layout,
+                    texture_bind_group_layout,
+                    texture_bind_group_layout,
+                ],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Inventory Screen Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[SlotVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        let (vertices, indices) = Self::create_geometry(window_width, window_height);
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Inventory Screen Vertex Buffer"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Inventory Screen Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self {
+            render_pipeline,
+            vertex_buffer,
+            index_buffer,
+            uniform_buffer,
+            storage_buffer,
+            bind_group,
+            atlas_tiles_per_row: slot_ui.atlas_tiles_per_row(),
+            num_indices: indices.len() as u32,
+            storage: [None; STORAGE_SLOTS],
+            picked: None,
+            visible: false,
+        }
+    }
+
+    /// Pixel rectangle of a storage slot, [left, top, right, bottom]. The grid is
+    /// centered on the screen.
+    fn slot_rect(slot: usize, window_width: u32, window_height: u32) -> [f32; 4] {
+        let grid_width = SLOT_SIZE_PX * STORAGE_COLUMNS as f32 + GAP_PX * 8.0;
+        let grid_height = SLOT_SIZE_PX * STORAGE_ROWS as f32 + GAP_PX * 2.0;
+        let start_x = (window_width as f32 - grid_width) / 2.0;
+        let start_y = (window_height as f32 - grid_height) / 2.0;
+
+        let left = start_x + (SLOT_SIZE_PX + GAP_PX) * (slot % STORAGE_COLUMNS) as f32;
+        let top = start_y + (SLOT_SIZE_PX + GAP_PX) * (slot / STORAGE_COLUMNS) as f32;
+        [left, top, left + SLOT_SIZE_PX, top + SLOT_SIZE_PX]
+    }
+
+    /// Background panel quad first, then one quad per storage slot
+    fn create_geometry(window_width: u32, window_height: u32) -> (Vec<SlotVertex>, Vec<u16>) {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+
+        let [first_left, first_top, ..] = Self::slot_rect(0, window_width, window_height);
+        let [.., last_right, last_bottom] =
+            Self::slot_rect(STORAGE_SLOTS - 1, window_width, window_height);
+        let panel = [
+            first_left - PANEL_PADDING_PX,
+            first_top - PANEL_PADDING_PX,
+            last_right + PANEL_PADDING_PX,
+            last_bottom + PANEL_PADDING_PX,
+        ];
+
+        let rects = std::iter::once(panel)
+            .chain((0..STORAGE_SLOTS).map(|i| Self::slot_rect(i, window_width, window_height)));
+        for [left_px, top_px, right_px, bottom_px] in rects {
+            let [x_left, y_top] = pixels_to_ndc(left_px, top_px, window_width, window_height);
+            let [x_right, y_bottom] =
+                pixels_to_ndc(right_px, bottom_px, window_width, window_height);
+
+            let vertex_start = vertices.len() as u16;
+            vertices.push(SlotVertex {
+                position: [x_left, y_bottom],
+                tex_coords: [0.0, 1.0],
+            });
+            vertices.push(SlotVertex {
+                position: [x_right, y_bottom],
+                tex_coords: [1.0, 1.0],
+            });
+            vertices.push(SlotVertex {
+                position: [x_right, y_top],
+                tex_coords: [1.0, 0.0],
+            });
+            vertices.push(SlotVertex {
+                position: [x_left, y_top],
+                tex_coords: [0.0, 0.0],
+            });
+
+            indices.extend(&[
+                vertex_start,
+                vertex_start + 1,
+                vertex_start + 2,
+                vertex_start,
+                vertex_start + 2,
+                vertex_start + 3,
+            ]);
+        }
+
+        (vertices, indices)
+    }
+
+    pub fn update_geometry(&self, queue: &wgpu::Queue, window_width: u32, window_height: u32) {
+        let (vertices, _) = Self::create_geometry(window_width, window_height);
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+    }
+
+    /// Storage slot under a pixel position, if any
+    pub fn slot_at(&self, x: f32, y: f32, window_width: u32, window_height: u32) -> Option<usize> {
+        (0..STORAGE_SLOTS).find(|&slot| {
+            let [left, top, right, bottom] = Self::slot_rect(slot, window_width, window_height);
+            (left..right).contains(&x) && (top..bottom).contains(&y)
+        })
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Show or hide the screen. Closing it drops whatever stack was picked up.
+    pub fn toggle(&mut self, queue: &wgpu::Queue) -> bool {
+        self.visible = !self.visible;
+        self.picked = None;
+        self.update_buffers(queue);
+        self.visible
+    }
+
+    /// Pick up the stack in `slot`, or move the picked up stack into it. Clicking the
+    /// picked slot again puts the stack back down.
+    pub fn click(
+        &mut self,
+        slot: InventorySlot,
+        hotbar: &mut [Option<(BlockType, u32)>; 10],
+        queue: &wgpu::Queue,
+    ) {
+        click_slot(&mut self.picked, &mut self.storage, hotbar, slot);
+        self.update_buffers(queue);
+    }
+
+    /// Put the picked up stack back down without moving it
+    pub fn clear_picked(&mut self, queue: &wgpu::Queue) {
+        self.picked = None;
+        self.update_buffers(queue);
+    }
+
+    pub fn storage(&self) -> [Option<(BlockType, u32)>; STORAGE_SLOTS] {
+        self.storage
+    }
+
+    /// Replace every storage slot, e.g. when loading a save. Drops the picked up stack.
+    pub fn set_storage(
+        &mut self,
+        storage: [Option<(BlockType, u32)>; STORAGE_SLOTS],
+        queue: &wgpu::Queue,
+    ) {
+        self.storage = storage;
+        self.picked = None;
+        self.update_buffers(queue);
+    }
+
+    /// Store one block, e.g. when the hotbar is full. Returns false when storage is full too.
+    pub fn add_block(&mut self, block_type: BlockType, queue: &wgpu::Queue) -> bool {
+        let added = add_to_slots(&mut self.storage, block_type);
+        self.update_buffers(queue);
+        added
+    }
+
+    fn update_buffers(&self, queue: &wgpu::Queue) {
+        let picked_slot = match self.picked {
+            Some(InventorySlot::Storage(slot)) => slot as u32,
+            _ => NO_SLOT,
+        };
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::cast_slice(&[InventoryUniform::new(picked_slot, self.atlas_tiles_per_row)]),
+        );
+
+        let mut data = StorageData::zeroed();
+        for (i, stack) in self.storage.iter().enumerate() {
+            if let Some((block_type, count)) = stack {
+                data.texture_ids[i / 4][i % 4] = SlotUI::block_type_to_texture_id(*block_type);
+                data.counts[i / 4][i % 4] = *count;
+            }
+        }
+        queue.write_buffer(&self.storage_buffer, 0, bytemuck::cast_slice(&[data]));
+    }
+
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, slot_ui: &'a SlotUI) {
+        let (texture_bind_group, ui_texture_bind_group) = slot_ui.texture_bind_groups();
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_bind_group(1, texture_bind_group, &[]);
+        render_pass.set_bind_group(2, ui_texture_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+    }
+}
+
+/// Handle a click on `slot`: pick up its stack when nothing is picked, otherwise move
+/// the picked stack there. A stack of the same block type merges as far as it fits and
+/// the rest stays behind; anything else swaps places.
+fn click_slot(
+    picked: &mut Option<InventorySlot>,
+    storage: &mut [Option<(BlockType, u32)>; STORAGE_SLOTS],
+    hotbar: &mut [Option<(BlockType, u32)>; 10],
+    slot: InventorySlot,
+) {
+    fn get<'a>(
+        storage: &'a mut [Option<(BlockType, u32)>; STORAGE_SLOTS],
+        hotbar: &'a mut [Option<(BlockType, u32)>; 10],
+        slot: InventorySlot,
+    ) -> &'a mut Option<(BlockType, u32)> {
+        match slot {
+            InventorySlot::Hotbar(i) => &mut hotbar[i],
+            InventorySlot::Storage(i) => &mut storage[i],
+        }
+    }
+
+    let Some(source) = picked.take() else {
+        if get(storage, hotbar, slot).is_some() {
+            *picked = Some(slot);
+        }
+        return;
+    };
+    if source == slot {
+        return;
+    }
+
+    let from = *get(storage, hotbar, source);
+    let to = *get(storage, hotbar, slot);
+    let (from, to) = match (from, to) {
+        (Some((block_type, count)), Some((target_type, target_count)))
+            if block_type == target_type =>
+        {
+            let moved = count.min(MAX_STACK_SIZE.saturating_sub(target_count));
+            let left = count - moved;
+            (
+                (left > 0).then_some((block_type, left)),
+                Some((block_type, target_count + moved)),
+            )
+        }
+        _ => (to, from),
+    };
+    *get(storage, hotbar, source) = from;
+    *get(storage, hotbar, slot) = to;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clicks_move_swap_and_merge_stacks() {
+        let mut picked = None;
+        let mut storage = [None; STORAGE_SLOTS];
+        let mut hotbar = [None; 10];
+        storage[0] = Some((BlockType::Stone, 10));
+        hotbar[2] = Some((BlockType::Dirt, 5));
+
+        // Clicking an empty slot with nothing picked does nothing
+        click_slot(
+            &mut picked,
+            &mut storage,
+            &mut hotbar,
+            InventorySlot::Storage(4),
+        );
+        assert_eq!(picked, None);
+
+        // Move stone into the empty hotbar slot
+        click_slot(
+            &mut picked,
+            &mut storage,
+            &mut hotbar,
+            InventorySlot::Storage(0),
+        );
+        assert_eq!(picked, Some(InventorySlot::Storage(0)));
+        click_slot(
+            &mut picked,
+            &mut storage,
+            &mut hotbar,
+            InventorySlot::Hotbar(0),
+        );
+        assert_eq!(picked, None);
+        assert_eq!(storage[0], None);
+        assert_eq!(hotbar[0], Some((BlockType::Stone, 10)));
+
+        // Different block types swap
+        click_slot(
+            &mut picked,
+            &mut storage,
+            &mut hotbar,
+            InventorySlot::Hotbar(2),
+        );
+        click_slot(
+            &mut picked,
+            &mut storage,
+            &mut hotbar,
+            InventorySlot::Hotbar(0),
+        );
+        assert_eq!(hotbar[0], Some((BlockType::Dirt, 5)));
+        assert_eq!(hotbar[2], Some((BlockType::Stone, 10)));
+
+        // Same block types merge up to a full stack and leave the rest behind
+        storage[1] = Some((BlockType::Stone, MAX_STACK_SIZE - 4));
+        click_slot(
+            &mut picked,
+            &mut storage,
+            &mut hotbar,
+            InventorySlot::Hotbar(2),
+        );
+        click_slot(
+            &mut picked,
+            &mut storage,
+            &mut hotbar,
+            InventorySlot::Storage(1),
+        );
+        assert_eq!(storage[1], Some((BlockType::Stone, MAX_STACK_SIZE)));
+        assert_eq!(hotbar[2], Some((BlockType::Stone, 6)));
+
+        // Clicking the picked slot again puts it back down
+        click_slot(
+            &mut picked,
+            &mut storage,
+            &mut hotbar,
+            InventorySlot::Hotbar(2),
+        );
+        click_slot(
+            &mut picked,
+            &mut storage,
+            &mut hotbar,
+            InventorySlot::Hotbar(2),
+        );
+        assert_eq!(picked, None);
+        assert_eq!(hotbar[2], Some((BlockType::Stone, 6)));
+    }
+}
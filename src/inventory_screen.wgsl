@@ -0,0 +1,142 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) quad_id: f32,
+}
+
+@vertex
+fn vs_main(model: VertexInput, @builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 0.0, 1.0);
+    out.tex_coords = model.tex_coords;
+
+    // Quad 0 is the background panel, quad N + 1 is storage slot N (4 vertices per quad)
+    out.quad_id = f32(vertex_index / 4u);
+
+    return out;
+}
+
+struct InventoryUniform {
+    // Storage slot picked up for moving, or 0xffffffff when none is
+    picked_slot: u32,
+    // Tiles along each side of the block atlas
+    atlas_tiles_per_row: u32,
+    // UI atlas rectangles of the slot frames (u_min, v_min, u_max, v_max)
+    frame_uv: vec4<f32>,
+    selected_frame_uv: vec4<f32>,
+    // Glyph of '0' in the UI atlas; digits 1-9 follow it in the same row
+    digit_uv: vec4<f32>,
+}
+
+struct StorageData {
+    // Texture ID and block count of each storage slot, four slots per vec4
+    texture_ids: array<vec4<u32>, 7>,
+    counts: array<vec4<u32>, 7>,
+}
+
+@group(0) @binding(0)
+var<uniform> inventory_uniform: InventoryUniform;
+
+@group(0) @binding(1)
+var<uniform> storage_data: StorageData;
+
+@group(1) @binding(0)
+var texture_atlas: texture_2d<f32>;
+
+@group(1) @binding(1)
+var atlas_sampler: sampler;
+
+@group(2) @binding(0)
+var ui_atlas: texture_2d<f32>;
+
+@group(2) @binding(1)
+var ui_sampler: sampler;
+
+// Stack count placement, in slot UV: digits are square and sit in the bottom-right corner
+const DIGIT_SIZE: f32 = 0.3;
+const COUNT_RIGHT: f32 = 0.94;
+const COUNT_BOTTOM: f32 = 0.94;
+
+// Same as the hotbar: glyph color of the stack count at `uv`, transparent elsewhere
+fn count_text(uv: vec2<f32>, count: u32) -> vec4<f32> {
+    if (count < 2u) {
+        return vec4<f32>(0.0);
+    }
+    let digits = select(1u, 2u, count >= 10u);
+    let origin = vec2<f32>(COUNT_RIGHT - f32(digits) * DIGIT_SIZE, COUNT_BOTTOM - DIGIT_SIZE);
+    let local = (uv - origin) / DIGIT_SIZE;
+    if (local.x < 0.0 || local.y < 0.0 || local.x >= f32(digits) || local.y >= 1.0) {
+        return vec4<f32>(0.0);
+    }
+
+    var digit = min(count, 99u) % 10u;
+    if (digits == 2u && local.x < 1.0) {
+        digit = min(count, 99u) / 10u;
+    }
+    let rect = inventory_uniform.digit_uv;
+    let glyph_uv = vec2<f32>(
+        rect.x + (f32(digit) + fract(local.x)) * (rect.z - rect.x),
+        mix(rect.y, rect.w, local.y)
+    );
+    return textureSampleLevel(ui_atlas, ui_sampler, glyph_uv, 0.0);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let quad_id = u32(in.quad_id);
+    if (quad_id == 0u) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.6);
+    }
+    let slot_id = quad_id - 1u;
+    let picked = slot_id == inventory_uniform.picked_slot;
+
+    var frame_rect = inventory_uniform.frame_uv;
+    if (picked) {
+        frame_rect = inventory_uniform.selected_frame_uv;
+    }
+    let frame = textureSampleLevel(ui_atlas, ui_sampler, mix(frame_rect.xy, frame_rect.zw, in.tex_coords), 0.0);
+    if (frame.a > 0.0) {
+        return frame;
+    }
+
+    let border_thickness = 0.05;
+    let is_border = in.tex_coords.x < border_thickness ||
+                   in.tex_coords.x > (1.0 - border_thickness) ||
+                   in.tex_coords.y < border_thickness ||
+                   in.tex_coords.y > (1.0 - border_thickness);
+    if (is_border) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    }
+
+    let texture_id = storage_data.texture_ids[slot_id / 4u][slot_id % 4u];
+    let count = storage_data.counts[slot_id / 4u][slot_id % 4u];
+
+    let count_color = count_text(in.tex_coords, count);
+    if (count_color.a > 0.5) {
+        return vec4<f32>(count_color.rgb, 1.0);
+    }
+
+    if (texture_id > 0u) {
+        let tiles_per_row = inventory_uniform.atlas_tiles_per_row;
+        let atlas_size = f32(tiles_per_row);
+        let texture_x = f32(texture_id % tiles_per_row);
+        let texture_y = f32(texture_id / tiles_per_row);
+
+        let inner_uv = (in.tex_coords - border_thickness) / (1.0 - 2.0 * border_thickness);
+        let atlas_uv = vec2<f32>(
+            (texture_x + inner_uv.x) / atlas_size,
+            (texture_y + inner_uv.y) / atlas_size
+        );
+        let texture_color = textureSampleLevel(texture_atlas, atlas_sampler, atlas_uv, 0.0);
+        return vec4<f32>(texture_color.rgb, 0.9);
+    } else if (picked) {
+        return vec4<f32>(0.3, 0.3, 0.3, 0.8);
+    } else {
+        return vec4<f32>(0.1, 0.1, 0.1, 0.6);
+    }
+}
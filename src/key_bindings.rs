@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use winit::keyboard::KeyCode;
+
+/// Something a key can be bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Forward,
+    Back,
+    Left,
+    Right,
+    Jump,
+    Run,
+    Descend,
+    SensitivityDown,
+    SensitivityUp,
+    SpeedDown,
+    SpeedUp,
+    HotbarSlot(usize),
+    ClearSlot,
+    ToggleDebug,
+    CycleRenderMode,
+    ReloadConfig,
+    RegenerateChunk,
+    RegenerateStructures,
+    ToggleSmoothNormals,
+    BakeAmbientOcclusion,
+    ToggleFullscreen,
+    TogglePhotoMode,
+    ToggleViewMode,
+    FovDown,
+    FovUp,
+    RenderDistanceDown,
+    RenderDistanceUp,
+    CycleTool,
+    ToggleInventory,
+    ToggleFly,
+    ToggleNoclip,
+    ToggleAreaTool,
+    BreakArea,
+    ToggleFastTime,
+    Save,
+    Load,
+    ExportObj,
+}
+
+/// Keys for each action, loaded from controls.toml. Keys are winit `KeyCode` names
+/// ("KeyW", "Space", "F3", ...) and an action can have several.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub forward: Vec<KeyCode>,
+    pub back: Vec<KeyCode>,
+    pub left: Vec<KeyCode>,
+    pub right: Vec<KeyCode>,
+    pub jump: Vec<KeyCode>,
+    pub run: Vec<KeyCode>,
+    /// Sink while flying
+    pub descend: Vec<KeyCode>,
+    /// Adjust mouse sensitivity and walking speed; saved to settings.toml on exit
+    pub sensitivity_down: Vec<KeyCode>,
+    pub sensitivity_up: Vec<KeyCode>,
+    pub speed_down: Vec<KeyCode>,
+    pub speed_up: Vec<KeyCode>,
+    /// One key per hotbar slot, leftmost first
+    pub hotbar: Vec<KeyCode>,
+    pub clear_slot: Vec<KeyCode>,
+    pub toggle_debug: Vec<KeyCode>,
+    pub cycle_render_mode: Vec<KeyCode>,
+    /// Reload biome.toml and controls.toml
+    pub reload_config: Vec<KeyCode>,
+    pub regenerate_chunk: Vec<KeyCode>,
+    pub regenerate_structures: Vec<KeyCode>,
+    pub toggle_smooth_normals: Vec<KeyCode>,
+    /// Re-mesh the loaded chunks once with slower, softer ambient occlusion for screenshots
+    pub bake_ambient_occlusion: Vec<KeyCode>,
+    pub toggle_fullscreen: Vec<KeyCode>,
+    pub toggle_photo_mode: Vec<KeyCode>,
+    /// Switch between first and third person
+    pub toggle_view_mode: Vec<KeyCode>,
+    /// Photo mode only
+    pub fov_down: Vec<KeyCode>,
+    pub fov_up: Vec<KeyCode>,
+    /// Change the time of day instead in photo mode
+    pub render_distance_down: Vec<KeyCode>,
+    pub render_distance_up: Vec<KeyCode>,
+    pub cycle_tool: Vec<KeyCode>,
+    pub toggle_inventory: Vec<KeyCode>,
+    pub toggle_fly: Vec<KeyCode>,
+    pub toggle_noclip: Vec<KeyCode>,
+    pub toggle_area_tool: Vec<KeyCode>,
+    /// Break the marked box while the area tool is on
+    pub break_area: Vec<KeyCode>,
+    /// Run the day/night cycle much faster, for checking lighting at every time of day
+    pub toggle_fast_time: Vec<KeyCode>,
+    pub save: Vec<KeyCode>,
+    pub load: Vec<KeyCode>,
+    /// Write the loaded chunks around the player to export.obj
+    pub export_obj: Vec<KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        use KeyCode::*;
+        Self {
+            forward: vec![KeyW, ArrowUp],
+            back: vec![KeyS, ArrowDown],
+            left: vec![KeyA, ArrowLeft],
+            right: vec![KeyD, ArrowRight],
+            jump: vec![Space],
+            run: vec![ControlLeft, ControlRight],
+            descend: vec![ShiftLeft, ShiftRight],
+            sensitivity_down: vec![Comma],
+            sensitivity_up: vec![Period],
+            speed_down: vec![Semicolon],
+            speed_up: vec![Quote],
+            hotbar: vec![
+                Digit1, Digit2, Digit3, Digit4, Digit5, Digit6, Digit7, Digit8, Digit9, Digit0,
+            ],
+            clear_slot: vec![Delete, Backspace],
+            toggle_debug: vec![F3],
+            cycle_render_mode: vec![F4],
+            reload_config: vec![F5],
+            regenerate_chunk: vec![F6],
+            regenerate_structures: vec![F7],
+            toggle_smooth_normals: vec![F8],
+            bake_ambient_occlusion: vec![F12],
+            toggle_fullscreen: vec![F11],
+            toggle_photo_mode: vec![KeyP],
+            toggle_view_mode: vec![KeyV],
+            fov_down: vec![BracketLeft],
+            fov_up: vec![BracketRight],
+            render_distance_down: vec![Minus, NumpadSubtract],
+            render_distance_up: vec![Equal, NumpadAdd],
+            cycle_tool: vec![KeyT],
+            toggle_inventory: vec![KeyE],
+            toggle_fly: vec![KeyF],
+            toggle_noclip: vec![KeyN],
+            toggle_area_tool: vec![KeyB],
+            break_area: vec![Enter],
+            toggle_fast_time: vec![KeyL],
+            save: vec![F9],
+            load: vec![F10],
+            export_obj: vec![KeyO],
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Load bindings from a TOML file. Actions missing from it keep their default keys.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        let bindings: KeyBindings = toml::from_str(&content)?;
+        Ok(bindings)
+    }
+
+    /// Reload bindings from file, keeping the current ones if it can't be read
+    pub fn reload_from_file<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        *self = Self::load_from_file(path)?;
+        Ok(())
+    }
+
+    /// Action bound to `key`. When a key is bound to several, hotbar slots win, then
+    /// the first in the order the fields are declared.
+    pub fn action(&self, key: KeyCode) -> Option<Action> {
+        if let Some(slot) = self.hotbar.iter().take(10).position(|&k| k == key) {
+            return Some(Action::HotbarSlot(slot));
+        }
+
+        let bindings = [
+            (Action::Forward, &self.forward),
+            (Action::Back, &self.back),
+            (Action::Left, &self.left),
+            (Action::Right, &self.right),
+            (Action::Jump, &self.jump),
+            (Action::Run, &self.run),
+            (Action::Descend, &self.descend),
+            (Action::SensitivityDown, &self.sensitivity_down),
+            (Action::SensitivityUp, &self.sensitivity_up),
+            (Action::SpeedDown, &self.speed_down),
+            (Action::SpeedUp, &self.speed_up),
+            (Action::ClearSlot, &self.clear_slot),
+            (Action::ToggleDebug, &self.toggle_debug),
+            (Action::CycleRenderMode, &self.cycle_render_mode),
+            (Action::ReloadConfig, &self.reload_config),
+            (Action::RegenerateChunk, &self.regenerate_chunk),
+            (Action::RegenerateStructures, &self.regenerate_structures),
+            (Action::ToggleSmoothNormals, &self.toggle_smooth_normals),
+            (Action::BakeAmbientOcclusion, &self.bake_ambient_occlusion),
+            (Action::ToggleFullscreen, &self.toggle_fullscreen),
+            (Action::TogglePhotoMode, &self.toggle_photo_mode),
+            (Action::ToggleViewMode, &self.toggle_view_mode),
+            (Action::FovDown, &self.fov_down),
+            (Action::FovUp, &self.fov_up),
+            (Action::RenderDistanceDown, &self.render_distance_down),
+            (Action::RenderDistanceUp, &self.render_distance_up),
+            (Action::CycleTool, &self.cycle_tool),
+            (Action::ToggleInventory, &self.toggle_inventory),
+            (Action::ToggleFly, &self.toggle_fly),
+            (Action::ToggleNoclip, &self.toggle_noclip),
+            (Action::ToggleAreaTool, &self.toggle_area_tool),
+            (Action::BreakArea, &self.break_area),
+            (Action::ToggleFastTime, &self.toggle_fast_time),
+            (Action::Save, &self.save),
+            (Action::Load, &self.load),
+            (Action::ExportObj, &self.export_obj),
+        ];
+        bindings
+            .into_iter()
+            .find(|(_, keys)| keys.contains(&key))
+            .map(|(action, _)| action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebinding_keeps_other_defaults() {
+        let bindings: KeyBindings =
+            toml::from_str("forward = [\"KeyZ\"]\nhotbar = [\"KeyQ\", \"KeyW\"]\n").unwrap();
+
+        assert_eq!(bindings.action(KeyCode::KeyZ), Some(Action::Forward));
+        assert_eq!(bindings.action(KeyCode::ArrowUp), None);
+        // Hotbar keys take priority over other bindings of the same key
+        assert_eq!(bindings.action(KeyCode::KeyW), Some(Action::HotbarSlot(1)));
+        assert_eq!(bindings.action(KeyCode::Digit3), None);
+        assert_eq!(bindings.action(KeyCode::F3), Some(Action::ToggleDebug));
+
+        assert!(toml::from_str::<KeyBindings>("jump = [\"NotAKey\"]\n").is_err());
+    }
+
+    #[test]
+    fn test_shipped_controls_file_matches_defaults() {
+        let bindings = KeyBindings::load_from_file("controls.toml").unwrap();
+        assert_eq!(bindings, KeyBindings::default());
+    }
+}
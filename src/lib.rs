@@ -0,0 +1,48 @@
+// Reusable voxel engine: world generation, meshing, block data and rendering helpers.
+// The `rustcraft` binary in main.rs adds the window, input handling and render loop.
+
+pub mod aabb;
+pub mod biome;
+pub mod block_light;
+pub mod blocks;
+pub mod camera;
+pub mod chunk;
+pub mod chunk_debug;
+pub mod crosshair;
+pub mod frame_limiter;
+pub mod frustum;
+pub mod ghost_block;
+pub mod inventory_screen;
+pub mod key_bindings;
+pub mod light;
+pub mod particles;
+pub mod progress_ui;
+pub mod raycast;
+pub mod render_mode;
+pub mod rng;
+pub mod save;
+pub mod settings;
+pub mod slot_ui;
+pub mod structure_file;
+pub mod structures;
+pub mod terrain;
+pub mod terrain_modifier;
+pub mod text_renderer;
+pub mod texture_atlas;
+pub mod texture_parser;
+pub mod tools;
+pub mod ui;
+pub mod ui_atlas;
+pub mod underwater;
+pub mod vignette;
+pub mod voxel;
+pub mod voxel_query;
+pub mod wireframe;
+pub mod world;
+
+pub use biome::BiomeManager;
+pub use blocks::{BlockRegistry, BlockType};
+pub use chunk::{ChunkGenerator, ChunkPos};
+pub use raycast::{create_camera_ray, raycast_blocks, RaycastHit};
+pub use terrain::{Terrain, TerrainKind};
+pub use world::{World, WorldStats};
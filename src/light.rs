@@ -1,38 +1,182 @@
 use bytemuck::{Pod, Zeroable};
 use cgmath::*;
+use std::time::Duration;
 use wgpu::util::DeviceExt;
 
+/// Most directional lights the shader sums per fragment
+pub const MAX_LIGHTS: usize = 4;
+
+/// Time of day the game starts at, in the morning
+const START_TIME_OF_DAY: f32 = 0.1;
+
+// Sun color low over the horizon and the moonlight that replaces it at night
+const SUNSET_COLOR: [f32; 3] = [1.0, 0.55, 0.25];
+const MOON_COLOR: [f32; 3] = [0.45, 0.55, 0.9];
+/// Moonlight brightness relative to the midday sun
+const MOON_INTENSITY: f32 = 0.2;
+/// How much of the ambient term is left at midnight, tinted blue
+const NIGHT_AMBIENT: [f32; 3] = [0.25, 0.3, 0.45];
+/// Direction the optional fill light shines in: low and toward +Z, onto the walls the
+/// sun's path (tilted toward +Z) never faces
+const FILL_LIGHT_DIRECTION: [f32; 3] = [0.3, -0.4, 1.0];
+
+/// Midday sky of biomes that don't set their own
+pub const DAY_SKY: [f32; 3] = [0.5, 0.8, 1.0];
+const NIGHT_SKY: [f32; 3] = [0.02, 0.03, 0.08];
+const SUNSET_SKY: [f32; 3] = [0.95, 0.55, 0.35];
+
+/// How long the sky and fog take to change over to a new biome's colors
+const BIOME_BLEND_SECS: f32 = 1.0;
+/// Fog starts at this fraction of the fog distance and is solid at the full distance
+const FOG_START_FRACTION: f32 = 0.6;
+
+/// One directional light as laid out in the shader
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
-pub struct LightUniform {
+pub struct LightData {
     pub direction: [f32; 3],
-    pub _padding: f32,
-    pub color: [f32; 3],
     pub intensity: f32,
+    pub color: [f32; 3],
+    pub _padding: f32,
 }
 
-pub struct DirectionalLight {
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct LightsUniform {
+    /// rgb ambient term, added before any light so shadows are never fully black
+    pub ambient: [f32; 4],
+    /// rgb color far terrain fades into (a unused)
+    pub fog_color: [f32; 4],
+    pub count: u32,
+    /// Distances from the player where fog starts and where it hides everything. No fog
+    /// unless the end is past the start.
+    pub fog_start: f32,
+    pub fog_end: f32,
+    pub _padding: u32, // 16-byte alignment for the array
+    pub lights: [LightData; MAX_LIGHTS],
+}
+
+impl LightsUniform {
+    /// Pack the ambient term and lights, without fog. Lights past `MAX_LIGHTS` are dropped.
+    pub fn new(ambient: Vector3<f32>, lights: &[Light]) -> Self {
+        let mut data = [LightData::zeroed(); MAX_LIGHTS];
+        for (slot, light) in data.iter_mut().zip(lights) {
+            *slot = LightData {
+                direction: light.direction.into(),
+                intensity: light.intensity,
+                color: light.color.into(),
+                _padding: 0.0,
+            };
+        }
+
+        Self {
+            ambient: [ambient.x, ambient.y, ambient.z, 0.0],
+            fog_color: [0.0; 4],
+            count: lights.len().min(MAX_LIGHTS) as u32,
+            fog_start: 0.0,
+            fog_end: 0.0,
+            _padding: 0,
+            lights: data,
+        }
+    }
+
+    /// Fade everything between `start` and `end` blocks from the player into `color`
+    pub fn with_fog(mut self, color: [f32; 3], start: f32, end: f32) -> Self {
+        self.fog_color = [color[0], color[1], color[2], 0.0];
+        self.fog_start = start;
+        self.fog_end = end;
+        self
+    }
+}
+
+/// A color easing from one value to another over `BIOME_BLEND_SECS`
+#[derive(Copy, Clone, Debug)]
+struct ColorBlend {
+    from: [f32; 3],
+    to: [f32; 3],
+    /// 0.0 at `from`, 1.0 once it has arrived at `to`
+    progress: f32,
+}
+
+impl ColorBlend {
+    fn new(color: [f32; 3]) -> Self {
+        Self {
+            from: color,
+            to: color,
+            progress: 1.0,
+        }
+    }
+
+    fn current(&self) -> [f32; 3] {
+        let t = smoothstep(0.0, 1.0, self.progress);
+        [0, 1, 2].map(|i| self.from[i] + (self.to[i] - self.from[i]) * t)
+    }
+
+    /// Start easing toward `color` from wherever the blend is now, so changing biomes
+    /// again halfway through doesn't jump
+    fn retarget(&mut self, color: [f32; 3]) {
+        if color != self.to {
+            self.from = self.current();
+            self.to = color;
+            self.progress = 0.0;
+        }
+    }
+
+    fn advance(&mut self, dt: Duration) {
+        self.progress = (self.progress + dt.as_secs_f32() / BIOME_BLEND_SECS).min(1.0);
+    }
+}
+
+/// A directional light such as the sun, the moon or a dim fill light
+#[derive(Copy, Clone, Debug)]
+pub struct Light {
     pub direction: Vector3<f32>,
     pub color: Vector3<f32>,
     pub intensity: f32,
-    uniform: LightUniform,
+}
+
+/// A single sun plus a grey ambient term. The sun gets whatever brightness the ambient
+/// term leaves, so faces pointing straight at it stay fully lit.
+fn default_lights(ambient: f32) -> (Vec<Light>, Vector3<f32>) {
+    let ambient = ambient.clamp(0.0, 1.0);
+    let sun = Light {
+        direction: Vector3::new(-0.5, -1.0, -0.5).normalize(), // More angled sunlight
+        color: Vector3::new(1.0, 1.0, 1.0),                    // Pure white light
+        intensity: 1.0 - ambient,
+    };
+    (vec![sun], Vector3::new(ambient, ambient, ambient))
+}
+
+/// The scene's directional lights plus an ambient term, shared by all chunk shading.
+/// The first light is the sun, which `update` moves through a day/night cycle; the
+/// second, if any, is the fill light from `set_fill_light`.
+pub struct DirectionalLight {
+    pub lights: Vec<Light>,
+    pub ambient: Vector3<f32>,
+    // Midday values of the sun intensity, ambient term and fill light intensity
+    sun_intensity: f32,
+    day_ambient: Vector3<f32>,
+    fill_intensity: f32,
+    // Position in the day/night cycle, see `set_time_of_day`
+    time_of_day: f32,
+    day_length: Duration,
+    // Midday sky and fog colors of the biome the player is in, eased between biomes
+    sky: ColorBlend,
+    fog: ColorBlend,
+    // Distance where the fog is solid, 0 for none
+    fog_distance: f32,
+    uniform: LightsUniform,
     buffer: wgpu::Buffer,
     pub bind_group: wgpu::BindGroup,
     pub bind_group_layout: wgpu::BindGroupLayout,
 }
 
 impl DirectionalLight {
-    pub fn new(device: &wgpu::Device) -> Self {
-        let direction = Vector3::new(-0.5, -1.0, -0.5).normalize(); // More angled sunlight
-        let color = Vector3::new(1.0, 1.0, 1.0); // Pure white light
-        let intensity = 1.0;
-
-        let uniform = LightUniform {
-            direction: direction.into(),
-            _padding: 0.0,
-            color: color.into(),
-            intensity,
-        };
+    /// `ambient` is the brightness of faces the sun doesn't reach (0-1)
+    pub fn new(device: &wgpu::Device, ambient: f32) -> Self {
+        let (lights, ambient) = default_lights(ambient);
+
+        let uniform = LightsUniform::new(ambient, &lights);
 
         let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Light Buffer"),
@@ -63,18 +207,279 @@ impl DirectionalLight {
             label: Some("light_bind_group"),
         });
 
-        Self {
-            direction,
-            color,
-            intensity,
+        let mut light = Self {
+            sun_intensity: lights[0].intensity,
+            day_ambient: ambient,
+            fill_intensity: 0.0,
+            lights,
+            ambient,
+            time_of_day: START_TIME_OF_DAY,
+            day_length: Duration::from_secs(1200),
+            sky: ColorBlend::new(DAY_SKY),
+            fog: ColorBlend::new(DAY_SKY),
+            fog_distance: 0.0,
             uniform,
             buffer,
             bind_group,
             bind_group_layout,
+        };
+        light.set_time_of_day(START_TIME_OF_DAY);
+        light
+    }
+
+    /// Midday ambient term, the light every face gets even when turned away from the sun.
+    /// It dims and turns blue at night like the default one.
+    pub fn set_ambient(&mut self, ambient: Vector3<f32>) {
+        self.day_ambient = ambient;
+        self.set_time_of_day(self.time_of_day);
+    }
+
+    /// Add a weak white light from the side the sun never reaches, or remove it with an
+    /// intensity of 0. Like the ambient term it fades to a dim blue at night.
+    pub fn set_fill_light(&mut self, intensity: f32) {
+        self.fill_intensity = intensity.max(0.0);
+        self.lights.truncate(1);
+        if self.fill_intensity > 0.0 {
+            self.lights
+                .push(fill_light_at(self.time_of_day, self.fill_intensity));
+        }
+    }
+
+    /// Real time one full day and night takes. Zero stops the sun where it is.
+    pub fn set_day_length(&mut self, day_length: Duration) {
+        self.day_length = day_length;
+    }
+
+    /// Advance the day/night cycle by `dt`
+    pub fn update(&mut self, dt: Duration) {
+        if self.day_length.is_zero() {
+            return;
+        }
+        let step = dt.as_secs_f32() / self.day_length.as_secs_f32();
+        self.set_time_of_day(self.time_of_day + step);
+    }
+
+    pub fn time_of_day(&self) -> f32 {
+        self.time_of_day
+    }
+
+    /// Move the sun through the day/night cycle: 0.0 is sunrise, 0.25 noon, 0.5 sunset
+    /// and 0.75 midnight. Values outside 0-1 wrap around.
+    pub fn set_time_of_day(&mut self, time: f32) {
+        self.time_of_day = time.rem_euclid(1.0);
+        if let Some(sun_light) = self.lights.first_mut() {
+            *sun_light = sun_light_at(self.time_of_day, self.sun_intensity);
+        }
+        if let Some(fill_light) = self.lights.get_mut(1) {
+            *fill_light = fill_light_at(self.time_of_day, self.fill_intensity);
         }
+        self.ambient = ambient_at(self.time_of_day, self.day_ambient);
+    }
+
+    /// Ease the sky and fog toward the midday colors of the biome the player is in,
+    /// moving `dt` further along
+    pub fn update_biome_colors(&mut self, sky: [f32; 3], fog: [f32; 3], dt: Duration) {
+        self.sky.retarget(sky);
+        self.fog.retarget(fog);
+        self.sky.advance(dt);
+        self.fog.advance(dt);
+    }
+
+    /// Distance from the player, in blocks, where the fog hides everything. 0 turns it off.
+    pub fn set_fog_distance(&mut self, distance: f32) {
+        self.fog_distance = distance;
     }
 
-    pub fn update_buffer(&self, queue: &wgpu::Queue) {
+    /// Clear color for the sky at the current time of day
+    pub fn sky_color(&self) -> wgpu::Color {
+        let [r, g, b] = sky_color_at(self.time_of_day, self.sky.current()).map(f64::from);
+        wgpu::Color { r, g, b, a: 1.0 }
+    }
+
+    /// Write the ambient term, fog and every light to the GPU
+    pub fn update_buffer(&mut self, queue: &wgpu::Queue) {
+        self.uniform = LightsUniform::new(self.ambient, &self.lights).with_fog(
+            sky_color_at(self.time_of_day, self.fog.current()),
+            self.fog_distance * FOG_START_FRACTION,
+            self.fog_distance,
+        );
         queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.uniform]));
     }
 }
+
+/// Unit vector toward the sun
+fn sun_position(time_of_day: f32) -> Vector3<f32> {
+    let angle = time_of_day * std::f32::consts::TAU;
+    // Tilted slightly toward +Z so faces along the sun's path aren't lit edge-on
+    Vector3::new(angle.cos(), angle.sin(), 0.3).normalize()
+}
+
+/// 1.0 while the sun is well above the horizon, 0.0 once it is well below
+fn daylight_at(time_of_day: f32) -> f32 {
+    smoothstep(-0.2, 0.2, sun_position(time_of_day).y)
+}
+
+/// The sun, orange and weak near the horizon and white at midday. Below the horizon the
+/// moon, opposite the sun, takes over; both fade to nothing at the horizon so the light
+/// never jumps when they swap.
+fn sun_light_at(time_of_day: f32, sun_intensity: f32) -> Light {
+    let sun = sun_position(time_of_day);
+    if sun.y >= 0.0 {
+        Light {
+            direction: -sun,
+            color: Vector3::from(SUNSET_COLOR)
+                .lerp(Vector3::new(1.0, 1.0, 1.0), smoothstep(0.0, 0.4, sun.y)),
+            intensity: sun_intensity * smoothstep(0.0, 0.15, sun.y),
+        }
+    } else {
+        Light {
+            direction: sun,
+            color: Vector3::from(MOON_COLOR),
+            intensity: sun_intensity * MOON_INTENSITY * smoothstep(0.0, 0.15, -sun.y),
+        }
+    }
+}
+
+/// The fill light, tinted and dimmed through the night the same way as the ambient term
+fn fill_light_at(time_of_day: f32, intensity: f32) -> Light {
+    Light {
+        direction: Vector3::from(FILL_LIGHT_DIRECTION).normalize(),
+        color: ambient_at(time_of_day, Vector3::new(1.0, 1.0, 1.0)),
+        intensity,
+    }
+}
+
+fn ambient_at(time_of_day: f32, day_ambient: Vector3<f32>) -> Vector3<f32> {
+    let night_ambient = Vector3::from(NIGHT_AMBIENT).mul_element_wise(day_ambient);
+    night_ambient.lerp(day_ambient, daylight_at(time_of_day))
+}
+
+/// `day_sky` by day, dark at night, with a warm glow while the sun is near the horizon
+fn sky_color_at(time_of_day: f32, day_sky: [f32; 3]) -> [f32; 3] {
+    let daylight = daylight_at(time_of_day);
+    let glow = 1.0 - smoothstep(0.0, 0.25, sun_position(time_of_day).y.abs());
+    [0, 1, 2].map(|i| {
+        let sky = NIGHT_SKY[i] + (day_sky[i] - NIGHT_SKY[i]) * daylight;
+        sky + (SUNSET_SKY[i] - sky) * glow * 0.5
+    })
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lights_uniform_layout_matches_shader() {
+        // ambient (16) + fog color (16) + count, fog distances and padding (16) + 4 lights
+        // of 32 bytes
+        assert_eq!(std::mem::size_of::<LightData>(), 32);
+        assert_eq!(std::mem::size_of::<LightsUniform>(), 48 + 32 * MAX_LIGHTS);
+    }
+
+    #[test]
+    fn test_undersides_get_ambient_light() {
+        let (lights, ambient) = default_lights(0.25);
+        let uniform = LightsUniform::new(ambient, &lights);
+
+        // Same sum as fs_main in shader.wgsl
+        let lighting = |normal: Vector3<f32>| {
+            let mut total =
+                Vector3::new(uniform.ambient[0], uniform.ambient[1], uniform.ambient[2]);
+            for light in &uniform.lights[..uniform.count as usize] {
+                let direction = Vector3::from(light.direction).normalize();
+                let diffuse = normal.dot(-direction).max(0.0);
+                total += Vector3::from(light.color) * light.intensity * diffuse;
+            }
+            total
+        };
+
+        // Facing away from the sun: dim but not black
+        let underside = lighting(Vector3::new(0.0, -1.0, 0.0));
+        assert!((underside.x - 0.25).abs() < 1e-6);
+
+        // Facing straight into the sun: fully lit
+        let sunward = lighting(-lights[0].direction);
+        assert!((sunward.x - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_extra_lights_are_dropped() {
+        let light = Light {
+            direction: Vector3::new(0.0, -1.0, 0.0),
+            color: Vector3::new(1.0, 0.9, 0.8),
+            intensity: 0.5,
+        };
+        let uniform = LightsUniform::new(Vector3::new(0.1, 0.2, 0.3), &[light; MAX_LIGHTS + 2]);
+
+        assert_eq!(uniform.count, MAX_LIGHTS as u32);
+        assert_eq!(uniform.ambient, [0.1, 0.2, 0.3, 0.0]);
+        assert_eq!(uniform.lights[MAX_LIGHTS - 1].color, [1.0, 0.9, 0.8]);
+    }
+
+    #[test]
+    fn test_day_night_cycle() {
+        let noon = sun_light_at(0.25, 0.75);
+        assert!(noon.direction.y < -0.9);
+        assert!((noon.intensity - 0.75).abs() < 1e-6);
+        assert_eq!(noon.color, Vector3::new(1.0, 1.0, 1.0));
+
+        // Low sun at dusk is orange and weaker
+        let dusk = sun_light_at(0.49, 0.75);
+        assert!(dusk.intensity < noon.intensity && dusk.color.z < dusk.color.x);
+
+        // The moon lights from above at midnight, dim and blue
+        let midnight = sun_light_at(0.75, 0.75);
+        assert!(midnight.direction.y < -0.9);
+        assert!(midnight.intensity < 0.2 && midnight.color.z > midnight.color.x);
+
+        // Both fade out at the horizon so swapping them doesn't pop
+        assert!(sun_light_at(0.5, 0.75).intensity < 1e-6);
+
+        let day_ambient = Vector3::new(0.25, 0.25, 0.25);
+        assert_eq!(ambient_at(0.25, day_ambient), day_ambient);
+        assert!(ambient_at(0.75, day_ambient).x < 0.1);
+
+        let [_, _, day_blue] = sky_color_at(0.25, DAY_SKY);
+        let [_, _, night_blue] = sky_color_at(0.75, DAY_SKY);
+        assert!(night_blue < day_blue * 0.2);
+    }
+
+    #[test]
+    fn test_fill_light_reaches_walls_the_sun_never_faces() {
+        // A wall facing away from the sun's path gets no sunlight all day
+        let wall = Vector3::new(0.0, 0.0, -1.0);
+        for time in [0.05, 0.25, 0.45] {
+            assert!(wall.dot(-sun_light_at(time, 1.0).direction) < 0.0);
+        }
+
+        let noon = fill_light_at(0.25, 0.2);
+        assert!(wall.dot(-noon.direction) > 0.5);
+        assert_eq!(noon.color, Vector3::new(1.0, 1.0, 1.0));
+        assert_eq!(noon.intensity, 0.2);
+        let midnight = fill_light_at(0.75, 0.2);
+        assert!(midnight.color.x < 0.3 && midnight.color.z > midnight.color.x);
+    }
+
+    #[test]
+    fn test_biome_colors_ease_over_a_second() {
+        let desert = [0.85, 0.78, 0.6];
+        let mut sky = ColorBlend::new(DAY_SKY);
+        sky.retarget(desert);
+        assert_eq!(sky.current(), DAY_SKY);
+
+        sky.advance(Duration::from_secs_f32(BIOME_BLEND_SECS / 2.0));
+        let halfway = sky.current();
+        assert!((halfway[0] - (DAY_SKY[0] + desert[0]) / 2.0).abs() < 1e-6);
+
+        // Turning back mid-blend continues from the in-between color
+        sky.retarget(DAY_SKY);
+        assert_eq!(sky.current(), halfway);
+        sky.advance(Duration::from_secs_f32(BIOME_BLEND_SECS * 2.0));
+        assert_eq!(sky.current(), DAY_SKY);
+    }
+}
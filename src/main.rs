@@ -1,35 +1,93 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
 use winit::{
     event::*,
     event_loop::{ControlFlow, EventLoop},
     keyboard::{KeyCode, PhysicalKey},
-    window::Window,
+    window::{Fullscreen, Window},
 };
 
-mod biome;
-mod blocks;
-mod camera;
-mod chunk;
-mod chunk_debug;
-mod light;
-mod raycast;
-mod slot_ui;
-mod structures;
-mod terrain;
-mod texture_atlas;
-mod texture_parser;
-mod voxel;
-mod wireframe;
-mod world;
+use rustcraft::{
+    aabb, biome, blocks, camera, chunk, chunk_debug, crosshair, frame_limiter, frustum,
+    ghost_block, inventory_screen, key_bindings, light, particles, progress_ui, raycast,
+    render_mode, save, settings, slot_ui, structure_file, terrain_modifier, text_renderer,
+    texture_atlas, tools, ui_atlas, underwater, vignette, voxel, wireframe, world,
+};
 
+use aabb::Aabb;
 use biome::{Biome, BiomeManager};
-use camera::CameraSystem;
+use camera::{CameraSystem, ViewMode};
 use chunk_debug::ChunkDebugRenderer;
+use crosshair::CrosshairRenderer;
+use frame_limiter::FrameLimiter;
+use frustum::Frustum;
+use ghost_block::GhostBlockRenderer;
+use inventory_screen::{InventoryScreen, InventorySlot};
+use key_bindings::{Action, KeyBindings};
 use light::DirectionalLight;
+use particles::{ParticleRenderer, ParticleSystem};
+use progress_ui::ProgressUI;
 use raycast::{create_camera_ray, raycast_blocks, RaycastHit};
+use render_mode::RenderMode;
+use save::SaveData;
+use settings::{ControlsSettings, GraphicsSettings, Settings};
 use slot_ui::SlotUI;
+use text_renderer::TextRenderer;
 use texture_atlas::TextureAtlas;
+use tools::{BreakProgress, Tool};
+use ui_atlas::UiAtlas;
+use underwater::UnderwaterOverlay;
+use vignette::Vignette;
 use wireframe::WireframeRenderer;
-use world::World;
+use world::{World, MAX_CHUNK_UPLOADS_PER_FRAME};
+
+/// Top-left corner of the debug overlay, in pixels
+const DEBUG_TEXT_POSITION: [f32; 2] = [8.0, 8.0];
+
+/// Weight of the newest frame in the FPS shown by the debug overlay
+const FPS_SMOOTHING: f32 = 0.1;
+
+/// Minimum time between blocks placed while dragging
+const DRAG_INTERVAL: Duration = Duration::from_millis(120);
+
+const SAVE_FILE: &str = "save.toml";
+
+/// Where the loaded chunks are exported as a model; the materials go to export.mtl
+const EXPORT_FILE: &str = "export.obj";
+
+const SETTINGS_FILE: &str = "settings.toml";
+
+/// Key bindings, read at startup and again on F5
+const CONTROLS_FILE: &str = "controls.toml";
+
+/// Largest box the area tool will break in one go
+const MAX_AREA_BREAK_VOLUME: i64 = 32 * 32 * 32;
+
+/// Mouse sensitivity is scaled by this per key press, within the min/max
+const SENSITIVITY_STEP: f32 = 1.25;
+const MIN_SENSITIVITY: f32 = 0.02;
+const MAX_SENSITIVITY: f32 = 5.0;
+
+/// Walking speed change per key press in blocks per second, within the min/max
+const SPEED_STEP: f32 = 0.5;
+const MIN_WALK_SPEED: f32 = 1.0;
+const MAX_WALK_SPEED: f32 = 20.0;
+
+/// Chunks generated per frame while the world warms up after launch
+const WARMUP_CHUNKS_PER_FRAME: usize = 8;
+
+/// Range the +/- keys can set the render distance to, in chunks
+const MIN_RENDER_DISTANCE: i32 = 2;
+const MAX_RENDER_DISTANCE: i32 = 16;
+
+/// Trackpad scroll distance, in pixels, that counts as one mouse wheel notch
+const SCROLL_PIXELS_PER_LINE: f32 = 40.0;
+
+/// How much faster the day/night cycle runs while fast time is on
+const FAST_TIME_FACTOR: u32 = 60;
+
+/// Half the player's width, used to keep placed blocks out of the player's body
+const PLAYER_HALF_WIDTH: f32 = 0.3;
 
 struct State<'window> {
     surface: wgpu::Surface<'window>,
@@ -41,22 +99,75 @@ struct State<'window> {
     world: World,
     light: DirectionalLight,
     render_pipeline: wgpu::RenderPipeline,
+    normals_pipeline: wgpu::RenderPipeline,
+    // None when the GPU doesn't support line polygon mode
+    wireframe_pipeline: Option<wgpu::RenderPipeline>,
+    render_mode: RenderMode,
     texture_atlas: TextureAtlas,
     _texture_bind_group_layout: wgpu::BindGroupLayout,
+    _ui_atlas: UiAtlas,
     wireframe_renderer: WireframeRenderer,
+    ghost_block_renderer: GhostBlockRenderer,
     chunk_debug_renderer: ChunkDebugRenderer,
+    particles: ParticleSystem,
+    particle_renderer: ParticleRenderer,
     slot_ui: SlotUI,
+    inventory_screen: InventoryScreen,
+    crosshair: CrosshairRenderer,
+    vignette: Vignette,
+    underwater_overlay: UnderwaterOverlay,
+    progress_ui: ProgressUI,
+    debug_text: TextRenderer,
     window: &'window Window,
+    exclusive_fullscreen: bool,
     game_mode: bool,
+    // Mouse wheel movement not yet turned into whole slot steps (trackpads scroll in fractions)
+    scroll_accumulator: f32,
+    // Last cursor position in physical pixels, for clicking inventory slots
+    cursor_position: winit::dpi::PhysicalPosition<f64>,
     window_focused: bool,
     selected_block: Option<RaycastHit>,
+    // Cell placed into or broken by the last left click/drag, to avoid repeating it
+    last_drag_pos: Option<[i32; 3]>,
+    last_drag_time: Instant,
+    // Block being mined while the left button is held in break mode
+    break_progress: BreakProgress,
+    held_tool: Tool,
+    // Photo mode: player camera and FOV to return to when it ends. None when not in photo mode.
+    photo_mode_return: Option<(camera::CameraState, f32)>,
+    // Run the day/night cycle FAST_TIME_FACTOR times faster
+    fast_time: bool,
+    // When the initial chunks started generating; None once the world around the player is ready
+    warmup_started: Option<Instant>,
+    // Seconds of animation time for the shaders (water waves)
+    time: f32,
+    // Area break tool: left clicks mark two corners, Enter breaks the box between them
+    area_tool: bool,
+    area_corners: Vec<[i32; 3]>,
     debug_mode: bool,
+    // Frames per second, averaged over the last few frames for the debug overlay
+    fps: f32,
+    // Mesh chunks with normals averaged across corners instead of flat per face
+    smooth_normals: bool,
+    // Chunks that passed frustum culling in the last frame, shown in the debug stats
+    chunks_drawn: usize,
     current_biome: Option<Biome>,
     biome_manager: BiomeManager,
+    // Rebuild loaded chunks in place on F5 instead of clearing them
+    reload_in_place: bool,
+    key_bindings: KeyBindings,
+    // Sensitivity and speed as last adjusted in-game, written back to settings.toml on
+    // exit when they changed
+    controls: ControlsSettings,
+    controls_changed: bool,
 }
 
 impl<'window> State<'window> {
-    async fn new(window: &'window Window) -> anyhow::Result<Self> {
+    async fn new(
+        window: &'window Window,
+        settings: &Settings,
+        seed: Option<u64>,
+    ) -> anyhow::Result<Self> {
         let size = window.inner_size();
 
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
@@ -75,10 +186,32 @@ impl<'window> State<'window> {
             .await
             .unwrap();
 
+        // Anisotropic filtering is a downlevel capability; fall back to 1 where unsupported
+        let anisotropy_supported = adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::ANISOTROPIC_FILTERING);
+        let anisotropy_clamp = if settings.graphics.anisotropic_filtering && anisotropy_supported {
+            settings.graphics.anisotropy_clamp
+        } else {
+            1
+        };
+
+        // Line polygon mode is only needed for the wireframe render mode, so request it
+        // when available and skip that mode otherwise
+        let wireframe_supported = adapter
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE);
+        let required_features = if wireframe_supported {
+            wgpu::Features::POLYGON_MODE_LINE
+        } else {
+            wgpu::Features::empty()
+        };
+
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    required_features: wgpu::Features::empty(),
+                    required_features,
                     required_limits: wgpu::Limits::default(),
                     label: None,
                 },
@@ -106,7 +239,7 @@ impl<'window> State<'window> {
         };
         surface.configure(&device, &config);
 
-        let camera = CameraSystem::new(
+        let mut camera = CameraSystem::new(
             camera::Camera::new(
                 cgmath::point3(0.0, 64.0, 0.0), // Higher spawn position
                 cgmath::Deg(-90.0),
@@ -115,9 +248,61 @@ impl<'window> State<'window> {
             ),
             &device,
         );
-
-        let world = World::new();
-        let light = DirectionalLight::new(&device);
+        camera.set_auto_jump(settings.controls.auto_jump);
+        camera.set_speed(settings.controls.walk_speed);
+        camera.set_sensitivity(settings.controls.mouse_sensitivity);
+
+        let mut world = match seed {
+            Some(seed) => {
+                println!("World seed: {}", seed);
+                World::with_seed(seed)
+            }
+            None => World::new(),
+        };
+        world.set_terrain_kind(settings.generation.terrain.clone());
+        world.set_sea_level(settings.generation.sea_level);
+        world.set_mesh_options(chunk::MeshOptions {
+            smart_grass_sides: settings.graphics.smart_grass_sides,
+            greedy: settings.graphics.greedy_meshing,
+            smooth_normals: false,
+            high_quality_ao: false,
+        });
+        if settings.generation.boulders {
+            // Follow the world seed like the terrain, so each seed gets its own boulders
+            let boulder_seed = world.get_terrain().seed();
+            world.register_terrain_modifier(Box::new(terrain_modifier::BoulderModifier::new(
+                boulder_seed,
+            )));
+        }
+        match structure_file::load_all_structures() {
+            Ok(structures) => {
+                for structure in structures {
+                    world.register_structure(structure);
+                }
+            }
+            Err(e) => println!("No structure files loaded: {}", e),
+        }
+        // Spread the first chunks over several frames so the window shows a loading bar
+        // instead of hanging before the first frame
+        world.set_max_chunks_per_update(Some(WARMUP_CHUNKS_PER_FRAME));
+        // Nothing is drawn behind the loading bar yet, so upload each batch right away
+        world.set_max_chunk_uploads_per_frame(None);
+        world.set_memory_budget(
+            settings
+                .graphics
+                .gpu_memory_budget_mb
+                .map(|mb| mb * 1024 * 1024),
+        );
+        let mut light = DirectionalLight::new(&device, settings.graphics.ambient_light);
+        let ambient = settings.graphics.ambient_light.clamp(0.0, 1.0);
+        light.set_ambient(cgmath::Vector3::from(settings.graphics.ambient_color) * ambient);
+        light.set_fill_light(settings.graphics.fill_light);
+        let day_length = settings.graphics.day_length().unwrap_or_else(|e| {
+            let default = GraphicsSettings::default();
+            eprintln!("Warning: {}, using {}", e, default.day_length_secs);
+            Duration::from_secs_f32(default.day_length_secs)
+        });
+        light.set_day_length(day_length);
 
         // Create texture atlas bind group layout
         let texture_bind_group_layout =
@@ -139,12 +324,32 @@ impl<'window> State<'window> {
                         ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
                         count: None,
                     },
+                    // Atlas grid size
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
                 ],
                 label: Some("texture_bind_group_layout"),
             });
 
         // Create texture atlas
-        let texture_atlas = TextureAtlas::new(&device, &queue, &texture_bind_group_layout);
+        let (texture_atlas, texture_problems) = TextureAtlas::new(
+            &device,
+            &queue,
+            &texture_bind_group_layout,
+            anisotropy_clamp,
+            settings.graphics.texture_size,
+        );
+        if let Some(problems) = texture_problems {
+            eprintln!("Warning: {}", problems);
+        }
 
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader"),
@@ -165,55 +370,69 @@ impl<'window> State<'window> {
 
         let wireframe_renderer =
             WireframeRenderer::new(&device, surface_format, &camera.bind_group_layout);
+        let ghost_block_renderer = GhostBlockRenderer::new(
+            &device,
+            surface_format,
+            &shader,
+            [
+                &camera.bind_group_layout,
+                &light.bind_group_layout,
+                &texture_bind_group_layout,
+            ],
+        );
+        let particle_renderer =
+            ParticleRenderer::new(&device, surface_format, &camera.bind_group_layout);
         let chunk_debug_renderer =
             ChunkDebugRenderer::new(&device, surface_format, &camera.bind_group_layout);
+        let ui_atlas = UiAtlas::new(&device, &queue);
         let slot_ui = SlotUI::new(
             &device,
             surface_format,
             &texture_atlas,
+            &ui_atlas,
+            config.width,
+            config.height,
+        );
+        let inventory_screen = InventoryScreen::new(
+            &device,
+            surface_format,
+            &slot_ui,
             config.width,
             config.height,
         );
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[voxel::Vertex::desc()],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            }),
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
+        let crosshair =
+            CrosshairRenderer::new(&device, surface_format, config.width, config.height);
+        let vignette = Vignette::new(&device, surface_format, settings.graphics.vignette_strength);
+        let underwater_overlay = UnderwaterOverlay::new(&device, surface_format);
+        let progress_ui = ProgressUI::new(&device, surface_format);
+        let debug_text = TextRenderer::new(&device, surface_format, &ui_atlas);
+
+        let render_pipeline = create_chunk_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &shader,
+            config.format,
+            "fs_main",
+            wgpu::PolygonMode::Fill,
+        );
+        let normals_pipeline = create_chunk_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &shader,
+            config.format,
+            "fs_normals",
+            wgpu::PolygonMode::Fill,
+        );
+        let wireframe_pipeline = wireframe_supported.then(|| {
+            create_chunk_pipeline(
+                &device,
+                &render_pipeline_layout,
+                &shader,
+                config.format,
+                "fs_main",
+                wgpu::PolygonMode::Line,
+            )
         });
 
         Ok(Self {
@@ -226,21 +445,60 @@ impl<'window> State<'window> {
             world,
             light,
             render_pipeline,
+            normals_pipeline,
+            wireframe_pipeline,
+            render_mode: RenderMode::default(),
             texture_atlas,
             _texture_bind_group_layout: texture_bind_group_layout,
+            _ui_atlas: ui_atlas,
             wireframe_renderer,
+            ghost_block_renderer,
             chunk_debug_renderer,
+            particles: ParticleSystem::new(rand::random()),
+            particle_renderer,
             slot_ui,
+            inventory_screen,
+            crosshair,
+            vignette,
+            underwater_overlay,
+            progress_ui,
+            debug_text,
             window,
+            exclusive_fullscreen: settings.window.exclusive_fullscreen,
             game_mode: true,
+            scroll_accumulator: 0.0,
+            cursor_position: winit::dpi::PhysicalPosition::new(0.0, 0.0),
             window_focused: true,
             selected_block: None,
+            last_drag_pos: None,
+            last_drag_time: Instant::now(),
+            break_progress: BreakProgress::default(),
+            held_tool: Tool::default(),
+            photo_mode_return: None,
+            fast_time: false,
+            warmup_started: Some(Instant::now()),
+            time: 0.0,
+            area_tool: false,
+            area_corners: Vec::new(),
             debug_mode: false,
+            fps: 0.0,
+            smooth_normals: false,
+            chunks_drawn: 0,
             current_biome: None,
             biome_manager: BiomeManager::load_from_file("biome.toml").unwrap_or_else(|e| {
                 println!("Failed to load biome.toml: {}. Using default configs.", e);
                 BiomeManager::new()
             }),
+            reload_in_place: settings.generation.reload_in_place,
+            key_bindings: KeyBindings::load_from_file(CONTROLS_FILE).unwrap_or_else(|e| {
+                println!(
+                    "Failed to load {}: {}. Using default key bindings.",
+                    CONTROLS_FILE, e
+                );
+                KeyBindings::default()
+            }),
+            controls: settings.controls.clone(),
+            controls_changed: false,
         })
     }
 
@@ -250,13 +508,21 @@ impl<'window> State<'window> {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
+            self.camera.set_aspect(new_size.width, new_size.height);
 
-            // Update slot UI geometry for new window size (fixed 100px slots)
-            self.slot_ui
-                .update_geometry(&self.queue, new_size.width, new_size.height);
+            self.resize_ui(new_size.width, new_size.height);
         }
     }
 
+    /// Rebuild the geometry of every screen-space UI element for a new window size
+    fn resize_ui(&mut self, width: u32, height: u32) {
+        // Slots keep their fixed pixel size
+        self.slot_ui.update_geometry(&self.queue, width, height);
+        self.inventory_screen
+            .update_geometry(&self.queue, width, height);
+        self.crosshair.update_geometry(&self.queue, width, height);
+    }
+
     fn input_window(&mut self, event: &WindowEvent) -> bool {
         // Handle slot selection first
         if let WindowEvent::KeyboardInput {
@@ -269,71 +535,262 @@ impl<'window> State<'window> {
             ..
         } = event
         {
-            match key_code {
-                KeyCode::Digit1 => {
-                    self.slot_ui.set_selected_slot(0, &self.queue);
-                    return true;
-                }
-                KeyCode::Digit2 => {
-                    self.slot_ui.set_selected_slot(1, &self.queue);
-                    return true;
-                }
-                KeyCode::Digit3 => {
-                    self.slot_ui.set_selected_slot(2, &self.queue);
-                    return true;
-                }
-                KeyCode::Digit4 => {
-                    self.slot_ui.set_selected_slot(3, &self.queue);
-                    return true;
-                }
-                KeyCode::Digit5 => {
-                    self.slot_ui.set_selected_slot(4, &self.queue);
-                    return true;
-                }
-                KeyCode::Digit6 => {
-                    self.slot_ui.set_selected_slot(5, &self.queue);
-                    return true;
-                }
-                KeyCode::Digit7 => {
-                    self.slot_ui.set_selected_slot(6, &self.queue);
-                    return true;
-                }
-                KeyCode::Digit8 => {
-                    self.slot_ui.set_selected_slot(7, &self.queue);
-                    return true;
-                }
-                KeyCode::Digit9 => {
-                    self.slot_ui.set_selected_slot(8, &self.queue);
-                    return true;
-                }
-                KeyCode::Digit0 => {
-                    self.slot_ui.set_selected_slot(9, &self.queue);
-                    return true;
-                }
-                KeyCode::Delete | KeyCode::Backspace => {
-                    self.slot_ui.clear_selected_slot();
-                    self.slot_ui.update_inventory_buffer(&self.queue);
-                    return true;
-                }
-                KeyCode::F3 => {
-                    self.debug_mode = !self.debug_mode;
-                    println!("Debug mode: {}", if self.debug_mode { "ON" } else { "OFF" });
-                    return true;
-                }
-                KeyCode::F5 => {
-                    match self.biome_manager.reload_from_file("biome.toml") {
-                        Ok(()) => {
-                            // Clear and regenerate all chunks
-                            self.world.clear_all_chunks();
-                            println!("Biome configuration reloaded! All chunks regenerated.");
+            if *key_code == KeyCode::Escape && self.inventory_screen.is_visible() {
+                self.toggle_inventory_screen();
+                return true;
+            }
+
+            if let Some(action) = self.key_bindings.action(*key_code) {
+                match action {
+                    Action::HotbarSlot(slot) => {
+                        self.slot_ui.set_selected_slot(slot, &self.queue);
+                        return true;
+                    }
+                    Action::ClearSlot => {
+                        self.slot_ui.clear_selected_slot();
+                        self.slot_ui.update_inventory_buffer(&self.queue);
+                        return true;
+                    }
+                    Action::ToggleDebug => {
+                        self.debug_mode = !self.debug_mode;
+                        println!("Debug mode: {}", if self.debug_mode { "ON" } else { "OFF" });
+                        if self.debug_mode {
+                            let stats = self.world.stats();
+                            println!(
+                            "Chunks: {} loaded, {} drawn, {} generated (avg {:.2?}) | Vertices: {} | GPU memory: {:.1} MB (render distance {}) | Blocks: {} placed, {} broken",
+                            stats.chunks_loaded,
+                            self.chunks_drawn,
+                            stats.chunks_generated,
+                            stats.average_generation_time,
+                            stats.total_vertices,
+                            stats.gpu_memory_bytes as f64 / (1024.0 * 1024.0),
+                            stats.render_distance,
+                            stats.blocks_placed,
+                            stats.blocks_broken
+                        );
                         }
-                        Err(e) => {
-                            println!("Failed to reload biome.toml: {}", e);
+                        return true;
+                    }
+                    Action::CycleRenderMode => {
+                        self.render_mode = self.render_mode.next(self.wireframe_pipeline.is_some());
+                        println!("Render mode: {}", self.render_mode.name());
+                        return true;
+                    }
+                    Action::ReloadConfig => {
+                        match self.key_bindings.reload_from_file(CONTROLS_FILE) {
+                            Ok(()) => println!("Key bindings reloaded from {}", CONTROLS_FILE),
+                            Err(e) => println!("Failed to reload {}: {}", CONTROLS_FILE, e),
+                        }
+                        match self.biome_manager.reload_from_file("biome.toml") {
+                            Ok(()) => {
+                                if self.reload_in_place {
+                                    self.world.regenerate_loaded_chunks(
+                                        &self.device,
+                                        &self.biome_manager,
+                                    );
+                                } else {
+                                    self.world.clear_all_chunks();
+                                }
+                                println!("Biome configuration reloaded! All chunks regenerated.");
+                            }
+                            Err(e) => {
+                                println!("Failed to reload biome.toml: {}", e);
+                            }
                         }
+                        return true;
+                    }
+                    Action::RegenerateChunk => {
+                        // Rebuild the chunk the camera is in, for iterating on generation code
+                        let camera_pos = self.camera.get_position();
+                        let chunk_pos = chunk::ChunkPos {
+                            x: (camera_pos.x / chunk::CHUNK_SIZE as f32).floor() as i32,
+                            z: (camera_pos.z / chunk::CHUNK_SIZE as f32).floor() as i32,
+                        };
+                        self.world
+                            .regenerate_chunk(chunk_pos, &self.device, &self.biome_manager);
+                        return true;
+                    }
+                    Action::RegenerateStructures => {
+                        // Rebuild structures only, for iterating on structures.rs
+                        self.world
+                            .regenerate_structures(&self.device, &self.biome_manager);
+                        return true;
+                    }
+                    Action::ToggleSmoothNormals => {
+                        self.smooth_normals = !self.smooth_normals;
+                        self.world.set_mesh_options(chunk::MeshOptions {
+                            smooth_normals: self.smooth_normals,
+                            ..self.world.mesh_options()
+                        });
+                        println!(
+                            "Smooth normals: {}",
+                            if self.smooth_normals { "ON" } else { "OFF" }
+                        );
+                        return true;
+                    }
+                    Action::BakeAmbientOcclusion => {
+                        self.world.bake_high_quality_ao();
+                        return true;
+                    }
+                    Action::ToggleFullscreen => {
+                        self.toggle_fullscreen();
+                        return true;
+                    }
+                    Action::ToggleViewMode => {
+                        let view_mode = match self.camera.view_mode() {
+                            ViewMode::First => ViewMode::Third,
+                            ViewMode::Third => ViewMode::First,
+                        };
+                        self.camera.set_view_mode(view_mode);
+                        println!("View: {:?} person", view_mode);
+                        return true;
+                    }
+                    Action::TogglePhotoMode => {
+                        self.toggle_photo_mode();
+                        return true;
+                    }
+                    Action::FovDown | Action::FovUp if self.photo_mode_return.is_some() => {
+                        let step = if action == Action::FovDown { -5.0 } else { 5.0 };
+                        self.camera
+                            .set_fov_degrees(self.camera.fov_degrees() + step);
+                        println!("FOV: {:.0}", self.camera.fov_degrees());
+                        return true;
+                    }
+                    Action::RenderDistanceDown | Action::RenderDistanceUp
+                        if self.photo_mode_return.is_some() =>
+                    {
+                        let step = if action == Action::RenderDistanceDown {
+                            -0.025
+                        } else {
+                            0.025
+                        };
+                        self.light.set_time_of_day(self.light.time_of_day() + step);
+                        println!("Time of day: {:.3}", self.light.time_of_day());
+                        return true;
+                    }
+                    Action::RenderDistanceDown | Action::RenderDistanceUp => {
+                        let step = if action == Action::RenderDistanceDown {
+                            -1
+                        } else {
+                            1
+                        };
+                        let render_distance = (self.world.render_distance() + step)
+                            .clamp(MIN_RENDER_DISTANCE, MAX_RENDER_DISTANCE);
+                        self.world.set_render_distance(render_distance);
+                        println!("Render distance: {} chunks", render_distance);
+                        return true;
+                    }
+                    Action::SensitivityDown | Action::SensitivityUp => {
+                        let factor = if action == Action::SensitivityDown {
+                            1.0 / SENSITIVITY_STEP
+                        } else {
+                            SENSITIVITY_STEP
+                        };
+                        self.controls.mouse_sensitivity = (self.camera.sensitivity() * factor)
+                            .clamp(MIN_SENSITIVITY, MAX_SENSITIVITY);
+                        self.camera.set_sensitivity(self.controls.mouse_sensitivity);
+                        self.controls_changed = true;
+                        println!("Mouse sensitivity: {:.3}", self.controls.mouse_sensitivity);
+                        return true;
+                    }
+                    Action::SpeedDown | Action::SpeedUp => {
+                        let step = if action == Action::SpeedDown {
+                            -SPEED_STEP
+                        } else {
+                            SPEED_STEP
+                        };
+                        self.controls.walk_speed =
+                            (self.camera.speed() + step).clamp(MIN_WALK_SPEED, MAX_WALK_SPEED);
+                        self.camera.set_speed(self.controls.walk_speed);
+                        self.controls_changed = true;
+                        println!("Walk speed: {:.1} blocks/s", self.controls.walk_speed);
+                        return true;
+                    }
+                    Action::CycleTool => {
+                        self.held_tool = self.held_tool.next();
+                        println!("Holding: {}", self.held_tool.name());
+                        return true;
+                    }
+                    Action::ToggleInventory => {
+                        self.toggle_inventory_screen();
+                        return true;
                     }
-                    return true;
+                    Action::ToggleFly => {
+                        let flying = self.camera.toggle_fly();
+                        println!("Flying: {}", if flying { "ON" } else { "OFF" });
+                        return true;
+                    }
+                    Action::ToggleNoclip => {
+                        let noclip = self.camera.toggle_noclip();
+                        println!("Noclip: {}", if noclip { "ON" } else { "OFF" });
+                        return true;
+                    }
+                    Action::ToggleAreaTool => {
+                        self.area_tool = !self.area_tool;
+                        self.area_corners.clear();
+                        println!(
+                            "Area break tool: {}",
+                            if self.area_tool { "ON" } else { "OFF" }
+                        );
+                        return true;
+                    }
+                    Action::BreakArea if self.area_tool => {
+                        self.break_area();
+                        return true;
+                    }
+                    Action::ToggleFastTime => {
+                        self.fast_time = !self.fast_time;
+                        println!("Fast time: {}", if self.fast_time { "ON" } else { "OFF" });
+                        return true;
+                    }
+                    Action::Save => {
+                        self.save();
+                        return true;
+                    }
+                    Action::Load => {
+                        self.load();
+                        return true;
+                    }
+                    Action::ExportObj => {
+                        self.export_obj();
+                        return true;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // Mouse wheel cycles hotbar slots; scrolling up moves left
+        if let WindowEvent::MouseWheel { delta, .. } = event {
+            if !self.game_mode {
+                return false;
+            }
+            self.scroll_accumulator += match delta {
+                MouseScrollDelta::LineDelta(_, y) => *y,
+                MouseScrollDelta::PixelDelta(position) => {
+                    position.y as f32 / SCROLL_PIXELS_PER_LINE
+                }
+            };
+            let steps = self.scroll_accumulator.trunc();
+            if steps != 0.0 {
+                self.scroll_accumulator -= steps;
+                self.slot_ui
+                    .scroll_selected_slot(-steps as i32, &self.queue);
+            }
+            return true;
+        }
+
+        if let WindowEvent::CursorMoved { position, .. } = event {
+            self.cursor_position = *position;
+        }
+
+        // While the inventory is open, clicks go to its slots instead of the world
+        if let WindowEvent::MouseInput { state, button, .. } = event {
+            if self.game_mode && self.inventory_screen.is_visible() {
+                if *state == ElementState::Pressed && *button == MouseButton::Left {
+                    self.click_inventory_slot();
                 }
-                _ => {}
+                return true;
             }
         }
 
@@ -355,12 +812,133 @@ impl<'window> State<'window> {
         }
 
         // If not a slot key or resume click, pass to camera
-        self.camera.process_window_events(event)
+        self.camera.process_window_events(event, &self.key_bindings)
+    }
+
+    /// Open or close the inventory screen. While it's open the cursor is free and the
+    /// world can't be interacted with.
+    fn toggle_inventory_screen(&mut self) {
+        if self.inventory_screen.toggle(&self.queue) {
+            self.selected_block = None;
+            self.break_progress.reset();
+        } else {
+            self.camera.reset_mouse_deltas();
+        }
+        self.update_cursor_state();
+    }
+
+    /// Pick up or drop a stack at the cursor. Clicking outside every slot puts the
+    /// picked up stack back.
+    fn click_inventory_slot(&mut self) {
+        let (x, y) = (self.cursor_position.x as f32, self.cursor_position.y as f32);
+        let (width, height) = (self.config.width, self.config.height);
+        let slot = match self.inventory_screen.slot_at(x, y, width, height) {
+            Some(slot) => InventorySlot::Storage(slot),
+            None => match self.slot_ui.slot_at(x, y, width, height) {
+                Some(slot) => InventorySlot::Hotbar(slot),
+                None => {
+                    self.inventory_screen.clear_picked(&self.queue);
+                    return;
+                }
+            },
+        };
+
+        if let InventorySlot::Hotbar(slot) = slot {
+            self.slot_ui.set_selected_slot(slot, &self.queue);
+        }
+        let mut hotbar = self.slot_ui.inventory();
+        self.inventory_screen.click(slot, &mut hotbar, &self.queue);
+        self.slot_ui.set_inventory(hotbar, &self.queue);
+    }
+
+    /// Write in-game sensitivity and speed changes back to the settings file
+    fn save_controls(&self) {
+        if !self.controls_changed {
+            return;
+        }
+        match self.controls.save_to_file(SETTINGS_FILE) {
+            Ok(()) => println!(
+                "Saved mouse sensitivity and walk speed to {}",
+                SETTINGS_FILE
+            ),
+            Err(e) => println!("Failed to save {}: {}", SETTINGS_FILE, e),
+        }
+    }
+
+    /// Write the player's position, view and inventory to the save file
+    fn save(&self) {
+        let inventory = std::array::from_fn(|slot| self.slot_ui.get_stack_in_slot(slot));
+        // In photo mode the player is still where the photo camera took off
+        let player_state = match self.photo_mode_return {
+            Some((player_state, _)) => player_state,
+            None => self.camera.camera_state(),
+        };
+        let save_data = SaveData::new(
+            player_state,
+            self.slot_ui.get_selected_slot(),
+            inventory,
+            self.inventory_screen.storage(),
+        );
+
+        match save_data.save_to_file(SAVE_FILE) {
+            Ok(()) => println!("Saved game to {}", SAVE_FILE),
+            Err(e) => println!("Failed to save {}: {}", SAVE_FILE, e),
+        }
+    }
+
+    /// Export the chunks within render distance of the camera as a model
+    fn export_obj(&self) {
+        let camera_pos = self.camera.get_position();
+        let distance = self.world.render_distance();
+        let chunk_x = (camera_pos.x / chunk::CHUNK_SIZE as f32).floor() as i32;
+        let chunk_z = (camera_pos.z / chunk::CHUNK_SIZE as f32).floor() as i32;
+        let region = (
+            chunk::ChunkPos {
+                x: chunk_x - distance,
+                z: chunk_z - distance,
+            },
+            chunk::ChunkPos {
+                x: chunk_x + distance,
+                z: chunk_z + distance,
+            },
+        );
+
+        let start = Instant::now();
+        match self.world.export_obj(Path::new(EXPORT_FILE), region) {
+            Ok(()) => println!(
+                "Exported world to {} in {:.2?}",
+                EXPORT_FILE,
+                start.elapsed()
+            ),
+            Err(e) => println!("Failed to export {}: {}", EXPORT_FILE, e),
+        }
+    }
+
+    /// Restore the player's position, view and inventory from the save file
+    fn load(&mut self) {
+        match SaveData::load_from_file(SAVE_FILE) {
+            Ok(save_data) => {
+                if self.photo_mode_return.is_some() {
+                    self.toggle_photo_mode();
+                }
+                self.camera.restore_camera_state(&save_data.camera);
+                self.slot_ui
+                    .set_inventory(save_data.inventory_slots(), &self.queue);
+                self.slot_ui
+                    .set_selected_slot(save_data.selected_slot, &self.queue);
+                self.inventory_screen
+                    .set_storage(save_data.storage_slots(), &self.queue);
+                self.selected_block = None;
+                println!("Loaded game from {}", SAVE_FILE);
+            }
+            Err(e) => println!("Failed to load {}: {}", SAVE_FILE, e),
+        }
     }
 
     fn input_device(&mut self, event: &DeviceEvent) -> bool {
-        // Only process mouse movement when in game mode and window is focused
-        if self.game_mode && self.window_focused {
+        // Only process mouse movement when in game mode and window is focused, and
+        // leave the view alone while the cursor is over the inventory
+        if self.game_mode && self.window_focused && !self.inventory_screen.is_visible() {
             self.camera.process_device_events(event)
         } else {
             false
@@ -372,8 +950,26 @@ impl<'window> State<'window> {
         self.update_cursor_state();
     }
 
+    /// Switch between windowed and fullscreen. The resulting Resized event rebuilds
+    /// the surface, camera projection and UI geometry.
+    fn toggle_fullscreen(&mut self) {
+        if self.window.fullscreen().is_some() {
+            self.window.set_fullscreen(None);
+            println!("Fullscreen: OFF");
+        } else {
+            self.window.set_fullscreen(Some(fullscreen_mode(
+                self.window,
+                self.exclusive_fullscreen,
+            )));
+            println!("Fullscreen: ON");
+        }
+
+        // The cursor grab is tied to the old window bounds, so re-apply it
+        self.update_cursor_state();
+    }
+
     fn update_cursor_state(&mut self) {
-        if self.game_mode && self.window_focused {
+        if self.game_mode && self.window_focused && !self.inventory_screen.is_visible() {
             // Game mode: center cursor, confine to window and hide it
             let window_size = self.window.inner_size();
             let center_x = window_size.width as f64 / 2.0;
@@ -395,9 +991,27 @@ impl<'window> State<'window> {
     }
 
     fn update(&mut self, dt: std::time::Duration) {
+        if let Some(started) = self.warmup_started {
+            self.update_warmup(started);
+            return;
+        }
+
         self.camera.update(dt, &self.world);
+        let photo_mode = self.photo_mode_return.is_some();
+        // Photo mode freezes the scene so the shot can be lined up: animations, the sun,
+        // particles and the world's simulation all hold still
+        if !photo_mode {
+            self.time += dt.as_secs_f32();
+        }
+        self.camera.set_time(self.time);
         self.camera.update_buffer(&self.queue);
-        self.light.update_buffer(&self.queue);
+        if !photo_mode {
+            if self.fast_time {
+                self.light.update(dt * FAST_TIME_FACTOR);
+            } else {
+                self.light.update(dt);
+            }
+        }
 
         let camera_pos = self.camera.get_position();
         self.world
@@ -413,6 +1027,28 @@ impl<'window> State<'window> {
             self.current_biome = Some(current_biome);
         }
 
+        // Sky and fog take on the biome's colors, with the fog closing in at the edge of
+        // the loaded chunks
+        let biome_config = self.biome_manager.get_config(current_biome);
+        self.light
+            .update_biome_colors(biome_config.sky_color, biome_config.fog_color, dt);
+        self.light
+            .set_fog_distance((self.world.render_distance() * chunk::CHUNK_SIZE as i32) as f32);
+        self.light.update_buffer(&self.queue);
+
+        // Ambient snow, dust or pollen for the biome the camera is in
+        let camera_yaw = self.camera.get_yaw();
+        if !photo_mode {
+            self.particles
+                .update(dt.as_secs_f32(), camera_pos, camera_yaw, current_biome);
+        }
+        self.particle_renderer.update(
+            &self.queue,
+            self.particles.particles(),
+            camera_pos,
+            camera_yaw,
+        );
+
         // Update chunk debug renderer if debug mode is enabled
         if self.debug_mode {
             let chunk_positions = self.world.get_loaded_chunk_positions();
@@ -420,20 +1056,100 @@ impl<'window> State<'window> {
                 .update_chunks(&self.device, &chunk_positions);
         }
 
-        // Update block selection (only when in game mode and window focused)
-        if self.game_mode && self.window_focused {
+        // Update block selection (only when in game mode and window focused, and never
+        // in photo mode, where the world is frozen, or with the inventory open)
+        if self.game_mode
+            && self.window_focused
+            && self.photo_mode_return.is_none()
+            && !self.inventory_screen.is_visible()
+        {
             self.update_block_selection();
 
             // Check for block interaction (place or break)
-            if self.camera.was_left_mouse_clicked() {
+            if self.area_tool {
+                if self.camera.was_left_mouse_clicked() {
+                    self.mark_area_corner();
+                }
+            } else if self.slot_ui.get_block_in_selected_slot().is_none() {
+                // Break mode: hold the button until the block's break time has passed
+                let clicked = self.camera.was_left_mouse_clicked();
+                if clicked || self.camera.is_left_mouse_held() {
+                    self.mine_selected_block(dt);
+                } else {
+                    self.break_progress.reset();
+                }
+            } else if self.camera.was_left_mouse_clicked() {
                 self.handle_left_click();
+            } else if self.camera.is_left_mouse_held() {
+                self.handle_left_drag();
+            } else {
+                self.last_drag_pos = None;
             }
 
-            // Check for putting block in slot
+            // Right click selects the slot holding the targeted block
             if self.camera.was_right_mouse_clicked() {
-                self.put_selected_block_in_slot();
+                self.pick_selected_block();
             }
         }
+
+        self.update_ghost_block();
+
+        let frame_fps = 1.0 / dt.as_secs_f32().max(f32::EPSILON);
+        self.fps = if self.fps > 0.0 {
+            self.fps + (frame_fps - self.fps) * FPS_SMOOTHING
+        } else {
+            frame_fps
+        };
+        if self.debug_mode {
+            self.update_debug_text(current_biome);
+        }
+    }
+
+    /// Lay out the debug overlay for the current frame
+    fn update_debug_text(&mut self, current_biome: Biome) {
+        let camera_pos = self.camera.get_position();
+        let chunk_size = chunk::CHUNK_SIZE as i32;
+        let text = format!(
+            "FPS: {:.0}\nXYZ: {:.1} / {:.1} / {:.1}\nChunk: {}, {}\nChunks loaded: {}\nBiome: {}",
+            self.fps,
+            camera_pos.x,
+            camera_pos.y,
+            camera_pos.z,
+            (camera_pos.x.floor() as i32).div_euclid(chunk_size),
+            (camera_pos.z.floor() as i32).div_euclid(chunk_size),
+            self.world.stats().chunks_loaded,
+            current_biome.name(),
+        );
+        self.debug_text.set_text(
+            &self.queue,
+            &text,
+            DEBUG_TEXT_POSITION,
+            self.config.width,
+            self.config.height,
+        );
+    }
+
+    /// Generate the next batch of initial chunks and fill the loading bar. The camera
+    /// stays put until the world around it is ready, so the player can't fall through it.
+    fn update_warmup(&mut self, started: Instant) {
+        self.camera.update_buffer(&self.queue);
+        self.light.update_buffer(&self.queue);
+
+        let camera_pos = self.camera.get_position();
+        self.world
+            .update(camera_pos, &self.device, &self.biome_manager);
+
+        let progress = self.world.load_progress(camera_pos);
+        self.progress_ui
+            .update(&self.queue, progress, self.config.width, self.config.height);
+
+        if progress >= 1.0 {
+            self.world.set_max_chunks_per_update(None);
+            self.world
+                .set_max_chunk_uploads_per_frame(Some(MAX_CHUNK_UPLOADS_PER_FRAME));
+            self.warmup_started = None;
+            println!("World ready in {:.2?}", started.elapsed());
+        }
     }
 
     fn update_block_selection(&mut self) {
@@ -445,45 +1161,154 @@ impl<'window> State<'window> {
         self.selected_block = new_selection;
     }
 
+    /// Place a block from the selected slot against the targeted block
     fn handle_left_click(&mut self) {
         if let Some(hit) = self.selected_block {
-            // Check if current slot has a block
+            self.last_drag_pos = Some(hit.prev_block_pos);
+            self.last_drag_time = Instant::now();
+
             if let Some(block_type) = self.slot_ui.get_block_in_selected_slot() {
-                // Place block mode
                 self.place_block_from_slot(hit, block_type);
-            } else {
-                // Remove block mode (original behavior)
-                println!("Breaking block at: {:?}", hit.block_pos);
-
-                // Actually remove the block from terrain
-                let removed_block_type = self.world.remove_block(
-                    hit.block_pos[0],
-                    hit.block_pos[1],
-                    hit.block_pos[2],
-                    &self.device,
-                );
+            }
+        }
+    }
 
-                if let Some(block_type) = removed_block_type {
-                    println!(
-                        "Successfully removed {:?} block at: {:?}",
-                        block_type, hit.block_pos
-                    );
-                    // Clear selection since the block is gone
-                    self.selected_block = None;
-                } else {
-                    println!("Failed to remove block at: {:?}", hit.block_pos);
-                }
+    /// Keep mining the targeted block for another `dt`, and remove it once its break time
+    /// (from its hardness and the held tool) has passed
+    fn mine_selected_block(&mut self, dt: Duration) {
+        let Some(hit) = self.selected_block else {
+            self.break_progress.reset();
+            return;
+        };
+        let [x, y, z] = hit.block_pos;
+        let Some(block_type) = self.world.get_block_type(x, y, z) else {
+            self.break_progress.reset();
+            return;
+        };
+
+        let break_time = tools::break_time(self.held_tool, block_type, self.world.block_registry());
+        if !self.break_progress.advance(hit.block_pos, dt, break_time) {
+            return;
+        }
+        self.break_progress.reset();
+
+        println!("Breaking block at: {:?}", hit.block_pos);
+        if let Some(block_type) = self.world.remove_block(x, y, z) {
+            println!(
+                "Successfully removed {:?} block at: {:?}",
+                block_type, hit.block_pos
+            );
+            if !self.slot_ui.add_block(block_type, &self.queue)
+                && !self.inventory_screen.add_block(block_type, &self.queue)
+            {
+                println!("Inventory full, {:?} block lost", block_type);
+            }
+            // Clear selection since the block is gone
+            self.selected_block = None;
+        } else {
+            println!("Failed to remove block at: {:?}", hit.block_pos);
+        }
+    }
+
+    /// Enter or leave photo mode: a free-flying camera with no block interaction.
+    /// Leaving puts the camera back where the player was.
+    fn toggle_photo_mode(&mut self) {
+        if let Some((player_state, fov)) = self.photo_mode_return.take() {
+            self.camera.set_free_fly(false);
+            self.camera.restore_camera_state(&player_state);
+            self.camera.set_fov_degrees(fov);
+            self.world.set_simulation_paused(false);
+            println!("Photo mode: OFF");
+        } else {
+            self.photo_mode_return = Some((self.camera.camera_state(), self.camera.fov_degrees()));
+            self.camera.set_free_fly(true);
+            self.world.set_simulation_paused(true);
+            self.selected_block = None;
+            println!(
+                "Photo mode: ON (Space/Shift fly up/down, [ ] change FOV, - = change time of day)"
+            );
+        }
+    }
+
+    /// Mark the targeted block as a corner of the area to break
+    fn mark_area_corner(&mut self) {
+        if let Some(hit) = self.selected_block {
+            if self.area_corners.len() == 2 {
+                self.area_corners.clear();
             }
+            self.area_corners.push(hit.block_pos);
+            println!(
+                "Area corner {} set at {:?}",
+                self.area_corners.len(),
+                hit.block_pos
+            );
         }
     }
 
+    /// Break every block in the box between the two marked corners
+    fn break_area(&mut self) {
+        let [corner_a, corner_b] = self.area_corners[..] else {
+            println!("Mark two corners with left click before breaking an area");
+            return;
+        };
+
+        let volume: i64 = (0..3)
+            .map(|i| (corner_a[i] - corner_b[i]).abs() as i64 + 1)
+            .product();
+        if volume > MAX_AREA_BREAK_VOLUME {
+            println!(
+                "Area too large to break ({} blocks, max {})",
+                volume, MAX_AREA_BREAK_VOLUME
+            );
+            return;
+        }
+
+        let removed = self.world.break_region(corner_a, corner_b);
+        println!(
+            "Broke {} blocks between {:?} and {:?}",
+            removed.len(),
+            corner_a,
+            corner_b
+        );
+        self.area_corners.clear();
+        self.selected_block = None;
+    }
+
+    /// Keep placing while the left button is held and the target moves to a new cell
+    fn handle_left_drag(&mut self) {
+        if self.last_drag_time.elapsed() < DRAG_INTERVAL {
+            return;
+        }
+
+        let Some(hit) = self.selected_block else {
+            return;
+        };
+
+        let target = hit.prev_block_pos;
+        if Some(target) == self.last_drag_pos {
+            return;
+        }
+
+        // Don't build off the block we just placed, or a held button stacks towards the camera
+        if Some(hit.block_pos) == self.last_drag_pos {
+            return;
+        }
+
+        // Only drag into empty cells
+        if self
+            .world
+            .get_block_type(target[0], target[1], target[2])
+            .is_some_and(|block_type| block_type != blocks::BlockType::Air)
+        {
+            return;
+        }
+
+        self.handle_left_click();
+    }
+
     fn place_block_from_slot(&mut self, hit: raycast::RaycastHit, block_type: blocks::BlockType) {
-        // Calculate placement position based on face normal
-        let placement_pos = [
-            hit.block_pos[0] + hit.face_normal.x as i32,
-            hit.block_pos[1] + hit.face_normal.y as i32,
-            hit.block_pos[2] + hit.face_normal.z as i32,
-        ];
+        // The empty cell the ray passed through just before the targeted block
+        let placement_pos = hit.prev_block_pos;
 
         println!(
             "Attempting to place {:?} block at: {:?}",
@@ -491,8 +1316,8 @@ impl<'window> State<'window> {
         );
 
         // Validate placement position
-        if !self.is_valid_placement_position(placement_pos) {
-            println!("Invalid placement position!");
+        if let Err(reason) = self.check_placement_position(placement_pos) {
+            println!("{}", reason);
             return;
         }
 
@@ -502,7 +1327,6 @@ impl<'window> State<'window> {
             placement_pos[1],
             placement_pos[2],
             block_type,
-            &self.device,
         );
 
         if success {
@@ -510,56 +1334,80 @@ impl<'window> State<'window> {
                 "Successfully placed {:?} block at: {:?}",
                 block_type, placement_pos
             );
-            // Note: We don't remove the block from inventory (infinite blocks)
+            self.slot_ui.take_block_from_selected_slot(&self.queue);
         } else {
             println!("Failed to place block at: {:?}", placement_pos);
         }
     }
 
-    fn is_valid_placement_position(&self, pos: [i32; 3]) -> bool {
+    /// Whether a block can go at `pos`, with the message to show the player if it can't.
+    /// Silent by itself, since the ghost block asks every frame.
+    fn check_placement_position(&self, pos: [i32; 3]) -> Result<(), &'static str> {
         // Check if position is within world bounds
         if pos[1] < 0 || pos[1] >= chunk::WORLD_HEIGHT as i32 {
-            return false;
+            return Err("Invalid placement position!");
         }
 
-        // Check if player would collide with placed block
-        let player_eye_pos = self.camera.get_position();
-        // Convert eye position to feet position (eyes are 1.6 blocks above feet)
-        let player_feet_y = player_eye_pos.y - 1.6;
-        let player_block_x = player_eye_pos.x.floor() as i32;
-        let player_feet_block_y = player_feet_y.floor() as i32;
-        let player_head_block_y = (player_feet_y + 1.8).floor() as i32;
-        let player_block_z = player_eye_pos.z.floor() as i32;
-
-        // Player occupies space from feet to head (1.8 blocks tall)
-        if pos[0] == player_block_x && pos[2] == player_block_z {
-            for y in player_feet_block_y..=player_head_block_y {
-                if pos[1] == y {
-                    println!("Cannot place block inside player position!");
-                    return false;
-                }
+        // Reject cells that would embed the player (and, later, entities like dropped items)
+        let occupants = [self.player_aabb()];
+        if aabb::block_overlaps_any(pos, &occupants) {
+            return Err("Cannot place block inside player position!");
+        }
+
+        Ok(())
+    }
+
+    /// Show the held block where a click would place it, or hide the preview if it can't go
+    /// there or clicks don't place blocks right now
+    fn update_ghost_block(&mut self) {
+        // Outside game mode the selection is stale, and the area tool's clicks mark
+        // corners instead of placing
+        let target = self
+            .selected_block
+            .filter(|_| self.game_mode && !self.area_tool);
+        let placement = target.and_then(|hit| {
+            let block_type = self.slot_ui.get_block_in_selected_slot()?;
+            let pos = hit.prev_block_pos;
+            let free = self.check_placement_position(pos).is_ok()
+                && !self.world.is_block_solid(pos[0], pos[1], pos[2]);
+            free.then_some((pos, block_type))
+        });
+
+        match placement {
+            Some((pos, block_type)) => {
+                let textures = self.world.block_registry().get_textures(block_type);
+                self.ghost_block_renderer.show(&self.queue, pos, &textures);
             }
+            None => self.ghost_block_renderer.hide(),
         }
+    }
 
-        true
+    /// The player's body, from feet to head
+    fn player_aabb(&self) -> Aabb {
+        let player_eye_pos = self.camera.get_position();
+        // Convert eye position to feet position (eyes are 1.6 blocks above feet)
+        let feet = cgmath::Point3::new(player_eye_pos.x, player_eye_pos.y - 1.6, player_eye_pos.z);
+        Aabb::from_feet(feet, PLAYER_HALF_WIDTH, 1.8)
     }
 
-    fn put_selected_block_in_slot(&mut self) {
+    fn pick_selected_block(&mut self) {
         if let Some(hit) = self.selected_block {
             // Get the block type at the selected position
             if let Some(block_type) =
                 self.world
                     .get_block_type(hit.block_pos[0], hit.block_pos[1], hit.block_pos[2])
             {
-                // Don't put air blocks in slots
+                // Blocks come from mining now, so picking only finds them in the hotbar
                 if block_type != blocks::BlockType::Air {
-                    self.slot_ui
-                        .put_block_in_selected_slot(block_type, &self.queue);
-                    println!(
-                        "Put {:?} block in slot {}",
-                        block_type,
-                        self.slot_ui.get_selected_slot()
-                    );
+                    if self.slot_ui.select_slot_with(block_type, &self.queue) {
+                        println!(
+                            "Selected {:?} in slot {}",
+                            block_type,
+                            self.slot_ui.get_selected_slot()
+                        );
+                    } else {
+                        println!("No {:?} blocks in the inventory", block_type);
+                    }
                 }
             }
         }
@@ -601,12 +1449,7 @@ impl<'window> State<'window> {
                     view: &view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.5,
-                            g: 0.8,
-                            b: 1.0,
-                            a: 1.0,
-                        }),
+                        load: wgpu::LoadOp::Clear(self.light.sky_color()),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -622,12 +1465,20 @@ impl<'window> State<'window> {
                 timestamp_writes: None,
             });
 
-            // Render normal terrain
-            render_pass.set_pipeline(&self.render_pipeline);
+            // Render terrain with the pipeline for the active render mode
+            let terrain_pipeline = match (self.render_mode, &self.wireframe_pipeline) {
+                (RenderMode::Wireframe, Some(wireframe_pipeline)) => wireframe_pipeline,
+                (RenderMode::Normals, _) => &self.normals_pipeline,
+                _ => &self.render_pipeline,
+            };
+            render_pass.set_pipeline(terrain_pipeline);
             render_pass.set_bind_group(0, &self.camera.bind_group, &[]);
             render_pass.set_bind_group(1, &self.light.bind_group, &[]);
             render_pass.set_bind_group(2, &self.texture_atlas.bind_group, &[]);
-            self.world.render(&mut render_pass);
+            let frustum = Frustum::from_view_proj(self.camera.view_proj());
+            self.chunks_drawn =
+                self.world
+                    .render_culled(&mut render_pass, &frustum, self.camera.get_position());
 
             // Render block selection wireframe
             if let Some(hit) = self.selected_block {
@@ -644,19 +1495,61 @@ impl<'window> State<'window> {
                     hit.block_pos[0] as f32,
                     hit.block_pos[1] as f32,
                     hit.block_pos[2] as f32,
+                    self.break_progress.fraction(hit.block_pos),
                 );
+                self.wireframe_renderer
+                    .update_face(&self.queue, hit.block_pos, hit.face_normal);
                 self.wireframe_renderer
                     .render(&mut render_pass, &self.camera.bind_group);
             }
 
+            // Translucent preview of the block about to be placed, after the opaque geometry
+            self.ghost_block_renderer.render(
+                &mut render_pass,
+                [
+                    &self.camera.bind_group,
+                    &self.light.bind_group,
+                    &self.texture_atlas.bind_group,
+                ],
+            );
+
+            self.particle_renderer
+                .render(&mut render_pass, &self.camera.bind_group);
+
             // Render chunk boundaries if debug mode is enabled
             if self.debug_mode {
                 self.chunk_debug_renderer
                     .render(&mut render_pass, &self.camera.bind_group);
             }
 
+            if !self.inventory_screen.is_visible() {
+                self.crosshair.render(&mut render_pass);
+            }
+
             // Always render slot UI on top
             self.slot_ui.render(&mut render_pass);
+
+            if self.inventory_screen.is_visible() {
+                self.inventory_screen
+                    .render(&mut render_pass, &self.slot_ui);
+            }
+
+            // Screen-edge darkening goes over everything
+            self.vignette.render(&mut render_pass);
+
+            // Except the debug overlay, which has to stay readable
+            if self.debug_mode && self.warmup_started.is_none() {
+                self.debug_text.render(&mut render_pass);
+            }
+
+            if self.warmup_started.is_some() {
+                self.progress_ui.render(&mut render_pass);
+            }
+
+            // Tint the whole view while the eye is in water
+            if self.camera.is_underwater(&self.world) {
+                self.underwater_overlay.render(&mut render_pass);
+            }
         }
 
         self.queue.submit(std::iter::once(encoder.finish()));
@@ -666,20 +1559,132 @@ impl<'window> State<'window> {
     }
 }
 
+/// Pipeline for drawing chunk meshes. Line mode disables culling so back faces show too.
+fn create_chunk_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    fragment_entry: &str,
+    polygon_mode: wgpu::PolygonMode,
+) -> wgpu::RenderPipeline {
+    let cull_mode = if polygon_mode == wgpu::PolygonMode::Fill {
+        Some(wgpu::Face::Back)
+    } else {
+        None
+    };
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Render Pipeline"),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[voxel::Vertex::desc()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: fragment_entry,
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode,
+            polygon_mode,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: wgpu::TextureFormat::Depth32Float,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    })
+}
+
+/// World seed from `--seed <n>` (or `--seed=<n>`) on the command line, falling back to
+/// the RUSTCRAFT_SEED environment variable. `None` keeps the built-in world.
+fn world_seed() -> Option<u64> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let value = args
+        .iter()
+        .position(|arg| arg == "--seed")
+        .and_then(|i| args.get(i + 1).cloned())
+        .or_else(|| {
+            args.iter()
+                .find_map(|arg| arg.strip_prefix("--seed=").map(str::to_string))
+        })
+        .or_else(|| std::env::var("RUSTCRAFT_SEED").ok())?;
+
+    match value.trim().parse() {
+        Ok(seed) => Some(seed),
+        Err(e) => {
+            println!("Ignoring seed '{}': {}", value, e);
+            None
+        }
+    }
+}
+
+/// Exclusive fullscreen at the current monitor's best video mode if requested and
+/// available, borderless otherwise
+fn fullscreen_mode(window: &Window, exclusive: bool) -> Fullscreen {
+    if exclusive {
+        let best_mode = window.current_monitor().and_then(|monitor| {
+            monitor.video_modes().max_by_key(|mode| {
+                let size = mode.size();
+                (size.width * size.height, mode.refresh_rate_millihertz())
+            })
+        });
+        if let Some(mode) = best_mode {
+            return Fullscreen::Exclusive(mode);
+        }
+    }
+    Fullscreen::Borderless(None)
+}
+
 fn main() -> anyhow::Result<()> {
     env_logger::init();
 
     println!("🎮 Starting Voxel Game...");
 
-    // Initialize the block registry
-    blocks::init_block_registry();
+    let settings = Settings::load_from_file(SETTINGS_FILE).unwrap_or_else(|e| {
+        println!(
+            "Failed to load {}: {}. Using default settings.",
+            SETTINGS_FILE, e
+        );
+        Settings::default()
+    });
 
     let event_loop = EventLoop::new()?;
     let window = winit::window::WindowBuilder::new()
         .with_title("Voxel Game")
-        .with_inner_size(winit::dpi::LogicalSize::new(1280, 800))
+        .with_inner_size(winit::dpi::LogicalSize::new(
+            settings.window.width,
+            settings.window.height,
+        ))
         .build(&event_loop)?;
 
+    if settings.window.fullscreen {
+        window.set_fullscreen(Some(fullscreen_mode(
+            &window,
+            settings.window.exclusive_fullscreen,
+        )));
+    }
+
     // Properly confine the cursor for FPS-style camera movement
     // Center the cursor first, then confine it within window bounds
     let window_size = window.inner_size();
@@ -690,13 +1695,14 @@ fn main() -> anyhow::Result<()> {
     window.set_cursor_visible(false);
 
     let window_id = window.id();
-    let mut state = pollster::block_on(State::new(&window))?;
+    let mut state = pollster::block_on(State::new(&window, &settings, world_seed()))?;
     let mut last_render_time = std::time::Instant::now();
+    let mut frame_limiter = FrameLimiter::new(settings.graphics.target_fps);
 
     println!("🌍 Use WASD to move, mouse to look around, Space to jump, Ctrl to run");
     println!("🖱️  Press ESC to pause/resume game, ESC again in pause mode to exit");
     println!("🔨 Left click to break blocks (bright red outline shows selected block)");
-    println!("📦 Right click to put selected block into current inventory slot");
+    println!("📦 Right click to select the inventory slot holding the targeted block");
     println!("🎒 Use number keys 1-0 to select inventory slots (1=leftmost, 0=rightmost)");
 
     event_loop.run(move |event, elwt| {
@@ -754,6 +1760,7 @@ fn main() -> anyhow::Result<()> {
                                 Err(wgpu::SurfaceError::OutOfMemory) => elwt.exit(),
                                 Err(e) => eprintln!("{:?}", e),
                             }
+                            frame_limiter.wait();
                         }
                         _ => {}
                     }
@@ -762,6 +1769,9 @@ fn main() -> anyhow::Result<()> {
             Event::AboutToWait => {
                 state.window.request_redraw();
             }
+            Event::LoopExiting => {
+                state.save_controls();
+            }
             _ => {}
         }
         elwt.set_control_flow(ControlFlow::Poll);
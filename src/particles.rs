@@ -0,0 +1,432 @@
+use crate::biome::Biome;
+use bytemuck::{Pod, Zeroable};
+use cgmath::{InnerSpace, Point3, Vector3};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use wgpu::util::DeviceExt;
+
+/// Hard cap on live particles across all kinds
+pub const MAX_PARTICLES: usize = 512;
+
+/// Spawn volume: a box this far ahead of the camera...
+const SPAWN_AHEAD: f32 = 6.0;
+/// ...extending this far to each side horizontally
+const SPAWN_HALF_WIDTH: f32 = 10.0;
+/// Vertical spawn range relative to the camera
+const SPAWN_BELOW: f32 = 4.0;
+const SPAWN_ABOVE: f32 = 10.0;
+/// Particles further than this (horizontally) from the camera are recycled
+const RECYCLE_RADIUS: f32 = 20.0;
+
+/// Particles fade out when closer to the camera than this, so none pop up in the player's face
+const NEAR_FADE_START: f32 = 2.0;
+const NEAR_FADE_END: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParticleKind {
+    Snow,
+    Dust,
+    Pollen,
+}
+
+impl ParticleKind {
+    fn color(&self) -> [f32; 3] {
+        match self {
+            ParticleKind::Snow => [0.95, 0.97, 1.0],
+            ParticleKind::Dust => [0.85, 0.74, 0.52],
+            ParticleKind::Pollen => [0.82, 0.88, 0.38],
+        }
+    }
+
+    /// Width of the billboard in blocks
+    fn size(&self) -> f32 {
+        match self {
+            ParticleKind::Snow => 0.08,
+            ParticleKind::Dust => 0.05,
+            ParticleKind::Pollen => 0.04,
+        }
+    }
+
+    /// Seconds a particle lives before it's recycled
+    fn lifetime(&self) -> f32 {
+        match self {
+            ParticleKind::Snow => 8.0,
+            ParticleKind::Dust => 5.0,
+            ParticleKind::Pollen => 6.0,
+        }
+    }
+
+    fn initial_velocity(&self, rng: &mut StdRng) -> Vector3<f32> {
+        match self {
+            // Falls slowly with a little sideways drift
+            ParticleKind::Snow => Vector3::new(
+                rng.gen_range(-0.3..0.3),
+                rng.gen_range(-1.8..-1.0),
+                rng.gen_range(-0.3..0.3),
+            ),
+            // Blown along by a steady wind, barely falling
+            ParticleKind::Dust => Vector3::new(
+                rng.gen_range(1.2..2.0),
+                rng.gen_range(-0.1..0.1),
+                rng.gen_range(0.2..0.6),
+            ),
+            // Hangs in the air, wandering slightly upward
+            ParticleKind::Pollen => Vector3::new(
+                rng.gen_range(-0.2..0.2),
+                rng.gen_range(0.0..0.1),
+                rng.gen_range(-0.2..0.2),
+            ),
+        }
+    }
+}
+
+/// Which particles a biome emits and how many
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmbientParticles {
+    pub kind: ParticleKind,
+    /// New particles per second
+    pub rate: f32,
+    /// Most particles of this kind alive at once
+    pub max_count: usize,
+}
+
+/// Per-biome ambient particle table. `None` for biomes without ambient particles.
+pub fn ambient_particles(biome: Biome) -> Option<AmbientParticles> {
+    match biome {
+        Biome::Tundra | Biome::Mountain => Some(AmbientParticles {
+            kind: ParticleKind::Snow,
+            rate: 60.0,
+            max_count: 400,
+        }),
+        Biome::Desert => Some(AmbientParticles {
+            kind: ParticleKind::Dust,
+            rate: 30.0,
+            max_count: 200,
+        }),
+        Biome::Swamp => Some(AmbientParticles {
+            kind: ParticleKind::Pollen,
+            rate: 15.0,
+            max_count: 120,
+        }),
+        Biome::Plains | Biome::Forest | Biome::Ocean => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Particle {
+    pub kind: ParticleKind,
+    pub position: Point3<f32>,
+    pub velocity: Vector3<f32>,
+    pub age: f32,
+}
+
+impl Particle {
+    /// Opacity from fading in after spawning, out before dying, and out near the camera
+    fn alpha(&self, camera_pos: Point3<f32>) -> f32 {
+        let lifetime = self.kind.lifetime();
+        let age_fade = (self.age / 0.5)
+            .min((lifetime - self.age) / 1.0)
+            .clamp(0.0, 1.0);
+
+        let distance = (self.position - camera_pos).magnitude();
+        let near_fade =
+            ((distance - NEAR_FADE_END) / (NEAR_FADE_START - NEAR_FADE_END)).clamp(0.0, 1.0);
+
+        age_fade * near_fade
+    }
+}
+
+/// Ambient particles spawned around the camera from the current biome
+pub struct ParticleSystem {
+    particles: Vec<Particle>,
+    rng: StdRng,
+    // Fractional particles carried over between frames so low rates still spawn
+    spawn_budget: f32,
+}
+
+impl ParticleSystem {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            particles: Vec::new(),
+            rng: StdRng::seed_from_u64(seed),
+            spawn_budget: 0.0,
+        }
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Move particles, recycle expired or distant ones and spawn new ones for `biome`
+    /// in a volume ahead of the camera. Particles from a previous biome play out normally.
+    pub fn update(&mut self, dt: f32, camera_pos: Point3<f32>, camera_yaw: f32, biome: Biome) {
+        for particle in &mut self.particles {
+            particle.position += particle.velocity * dt;
+            particle.age += dt;
+        }
+        self.particles.retain(|particle| {
+            let dx = particle.position.x - camera_pos.x;
+            let dz = particle.position.z - camera_pos.z;
+            particle.age < particle.kind.lifetime()
+                && dx * dx + dz * dz < RECYCLE_RADIUS * RECYCLE_RADIUS
+        });
+
+        let Some(emitter) = ambient_particles(biome) else {
+            self.spawn_budget = 0.0;
+            return;
+        };
+
+        self.spawn_budget += emitter.rate * dt;
+        let live = self
+            .particles
+            .iter()
+            .filter(|particle| particle.kind == emitter.kind)
+            .count();
+        let room = emitter
+            .max_count
+            .saturating_sub(live)
+            .min(MAX_PARTICLES - self.particles.len());
+        let to_spawn = (self.spawn_budget as usize).min(room);
+        self.spawn_budget -= self.spawn_budget.floor();
+
+        let forward = Vector3::new(camera_yaw.cos(), 0.0, camera_yaw.sin());
+        let center = camera_pos + forward * SPAWN_AHEAD;
+        for _ in 0..to_spawn {
+            let position = Point3::new(
+                center.x + self.rng.gen_range(-SPAWN_HALF_WIDTH..SPAWN_HALF_WIDTH),
+                camera_pos.y + self.rng.gen_range(-SPAWN_BELOW..SPAWN_ABOVE),
+                center.z + self.rng.gen_range(-SPAWN_HALF_WIDTH..SPAWN_HALF_WIDTH),
+            );
+            let velocity = emitter.kind.initial_velocity(&mut self.rng);
+            self.particles.push(Particle {
+                kind: emitter.kind,
+                position,
+                velocity,
+                age: 0.0,
+            });
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct ParticleVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 4],
+}
+
+impl ParticleVertex {
+    pub fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ParticleVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
+
+/// Draws particles as small quads that turn to face the camera around the vertical axis
+pub struct ParticleRenderer {
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+}
+
+impl ParticleRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        camera_bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> Self {
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particle Vertex Buffer"),
+            size: (MAX_PARTICLES * 4 * std::mem::size_of::<ParticleVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let indices: Vec<u32> = (0..MAX_PARTICLES as u32)
+            .flat_map(|i| {
+                let base = i * 4;
+                [base, base + 1, base + 2, base + 2, base + 3, base]
+            })
+            .collect();
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Particle Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Particle Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("particles.wgsl").into()),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Particle Pipeline Layout"),
+                bind_group_layouts: &[camera_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Particle Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[ParticleVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None, // Quads can be seen from either side
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false, // Translucent, hidden by terrain but not by each other
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        Self {
+            render_pipeline,
+            vertex_buffer,
+            index_buffer,
+            num_indices: 0,
+        }
+    }
+
+    /// Rebuild the quads for this frame's particles
+    pub fn update(
+        &mut self,
+        queue: &wgpu::Queue,
+        particles: &[Particle],
+        camera_pos: Point3<f32>,
+        camera_yaw: f32,
+    ) {
+        // Quads face the camera's horizontal direction and stay upright
+        let right = Vector3::new(-camera_yaw.sin(), 0.0, camera_yaw.cos());
+        let up = Vector3::unit_y();
+
+        let vertices: Vec<ParticleVertex> = particles
+            .iter()
+            .take(MAX_PARTICLES)
+            .flat_map(|particle| {
+                let half = particle.kind.size() / 2.0;
+                let [r, g, b] = particle.kind.color();
+                let color = [r, g, b, particle.alpha(camera_pos)];
+                let (dx, dy) = (right * half, up * half);
+                let p = particle.position;
+                [p - dx - dy, p + dx - dy, p + dx + dy, p - dx + dy].map(|corner| ParticleVertex {
+                    position: corner.into(),
+                    color,
+                })
+            })
+            .collect();
+
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        self.num_indices = (vertices.len() / 4 * 6) as u32;
+    }
+
+    pub fn render<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        camera_bind_group: &'a wgpu::BindGroup,
+    ) {
+        if self.num_indices == 0 {
+            return;
+        }
+
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_biome_particles_stay_capped_and_near_the_camera() {
+        let mut system = ParticleSystem::new(1);
+        let camera_pos = Point3::new(100.0, 80.0, -50.0);
+
+        // Plains have no ambient particles
+        system.update(1.0, camera_pos, 0.0, Biome::Plains);
+        assert!(system.particles().is_empty());
+
+        let snow = ambient_particles(Biome::Tundra).unwrap();
+        for _ in 0..600 {
+            system.update(1.0 / 30.0, camera_pos, 0.0, Biome::Tundra);
+            assert!(system.particles().len() <= snow.max_count);
+        }
+        assert!(!system.particles().is_empty());
+        for particle in system.particles() {
+            assert_eq!(particle.kind, ParticleKind::Snow);
+            let dx = particle.position.x - camera_pos.x;
+            let dz = particle.position.z - camera_pos.z;
+            assert!(dx * dx + dz * dz < RECYCLE_RADIUS * RECYCLE_RADIUS);
+        }
+
+        // Walking into a desert: the snow plays out and is replaced by dust
+        for _ in 0..600 {
+            system.update(1.0 / 30.0, camera_pos, 0.0, Biome::Desert);
+        }
+        assert!(system
+            .particles()
+            .iter()
+            .all(|particle| particle.kind == ParticleKind::Dust));
+    }
+
+    #[test]
+    fn test_particles_fade_out_at_the_camera() {
+        let camera_pos = Point3::new(0.0, 0.0, 0.0);
+        let mut particle = Particle {
+            kind: ParticleKind::Snow,
+            position: Point3::new(0.0, 0.0, 10.0),
+            velocity: Vector3::new(0.0, 0.0, 0.0),
+            age: 2.0,
+        };
+        assert_eq!(particle.alpha(camera_pos), 1.0);
+
+        particle.position = Point3::new(0.0, 0.2, 0.0);
+        assert_eq!(particle.alpha(camera_pos), 0.0);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::ui::pixels_to_ndc;
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+/// Bar size in pixels
+const BAR_WIDTH: f32 = 400.0;
+const BAR_HEIGHT: f32 = 12.0;
+
+/// Track and fill
+const QUAD_COUNT: usize = 2;
+
+const TRACK_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const FILL_COLOR: [f32; 4] = [0.35, 0.8, 0.35, 1.0];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct ProgressVertex {
+    position: [f32; 2],
+    color: [f32; 4],
+}
+
+impl ProgressVertex {
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<ProgressVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
+
+/// Loading bar in the middle of the screen, shown while the world warms up
+pub struct ProgressUI {
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    // Zero until the first `update`, so nothing is drawn from the empty vertex buffer
+    num_indices: u32,
+}
+
+impl ProgressUI {
+    pub fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Progress UI Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("progress_ui.wgsl").into()),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Progress UI Pipeline Layout"),
+                bind_group_layouts: &[],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Progress UI Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[ProgressVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always, // Overlay everything
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        // Track and fill quads, rewritten by `update`
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Progress UI Vertex Buffer"),
+            size: (QUAD_COUNT * 4 * std::mem::size_of::<ProgressVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let indices: Vec<u16> = (0..QUAD_COUNT as u16)
+            .flat_map(|quad| [0, 1, 2, 0, 2, 3].map(|i| quad * 4 + i))
+            .collect();
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Progress UI Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self {
+            render_pipeline,
+            vertex_buffer,
+            index_buffer,
+            num_indices: 0,
+        }
+    }
+
+    /// Fill the bar to `progress` (0-1), centered in a window of the given size
+    pub fn update(
+        &mut self,
+        queue: &wgpu::Queue,
+        progress: f32,
+        window_width: u32,
+        window_height: u32,
+    ) {
+        let left = (window_width as f32 - BAR_WIDTH) / 2.0;
+        let top = (window_height as f32 - BAR_HEIGHT) / 2.0;
+        let fill_width = BAR_WIDTH * progress.clamp(0.0, 1.0);
+
+        let quad = |width: f32, color: [f32; 4]| {
+            [
+                (left, top + BAR_HEIGHT),
+                (left + width, top + BAR_HEIGHT),
+                (left + width, top),
+                (left, top),
+            ]
+            .map(|(px, py)| ProgressVertex {
+                position: pixels_to_ndc(px, py, window_width, window_height),
+                color,
+            })
+        };
+
+        let mut vertices = Vec::with_capacity(QUAD_COUNT * 4);
+        vertices.extend(quad(BAR_WIDTH, TRACK_COLOR));
+        vertices.extend(quad(fill_width, FILL_COLOR));
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        self.num_indices = (vertices.len() / 4 * 6) as u32;
+    }
+
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.num_indices == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::world::World;
+use crate::blocks::BlockType;
+use crate::voxel_query::VoxelQuery;
 use cgmath::*;
 
 /// Represents a 3D ray for raycasting
@@ -22,18 +23,42 @@ impl Ray {
     }
 }
 
+/// What a raycast stops at
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RaycastOptions {
+    /// Stop at blocks that are present but not solid, like the surface of water,
+    /// instead of passing through them (e.g. for filling a bucket)
+    pub hit_non_solid: bool,
+}
+
 /// Result of a raycast hit
 #[derive(Debug, Clone, Copy)]
 pub struct RaycastHit {
     pub block_pos: [i32; 3],
+    /// Last cell the ray passed through before the hit, where a new block would be
+    /// placed. Same as `block_pos` when the ray starts inside the hit block.
+    pub prev_block_pos: [i32; 3],
+    pub block_type: BlockType,
     pub distance: f32,
     pub hit_point: Point3<f32>,
     pub face_normal: Vector3<f32>,
 }
 
-/// Perform DDA (Digital Differential Analyzer) raycasting to find block intersections
-pub fn raycast_blocks(ray: Ray, max_distance: f32, world: &World) -> Option<RaycastHit> {
-    let max_steps = (max_distance * 2.0) as i32; // Reasonable step limit
+/// Perform DDA (Digital Differential Analyzer) raycasting to find block intersections.
+/// Passes through non-solid blocks like water, as targeting for breaking and placing does.
+pub fn raycast_blocks(ray: Ray, max_distance: f32, world: &dyn VoxelQuery) -> Option<RaycastHit> {
+    raycast_blocks_with(ray, max_distance, world, RaycastOptions::default())
+}
+
+/// `raycast_blocks` with a choice of which blocks stop the ray
+pub fn raycast_blocks_with(
+    ray: Ray,
+    max_distance: f32,
+    world: &dyn VoxelQuery,
+    options: RaycastOptions,
+) -> Option<RaycastHit> {
+    // Worst case the ray crosses a boundary on every axis for each unit travelled
+    let max_steps = max_distance.ceil() as i32 * 3 + 3;
 
     // Current position in the grid
     let mut current_block = [
@@ -72,7 +97,11 @@ pub fn raycast_blocks(ray: Ray, max_distance: f32, world: &World) -> Option<Rayc
     let mut side_dist = [0.0f32; 3];
 
     for i in 0..3 {
-        if step[i] > 0 {
+        if delta_dist[i].is_infinite() {
+            // Never crosses a boundary on this axis. Skipping the math below also avoids
+            // 0 * inf = NaN when the origin sits exactly on a boundary.
+            side_dist[i] = f32::INFINITY;
+        } else if step[i] > 0 {
             side_dist[i] = (current_block[i] as f32 + 1.0
                 - match i {
                     0 => ray.origin.x,
@@ -91,31 +120,19 @@ pub fn raycast_blocks(ray: Ray, max_distance: f32, world: &World) -> Option<Rayc
     }
 
     let mut last_side = 0; // Which axis was crossed last
+    let mut prev_block = current_block;
+    let mut distance = 0.0f32; // Distance along the ray where the current block was entered
 
     // DDA algorithm
     for _ in 0..max_steps {
-        // Check if current block is solid (not air)
-        if world.is_block_solid(current_block[0], current_block[1], current_block[2]) {
-            // Calculate hit distance
-            let distance = match last_side {
-                0 => {
-                    (current_block[0] as f32 - ray.origin.x + if step[0] > 0 { 0.0 } else { 1.0 })
-                        / ray.direction.x
-                }
-                1 => {
-                    (current_block[1] as f32 - ray.origin.y + if step[1] > 0 { 0.0 } else { 1.0 })
-                        / ray.direction.y
-                }
-                _ => {
-                    (current_block[2] as f32 - ray.origin.z + if step[2] > 0 { 0.0 } else { 1.0 })
-                        / ray.direction.z
-                }
-            };
-
-            if distance > max_distance {
-                break;
-            }
-
+        let [x, y, z] = current_block;
+        let block_type = world.get_block(x, y, z);
+        let is_hit = if options.hit_non_solid {
+            block_type != BlockType::Air
+        } else {
+            world.is_solid(x, y, z)
+        };
+        if is_hit {
             let hit_point = ray.point_at(distance);
 
             // Calculate face normal based on which side was hit
@@ -127,6 +144,8 @@ pub fn raycast_blocks(ray: Ray, max_distance: f32, world: &World) -> Option<Rayc
 
             return Some(RaycastHit {
                 block_pos: current_block,
+                prev_block_pos: prev_block,
+                block_type,
                 distance,
                 hit_point,
                 face_normal,
@@ -134,25 +153,26 @@ pub fn raycast_blocks(ray: Ray, max_distance: f32, world: &World) -> Option<Rayc
         }
 
         // Move to next block boundary
+        prev_block = current_block;
         if side_dist[0] < side_dist[1] && side_dist[0] < side_dist[2] {
+            distance = side_dist[0];
             side_dist[0] += delta_dist[0];
             current_block[0] += step[0];
             last_side = 0;
         } else if side_dist[1] < side_dist[2] {
+            distance = side_dist[1];
             side_dist[1] += delta_dist[1];
             current_block[1] += step[1];
             last_side = 1;
         } else {
+            distance = side_dist[2];
             side_dist[2] += delta_dist[2];
             current_block[2] += step[2];
             last_side = 2;
         }
 
-        // Check distance limit
-        let current_distance = (current_block[0] as f32 - ray.origin.x).abs()
-            + (current_block[1] as f32 - ray.origin.y).abs()
-            + (current_block[2] as f32 - ray.origin.z).abs();
-        if current_distance > max_distance * 2.0 {
+        // Stop once the next block starts beyond reach
+        if distance > max_distance {
             break;
         }
     }
@@ -169,3 +189,162 @@ pub fn create_camera_ray(camera_pos: Point3<f32>, camera_yaw: f32, camera_pitch:
 
     Ray::new(camera_pos, direction)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::World;
+
+    #[test]
+    fn test_water_stops_the_ray_only_when_asked() {
+        let mut world = World::new();
+        world.set_block_for_test(0, 5, 0, BlockType::Sand);
+        world.set_block_for_test(0, 6, 0, BlockType::Water);
+        let ray = || Ray::new(Point3::new(0.5, 9.5, 0.5), Vector3::new(0.0, -1.0, 0.0));
+
+        let hit = raycast_blocks(ray(), 5.0, &world).unwrap();
+        assert_eq!(hit.block_pos, [0, 5, 0]);
+        assert_eq!(hit.block_type, BlockType::Sand);
+
+        let options = RaycastOptions {
+            hit_non_solid: true,
+        };
+        let hit = raycast_blocks_with(ray(), 5.0, &world, options).unwrap();
+        assert_eq!(hit.block_pos, [0, 6, 0]);
+        assert_eq!(hit.block_type, BlockType::Water);
+        assert!((hit.distance - 2.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_previous_cell_is_the_one_the_ray_came_through() {
+        let mut world = World::new();
+        world.set_block_for_test(3, 10, 0, BlockType::Stone);
+
+        // Aimed just past the block's near top edge: the ray crosses into the cell above
+        // the block first and enters the block through its top face
+        let ray = Ray::new(Point3::new(0.5, 12.5, 0.5), Vector3::new(3.0, -1.5, 0.0));
+        let hit = raycast_blocks(ray, 5.0, &world).unwrap();
+        assert_eq!(hit.block_pos, [3, 10, 0]);
+        assert_eq!(hit.prev_block_pos, [3, 11, 0]);
+
+        // Starting inside the block there's no previous cell
+        let ray = Ray::new(Point3::new(3.5, 10.5, 0.5), Vector3::new(1.0, 0.0, 0.0));
+        let hit = raycast_blocks(ray, 5.0, &world).unwrap();
+        assert_eq!(hit.prev_block_pos, hit.block_pos);
+    }
+
+    #[test]
+    fn test_near_vertical_ray_hits_block_within_reach() {
+        let mut world = World::new();
+        // Floor at the bottom of a long vertical shaft, top face 4.9 below the eye
+        world.set_block_for_test(0, 5, 0, BlockType::Stone);
+
+        let ray = Ray::new(
+            Point3::new(0.5, 10.9, 0.5),
+            Vector3::new(0.001, -1.0, 0.0005),
+        );
+        let hit = raycast_blocks(ray, 5.0, &world).expect("block within reach should be hit");
+
+        assert_eq!(hit.block_pos, [0, 5, 0]);
+        assert!((hit.distance - 4.9).abs() < 0.01);
+        assert_eq!(hit.face_normal, Vector3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn test_diagonal_ray_hits_block_within_reach() {
+        let mut world = World::new();
+        // Diagonal rays cross a boundary on every axis, needing many more DDA steps
+        world.set_block_for_test(2, 2, 2, BlockType::Stone);
+
+        let ray = Ray::new(Point3::new(0.1, 0.3, 0.5), Vector3::new(1.0, 1.0, 1.0));
+        let hit = raycast_blocks(ray, 5.0, &world).expect("block within reach should be hit");
+
+        assert_eq!(hit.block_pos, [2, 2, 2]);
+        assert!((hit.distance - 1.9 * 3f32.sqrt()).abs() < 0.01);
+        assert_eq!(hit.face_normal, Vector3::new(-1.0, 0.0, 0.0));
+        assert_eq!(hit.prev_block_pos, [1, 2, 2]);
+    }
+
+    #[test]
+    fn test_block_beyond_reach_is_not_hit() {
+        let mut world = World::new();
+        world.set_block_for_test(0, 5, 0, BlockType::Stone);
+
+        let ray = Ray::new(Point3::new(0.5, 11.1, 0.5), Vector3::new(0.0, -1.0, 0.0));
+        assert!(raycast_blocks(ray, 5.0, &world).is_none());
+    }
+
+    #[test]
+    fn test_axis_aligned_rays_hit_the_facing_side() {
+        let mut world = World::new();
+        world.set_block_for_test(4, 20, 4, BlockType::Stone);
+        let center = Point3::new(4.5, 20.5, 4.5);
+
+        for direction in [
+            Vector3::unit_x(),
+            -Vector3::unit_x(),
+            Vector3::unit_y(),
+            -Vector3::unit_y(),
+            Vector3::unit_z(),
+            -Vector3::unit_z(),
+        ] {
+            // Start 3 blocks away, heading toward the block
+            let ray = Ray::new(center - direction * 3.0, direction);
+            let hit = raycast_blocks(ray, 5.0, &world).expect("block ahead should be hit");
+
+            assert_eq!(hit.block_pos, [4, 20, 4], "{:?}", direction);
+            assert_eq!(hit.face_normal, -direction, "{:?}", direction);
+            assert!((hit.distance - 2.5).abs() < 1e-4, "{:?}", direction);
+            assert_eq!(
+                hit.prev_block_pos,
+                [
+                    4 - direction.x as i32,
+                    20 - direction.y as i32,
+                    4 - direction.z as i32
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_ray_starting_on_a_cell_boundary() {
+        let mut world = World::new();
+        world.set_block_for_test(3, 10, 0, BlockType::Stone);
+
+        // y and z sit exactly on cell boundaries while the ray travels along x
+        let ray = Ray::new(Point3::new(0.5, 10.0, 0.0), Vector3::unit_x());
+        let hit = raycast_blocks(ray, 5.0, &world).expect("block ahead should be hit");
+
+        assert_eq!(hit.block_pos, [3, 10, 0]);
+        assert_eq!(hit.face_normal, -Vector3::unit_x());
+        assert!((hit.distance - 2.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_flat_diagonal_ray_distance() {
+        let mut world = World::new();
+        world.set_block_for_test(3, 0, 3, BlockType::Stone);
+
+        // 45 degrees in the XZ plane, entering the block's -X face at z = 3.5
+        let ray = Ray::new(Point3::new(0.5, 0.5, 1.0), Vector3::new(1.0, 0.0, 1.0));
+        let hit = raycast_blocks(ray, 5.0, &world).expect("block ahead should be hit");
+
+        assert_eq!(hit.block_pos, [3, 0, 3]);
+        assert_eq!(hit.face_normal, -Vector3::unit_x());
+        assert!((hit.distance - 2.5 * 2f32.sqrt()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_rays_missing_the_block() {
+        let mut world = World::new();
+        world.set_block_for_test(4, 20, 4, BlockType::Stone);
+
+        // Pointing away
+        let ray = Ray::new(Point3::new(4.5, 20.5, 1.5), -Vector3::unit_z());
+        assert!(raycast_blocks(ray, 5.0, &world).is_none());
+
+        // Passing just beside it
+        let ray = Ray::new(Point3::new(5.05, 20.5, 1.5), Vector3::unit_z());
+        assert!(raycast_blocks(ray, 5.0, &world).is_none());
+    }
+}
@@ -0,0 +1,47 @@
+/// How chunk geometry is drawn. F4 cycles through these to debug meshing and lighting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderMode {
+    /// Normal textured and lit terrain
+    #[default]
+    Solid,
+    /// Edges of every chunk triangle, including back faces
+    Wireframe,
+    /// Surface normals shown as colors
+    Normals,
+}
+
+impl RenderMode {
+    /// The next mode in the cycle. Wireframe is skipped when the GPU can't draw lines.
+    pub fn next(self, wireframe_supported: bool) -> Self {
+        match self {
+            RenderMode::Solid if wireframe_supported => RenderMode::Wireframe,
+            RenderMode::Solid | RenderMode::Wireframe => RenderMode::Normals,
+            RenderMode::Normals => RenderMode::Solid,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::Solid => "Solid",
+            RenderMode::Wireframe => "Wireframe",
+            RenderMode::Normals => "Normals",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_skips_wireframe_when_unsupported() {
+        let mut mode = RenderMode::Solid;
+        mode = mode.next(false);
+        assert_eq!(mode, RenderMode::Normals);
+        mode = mode.next(false);
+        assert_eq!(mode, RenderMode::Solid);
+
+        assert_eq!(RenderMode::Solid.next(true), RenderMode::Wireframe);
+        assert_eq!(RenderMode::Wireframe.next(true), RenderMode::Normals);
+    }
+}
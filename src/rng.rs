@@ -0,0 +1,85 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Salts that separate independent random streams at the same position
+pub mod salt {
+    /// Roll that decides which kind of structure spawns at a position
+    pub const STRUCTURE_TYPE: u64 = 0x5354_5255_4354_5950;
+    /// Shape/variant randomness of the structure itself (tree height, house type, ...)
+    pub const STRUCTURE: u64 = 0x5354_5255_4354_5552;
+    /// Roll for structures loaded from .struct files, separate so adding one doesn't
+    /// move the trees and houses
+    pub const FILE_STRUCTURE: u64 = 0x4649_4c45_5354_5243;
+    /// Roll for villages, separate for the same reason
+    pub const VILLAGE: u64 = 0x5649_4c4c_4147_4553;
+    /// Which way a placed structure faces
+    pub const ROTATION: u64 = 0x524f_5441_5449_4f4e;
+    /// Dithering of surface blocks between neighboring biomes
+    pub const SURFACE_BLEND: u64 = 0x5355_5246_424c_4e44;
+    /// Boulder placement from the example terrain modifier
+    pub const BOULDER: u64 = 0x424f_554c_4445_5253;
+    /// Ore vein placement in the stone layer
+    pub const ORE: u64 = 0x4f52_4556_4549_4e53;
+    /// Terrain seed derived from a world seed
+    pub const TERRAIN_SEED: u64 = 0x5445_5252_5345_4544;
+    /// Structure seed derived from a world seed
+    pub const STRUCTURE_SEED: u64 = 0x5354_5243_5345_4544;
+}
+
+/// Create a deterministic RNG for a world column.
+///
+/// The same `(world_x, world_z, salt, seed)` always yields the same sequence, so a seed
+/// reproduces the same decorations. Different salts give independent streams at the
+/// same position (e.g. ores vs. trees).
+pub fn position_rng(world_x: i32, world_z: i32, salt: u64, seed: u32) -> StdRng {
+    StdRng::seed_from_u64(position_hash(world_x, world_z, salt, seed))
+}
+
+/// Derive one of the generator seeds from a world seed. The world seed is mixed with a
+/// salt from `salt` (e.g. `salt::TERRAIN_SEED`) through the splitmix64 finalizer and the
+/// high 32 bits are kept, so every world seed gives the same, unrelated generator seeds.
+pub fn derive_seed(world_seed: u64, salt: u64) -> u32 {
+    let mut h = world_seed ^ salt.rotate_left(17);
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    ((h ^ (h >> 31)) >> 32) as u32
+}
+
+/// Mix position, salt and seed into a well-distributed 64-bit value (splitmix64 finalizer)
+fn position_hash(world_x: i32, world_z: i32, salt: u64, seed: u32) -> u64 {
+    let mut h = (world_x as u32 as u64) | ((world_z as u32 as u64) << 32);
+    h ^= salt.rotate_left(17);
+    h ^= (seed as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^ (h >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::Rng;
+
+    fn sample(rng: &mut StdRng) -> Vec<u32> {
+        (0..16).map(|_| rng.gen()).collect()
+    }
+
+    #[test]
+    fn test_identical_inputs_give_identical_sequences() {
+        let a = sample(&mut position_rng(-37, 1024, salt::STRUCTURE, 7777));
+        let b = sample(&mut position_rng(-37, 1024, salt::STRUCTURE, 7777));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_salt_and_position_give_independent_streams() {
+        let base = sample(&mut position_rng(5, 9, salt::STRUCTURE, 42));
+        let other_salt = sample(&mut position_rng(5, 9, salt::STRUCTURE_TYPE, 42));
+        let swapped_axes = sample(&mut position_rng(9, 5, salt::STRUCTURE, 42));
+        let other_seed = sample(&mut position_rng(5, 9, salt::STRUCTURE, 43));
+
+        assert_ne!(base, other_salt);
+        assert_ne!(base, swapped_axes);
+        assert_ne!(base, other_seed);
+    }
+}
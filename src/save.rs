@@ -0,0 +1,228 @@
+use crate::blocks::BlockType;
+use crate::camera::CameraState;
+use crate::inventory_screen::STORAGE_SLOTS;
+use crate::slot_ui::MAX_STACK_SIZE;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Current save file format. Bump it and add a step to `migrate` whenever the format changes.
+pub const SAVE_VERSION: u32 = 4;
+
+/// Player state written to the save file so loading resumes where the player left off
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SaveData {
+    /// Format version, written first so older and newer files can be told apart
+    pub version: u32,
+    pub camera: CameraState,
+    pub selected_slot: usize,
+    /// Block in each inventory slot, `Air` for an empty slot (TOML has no null)
+    pub inventory: Vec<BlockType>,
+    /// Number of blocks in each inventory slot
+    pub counts: Vec<u32>,
+    /// Blocks and counts of the inventory screen's storage grid, stored like the
+    /// hotbar's. Added in version 4; older saves load with empty storage.
+    #[serde(default)]
+    pub storage: Vec<BlockType>,
+    #[serde(default)]
+    pub storage_counts: Vec<u32>,
+}
+
+impl SaveData {
+    pub fn new(
+        camera: CameraState,
+        selected_slot: usize,
+        inventory: [Option<(BlockType, u32)>; 10],
+        storage: [Option<(BlockType, u32)>; STORAGE_SLOTS],
+    ) -> Self {
+        let (inventory, counts) = split_slots(&inventory);
+        let (storage, storage_counts) = split_slots(&storage);
+        Self {
+            version: SAVE_VERSION,
+            camera,
+            selected_slot,
+            inventory,
+            counts,
+            storage,
+            storage_counts,
+        }
+    }
+
+    /// Inventory slots with empty slots mapped back to `None`
+    pub fn inventory_slots(&self) -> [Option<(BlockType, u32)>; 10] {
+        join_slots(&self.inventory, &self.counts)
+    }
+
+    /// Storage slots with empty slots mapped back to `None`
+    pub fn storage_slots(&self) -> [Option<(BlockType, u32)>; STORAGE_SLOTS] {
+        join_slots(&self.storage, &self.storage_counts)
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let toml_content = toml::to_string_pretty(self)?;
+        fs::write(path, toml_content)?;
+        Ok(())
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        Self::load_from_str(&content)
+    }
+
+    /// Parse save data, migrating older formats to `SAVE_VERSION`
+    pub fn load_from_str(content: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let data: toml::Value = toml::from_str(content)?;
+
+        // Saves from before versioning have no version key
+        let version = match data.get("version") {
+            None => 1,
+            Some(value) => value
+                .as_integer()
+                .and_then(|version| u32::try_from(version).ok())
+                .ok_or("Save file version must be a positive integer")?,
+        };
+        if version > SAVE_VERSION {
+            return Err(format!(
+                "Save file version {} is newer than the supported version {}",
+                version, SAVE_VERSION
+            )
+            .into());
+        }
+
+        let save_data: SaveData = migrate(version, data)?.try_into()?;
+        Ok(save_data)
+    }
+}
+
+/// Block and count lists for slots, with `Air` and 0 for empty ones
+fn split_slots(slots: &[Option<(BlockType, u32)>]) -> (Vec<BlockType>, Vec<u32>) {
+    slots
+        .iter()
+        .map(|slot| slot.unwrap_or((BlockType::Air, 0)))
+        .unzip()
+}
+
+/// Slots from block and count lists. Missing entries, `Air` and empty stacks are `None`.
+fn join_slots<const N: usize>(
+    blocks: &[BlockType],
+    counts: &[u32],
+) -> [Option<(BlockType, u32)>; N] {
+    let mut slots = [None; N];
+    for (i, (slot, &block_type)) in slots.iter_mut().zip(blocks.iter()).enumerate() {
+        let count = counts.get(i).copied().unwrap_or(0);
+        if block_type != BlockType::Air && count > 0 {
+            *slot = Some((block_type, count));
+        }
+    }
+    slots
+}
+
+/// Upgrade raw save data one version at a time until it matches `SAVE_VERSION`
+fn migrate(version: u32, mut data: toml::Value) -> Result<toml::Value, String> {
+    for from in version..SAVE_VERSION {
+        match from {
+            // v1 -> v2: adds the version header, nothing else changed
+            1 => {}
+            // v2 -> v3: slots get counts. Blocks used to be infinite, so fill each
+            // occupied slot with a full stack.
+            2 => {
+                let counts: Vec<toml::Value> = data
+                    .get("inventory")
+                    .and_then(toml::Value::as_array)
+                    .ok_or("Save file has no inventory")?
+                    .iter()
+                    .map(|block| {
+                        let count = if block.as_str() == Some("Air") {
+                            0
+                        } else {
+                            MAX_STACK_SIZE
+                        };
+                        toml::Value::Integer(count as i64)
+                    })
+                    .collect();
+                if let Some(table) = data.as_table_mut() {
+                    table.insert("counts".to_string(), toml::Value::Array(counts));
+                }
+            }
+            // v3 -> v4: adds the storage grid, which `serde(default)` leaves empty
+            3 => {}
+            _ => return Err(format!("Unsupported save file version {}", from)),
+        }
+    }
+
+    if let Some(table) = data.as_table_mut() {
+        table.insert(
+            "version".to_string(),
+            toml::Value::Integer(SAVE_VERSION as i64),
+        );
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camera_snapshot_round_trip() {
+        let mut inventory = [None; 10];
+        inventory[0] = Some((BlockType::Stone, 12));
+        inventory[7] = Some((BlockType::Glass, 1));
+        let mut storage = [None; STORAGE_SLOTS];
+        storage[3] = Some((BlockType::Sand, 40));
+        storage[STORAGE_SLOTS - 1] = Some((BlockType::Planks, 2));
+
+        let save_data = SaveData::new(
+            CameraState {
+                position: [12.5, 70.25, -3.75],
+                yaw: -1.2,
+                pitch: 0.35,
+            },
+            7,
+            inventory,
+            storage,
+        );
+
+        let content = toml::to_string_pretty(&save_data).unwrap();
+        let loaded: SaveData = toml::from_str(&content).unwrap();
+
+        assert_eq!(loaded, save_data);
+        assert_eq!(loaded.inventory_slots(), inventory);
+        assert_eq!(loaded.storage_slots(), storage);
+        assert!(content.starts_with(&format!("version = {}", SAVE_VERSION)));
+    }
+
+    #[test]
+    fn test_v1_save_migrates_to_current_version() {
+        // Written before saves carried a version
+        let v1 = r#"
+selected_slot = 2
+inventory = ["Air", "Air", "Planks", "Air", "Air", "Air", "Air", "Air", "Air", "Air"]
+
+[camera]
+position = [1.0, 80.0, -4.5]
+yaw = 0.5
+pitch = -0.25
+"#;
+
+        let loaded = SaveData::load_from_str(v1).unwrap();
+
+        assert_eq!(loaded.version, SAVE_VERSION);
+        assert_eq!(loaded.selected_slot, 2);
+        assert_eq!(
+            loaded.inventory_slots()[2],
+            Some((BlockType::Planks, MAX_STACK_SIZE))
+        );
+        assert_eq!(loaded.inventory_slots()[3], None);
+        assert_eq!(loaded.storage_slots(), [None; STORAGE_SLOTS]);
+        assert_eq!(loaded.camera.position, [1.0, 80.0, -4.5]);
+    }
+
+    #[test]
+    fn test_newer_save_version_is_rejected() {
+        let future = format!("version = {}\n", SAVE_VERSION + 1);
+        let err = SaveData::load_from_str(&future).unwrap_err();
+
+        assert!(err.to_string().contains("newer"), "{}", err);
+    }
+}
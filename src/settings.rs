@@ -0,0 +1,254 @@
+use crate::terrain::{TerrainKind, SEA_LEVEL};
+use crate::texture_atlas::ATLAS_TILE_SIZE;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+/// Rendering quality options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    /// Sharpen textures viewed at grazing angles. Off by default: wgpu needs linear
+    /// magnification with it, which blurs the pixel-art textures up close.
+    pub anisotropic_filtering: bool,
+    /// Maximum anisotropy level, 1-16
+    pub anisotropy_clamp: u16,
+    /// Frame rate cap, applied on top of the present mode. `None` renders as fast as possible
+    pub target_fps: Option<u32>,
+    /// Darkening toward the screen corners, 0.0 disables it
+    pub vignette_strength: f32,
+    /// Plain dirt on grass sides covered from above instead of the green overlay
+    pub smart_grass_sides: bool,
+    /// Merge neighboring block faces into larger quads when building chunk meshes
+    pub greedy_meshing: bool,
+    /// Brightness of faces the sun doesn't reach, 0.0 leaves them black
+    pub ambient_light: f32,
+    /// Color the ambient light is tinted with, as linear RGB (0-1)
+    pub ambient_color: [f32; 3],
+    /// Strength of a second light from the side the sun never reaches, 0.0 disables it
+    pub fill_light: f32,
+    /// Length of a full day and night in seconds, 0 keeps the sun still
+    pub day_length_secs: f32,
+    /// Cap on chunk mesh GPU memory in MB. Going over it reduces the render distance
+    /// until the chunks fit again.
+    pub gpu_memory_budget_mb: Option<u64>,
+    /// Pixel size of block textures in the atlas, a power of two. Textures of other sizes
+    /// are scaled to it.
+    pub texture_size: u32,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            anisotropic_filtering: false,
+            anisotropy_clamp: 16,
+            target_fps: None,
+            vignette_strength: 0.0,
+            smart_grass_sides: false,
+            greedy_meshing: false,
+            ambient_light: 0.25,
+            ambient_color: [1.0, 1.0, 1.0],
+            fill_light: 0.0,
+            day_length_secs: 1200.0,
+            gpu_memory_budget_mb: None,
+            texture_size: ATLAS_TILE_SIZE,
+        }
+    }
+}
+
+impl GraphicsSettings {
+    /// `day_length_secs` as a duration, with negative values stopping the sun. Fails on
+    /// values too large for a `Duration`, such as `inf`.
+    pub fn day_length(&self) -> Result<Duration, String> {
+        Duration::try_from_secs_f32(self.day_length_secs.max(0.0)).map_err(|_| {
+            format!(
+                "day_length_secs {} is not a valid length of day",
+                self.day_length_secs
+            )
+        })
+    }
+}
+
+/// Window options applied at startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowSettings {
+    /// Initial window size in logical pixels
+    pub width: u32,
+    pub height: u32,
+    /// Start in fullscreen (F11 toggles it at runtime)
+    pub fullscreen: bool,
+    /// Use exclusive fullscreen with the monitor's best video mode instead of borderless
+    pub exclusive_fullscreen: bool,
+}
+
+impl Default for WindowSettings {
+    fn default() -> Self {
+        Self {
+            width: 1280,
+            height: 800,
+            fullscreen: false,
+            exclusive_fullscreen: false,
+        }
+    }
+}
+
+/// Movement options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ControlsSettings {
+    /// Jump over 1-block steps instead of stepping straight up onto them
+    pub auto_jump: bool,
+    /// Radians turned per second for each pixel of mouse movement per frame
+    pub mouse_sensitivity: f32,
+    /// Walking speed in blocks per second (running is twice as fast)
+    pub walk_speed: f32,
+}
+
+impl Default for ControlsSettings {
+    fn default() -> Self {
+        Self {
+            auto_jump: false,
+            mouse_sensitivity: 0.5,
+            walk_speed: 4.0,
+        }
+    }
+}
+
+impl ControlsSettings {
+    /// Write the sensitivity and speed back into the `[controls]` table of a settings
+    /// file, keeping everything else in it (including comments) as it was
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let content = if path.exists() {
+            fs::read_to_string(path)?
+        } else {
+            String::new()
+        };
+        fs::write(path, self.write_into(&content)?)?;
+        Ok(())
+    }
+
+    /// `content` with the sensitivity and speed replaced or added
+    fn write_into(&self, content: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let mut document: toml_edit::DocumentMut = content.parse()?;
+
+        let controls = document
+            .entry("controls")
+            .or_insert_with(toml_edit::table)
+            .as_table_like_mut()
+            .ok_or("[controls] in the settings file is not a table")?;
+        // Round away f32 noise so the file keeps readable values like 0.4
+        let rounded = |value: f32| (value as f64 * 1000.0).round() / 1000.0;
+        controls.insert(
+            "mouse_sensitivity",
+            toml_edit::value(rounded(self.mouse_sensitivity)),
+        );
+        controls.insert("walk_speed", toml_edit::value(rounded(self.walk_speed)));
+
+        Ok(document.to_string())
+    }
+}
+
+/// World generation options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GenerationSettings {
+    /// Scatter boulders using the example terrain modifier
+    pub boulders: bool,
+    /// Terrain generator used for new chunks
+    pub terrain: TerrainKind,
+    /// Height water fills up to on noise terrain
+    pub sea_level: usize,
+    /// Rebuild every loaded chunk in place, synchronously in one frame, when biome.toml
+    /// is reloaded, instead of dropping them and regenerating them over the next frames
+    pub reload_in_place: bool,
+}
+
+impl Default for GenerationSettings {
+    fn default() -> Self {
+        Self {
+            boulders: false,
+            terrain: TerrainKind::default(),
+            sea_level: SEA_LEVEL,
+            reload_in_place: true,
+        }
+    }
+}
+
+/// User settings loaded from settings.toml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub window: WindowSettings,
+    pub graphics: GraphicsSettings,
+    pub controls: ControlsSettings,
+    pub generation: GenerationSettings,
+}
+
+impl Settings {
+    /// Load settings from a TOML file. Missing keys fall back to their defaults.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        let settings: Settings = toml::from_str(&content)?;
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::BlockType;
+
+    #[test]
+    fn test_missing_keys_use_defaults() {
+        let settings: Settings = toml::from_str("[graphics]\nanisotropy_clamp = 4\n").unwrap();
+        assert_eq!(settings.graphics.anisotropy_clamp, 4);
+        assert!(!settings.graphics.anisotropic_filtering);
+    }
+
+    #[test]
+    fn test_day_length_rejects_values_too_large_for_a_duration() {
+        let settings: Settings = toml::from_str("[graphics]\nday_length_secs = inf\n").unwrap();
+        assert!(settings.graphics.day_length().is_err());
+
+        let graphics = GraphicsSettings {
+            day_length_secs: -5.0,
+            ..GraphicsSettings::default()
+        };
+        assert_eq!(graphics.day_length(), Ok(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_saving_controls_keeps_the_rest_of_the_file() {
+        let original = "# Comment\n[graphics]\nambient_light = 0.5\n\n[controls]\n# Doc\nauto_jump = true\nwalk_speed = 4.0\n";
+        let controls = ControlsSettings {
+            auto_jump: true,
+            mouse_sensitivity: 0.2,
+            walk_speed: 6.5,
+        };
+        let saved = controls.write_into(original).unwrap();
+
+        assert!(saved.starts_with("# Comment\n[graphics]\nambient_light = 0.5\n"));
+        assert!(saved.contains("# Doc\nauto_jump = true\n"));
+        let settings: Settings = toml::from_str(&saved).unwrap();
+        assert_eq!(settings.controls.mouse_sensitivity, 0.2);
+        assert_eq!(settings.controls.walk_speed, 6.5);
+        assert_eq!(settings.graphics.ambient_light, 0.5);
+    }
+
+    #[test]
+    fn test_superflat_terrain_parses() {
+        let settings: Settings = toml::from_str(
+            "[generation.terrain]\ntype = \"super_flat\"\nlayers = [[\"Stone\", 3], [\"Grass\", 1]]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            settings.generation.terrain,
+            TerrainKind::SuperFlat {
+                layers: vec![(BlockType::Stone, 3), (BlockType::Grass, 1)]
+            }
+        );
+    }
+}
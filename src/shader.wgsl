@@ -1,29 +1,56 @@
 struct CameraUniform {
     view_proj: mat4x4<f32>,
+    time: f32,
+    // Player position, which fog distances are measured from (w unused)
+    position: vec4<f32>,
 }
 
-struct LightUniform {
+struct LightData {
     direction: vec3<f32>,
-    color: vec3<f32>,
     intensity: f32,
+    color: vec3<f32>,
+}
+
+const MAX_LIGHTS: u32 = 4u;
+
+struct LightsUniform {
+    ambient: vec4<f32>,
+    fog_color: vec4<f32>,
+    count: u32,
+    // No fog unless fog_end is past fog_start
+    fog_start: f32,
+    fog_end: f32,
+    lights: array<LightData, MAX_LIGHTS>,
 }
 
 @group(0) @binding(0)
 var<uniform> camera: CameraUniform;
 
 @group(1) @binding(0)
-var<uniform> light: LightUniform;
+var<uniform> lights: LightsUniform;
 
 @group(2) @binding(0)
 var texture_atlas: texture_2d<f32>;
 @group(2) @binding(1)
 var texture_sampler: sampler;
 
+struct AtlasUniform {
+    // Tiles along each side of the atlas (TextureAtlas::tiles_per_row)
+    tiles_per_row: u32,
+}
+
+@group(2) @binding(2)
+var<uniform> atlas: AtlasUniform;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) tex_coords: vec2<f32>,
     @location(2) normal: vec3<f32>,
     @location(3) texture_id: u32,
+    @location(4) ao: f32,
+    @location(5) emission: f32,
+    @location(6) block_light: f32,
+    @location(7) skylight: f32,
 }
 
 struct VertexOutput {
@@ -32,54 +59,156 @@ struct VertexOutput {
     @location(1) world_position: vec3<f32>,
     @location(2) normal: vec3<f32>,
     @location(3) texture_id: u32,
+    @location(4) ao: f32,
+    @location(5) emission: f32,
+    @location(6) block_light: f32,
+    @location(7) skylight: f32,
 }
 
+const WATER_TEXTURE_ID: u32 = 5u; // TextureId::Water
+// How far the water surface dips below the block top. It only ever moves down, so it
+// never pokes out of the blocks around it and the shore stays covered.
+const WATER_WAVE_HEIGHT: f32 = 0.06;
+// Warm tint of the light spreading from glowing blocks
+const BLOCK_LIGHT_COLOR: vec3<f32> = vec3<f32>(1.0, 0.85, 0.6);
+
 @vertex
 fn vs_main(
     model: VertexInput,
 ) -> VertexOutput {
     var out: VertexOutput;
-    out.tex_coords = model.tex_coords;
-    out.world_position = model.position;
+    var position = model.position;
+    var tex_coords = model.tex_coords;
+
+    // Ripple the top of water with a sine wave across the world, and drift its texture
+    if (model.texture_id == WATER_TEXTURE_ID) {
+        let phase = camera.time * 1.5 + position.x * 0.8 + position.z * 0.6;
+        if (model.normal.y > 0.5) {
+            position.y -= WATER_WAVE_HEIGHT * (0.5 + 0.5 * sin(phase));
+        }
+        tex_coords += vec2<f32>(camera.time * 0.05, 0.03 * sin(phase));
+    }
+
+    out.tex_coords = tex_coords;
+    out.world_position = position;
     out.normal = model.normal;
     out.texture_id = model.texture_id;
-    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
+    out.ao = model.ao;
+    out.emission = model.emission;
+    out.block_light = model.block_light;
+    out.skylight = model.skylight;
+    out.clip_position = camera.view_proj * vec4<f32>(position, 1.0);
     return out;
 }
 
 
-// Calculate texture coordinates within the atlas
-fn get_atlas_coords(tex_coords: vec2<f32>, texture_id: u32) -> vec2<f32> {
-    let atlas_size = 4u; // 4x4 texture atlas
+// Calculate texture coordinates within the atlas. `dx` and `dy` are the screen-space
+// gradients of `tex_coords`, which decide how coarse a mip level gets sampled.
+fn get_atlas_coords(tex_coords: vec2<f32>, texture_id: u32, dx: vec2<f32>, dy: vec2<f32>) -> vec2<f32> {
+    let atlas_size = atlas.tiles_per_row;
     let tile_size = 1.0 / f32(atlas_size);
     
     let tile_x = f32(texture_id % atlas_size);
     let tile_y = f32(texture_id / atlas_size);
     
+    // Greedy-meshed quads span several blocks with UVs running past 1.0, so wrap them to
+    // repeat the tile. Then pull in by half a texel of the coarsest mip level sampled so
+    // filtering never reaches into a neighboring tile. At the last level a tile is a
+    // single texel and the inset lands on its center.
+    let tile_texels = f32(textureDimensions(texture_atlas).x) * tile_size;
+    let texels_per_pixel = max(length(dx), length(dy)) * tile_texels;
+    let max_level = f32(textureNumLevels(texture_atlas) - 1u);
+    let level = clamp(ceil(log2(max(texels_per_pixel, 1.0))), 0.0, max_level);
+    let inset = min(0.5 * exp2(level) / tile_texels, 0.5);
+    let tile_uv = inset + fract(tex_coords) * (1.0 - 2.0 * inset);
+    
     // Map texture coordinates to the correct tile in the atlas
-    let atlas_x = (tile_x + tex_coords.x) * tile_size;
-    let atlas_y = (tile_y + tex_coords.y) * tile_size;
+    let atlas_x = (tile_x + tile_uv.x) * tile_size;
+    let atlas_y = (tile_y + tile_uv.y) * tile_size;
     
     return vec2<f32>(atlas_x, atlas_y);
 }
 
-@fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+// Distance along the view direction for a 0..1 depth buffer value. near/far come from
+// CameraSystem::depth_params; the 0..1 range is what OPENGL_TO_WGPU_MATRIX produces.
+fn linearize_depth(depth: f32, near: f32, far: f32) -> f32 {
+    return near * far / (far - depth * (far - near));
+}
+
+// Lit texture color shared by the solid and ghost passes
+fn shade(in: VertexOutput) -> vec4<f32> {
     // Sample from texture atlas (including alpha channel)
-    let atlas_coords = get_atlas_coords(in.tex_coords, in.texture_id);
-    let texture_color = textureSample(texture_atlas, texture_sampler, atlas_coords);
+    // Gradients come from the unwrapped UVs so the wrap doesn't pick the smallest mip at tile seams
+    let dx = dpdx(in.tex_coords);
+    let dy = dpdy(in.tex_coords);
+    let atlas_coords = get_atlas_coords(in.tex_coords, in.texture_id, dx, dy);
+    let uv_scale = 1.0 / f32(atlas.tiles_per_row);
+    let texture_color = textureSampleGrad(
+        texture_atlas,
+        texture_sampler,
+        atlas_coords,
+        dx * uv_scale,
+        dy * uv_scale,
+    );
     
-    // Use the actual surface normal from the vertex
+    // Use the actual surface normal from the vertex (interpolated across the face with
+    // smooth normals, so renormalize)
     let normal = normalize(in.normal);
-    let light_dir = normalize(-light.direction);
-    
-    // Calculate diffuse lighting with good ambient
-    let diffuse_strength = max(dot(normal, light_dir), 0.0);
-    let ambient = 0.4; // Ambient lighting - increased for softer shadows
-    let lighting = ambient + (1.0 - ambient) * diffuse_strength;
+
+    // Ambient keeps shadows from going fully black, then add each light's diffuse term,
+    // dimmed by the blocks overhead so caves stay dark
+    var lighting = lights.ambient.rgb;
+    for (var i = 0u; i < min(lights.count, MAX_LIGHTS); i++) {
+        let light = lights.lights[i];
+        let diffuse_strength = max(dot(normal, normalize(-light.direction)), 0.0);
+        lighting += light.color * light.intensity * diffuse_strength * in.skylight;
+    }
+    lighting = min(lighting, vec3<f32>(1.0));
+
+    // Darken corners tucked in against neighboring blocks
+    lighting *= in.ao;
+
+    // Glowing blocks stay at least as bright as their emission, in shadow and at night
+    lighting = max(lighting, vec3<f32>(in.emission));
+
+    // Light spreading from glowing blocks brightens caves and nights around them
+    lighting = max(lighting, in.block_light * BLOCK_LIGHT_COLOR * in.ao);
     
     // Apply lighting to the RGB channels, preserve alpha
-    let final_color = vec3<f32>(texture_color.rgb * lighting);
+    var final_color = vec3<f32>(texture_color.rgb * lighting);
+
+    // Fade toward the fog color near the render distance so chunks don't pop in
+    if (lights.fog_end > lights.fog_start) {
+        let distance = length(in.world_position - camera.position.xyz);
+        let fog = smoothstep(lights.fog_start, lights.fog_end, distance);
+        final_color = mix(final_color, lights.fog_color.rgb, fog);
+    }
     
     return vec4<f32>(final_color, texture_color.a);
-}
\ No newline at end of file
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return shade(in);
+}
+
+struct GhostUniform {
+    alpha: f32,
+}
+
+@group(3) @binding(0)
+var<uniform> ghost: GhostUniform;
+
+// Placement preview: the block as it would look, faded out
+@fragment
+fn fs_ghost(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = shade(in);
+    return vec4<f32>(color.rgb, color.a * ghost.alpha);
+}
+
+// Debug view: map each normal component from -1..1 to a 0..1 color channel
+@fragment
+fn fs_normals(in: VertexOutput) -> @location(0) vec4<f32> {
+    let normal = normalize(in.normal);
+    return vec4<f32>(normal * 0.5 + 0.5, 1.0);
+}
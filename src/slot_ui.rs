@@ -1,7 +1,12 @@
 use crate::blocks::BlockType;
+use crate::ui::pixels_to_ndc;
+use crate::ui_atlas::{glyph_uv, sprite_uv, UiSprite};
 use bytemuck::{Pod, Zeroable};
 use wgpu::util::DeviceExt;
 
+/// Most blocks of one type a slot can hold
+pub const MAX_STACK_SIZE: u32 = 64;
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 pub struct SlotVertex {
@@ -13,17 +18,41 @@ pub struct SlotVertex {
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 struct SlotUniform {
     selected_slot: u32,
-    _padding: [u32; 3], // 16-byte alignment
+    // Tiles along each side of the block atlas
+    atlas_tiles_per_row: u32,
+    _padding: [u32; 2], // 16-byte alignment
+    // UI atlas rectangles of the slot frames, [u_min, v_min, u_max, v_max]
+    frame_uv: [f32; 4],
+    selected_frame_uv: [f32; 4],
+    // Glyph of '0' in the UI atlas; the other digits follow it in the same row
+    digit_uv: [f32; 4],
+}
+
+impl SlotUniform {
+    fn new(selected_slot: u32, atlas_tiles_per_row: u32) -> Self {
+        Self {
+            selected_slot,
+            atlas_tiles_per_row,
+            _padding: [0; 2],
+            frame_uv: sprite_uv(UiSprite::SlotFrame),
+            selected_frame_uv: sprite_uv(UiSprite::SlotFrameSelected),
+            digit_uv: glyph_uv('0'),
+        }
+    }
 }
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 struct SlotInventoryData {
-    // Each slot stores texture ID (0-15) and whether it has a block (0 or 1)
+    // Each slot stores texture ID (0-63) and whether it has a block (0 or 1)
     // Using vec4 for proper alignment in WGSL
     slot_data_1: [u32; 4], // slots 0-3
     slot_data_2: [u32; 4], // slots 4-7
     slot_data_3: [u32; 4], // slots 8-9 (10 and 11 unused)
+    // Number of blocks in each slot, laid out the same way
+    count_data_1: [u32; 4],
+    count_data_2: [u32; 4],
+    count_data_3: [u32; 4],
 }
 
 impl SlotVertex {
@@ -54,10 +83,13 @@ pub struct SlotUI {
     uniform_buffer: wgpu::Buffer,
     inventory_buffer: wgpu::Buffer,
     bind_group: wgpu::BindGroup,
+    texture_bind_group_layout: wgpu::BindGroupLayout,
     texture_bind_group: wgpu::BindGroup,
+    ui_texture_bind_group: wgpu::BindGroup,
     selected_slot: usize, // 0-9, where 0 is leftmost
+    atlas_tiles_per_row: u32,
     num_indices: u32,
-    inventory: [Option<BlockType>; 10], // 10 slots for blocks
+    inventory: [Option<(BlockType, u32)>; 10], // 10 slots for blocks and their counts
 }
 
 impl SlotUI {
@@ -65,6 +97,7 @@ impl SlotUI {
         device: &wgpu::Device,
         surface_format: wgpu::TextureFormat,
         texture_atlas: &crate::texture_atlas::TextureAtlas,
+        ui_atlas: &crate::ui_atlas::UiAtlas,
         window_width: u32,
         window_height: u32,
     ) -> Self {
@@ -74,10 +107,7 @@ impl SlotUI {
         });
 
         // Create uniform buffer
-        let uniform = SlotUniform {
-            selected_slot: 0,
-            _padding: [0; 3],
-        };
+        let uniform = SlotUniform::new(0, texture_atlas.tiles_per_row);
 
         let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Slot UI Uniform Buffer"),
@@ -90,6 +120,9 @@ impl SlotUI {
             slot_data_1: [0; 4], // All slots start empty
             slot_data_2: [0; 4],
             slot_data_3: [0; 4],
+            count_data_1: [0; 4],
+            count_data_2: [0; 4],
+            count_data_3: [0; 4],
         };
 
         let inventory_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -181,10 +214,30 @@ impl SlotUI {
             label: Some("slot_ui_texture_bind_group"),
         });
 
+        // Frames come from the UI atlas; item icons still sample the block atlas
+        let ui_texture_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &texture_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&ui_atlas.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&ui_atlas.sampler),
+                },
+            ],
+            label: Some("slot_ui_ui_texture_bind_group"),
+        });
+
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Slot UI Pipeline Layout"),
-                bind_group_layouts: &[&bind_group_layout, &texture_bind_group_layout],
+                bind_group_layouts: &[
+                    &bind_group_layout,
+                    &texture_bind_group_layout,
+                    &texture_bind_group_layout,
+                ],
                 push_constant_ranges: &[],
             });
 
@@ -250,39 +303,47 @@ impl SlotUI {
             uniform_buffer,
             inventory_buffer,
             bind_group,
+            texture_bind_group_layout,
             texture_bind_group,
+            ui_texture_bind_group,
             selected_slot: 0, // Start with leftmost slot selected
+            atlas_tiles_per_row: texture_atlas.tiles_per_row,
             num_indices: indices.len() as u32,
             inventory: [None; 10], // Initialize all slots as empty
         }
     }
 
-    fn create_slot_geometry(window_width: u32, window_height: u32) -> (Vec<SlotVertex>, Vec<u16>) {
-        let mut vertices = Vec::new();
-        let mut indices = Vec::new();
-
+    /// Pixel rectangle of a slot, [left, top, right, bottom]
+    fn slot_rect(slot: usize, window_width: u32, window_height: u32) -> [f32; 4] {
         // Fixed pixel dimensions
-        const SLOT_SIZE_PX: f32 = 70.0; // 100px slots
+        const SLOT_SIZE_PX: f32 = 70.0; // 70px slots
         const GAP_PX: f32 = 8.0; // 8px gap between slots
         const BOTTOM_MARGIN_PX: f32 = 20.0; // 20px from bottom of screen
 
-        // Convert pixels to normalized coordinates (-1 to 1)
-        let slot_width_norm = (SLOT_SIZE_PX * 2.0) / window_width as f32;
-        let slot_height_norm = (SLOT_SIZE_PX * 2.0) / window_height as f32;
-        let gap_norm = (GAP_PX * 2.0) / window_width as f32;
-
-        // Calculate total width and center horizontally
-        let total_width_norm = slot_width_norm * 10.0 + gap_norm * 9.0;
-        let start_x = -total_width_norm / 2.0;
+        // Lay out in pixels, centered horizontally and anchored to the bottom
+        let total_width_px = SLOT_SIZE_PX * 10.0 + GAP_PX * 9.0;
+        let start_x_px = (window_width as f32 - total_width_px) / 2.0;
+        let bottom_px = window_height as f32 - BOTTOM_MARGIN_PX;
+        let left_px = start_x_px + (SLOT_SIZE_PX + GAP_PX) * slot as f32;
+
+        [
+            left_px,
+            bottom_px - SLOT_SIZE_PX,
+            left_px + SLOT_SIZE_PX,
+            bottom_px,
+        ]
+    }
 
-        // Position at bottom with margin
-        let bottom_margin_norm = (BOTTOM_MARGIN_PX * 2.0) / window_height as f32;
-        let y_bottom = -1.0 + bottom_margin_norm;
+    fn create_slot_geometry(window_width: u32, window_height: u32) -> (Vec<SlotVertex>, Vec<u16>) {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
 
         for i in 0..10 {
-            let x_left = start_x + (slot_width_norm + gap_norm) * i as f32;
-            let x_right = x_left + slot_width_norm;
-            let y_top = y_bottom + slot_height_norm;
+            let [left_px, top_px, right_px, bottom_px] =
+                Self::slot_rect(i, window_width, window_height);
+            let [x_left, y_top] = pixels_to_ndc(left_px, top_px, window_width, window_height);
+            let [x_right, y_bottom] =
+                pixels_to_ndc(right_px, bottom_px, window_width, window_height);
 
             let vertex_start = vertices.len() as u16;
 
@@ -327,28 +388,59 @@ impl SlotUI {
             self.selected_slot = slot;
 
             // Update uniform buffer
-            let uniform = SlotUniform {
-                selected_slot: slot as u32,
-                _padding: [0; 3],
-            };
+            let uniform = SlotUniform::new(slot as u32, self.atlas_tiles_per_row);
             queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[uniform]));
         }
     }
 
+    /// Move the selection by `steps` slots, wrapping around at either end
+    pub fn scroll_selected_slot(&mut self, steps: i32, queue: &wgpu::Queue) {
+        self.set_selected_slot(scrolled_slot(self.selected_slot, steps), queue);
+    }
+
+    /// Hotbar slot under a pixel position, if any
+    pub fn slot_at(&self, x: f32, y: f32, window_width: u32, window_height: u32) -> Option<usize> {
+        (0..10).find(|&slot| {
+            let [left, top, right, bottom] = Self::slot_rect(slot, window_width, window_height);
+            (left..right).contains(&x) && (top..bottom).contains(&y)
+        })
+    }
+
+    /// Rebuild the slot quads for a new window size, so slots keep their pixel size and
+    /// stay centered above the bottom edge
     pub fn update_geometry(&self, queue: &wgpu::Queue, window_width: u32, window_height: u32) {
         let (vertices, _) = Self::create_slot_geometry(window_width, window_height);
         queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
     }
 
-    pub fn put_block_in_selected_slot(&mut self, block_type: BlockType, queue: &wgpu::Queue) {
-        self.inventory[self.selected_slot] = Some(block_type);
-        println!("Put {:?} in slot {}", block_type, self.selected_slot);
+    /// Select the slot holding `block_type`, if any. Returns whether one was found.
+    pub fn select_slot_with(&mut self, block_type: BlockType, queue: &wgpu::Queue) -> bool {
+        let slot = self
+            .inventory
+            .iter()
+            .position(|stack| matches!(stack, Some((held, _)) if *held == block_type));
+        if let Some(slot) = slot {
+            self.set_selected_slot(slot, queue);
+        }
+        slot.is_some()
+    }
+
+    /// Add one block, to a slot already holding that type if it has room, otherwise the
+    /// first empty one. Returns false when the inventory is full.
+    pub fn add_block(&mut self, block_type: BlockType, queue: &wgpu::Queue) -> bool {
+        let added = add_to_slots(&mut self.inventory, block_type);
+        self.update_inventory_buffer(queue);
+        added
+    }
 
-        // Update the inventory buffer
+    /// Use up one block from the selected slot, emptying it at zero
+    pub fn take_block_from_selected_slot(&mut self, queue: &wgpu::Queue) -> Option<BlockType> {
+        let taken = take_from_slot(&mut self.inventory[self.selected_slot]);
         self.update_inventory_buffer(queue);
+        taken
     }
 
-    fn block_type_to_texture_id(block_type: BlockType) -> u32 {
+    pub fn block_type_to_texture_id(block_type: BlockType) -> u32 {
         use crate::blocks::TextureId;
         match block_type {
             BlockType::Air => 0,
@@ -363,6 +455,10 @@ impl SlotUI {
             BlockType::Planks => TextureId::Planks as u32,
             BlockType::Cobblestone => TextureId::Cobblestone as u32,
             BlockType::Glass => TextureId::Glass as u32,
+            BlockType::Coal => TextureId::CoalOre as u32,
+            BlockType::Iron => TextureId::IronOre as u32,
+            BlockType::Gold => TextureId::GoldOre as u32,
+            BlockType::Glowstone => TextureId::Glowstone as u32,
         }
     }
 
@@ -370,12 +466,13 @@ impl SlotUI {
         let mut slot_data_1 = [0u32; 4];
         let mut slot_data_2 = [0u32; 4];
         let mut slot_data_3 = [0u32; 4];
+        let mut count_data = [[0u32; 4]; 3];
 
-        for (i, block_opt) in self.inventory.iter().enumerate() {
-            let texture_id = if let Some(block_type) = block_opt {
-                Self::block_type_to_texture_id(*block_type)
+        for (i, stack) in self.inventory.iter().enumerate() {
+            let (texture_id, count) = if let Some((block_type, count)) = stack {
+                (Self::block_type_to_texture_id(*block_type), *count)
             } else {
-                0 // Empty slot
+                (0, 0) // Empty slot
             };
 
             if i < 4 {
@@ -385,12 +482,16 @@ impl SlotUI {
             } else {
                 slot_data_3[i - 8] = texture_id;
             }
+            count_data[i / 4][i % 4] = count;
         }
 
         let inventory_data = SlotInventoryData {
             slot_data_1,
             slot_data_2,
             slot_data_3,
+            count_data_1: count_data[0],
+            count_data_2: count_data[1],
+            count_data_3: count_data[2],
         };
         queue.write_buffer(
             &self.inventory_buffer,
@@ -400,15 +501,37 @@ impl SlotUI {
     }
 
     pub fn get_block_in_slot(&self, slot: usize) -> Option<BlockType> {
-        if slot < 10 {
-            self.inventory[slot]
-        } else {
-            None
-        }
+        self.get_stack_in_slot(slot)
+            .map(|(block_type, _)| block_type)
+    }
+
+    /// Block type and count in a slot
+    pub fn get_stack_in_slot(&self, slot: usize) -> Option<(BlockType, u32)> {
+        self.inventory.get(slot).copied().flatten()
+    }
+
+    /// Number of blocks in a slot, 0 when it's empty
+    pub fn slot_count(&self, slot: usize) -> u32 {
+        self.get_stack_in_slot(slot).map_or(0, |(_, count)| count)
+    }
+
+    /// Contents of every slot
+    pub fn inventory(&self) -> [Option<(BlockType, u32)>; 10] {
+        self.inventory
+    }
+
+    /// Replace the contents of every slot, e.g. when loading a save
+    pub fn set_inventory(
+        &mut self,
+        inventory: [Option<(BlockType, u32)>; 10],
+        queue: &wgpu::Queue,
+    ) {
+        self.inventory = inventory;
+        self.update_inventory_buffer(queue);
     }
 
     pub fn get_block_in_selected_slot(&self) -> Option<BlockType> {
-        self.inventory[self.selected_slot]
+        self.get_block_in_slot(self.selected_slot)
     }
 
     pub fn clear_selected_slot(&mut self) {
@@ -416,12 +539,125 @@ impl SlotUI {
         println!("Cleared slot {}", self.selected_slot);
     }
 
+    /// Layout of the block and UI atlas bind groups, for other slot-style screens
+    pub fn texture_bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.texture_bind_group_layout
+    }
+
+    /// Tiles along each side of the block atlas the slot icons come from
+    pub fn atlas_tiles_per_row(&self) -> u32 {
+        self.atlas_tiles_per_row
+    }
+
+    /// Block atlas and UI atlas bind groups used to draw slot contents and frames
+    pub fn texture_bind_groups(&self) -> (&wgpu::BindGroup, &wgpu::BindGroup) {
+        (&self.texture_bind_group, &self.ui_texture_bind_group)
+    }
+
     pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
         render_pass.set_pipeline(&self.render_pipeline);
         render_pass.set_bind_group(0, &self.bind_group, &[]);
         render_pass.set_bind_group(1, &self.texture_bind_group, &[]);
+        render_pass.set_bind_group(2, &self.ui_texture_bind_group, &[]);
         render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
         render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
         render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
     }
 }
+
+/// Slot `steps` away from `slot` in the 10-slot hotbar, wrapping around
+fn scrolled_slot(slot: usize, steps: i32) -> usize {
+    (slot as i32 + steps).rem_euclid(10) as usize
+}
+
+/// Add one `block_type` to the first stack of it with room, or else the first empty slot
+pub fn add_to_slots(slots: &mut [Option<(BlockType, u32)>], block_type: BlockType) -> bool {
+    let stack = slots
+        .iter()
+        .position(|stack| {
+            matches!(stack, Some((held, count)) if *held == block_type && *count < MAX_STACK_SIZE)
+        })
+        .or_else(|| slots.iter().position(Option::is_none));
+
+    match stack {
+        Some(slot) => {
+            let count = slots[slot].map_or(0, |(_, count)| count);
+            slots[slot] = Some((block_type, count + 1));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Take one block off a stack, leaving the slot empty when it runs out
+fn take_from_slot(slot: &mut Option<(BlockType, u32)>) -> Option<BlockType> {
+    let (block_type, count) = (*slot)?;
+    *slot = (count > 1).then_some((block_type, count - 1));
+    Some(block_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrolling_wraps_around_the_hotbar() {
+        assert_eq!(scrolled_slot(3, 1), 4);
+        assert_eq!(scrolled_slot(9, 1), 0);
+        assert_eq!(scrolled_slot(0, -1), 9);
+        assert_eq!(scrolled_slot(2, -13), 9);
+    }
+
+    /// The four vertices of a slot's quad. Fails if the geometry isn't one quad per slot.
+    fn slot_quad(vertices: &[SlotVertex], slot: usize) -> &[SlotVertex] {
+        assert_eq!(vertices.len(), 10 * 4, "expected one quad per hotbar slot");
+        &vertices[slot * 4..slot * 4 + 4]
+    }
+
+    #[test]
+    fn test_hotbar_stays_anchored_when_the_window_gets_tall() {
+        for (width, height) in [(1280, 800), (800, 1600)] {
+            let (vertices, _) = SlotUI::create_slot_geometry(width, height);
+            let first = slot_quad(&vertices, 0);
+            let last = slot_quad(&vertices, 9);
+
+            // 20px above the bottom edge, 70px tall and centered horizontally
+            let [_, bottom] = pixels_to_ndc(0.0, height as f32 - 20.0, width, height);
+            let [_, top] = pixels_to_ndc(0.0, height as f32 - 90.0, width, height);
+            assert!(first.iter().any(|v| (v.position[1] - bottom).abs() < 1e-6));
+            assert!(first.iter().any(|v| (v.position[1] - top).abs() < 1e-6));
+            let left = first.iter().map(|v| v.position[0]).fold(f32::MAX, f32::min);
+            let right = last.iter().map(|v| v.position[0]).fold(f32::MIN, f32::max);
+            assert!((left + right).abs() < 1e-6);
+
+            let [left, top, right, bottom] = SlotUI::slot_rect(0, width, height);
+            assert_eq!((right - left, bottom - top), (70.0, 70.0));
+        }
+    }
+
+    #[test]
+    fn test_blocks_stack_and_run_out() {
+        let mut slots = [None; 3];
+        slots[0] = Some((BlockType::Dirt, MAX_STACK_SIZE));
+
+        // A full dirt stack overflows into the first empty slot
+        assert!(add_to_slots(&mut slots, BlockType::Stone));
+        assert!(add_to_slots(&mut slots, BlockType::Dirt));
+        assert!(add_to_slots(&mut slots, BlockType::Stone));
+        assert_eq!(
+            slots,
+            [
+                Some((BlockType::Dirt, MAX_STACK_SIZE)),
+                Some((BlockType::Stone, 2)),
+                Some((BlockType::Dirt, 1)),
+            ]
+        );
+        assert!(!add_to_slots(&mut slots, BlockType::Sand));
+
+        assert_eq!(take_from_slot(&mut slots[1]), Some(BlockType::Stone));
+        assert_eq!(slots[1], Some((BlockType::Stone, 1)));
+        assert_eq!(take_from_slot(&mut slots[1]), Some(BlockType::Stone));
+        assert_eq!(slots[1], None);
+        assert_eq!(take_from_slot(&mut slots[1]), None);
+    }
+}
@@ -23,12 +23,23 @@ fn vs_main(model: VertexInput, @builtin(vertex_index) vertex_index: u32) -> Vert
 
 struct SlotUniform {
     selected_slot: u32,
+    // Tiles along each side of the block atlas
+    atlas_tiles_per_row: u32,
+    // UI atlas rectangles of the slot frames (u_min, v_min, u_max, v_max)
+    frame_uv: vec4<f32>,
+    selected_frame_uv: vec4<f32>,
+    // Glyph of '0' in the UI atlas; digits 1-9 follow it in the same row
+    digit_uv: vec4<f32>,
 }
 
 struct SlotInventoryData {
     slot_data_1: vec4<u32>, // slots 0-3
     slot_data_2: vec4<u32>, // slots 4-7  
     slot_data_3: vec4<u32>, // slots 8-9 (z and w unused)
+    // Number of blocks in each slot, laid out the same way
+    count_data_1: vec4<u32>,
+    count_data_2: vec4<u32>,
+    count_data_3: vec4<u32>,
 }
 
 @group(0) @binding(0)
@@ -43,11 +54,55 @@ var texture_atlas: texture_2d<f32>;
 @group(1) @binding(1)
 var atlas_sampler: sampler;
 
+@group(2) @binding(0)
+var ui_atlas: texture_2d<f32>;
+
+@group(2) @binding(1)
+var ui_sampler: sampler;
+
+// Stack count placement, in slot UV: digits are square and sit in the bottom-right corner
+const DIGIT_SIZE: f32 = 0.3;
+const COUNT_RIGHT: f32 = 0.94;
+const COUNT_BOTTOM: f32 = 0.94;
+
+// Glyph color of the slot's stack count at `uv`, transparent away from the digits.
+// Single blocks show no count.
+fn count_text(uv: vec2<f32>, count: u32) -> vec4<f32> {
+    if (count < 2u) {
+        return vec4<f32>(0.0);
+    }
+    let digits = select(1u, 2u, count >= 10u);
+    let origin = vec2<f32>(COUNT_RIGHT - f32(digits) * DIGIT_SIZE, COUNT_BOTTOM - DIGIT_SIZE);
+    let local = (uv - origin) / DIGIT_SIZE;
+    if (local.x < 0.0 || local.y < 0.0 || local.x >= f32(digits) || local.y >= 1.0) {
+        return vec4<f32>(0.0);
+    }
+
+    var digit = min(count, 99u) % 10u;
+    if (digits == 2u && local.x < 1.0) {
+        digit = min(count, 99u) / 10u;
+    }
+    let rect = slot_uniform.digit_uv;
+    let glyph_uv = vec2<f32>(
+        rect.x + (f32(digit) + fract(local.x)) * (rect.z - rect.x),
+        mix(rect.y, rect.w, local.y)
+    );
+    return textureSampleLevel(ui_atlas, ui_sampler, glyph_uv, 0.0);
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let slot_id = u32(in.slot_id);
     let selected_slot = slot_uniform.selected_slot;
     
+    // Frame sprite from the UI atlas - bright for the selected slot, gray for others
+    var frame_rect = slot_uniform.frame_uv;
+    if (slot_id == selected_slot) {
+        frame_rect = slot_uniform.selected_frame_uv;
+    }
+    let frame_uv = mix(frame_rect.xy, frame_rect.zw, in.tex_coords);
+    let frame = textureSample(ui_atlas, ui_sampler, frame_uv);
+    
     // Border thickness
     let border_thickness = 0.05;
     
@@ -57,30 +112,36 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
                    in.tex_coords.y < border_thickness || 
                    in.tex_coords.y > (1.0 - border_thickness);
     
-    if (is_border) {
-        // Border color - bright white for selected slot, gray for others
-        if (slot_id == selected_slot) {
-            return vec4<f32>(1.0, 1.0, 1.0, 0.9); // Bright white border for selected
-        } else {
-            return vec4<f32>(0.4, 0.4, 0.4, 0.7); // Gray border for unselected
-        }
+    if (frame.a > 0.0) {
+        return frame;
+    } else if (is_border) {
+        return vec4<f32>(0.0, 0.0, 0.0, 0.0);
     } else {
         // Interior - check if slot has a block
         var texture_id: u32 = 0u;
+        var count: u32 = 0u;
         if (slot_id < 4u) {
             texture_id = inventory_data.slot_data_1[slot_id];
+            count = inventory_data.count_data_1[slot_id];
         } else if (slot_id < 8u) {
             texture_id = inventory_data.slot_data_2[slot_id - 4u];
+            count = inventory_data.count_data_2[slot_id - 4u];
         } else {
             texture_id = inventory_data.slot_data_3[slot_id - 8u];
+            count = inventory_data.count_data_3[slot_id - 8u];
+        }
+
+        let count_color = count_text(in.tex_coords, count);
+        if (count_color.a > 0.5) {
+            return vec4<f32>(count_color.rgb, 1.0);
         }
         
         if (texture_id > 0u) {
             // Calculate texture coordinates in the atlas
-            // Texture atlas is 4x4, so we have 16 textures total (0-15)
-            let atlas_size = 4.0;
-            let texture_x = f32(texture_id % 4u);
-            let texture_y = f32(texture_id / 4u);
+            let tiles_per_row = slot_uniform.atlas_tiles_per_row;
+            let atlas_size = f32(tiles_per_row);
+            let texture_x = f32(texture_id % tiles_per_row);
+            let texture_y = f32(texture_id / tiles_per_row);
             
             // Map slot UV to texture UV within the atlas
             let inner_uv = (in.tex_coords - border_thickness) / (1.0 - 2.0 * border_thickness);
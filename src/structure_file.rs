@@ -0,0 +1,326 @@
+use crate::blocks::BlockType;
+use crate::chunk::WORLD_HEIGHT;
+use crate::structures::{rotate_in_footprint, BlockPlacement, Rotation, Structure};
+use rand::rngs::StdRng;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Character that leaves the terrain as it is, unless the palette gives it a block
+const EMPTY_CHAR: char = '.';
+
+/// .struct file structure (TOML)
+#[derive(Debug, Deserialize)]
+struct StructureToml {
+    structure: StructureInfo,
+    palette: HashMap<String, BlockType>,
+    layers: LayerData,
+}
+
+#[derive(Debug, Deserialize)]
+struct StructureInfo {
+    name: String,
+    /// Width (x), height (y) and depth (z) in blocks
+    size: [u32; 3],
+    /// Share of structure spots that get this structure, 0.0-1.0
+    #[serde(default = "default_chance")]
+    chance: f32,
+}
+
+fn default_chance() -> f32 {
+    0.05
+}
+
+#[derive(Debug, Deserialize)]
+struct LayerData {
+    data: String,
+}
+
+/// A fixed block layout loaded from a .struct file.
+///
+/// Layers are listed bottom first and separated by blank lines. Each layer has one row
+/// per z and one character per x, looked up in the palette; `.` leaves the terrain alone
+/// and mapping a character to "Air" carves out space.
+#[derive(Debug, Clone)]
+pub struct FileStructure {
+    pub name: String,
+    /// Share of structure spots that get this structure
+    pub chance: f32,
+    size: (i32, i32, i32),
+    blocks: Vec<BlockPlacement>,
+}
+
+impl FileStructure {
+    /// Parse a .struct file. Errors are prefixed with the file path; recoverable
+    /// problems are printed as warnings.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("{}: Failed to read file: {}", path.display(), e))?;
+
+        let (structure, warnings) =
+            parse_structure_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+        for warning in warnings {
+            eprintln!("Warning: {}: {}", path.display(), warning);
+        }
+
+        Ok(structure)
+    }
+}
+
+impl Structure for FileStructure {
+    fn generate(&self, _rng: &mut StdRng, rotation: Rotation) -> Vec<BlockPlacement> {
+        rotate_in_footprint(&self.blocks, rotation, self.size)
+    }
+
+    fn get_bounds(&self, rotation: Rotation) -> (i32, i32, i32) {
+        rotation.rotate_bounds(self.size)
+    }
+
+    fn can_place_at_height(&self, height: i32) -> bool {
+        height > 0 && height + self.size.1 <= WORLD_HEIGHT as i32
+    }
+}
+
+/// Parses .struct content. Returns the structure along with warnings about characters
+/// missing from the palette, which are left empty.
+fn parse_structure_str(content: &str) -> Result<(FileStructure, Vec<String>), String> {
+    let structure_toml: StructureToml =
+        toml::from_str(content).map_err(|e| format!("Failed to parse TOML: {}", e))?;
+    let [width, height, depth] = structure_toml.structure.size;
+    if width == 0 || height == 0 || depth == 0 {
+        return Err("Invalid structure dimensions".to_string());
+    }
+
+    let mut palette: HashMap<char, BlockType> = HashMap::new();
+    for (key_str, block_type) in structure_toml.palette {
+        let mut chars = key_str.chars();
+        match (chars.next(), chars.next()) {
+            (Some(key_char), None) => {
+                palette.insert(key_char, block_type);
+            }
+            _ => return Err(format!("palette key '{}' must be one character", key_str)),
+        }
+    }
+
+    // Group rows into layers at blank lines
+    let mut layers: Vec<Vec<&str>> = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    for line in structure_toml.layers.data.lines().map(str::trim) {
+        if line.is_empty() {
+            if !current.is_empty() {
+                layers.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        layers.push(current);
+    }
+
+    if layers.len() != height as usize {
+        return Err(format!(
+            "Expected {} layers, found {}",
+            height,
+            layers.len()
+        ));
+    }
+
+    let mut blocks = Vec::new();
+    let mut unknown_chars = Vec::new();
+    for (y, rows) in layers.iter().enumerate() {
+        if rows.len() != depth as usize {
+            return Err(format!(
+                "Layer {} has {} rows, expected {}",
+                y,
+                rows.len(),
+                depth
+            ));
+        }
+
+        for (z, row) in rows.iter().enumerate() {
+            let chars: Vec<char> = row.chars().collect();
+            if chars.len() != width as usize {
+                return Err(format!(
+                    "Layer {} row {} has {} characters, expected {}",
+                    y,
+                    z,
+                    chars.len(),
+                    width
+                ));
+            }
+
+            for (x, ch) in chars.into_iter().enumerate() {
+                match palette.get(&ch) {
+                    Some(&block_type) => blocks.push(BlockPlacement {
+                        relative_pos: (x as i32, y as i32, z as i32),
+                        block_type,
+                    }),
+                    None if ch == EMPTY_CHAR => {}
+                    None => {
+                        if !unknown_chars.contains(&ch) {
+                            unknown_chars.push(ch);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut warnings = Vec::new();
+    if !unknown_chars.is_empty() {
+        let listed: Vec<String> = unknown_chars.iter().map(|ch| format!("'{}'", ch)).collect();
+        warnings.push(format!(
+            "characters with no palette entry (left empty): {}",
+            listed.join(", ")
+        ));
+    }
+
+    Ok((
+        FileStructure {
+            name: structure_toml.structure.name,
+            chance: structure_toml.structure.chance.clamp(0.0, 1.0),
+            size: (width as i32, height as i32, depth as i32),
+            blocks,
+        },
+        warnings,
+    ))
+}
+
+/// Load all structure files from the structures directory
+pub fn load_all_structures() -> Result<Vec<FileStructure>, String> {
+    load_structures_from_dir("structures")
+}
+
+/// Load every .struct file in a directory, sorted by file name so the order (and with
+/// it which structure wins a spot) doesn't depend on the file system
+pub fn load_structures_from_dir<P: AsRef<Path>>(dir: P) -> Result<Vec<FileStructure>, String> {
+    let structures_dir = dir.as_ref();
+    if !structures_dir.exists() {
+        return Err(format!(
+            "Structures directory {} not found",
+            structures_dir.display()
+        ));
+    }
+
+    let entries = fs::read_dir(structures_dir)
+        .map_err(|e| format!("Failed to read structures directory: {}", e))?;
+    let mut paths = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "struct")
+        {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut structures = Vec::new();
+    for path in paths {
+        match FileStructure::load_from_file(&path) {
+            Ok(structure) => {
+                println!("Loaded structure: {}", structure.name);
+                structures.push(structure);
+            }
+            Err(e) => eprintln!("Failed to parse structure: {}", e),
+        }
+    }
+
+    Ok(structures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::biome::BiomeManager;
+    use crate::chunk::{ChunkGenerator, ChunkPos, CHUNK_SIZE};
+    use crate::terrain::Terrain;
+
+    #[test]
+    fn test_layers_map_to_block_placements() {
+        let content = r#"
+[structure]
+name = "Post"
+size = [2, 2, 1]
+
+[palette]
+"c" = "Cobblestone"
+"_" = "Air"
+
+[layers]
+data = '''
+c?
+
+c_
+'''
+"#;
+        let (structure, warnings) = parse_structure_str(content).unwrap();
+        assert_eq!(structure.get_bounds(Rotation::Deg90), (1, 2, 2));
+        assert_eq!(structure.chance, 0.05);
+
+        let blocks: Vec<_> = structure
+            .blocks
+            .iter()
+            .map(|block| (block.relative_pos, block.block_type))
+            .collect();
+        assert_eq!(
+            blocks,
+            vec![
+                ((0, 0, 0), BlockType::Cobblestone),
+                ((0, 1, 0), BlockType::Cobblestone),
+                ((1, 1, 0), BlockType::Air),
+            ]
+        );
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("'?'"));
+
+        let short = content.replace("c_\n", "");
+        assert_eq!(
+            parse_structure_str(&short).unwrap_err(),
+            "Expected 2 layers, found 1"
+        );
+    }
+
+    #[test]
+    fn test_registered_structure_appears_in_generated_chunks() {
+        let content = "[structure]\nname = \"Pillar\"\nsize = [1, 2, 1]\nchance = 1.0\n\n[palette]\n\"g\" = \"Gold\"\n\n[layers]\ndata = '''\ng\n\ng\n'''\n";
+        let (pillar, _) = parse_structure_str(content).unwrap();
+        let mut generator = ChunkGenerator::new(42);
+        generator.register_structure(pillar);
+
+        let terrain = Terrain::new(42);
+        let biome_manager = BiomeManager::new();
+        // Every flat structure spot gets a pillar
+        let mut pillar_blocks = 0;
+        for chunk_x in 0..4 {
+            let blocks = generator.generate_chunk_blocks(
+                ChunkPos { x: chunk_x, z: 0 },
+                &terrain,
+                &biome_manager,
+            );
+            for x in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    for y in 0..WORLD_HEIGHT {
+                        if blocks.get(x, y, z) == BlockType::Gold
+                            && blocks.is_structure_block(x, y, z)
+                        {
+                            pillar_blocks += 1;
+                        }
+                    }
+                }
+            }
+        }
+        assert!(pillar_blocks > 0);
+    }
+
+    #[test]
+    fn test_shipped_structures_parse() {
+        let structures = load_all_structures().unwrap();
+        assert!(structures.iter().any(|structure| structure.name == "Well"));
+    }
+}
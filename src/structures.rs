@@ -1,9 +1,12 @@
 use crate::biome::{Biome, BiomeManager};
 use crate::blocks::BlockType;
-use crate::chunk::CHUNK_SIZE;
+use crate::chunk::{CHUNK_SIZE, WORLD_HEIGHT};
+use crate::rng::{position_rng, salt};
+use crate::structure_file::FileStructure;
+use crate::terrain::SEA_LEVEL;
 use noise::{NoiseFn, Perlin};
 use rand::rngs::StdRng;
-use rand::{Rng, SeedableRng};
+use rand::Rng;
 
 /// Represents a block placement in a structure
 #[derive(Debug, Clone)]
@@ -12,13 +15,84 @@ pub struct BlockPlacement {
     pub block_type: BlockType,
 }
 
+/// Quarter turns about the vertical axis. `Deg90` turns +Z toward -X.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Rotation {
+    pub const ALL: [Rotation; 4] = [
+        Rotation::Deg0,
+        Rotation::Deg90,
+        Rotation::Deg180,
+        Rotation::Deg270,
+    ];
+
+    pub fn random(rng: &mut StdRng) -> Self {
+        Self::ALL[rng.gen_range(0..Self::ALL.len())]
+    }
+
+    /// Bounds (width, height, depth) after turning, with width and depth swapped for
+    /// quarter turns
+    pub fn rotate_bounds(self, (width, height, depth): (i32, i32, i32)) -> (i32, i32, i32) {
+        match self {
+            Rotation::Deg0 | Rotation::Deg180 => (width, height, depth),
+            Rotation::Deg90 | Rotation::Deg270 => (depth, height, width),
+        }
+    }
+}
+
+/// Turn a placement about the structure's origin column
+pub fn rotate(placement: &BlockPlacement, rotation: Rotation) -> BlockPlacement {
+    let (x, y, z) = placement.relative_pos;
+    let (x, z) = match rotation {
+        Rotation::Deg0 => (x, z),
+        Rotation::Deg90 => (-z, x),
+        Rotation::Deg180 => (-x, -z),
+        Rotation::Deg270 => (z, -x),
+    };
+    BlockPlacement {
+        relative_pos: (x, y, z),
+        block_type: placement.block_type,
+    }
+}
+
+/// Turn placements that fill the box from the origin to `(width, _, depth)`, then move
+/// them so the turned box starts at the origin again
+pub fn rotate_in_footprint(
+    blocks: &[BlockPlacement],
+    rotation: Rotation,
+    (width, _, depth): (i32, i32, i32),
+) -> Vec<BlockPlacement> {
+    let (offset_x, offset_z) = match rotation {
+        Rotation::Deg0 => (0, 0),
+        Rotation::Deg90 => (depth - 1, 0),
+        Rotation::Deg180 => (width - 1, depth - 1),
+        Rotation::Deg270 => (0, width - 1),
+    };
+    blocks
+        .iter()
+        .map(|block| {
+            let mut turned = rotate(block, rotation);
+            turned.relative_pos.0 += offset_x;
+            turned.relative_pos.2 += offset_z;
+            turned
+        })
+        .collect()
+}
+
 /// Trait for all structures that can be generated in the world
 pub trait Structure {
-    /// Generate the blocks that make up this structure
-    fn generate(&self, rng: &mut StdRng) -> Vec<BlockPlacement>;
+    /// Generate the blocks that make up this structure, turned by `rotation`
+    fn generate(&self, rng: &mut StdRng, rotation: Rotation) -> Vec<BlockPlacement>;
 
-    /// Get the bounding box size of this structure
-    fn get_bounds(&self) -> (i32, i32, i32);
+    /// Get the bounding box size of this structure when turned by `rotation`
+    fn get_bounds(&self, rotation: Rotation) -> (i32, i32, i32);
 
     /// Check if this structure can be placed at the given height
     fn can_place_at_height(&self, height: i32) -> bool;
@@ -72,6 +146,11 @@ impl TreeStructure {
                     _ => TreeType::Birch,
                 }
             }
+            Biome::Ocean => {
+                // Ocean biome - nothing grows on the seafloor, so only reached if
+                // tree_density is raised in biome.toml
+                TreeType::Oak
+            }
             Biome::Swamp => {
                 // Swamp biome - mostly oak and birch
                 if rng.gen::<f32>() < 0.6 {
@@ -87,7 +166,7 @@ impl TreeStructure {
 }
 
 impl Structure for TreeStructure {
-    fn generate(&self, rng: &mut StdRng) -> Vec<BlockPlacement> {
+    fn generate(&self, rng: &mut StdRng, rotation: Rotation) -> Vec<BlockPlacement> {
         let mut blocks = Vec::new();
 
         match self.tree_type {
@@ -241,15 +320,16 @@ impl Structure for TreeStructure {
             }
         }
 
-        blocks
+        // Trees turn about their trunk
+        blocks.iter().map(|block| rotate(block, rotation)).collect()
     }
 
-    fn get_bounds(&self) -> (i32, i32, i32) {
-        match self.tree_type {
+    fn get_bounds(&self, rotation: Rotation) -> (i32, i32, i32) {
+        rotation.rotate_bounds(match self.tree_type {
             TreeType::Oak => (5, 8, 5),
             TreeType::Birch => (5, 10, 5),
             TreeType::Pine => (7, 9, 7),
-        }
+        })
     }
 
     fn can_place_at_height(&self, height: i32) -> bool {
@@ -285,7 +365,7 @@ impl HouseStructure {
 }
 
 impl Structure for HouseStructure {
-    fn generate(&self, _rng: &mut StdRng) -> Vec<BlockPlacement> {
+    fn generate(&self, _rng: &mut StdRng, rotation: Rotation) -> Vec<BlockPlacement> {
         let mut blocks = Vec::new();
 
         match self.house_type {
@@ -468,14 +548,14 @@ impl Structure for HouseStructure {
             }
         }
 
-        blocks
+        rotate_in_footprint(&blocks, rotation, self.get_bounds(Rotation::Deg0))
     }
 
-    fn get_bounds(&self) -> (i32, i32, i32) {
-        match self.house_type {
+    fn get_bounds(&self, rotation: Rotation) -> (i32, i32, i32) {
+        rotation.rotate_bounds(match self.house_type {
             HouseType::Small => (5, 7, 5),
             HouseType::Medium => (7, 9, 7),
-        }
+        })
     }
 
     fn can_place_at_height(&self, height: i32) -> bool {
@@ -483,10 +563,103 @@ impl Structure for HouseStructure {
     }
 }
 
+/// Side of a village lot, enough for the medium house
+const VILLAGE_LOT_SIZE: i32 = 7;
+/// Width of the paths around village lots
+const VILLAGE_PATH_WIDTH: i32 = 2;
+/// Largest number of lots along x in a village
+const VILLAGE_MAX_COLUMNS: i32 = 3;
+/// Rows of lots along z in a village
+const VILLAGE_ROWS: i32 = 2;
+
+/// Houses on a grid of lots with dirt and cobblestone paths between them.
+/// Doors face -Z onto the path in front of each row.
+pub struct VillageStructure {
+    pub columns: i32,
+    pub rows: i32,
+}
+
+impl VillageStructure {
+    pub fn new(columns: i32, rows: i32) -> Self {
+        Self { columns, rows }
+    }
+
+    pub fn random(rng: &mut StdRng) -> Self {
+        Self::new(rng.gen_range(2..=VILLAGE_MAX_COLUMNS), VILLAGE_ROWS)
+    }
+}
+
+impl Structure for VillageStructure {
+    fn generate(&self, rng: &mut StdRng, rotation: Rotation) -> Vec<BlockPlacement> {
+        let mut blocks = Vec::new();
+        let bounds = self.get_bounds(Rotation::Deg0);
+        let (width, _, depth) = bounds;
+        let stride = VILLAGE_LOT_SIZE + VILLAGE_PATH_WIDTH;
+
+        // Paths replace the surface block around every lot
+        for x in 0..width {
+            for z in 0..depth {
+                if x % stride < VILLAGE_PATH_WIDTH || z % stride < VILLAGE_PATH_WIDTH {
+                    let block_type = if rng.gen::<f32>() < 0.3 {
+                        BlockType::Cobblestone
+                    } else {
+                        BlockType::Dirt
+                    };
+                    blocks.push(BlockPlacement {
+                        relative_pos: (x, -1, z),
+                        block_type,
+                    });
+                }
+            }
+        }
+
+        // One house per lot, centered and facing any way since paths run on every side
+        for column in 0..self.columns {
+            for row in 0..self.rows {
+                let house = HouseStructure::random(rng);
+                let house_rotation = Rotation::random(rng);
+                let (house_width, _, house_depth) = house.get_bounds(house_rotation);
+                let lot_x =
+                    VILLAGE_PATH_WIDTH + column * stride + (VILLAGE_LOT_SIZE - house_width) / 2;
+                let lot_z =
+                    VILLAGE_PATH_WIDTH + row * stride + (VILLAGE_LOT_SIZE - house_depth) / 2;
+
+                for block in house.generate(rng, house_rotation) {
+                    let (x, y, z) = block.relative_pos;
+                    blocks.push(BlockPlacement {
+                        relative_pos: (lot_x + x, y, lot_z + z),
+                        block_type: block.block_type,
+                    });
+                }
+            }
+        }
+
+        rotate_in_footprint(&blocks, rotation, bounds)
+    }
+
+    fn get_bounds(&self, rotation: Rotation) -> (i32, i32, i32) {
+        let stride = VILLAGE_LOT_SIZE + VILLAGE_PATH_WIDTH;
+        rotation.rotate_bounds((
+            self.columns * stride + VILLAGE_PATH_WIDTH,
+            HouseStructure::new(HouseType::Medium)
+                .get_bounds(Rotation::Deg0)
+                .1,
+            self.rows * stride + VILLAGE_PATH_WIDTH,
+        ))
+    }
+
+    fn can_place_at_height(&self, height: i32) -> bool {
+        // Keep the paths out of the water
+        height > SEA_LEVEL as i32
+            && height + self.get_bounds(Rotation::Deg0).1 < WORLD_HEIGHT as i32
+    }
+}
+
 /// Manages structure generation and placement
 pub struct StructureGenerator {
     structure_noise: Perlin,
     seed: u32,
+    file_structures: Vec<FileStructure>,
 }
 
 impl StructureGenerator {
@@ -494,9 +667,15 @@ impl StructureGenerator {
         Self {
             structure_noise: Perlin::new(seed),
             seed,
+            file_structures: Vec::new(),
         }
     }
 
+    /// Place a structure loaded from a file alongside trees and houses
+    pub fn add_file_structure(&mut self, structure: FileStructure) {
+        self.file_structures.push(structure);
+    }
+
     /// Determine if a structure should be placed at this position
     pub fn should_place_structure(&self, world_x: i32, world_z: i32) -> bool {
         // Use noise to determine structure placement
@@ -517,19 +696,26 @@ impl StructureGenerator {
         biome: Biome,
         biome_manager: &BiomeManager,
     ) -> StructureType {
-        // Create a deterministic RNG based on position
-        let mut hasher = std::collections::hash_map::DefaultHasher::new();
-        use std::hash::{Hash, Hasher};
-        world_x.hash(&mut hasher);
-        world_z.hash(&mut hasher);
-        self.seed.hash(&mut hasher);
-        let hash = hasher.finish();
-
-        let mut rng = StdRng::seed_from_u64(hash);
-        let structure_roll = rng.gen::<f32>();
+        // File structures get first pick, each with its own chance
+        let mut file_rng = position_rng(world_x, world_z, salt::FILE_STRUCTURE, self.seed);
+        for (index, structure) in self.file_structures.iter().enumerate() {
+            if file_rng.gen::<f32>() < structure.chance {
+                return StructureType::File(index);
+            }
+        }
 
         let config = biome_manager.get_config(biome);
 
+        // Villages roll next, weighted by biome
+        let mut village_rng = position_rng(world_x, world_z, salt::VILLAGE, self.seed);
+        if village_rng.gen::<f64>() < config.village_chance {
+            return StructureType::Village;
+        }
+
+        // Create a deterministic RNG based on position
+        let mut rng = position_rng(world_x, world_z, salt::STRUCTURE_TYPE, self.seed);
+        let structure_roll = rng.gen::<f32>();
+
         // Use biome-specific structure spawn rates
         if structure_roll < (config.tree_density * 100.0) as f32 {
             StructureType::Tree
@@ -554,9 +740,21 @@ impl StructureGenerator {
         let mut structures = Vec::new();
 
         // Maximum structure bounds analysis shows largest structures are 7x7
-        // So we need to check positions up to 4 blocks outside chunk boundaries
-        let search_radius = 4;
+        // So we need to check positions up to 4 blocks outside chunk boundaries.
+        // Villages and file structures extend from their origin toward +x and +z and
+        // are larger; the radius grows in whole spacings so the grid of spots stays put.
         let spacing = 8;
+        let largest_village = VillageStructure::new(VILLAGE_MAX_COLUMNS, VILLAGE_ROWS);
+        let largest_extent = self
+            .file_structures
+            .iter()
+            .map(|structure| structure.get_bounds(Rotation::Deg0))
+            .chain([largest_village.get_bounds(Rotation::Deg0)])
+            .map(|(width, _, depth)| (width.max(depth) - 1) as usize)
+            .max()
+            .unwrap_or(0);
+        let search_radius =
+            (4 + largest_extent.saturating_sub(4).div_ceil(spacing) * spacing) as i32;
 
         // Calculate the range of world coordinates we need to check
         let chunk_start_x = chunk_x * CHUNK_SIZE as i32;
@@ -566,6 +764,22 @@ impl StructureGenerator {
         let search_start_z = chunk_start_z - search_radius;
         let search_end_z = chunk_start_z + CHUNK_SIZE as i32 + search_radius;
 
+        // Chunk-local coordinates of a column inside the chunk
+        let local = |world_x: i32, world_z: i32| -> Option<(usize, usize)> {
+            let local_x = world_x - chunk_start_x;
+            let local_z = world_z - chunk_start_z;
+            ((0..CHUNK_SIZE as i32).contains(&local_x) && (0..CHUNK_SIZE as i32).contains(&local_z))
+                .then_some((local_x as usize, local_z as usize))
+        };
+        // First free block above the terrain in any column, from the height map inside
+        // the chunk and sampled from the terrain outside it
+        let surface_height = |world_x: i32, world_z: i32| -> i32 {
+            match local(world_x, world_z) {
+                Some((x, z)) => terrain_height_map[x][z] as i32,
+                None => terrain.filled_height_at(world_x, world_z, biome_manager) as i32,
+            }
+        };
+
         // Check positions in expanded search area
         for world_x in (search_start_x..search_end_x).step_by(spacing) {
             for world_z in (search_start_z..search_end_z).step_by(spacing) {
@@ -573,36 +787,16 @@ impl StructureGenerator {
                     continue;
                 }
 
-                // Calculate local coordinates relative to the current chunk
-                let local_x = world_x - chunk_start_x;
-                let local_z = world_z - chunk_start_z;
-
-                // For positions outside the chunk, we need to calculate height and biome using terrain
-                let (terrain_height, biome) = if local_x >= 0
-                    && local_x < CHUNK_SIZE as i32
-                    && local_z >= 0
-                    && local_z < CHUNK_SIZE as i32
-                {
-                    // Position is within current chunk - use provided height map
-                    (
-                        terrain_height_map[local_x as usize][local_z as usize],
-                        biome_map[local_x as usize][local_z as usize],
-                    )
-                } else {
-                    // Position is outside current chunk - query terrain for values
-                    let height = terrain.height_at(world_x, world_z, biome_manager);
-                    let biome = terrain.biome_at(world_x, world_z);
-                    (height, biome)
+                // Each structure sits on the surface of its own origin column; a tree's
+                // origin is its trunk
+                let terrain_height = surface_height(world_x, world_z);
+                let biome = match local(world_x, world_z) {
+                    Some((x, z)) => biome_map[x][z],
+                    None => terrain.biome_at(world_x, world_z),
                 };
 
                 // Create deterministic RNG for this position
-                let mut hasher = std::collections::hash_map::DefaultHasher::new();
-                use std::hash::{Hash, Hasher};
-                world_x.hash(&mut hasher);
-                world_z.hash(&mut hasher);
-                self.seed.hash(&mut hasher);
-                let hash = hasher.finish();
-                let mut rng = StdRng::seed_from_u64(hash);
+                let mut rng = position_rng(world_x, world_z, salt::STRUCTURE, self.seed);
 
                 let structure_type =
                     self.get_structure_type(world_x, world_z, biome, biome_manager);
@@ -612,39 +806,34 @@ impl StructureGenerator {
                         Box::new(TreeStructure::random_for_biome(biome, &mut rng))
                     }
                     StructureType::House => Box::new(HouseStructure::random(&mut rng)),
+                    StructureType::Village => Box::new(VillageStructure::random(&mut rng)),
+                    StructureType::File(index) => Box::new(self.file_structures[index].clone()),
                 };
+                let rotation = Rotation::random(&mut position_rng(
+                    world_x,
+                    world_z,
+                    salt::ROTATION,
+                    self.seed,
+                ));
 
                 // Check if structure can be placed at this height
-                if !structure.can_place_at_height(terrain_height as i32) {
+                if !structure.can_place_at_height(terrain_height) {
                     continue;
                 }
 
-                // Check if there's enough flat area for houses
-                if matches!(structure_type, StructureType::House) {
-                    let (width, _, depth) = structure.get_bounds();
+                // Check if there's enough flat area for houses, villages and file structures
+                if matches!(
+                    structure_type,
+                    StructureType::House | StructureType::Village | StructureType::File(_)
+                ) {
+                    let (width, _, depth) = structure.get_bounds(rotation);
                     let mut height_variance = 0i32;
 
                     for dx in 0..width {
                         for dz in 0..depth {
-                            let check_world_x = world_x + dx;
-                            let check_world_z = world_z + dz;
-                            let check_local_x = check_world_x - chunk_start_x;
-                            let check_local_z = check_world_z - chunk_start_z;
-
-                            let check_height = if check_local_x >= 0
-                                && check_local_x < CHUNK_SIZE as i32
-                                && check_local_z >= 0
-                                && check_local_z < CHUNK_SIZE as i32
-                            {
-                                terrain_height_map[check_local_x as usize][check_local_z as usize]
-                                    as i32
-                            } else {
-                                terrain.height_at(check_world_x, check_world_z, biome_manager)
-                                    as i32
-                            };
-
+                            let check_height = surface_height(world_x + dx, world_z + dz);
                             height_variance =
-                                height_variance.max((check_height - terrain_height as i32).abs());
+                                height_variance.max((check_height - terrain_height).abs());
                         }
                     }
 
@@ -656,10 +845,10 @@ impl StructureGenerator {
 
                 structures.push(PlacedStructure {
                     world_x,
-                    world_y: terrain_height as i32,
+                    world_y: terrain_height,
                     world_z,
                     structure_type,
-                    blocks: structure.generate(&mut rng),
+                    blocks: structure.generate(&mut rng, rotation),
                 });
             }
         }
@@ -672,6 +861,9 @@ impl StructureGenerator {
 pub enum StructureType {
     Tree,
     House,
+    Village,
+    /// Index into the structures loaded from files
+    File(usize),
 }
 
 /// A structure that has been placed in the world
@@ -699,3 +891,77 @@ impl PlacedStructure {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_village_houses_stay_off_the_paths() {
+        let village = VillageStructure::new(3, 2);
+        let blocks = village.generate(&mut StdRng::seed_from_u64(7), Rotation::Deg90);
+        let (width, _, depth) = village.get_bounds(Rotation::Deg90);
+        assert_eq!((width, depth), (20, 29));
+
+        let paths: HashSet<(i32, i32)> = blocks
+            .iter()
+            .filter(|block| block.relative_pos.1 == -1)
+            .map(|block| {
+                assert!(matches!(
+                    block.block_type,
+                    BlockType::Dirt | BlockType::Cobblestone
+                ));
+                (block.relative_pos.0, block.relative_pos.2)
+            })
+            .collect();
+        let lots = 3 * 2 * VILLAGE_LOT_SIZE * VILLAGE_LOT_SIZE;
+        assert_eq!(paths.len() as i32, width * depth - lots);
+
+        for block in blocks.iter().filter(|block| block.relative_pos.1 >= 0) {
+            let (x, _, z) = block.relative_pos;
+            assert!((0..width).contains(&x) && (0..depth).contains(&z));
+            assert!(!paths.contains(&(x, z)));
+        }
+    }
+
+    #[test]
+    fn test_rotated_house_door_stays_on_an_outer_wall() {
+        for house_type in [HouseType::Small, HouseType::Medium] {
+            let house = HouseStructure::new(house_type);
+            for rotation in Rotation::ALL {
+                let (width, _, depth) = house.get_bounds(rotation);
+                let walls: HashSet<(i32, i32)> = house
+                    .generate(&mut StdRng::seed_from_u64(0), rotation)
+                    .iter()
+                    .filter(|block| block.relative_pos.1 == 1)
+                    .map(|block| (block.relative_pos.0, block.relative_pos.2))
+                    .collect();
+
+                // Gaps in the ground floor wall ring are the door
+                let door: Vec<(i32, i32)> = (0..width)
+                    .flat_map(|x| (0..depth).map(move |z| (x, z)))
+                    .filter(|&(x, z)| x == 0 || x == width - 1 || z == 0 || z == depth - 1)
+                    .filter(|cell| !walls.contains(cell))
+                    .collect();
+                let expected_side = |&(x, z): &(i32, i32)| match rotation {
+                    Rotation::Deg0 => z == 0,
+                    Rotation::Deg90 => x == width - 1,
+                    Rotation::Deg180 => z == depth - 1,
+                    Rotation::Deg270 => x == 0,
+                };
+                assert!(!door.is_empty(), "{:?} {:?}", house_type, rotation);
+                assert!(
+                    door.iter().all(expected_side),
+                    "{:?} {:?}",
+                    house_type,
+                    rotation
+                );
+                assert!(walls
+                    .iter()
+                    .all(|&(x, z)| (0..width).contains(&x) && (0..depth).contains(&z)));
+            }
+        }
+    }
+}
@@ -1,25 +1,126 @@
 use crate::biome::{Biome, BiomeManager, BiomeSelector};
 use crate::blocks::BlockType;
 use crate::chunk::{ChunkBlocks, ChunkPos, CHUNK_SIZE, TERRAIN_MAX_HEIGHT, WORLD_HEIGHT};
+use crate::rng::{position_rng, salt};
 use noise::{NoiseFn, Perlin};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Which strategy shapes the terrain
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TerrainKind {
+    /// Noise-based heights with biomes and structures
+    #[default]
+    Noise,
+    /// The same stack of layers everywhere, listed bottom to top as (block, thickness).
+    /// No noise and no structures, which makes building and physics bugs easy to isolate.
+    SuperFlat { layers: Vec<(BlockType, usize)> },
+}
+
+/// Default height water fills up to, so low ground (e.g. swamps) ends up underwater
+pub const SEA_LEVEL: usize = 6;
+
+/// Columns whose height is within this many blocks of the sea level get a sand beach
+const BEACH_WIDTH: usize = 2;
+
+/// Ore that forms small clusters in the stone layer
+struct OreVein {
+    block_type: BlockType,
+    /// Height range the cluster starts in
+    min_y: usize,
+    max_y: usize,
+    veins_per_chunk: usize,
+    /// Number of blocks in a cluster (fewer where it runs out of stone)
+    min_size: usize,
+    max_size: usize,
+}
+
+/// Gold is rarer and deeper than iron, which is rarer and deeper than coal
+const ORE_VEINS: [OreVein; 3] = [
+    OreVein {
+        block_type: BlockType::Coal,
+        min_y: 4,
+        max_y: 64,
+        veins_per_chunk: 10,
+        min_size: 4,
+        max_size: 8,
+    },
+    OreVein {
+        block_type: BlockType::Iron,
+        min_y: 2,
+        max_y: 40,
+        veins_per_chunk: 6,
+        min_size: 3,
+        max_size: 6,
+    },
+    OreVein {
+        block_type: BlockType::Gold,
+        min_y: 1,
+        max_y: 16,
+        veins_per_chunk: 2,
+        min_size: 3,
+        max_size: 5,
+    },
+];
+
+/// What terrain generation puts in one column, without generating its chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerrainSample {
+    /// Number of blocks filled from y = 0 up, as `Terrain::filled_height_at`
+    pub height: usize,
+    pub biome: Biome,
+    /// Topmost filled block before ores, structures and terrain modifiers. Near biome
+    /// edges it may come from the neighboring biome's palette. Air when nothing is filled.
+    pub surface_block: BlockType,
+}
 
 /// Terrain generation with biome-aware shaping and block selection
 pub struct Terrain {
+    seed: u32,
+    kind: TerrainKind,
+    sea_level: usize,
     height_noise: Perlin,
     biome_selector: BiomeSelector,
 }
 
 impl Terrain {
     pub fn new(seed: u32) -> Self {
+        Self::with_kind(seed, TerrainKind::Noise)
+    }
+
+    pub fn with_kind(seed: u32, kind: TerrainKind) -> Self {
         let height_noise = Perlin::new(seed);
         let biome_selector = BiomeSelector::new(seed);
 
         Self {
+            seed,
+            kind,
+            sea_level: SEA_LEVEL,
             height_noise,
             biome_selector,
         }
     }
 
+    /// Height below which air above the surface is filled with water (noise terrain only)
+    pub fn sea_level(&self) -> usize {
+        self.sea_level
+    }
+
+    pub fn set_sea_level(&mut self, sea_level: usize) {
+        self.sea_level = sea_level.min(WORLD_HEIGHT);
+    }
+
+    /// Seed the terrain noise was created from
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// Whether structures (trees, houses, ...) should be placed on this terrain
+    pub fn has_structures(&self) -> bool {
+        matches!(self.kind, TerrainKind::Noise)
+    }
+
     /// Generate terrain blocks for a chunk with biome-aware block selection
     pub fn generate_terrain_blocks(
         &self,
@@ -28,7 +129,25 @@ impl Terrain {
         biome_map: &[Vec<Biome>],
         biome_manager: &BiomeManager,
     ) -> ChunkBlocks {
-        let mut chunk_blocks = [[[BlockType::Air; WORLD_HEIGHT]; CHUNK_SIZE]; CHUNK_SIZE];
+        let mut chunk_blocks = ChunkBlocks::new();
+
+        if let TerrainKind::SuperFlat { layers } = &self.kind {
+            let mut y = 0;
+            for &(block_type, thickness) in layers {
+                for _ in 0..thickness {
+                    if y >= WORLD_HEIGHT {
+                        return chunk_blocks;
+                    }
+                    for x in 0..CHUNK_SIZE {
+                        for z in 0..CHUNK_SIZE {
+                            chunk_blocks.set(x, y, z, block_type);
+                        }
+                    }
+                    y += 1;
+                }
+            }
+            return chunk_blocks;
+        }
 
         // Generate block types using pre-computed biome data
         for x in 0..CHUNK_SIZE {
@@ -38,12 +157,15 @@ impl Terrain {
                 let world_x = chunk_pos.x * CHUNK_SIZE as i32 + x as i32;
                 let world_z = chunk_pos.z * CHUNK_SIZE as i32 + z as i32;
 
+                // Near biome edges the surface palette may come from the neighboring biome
+                let biome = self.surface_biome_at(world_x, world_z, biome, biome_manager);
+
                 // Process all Y levels in the chunk, not just natural terrain height
                 for y in 0..WORLD_HEIGHT {
                     // Generate terrain blocks (removed/placed blocks will be handled after generation)
                     if y < height.min(TERRAIN_MAX_HEIGHT) {
                         // Use new biome-aware block selection
-                        chunk_blocks[x][z][y] = self.get_block_for_position(
+                        let block_type = self.get_block_for_position(
                             world_x,
                             y,
                             world_z,
@@ -51,16 +173,81 @@ impl Terrain {
                             biome,
                             biome_manager,
                         );
+                        chunk_blocks.set(x, y, z, block_type);
+                    } else if y < self.sea_level {
+                        chunk_blocks.set(x, y, z, BlockType::Water);
                     }
                 }
             }
         }
 
+        self.place_ores(chunk_pos, &mut chunk_blocks);
+
         chunk_blocks
     }
 
+    /// Replace stone with ore clusters. Clusters started by neighboring chunks are placed
+    /// too, so veins continue across chunk borders.
+    fn place_ores(&self, chunk_pos: ChunkPos, chunk_blocks: &mut ChunkBlocks) {
+        let origin_x = chunk_pos.x * CHUNK_SIZE as i32;
+        let origin_z = chunk_pos.z * CHUNK_SIZE as i32;
+
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                // Every chunk rolls its own clusters the same way, whichever chunk asks
+                let source_x = origin_x + dx * CHUNK_SIZE as i32;
+                let source_z = origin_z + dz * CHUNK_SIZE as i32;
+                let mut rng = position_rng(source_x, source_z, salt::ORE, self.seed);
+
+                for vein in &ORE_VEINS {
+                    for _ in 0..vein.veins_per_chunk {
+                        let start = [
+                            source_x + rng.gen_range(0..CHUNK_SIZE as i32),
+                            rng.gen_range(vein.min_y..=vein.max_y) as i32,
+                            source_z + rng.gen_range(0..CHUNK_SIZE as i32),
+                        ];
+                        let size = rng.gen_range(vein.min_size..=vein.max_size);
+
+                        // Grow a blob by stepping off random blocks already in it
+                        let mut cluster = vec![start];
+                        for _ in 0..size * 4 {
+                            if cluster.len() >= size {
+                                break;
+                            }
+                            let mut next = cluster[rng.gen_range(0..cluster.len())];
+                            next[rng.gen_range(0..3)] += if rng.gen_bool(0.5) { 1 } else { -1 };
+                            if !cluster.contains(&next) {
+                                cluster.push(next);
+                            }
+                        }
+
+                        for [x, y, z] in cluster {
+                            let (local_x, local_z) = (x - origin_x, z - origin_z);
+                            if !(0..CHUNK_SIZE as i32).contains(&local_x)
+                                || !(0..CHUNK_SIZE as i32).contains(&local_z)
+                                || !(0..WORLD_HEIGHT as i32).contains(&y)
+                            {
+                                continue;
+                            }
+                            let (local_x, y, local_z) =
+                                (local_x as usize, y as usize, local_z as usize);
+                            if chunk_blocks.get(local_x, y, local_z) == BlockType::Stone {
+                                chunk_blocks.set(local_x, y, local_z, vein.block_type);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     /// Calculate terrain height at any world position using IWD-blended heights from nearby biomes
     pub fn height_at(&self, world_x: i32, world_z: i32, biome_manager: &BiomeManager) -> usize {
+        if let TerrainKind::SuperFlat { layers } = &self.kind {
+            let total: usize = layers.iter().map(|&(_, thickness)| thickness).sum();
+            return total.min(WORLD_HEIGHT);
+        }
+
         let current_biome = self.biome_selector.select_biome(world_x, world_z);
         let current_height =
             self.calculate_height_for_biome(world_x, world_z, current_biome, biome_manager);
@@ -161,6 +348,116 @@ impl Terrain {
         boundaries
     }
 
+    /// Pick the biome whose surface palette a column uses.
+    ///
+    /// Inside the transition band around a biome edge, columns randomly take the
+    /// neighboring biome's palette, more often the closer they are to the edge, so
+    /// grass and sand interleave instead of switching along a hard line.
+    fn surface_biome_at(
+        &self,
+        world_x: i32,
+        world_z: i32,
+        biome: Biome,
+        biome_manager: &BiomeManager,
+    ) -> Biome {
+        let band = biome_manager.get_config(biome).transition_band;
+        if band <= 0 {
+            return biome;
+        }
+
+        // Nearest differing biome along the cardinal directions
+        let mut nearest: Option<(i32, Biome)> = None;
+        for (dx, dz) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+            for distance in 1..=band {
+                let neighbor_biome = self
+                    .biome_selector
+                    .select_biome(world_x + dx * distance, world_z + dz * distance);
+                if neighbor_biome != biome {
+                    if nearest.is_none_or(|(d, _)| distance < d) {
+                        nearest = Some((distance, neighbor_biome));
+                    }
+                    break;
+                }
+            }
+        }
+
+        let Some((distance, neighbor_biome)) = nearest else {
+            return biome;
+        };
+
+        // 50/50 right at the edge, fading out to the biome's own palette at the band edge
+        let chance = 0.5 * (1.0 - (distance - 1) as f32 / band as f32);
+        let mut rng = position_rng(world_x, world_z, salt::SURFACE_BLEND, self.seed);
+        if rng.gen::<f32>() < chance {
+            neighbor_biome
+        } else {
+            biome
+        }
+    }
+
+    /// Number of blocks, from y = 0 up, that `generate_terrain_blocks` fills in a column.
+    /// Structures and terrain modifiers may add blocks above it.
+    pub fn filled_height_at(
+        &self,
+        world_x: i32,
+        world_z: i32,
+        biome_manager: &BiomeManager,
+    ) -> usize {
+        let height = self.height_at(world_x, world_z, biome_manager);
+        match self.kind {
+            TerrainKind::SuperFlat { .. } => height,
+            TerrainKind::Noise => height.min(TERRAIN_MAX_HEIGHT),
+        }
+    }
+
+    /// Height, biome and surface block of the column at a world position, matching
+    /// what `generate_terrain_blocks` fills it with
+    pub fn sample(
+        &self,
+        world_x: i32,
+        world_z: i32,
+        biome_manager: &BiomeManager,
+    ) -> TerrainSample {
+        let height = self.height_at(world_x, world_z, biome_manager);
+        let biome = self.biome_at(world_x, world_z);
+
+        let (filled_height, surface_block) = match &self.kind {
+            TerrainKind::SuperFlat { layers } => {
+                let mut top = 0;
+                let mut surface_block = BlockType::Air;
+                for &(block_type, thickness) in layers {
+                    if thickness > 0 && top < height {
+                        surface_block = block_type;
+                    }
+                    top += thickness;
+                }
+                (height, surface_block)
+            }
+            TerrainKind::Noise => {
+                let filled_height = height.min(TERRAIN_MAX_HEIGHT);
+                let surface_biome = self.surface_biome_at(world_x, world_z, biome, biome_manager);
+                let surface_block = match filled_height {
+                    0 => BlockType::Air,
+                    _ => self.get_block_for_position(
+                        world_x,
+                        filled_height - 1,
+                        world_z,
+                        height,
+                        surface_biome,
+                        biome_manager,
+                    ),
+                };
+                (filled_height, surface_block)
+            }
+        };
+
+        TerrainSample {
+            height: filled_height,
+            biome,
+            surface_block,
+        }
+    }
+
     /// Select biome at any world position
     pub fn biome_at(&self, world_x: i32, world_z: i32) -> Biome {
         self.biome_selector.select_biome(world_x, world_z)
@@ -184,13 +481,304 @@ impl Terrain {
             return BlockType::Snow;
         }
 
+        // Shorelines and shallow water floors are sand whatever the biome
+        let is_beach = height.abs_diff(self.sea_level) <= BEACH_WIDTH;
+
         // Biome-specific layering using config
         if y >= surface_level {
-            config.surface_block
+            if is_beach {
+                BlockType::Sand
+            } else {
+                config.surface_block
+            }
         } else if y >= height.saturating_sub(4) {
-            config.subsurface_block
+            if is_beach {
+                BlockType::Sand
+            } else {
+                config.subsurface_block
+            }
         } else {
             config.stone_block
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::BlockRegistry;
+    use crate::chunk::ChunkGenerator;
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn test_superflat_columns_match_layer_stack() {
+        let layers = vec![
+            (BlockType::Cobblestone, 1),
+            (BlockType::Stone, 3),
+            (BlockType::Dirt, 2),
+            (BlockType::Grass, 1),
+        ];
+        let terrain = Terrain::with_kind(42, TerrainKind::SuperFlat { layers });
+        let biome_manager = BiomeManager::new();
+        let generator = ChunkGenerator::new(7777);
+
+        let mut expected = vec![BlockType::Cobblestone];
+        expected.extend([BlockType::Stone; 3]);
+        expected.extend([BlockType::Dirt; 2]);
+        expected.push(BlockType::Grass);
+
+        for chunk_pos in [ChunkPos { x: 0, z: 0 }, ChunkPos { x: -5, z: 12 }] {
+            let (_, chunk_blocks) = generator.generate_chunk(
+                chunk_pos,
+                &terrain,
+                &biome_manager,
+                &BlockRegistry::new(),
+            );
+
+            for x in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    for (y, &block_type) in expected.iter().enumerate() {
+                        assert_eq!(chunk_blocks.get(x, y, z), block_type);
+                    }
+                    for y in expected.len()..WORLD_HEIGHT {
+                        assert_eq!(chunk_blocks.get(x, y, z), BlockType::Air);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_samples_match_generated_columns() {
+        let terrain = Terrain::new(42);
+        let biome_manager = BiomeManager::new();
+
+        let mut surfaces = HashSet::new();
+        for chunk_pos in [ChunkPos { x: 0, z: 0 }, ChunkPos { x: 5, z: 0 }] {
+            let world_pos = |x: usize, z: usize| {
+                (
+                    chunk_pos.x * CHUNK_SIZE as i32 + x as i32,
+                    chunk_pos.z * CHUNK_SIZE as i32 + z as i32,
+                )
+            };
+            let mut height_values = Vec::new();
+            let mut biome_map = Vec::new();
+            for x in 0..CHUNK_SIZE {
+                let row = (0..CHUNK_SIZE).map(|z| world_pos(x, z));
+                height_values.push(
+                    row.clone()
+                        .map(|(x, z)| terrain.height_at(x, z, &biome_manager))
+                        .collect::<Vec<_>>(),
+                );
+                biome_map.push(row.map(|(x, z)| terrain.biome_at(x, z)).collect::<Vec<_>>());
+            }
+            let blocks = terrain.generate_terrain_blocks(
+                chunk_pos,
+                &height_values,
+                &biome_map,
+                &biome_manager,
+            );
+
+            for (x, biomes) in biome_map.iter().enumerate() {
+                for (z, &biome) in biomes.iter().enumerate() {
+                    let (world_x, world_z) = world_pos(x, z);
+                    let sample = terrain.sample(world_x, world_z, &biome_manager);
+
+                    assert_eq!(sample.biome, biome);
+                    assert_eq!(blocks.get(x, sample.height - 1, z), sample.surface_block);
+                    let above = blocks.get(x, sample.height, z);
+                    assert!(above == BlockType::Air || above == BlockType::Water);
+                    surfaces.insert(sample.surface_block);
+                }
+            }
+        }
+        assert!(surfaces.len() > 1);
+
+        let layers = vec![(BlockType::Stone, 3), (BlockType::Dirt, 0)];
+        let flat = Terrain::with_kind(42, TerrainKind::SuperFlat { layers });
+        let sample = flat.sample(-100, 5, &biome_manager);
+        assert_eq!((sample.height, sample.surface_block), (3, BlockType::Stone));
+    }
+
+    #[test]
+    fn test_shoreline_columns_are_sand() {
+        let terrain = Terrain::new(42);
+        let biome_manager = BiomeManager::new();
+        let block_at = |y, height| {
+            terrain.get_block_for_position(0, y, 0, height, Biome::Plains, &biome_manager)
+        };
+
+        // Just above, at and just below the water line
+        for height in SEA_LEVEL - BEACH_WIDTH..=SEA_LEVEL + BEACH_WIDTH {
+            assert_eq!(block_at(height - 1, height), BlockType::Sand);
+            assert_eq!(block_at(height - 3, height), BlockType::Sand);
+        }
+
+        let inland = SEA_LEVEL + BEACH_WIDTH + 1;
+        assert_eq!(block_at(inland - 1, inland), BlockType::Grass);
+        assert_eq!(block_at(inland - 2, inland), BlockType::Dirt);
+    }
+
+    #[test]
+    fn test_surface_palettes_speckle_across_biome_edges() {
+        let terrain = Terrain::new(42);
+        let biome_manager = BiomeManager::new();
+        let band = biome_manager.get_config(Biome::Plains).transition_band;
+
+        let row: Vec<(Biome, Biome)> = (0..2000)
+            .map(|x| {
+                let biome = terrain.biome_at(x, 0);
+                (biome, terrain.surface_biome_at(x, 0, biome, &biome_manager))
+            })
+            .collect();
+
+        // Right at an edge, columns on both sides sometimes borrow the other's palette,
+        // while deep inside a biome they never do
+        let edges: Vec<usize> = (1..row.len())
+            .filter(|&x| row[x].0 != row[x - 1].0)
+            .collect();
+        assert!(!edges.is_empty());
+        let near_edge = |x: usize| edges.iter().any(|&edge| x.abs_diff(edge) <= 1);
+        let borrowed = |&(biome, surface): &(Biome, Biome)| biome != surface;
+        assert!((0..row.len()).any(|x| near_edge(x) && borrowed(&row[x])));
+        let deep_inside = |x: usize| {
+            let (x, biome) = (x as i32, row[x].0);
+            (1..=band).all(|d| {
+                [(d, 0), (-d, 0), (0, d), (0, -d)]
+                    .iter()
+                    .all(|&(dx, dz)| terrain.biome_at(x + dx, dz) == biome)
+            })
+        };
+        assert!((0..row.len())
+            .filter(|&x| deep_inside(x))
+            .all(|x| !borrowed(&row[x])));
+
+        // The same world picks the same palettes again, so regenerated chunks match
+        let again = Terrain::new(42);
+        for (x, &(biome, surface)) in row.iter().enumerate() {
+            assert_eq!(
+                again.surface_biome_at(x as i32, 0, biome, &biome_manager),
+                surface
+            );
+        }
+    }
+
+    #[test]
+    fn test_wet_regions_hold_open_sea() {
+        let terrain = Terrain::new(42);
+        let biome_manager = BiomeManager::new();
+
+        // Longest stretch of flooded ocean columns along a line through the world
+        let mut longest = 0;
+        let mut run = 0;
+        for x in 0..4000 {
+            let flooded = terrain.biome_at(x, 0) == Biome::Ocean
+                && terrain.height_at(x, 0, &biome_manager) < SEA_LEVEL;
+            run = if flooded { run + 1 } else { 0 };
+            longest = longest.max(run);
+        }
+        assert!(
+            longest >= 32,
+            "longest stretch of sea was {} blocks",
+            longest
+        );
+
+        // Cold regions turn to sea just as well
+        assert_eq!(BiomeSelector::biome_for_climate(-0.5, 0.7), Biome::Ocean);
+        assert_eq!(BiomeSelector::biome_for_climate(-0.5, 0.5), Biome::Mountain);
+    }
+
+    #[test]
+    fn test_ores_form_small_clusters_with_gold_deepest() {
+        let terrain = Terrain::new(42);
+        // The built-in biome heights are too shallow to reach the coal layer
+        let biome_manager =
+            BiomeManager::load_from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/biome.toml"))
+                .unwrap();
+        let generator = ChunkGenerator::new(7777);
+
+        let mut counts: HashMap<BlockType, usize> = HashMap::new();
+        for chunk_x in 0..3 {
+            for chunk_z in 0..3 {
+                let chunk_pos = ChunkPos {
+                    x: chunk_x,
+                    z: chunk_z,
+                };
+                let chunk_blocks =
+                    generator.generate_chunk_blocks(chunk_pos, &terrain, &biome_manager);
+
+                let mut seen = HashSet::new();
+                for x in 0..CHUNK_SIZE {
+                    for y in 0..WORLD_HEIGHT {
+                        for z in 0..CHUNK_SIZE {
+                            let block_type = chunk_blocks.get(x, y, z);
+                            if !matches!(
+                                block_type,
+                                BlockType::Coal | BlockType::Iron | BlockType::Gold
+                            ) {
+                                continue;
+                            }
+                            *counts.entry(block_type).or_default() += 1;
+                            if block_type == BlockType::Gold {
+                                assert!(y <= ORE_VEINS[2].max_y + ORE_VEINS[2].max_size);
+                            }
+
+                            // Flood fill the cluster this block belongs to
+                            if !seen.insert((x, y, z)) {
+                                continue;
+                            }
+                            let mut stack = vec![(x, y, z)];
+                            let mut cluster_size = 0;
+                            while let Some((cx, cy, cz)) = stack.pop() {
+                                cluster_size += 1;
+                                for (dx, dy, dz) in [
+                                    (1, 0, 0),
+                                    (-1, 0, 0),
+                                    (0, 1, 0),
+                                    (0, -1, 0),
+                                    (0, 0, 1),
+                                    (0, 0, -1),
+                                ] {
+                                    let (nx, ny, nz) =
+                                        (cx as i32 + dx, cy as i32 + dy, cz as i32 + dz);
+                                    if nx < 0
+                                        || ny < 0
+                                        || nz < 0
+                                        || nx >= CHUNK_SIZE as i32
+                                        || nz >= CHUNK_SIZE as i32
+                                    {
+                                        continue;
+                                    }
+                                    let neighbor = (nx as usize, ny as usize, nz as usize);
+                                    if chunk_blocks.get(neighbor.0, neighbor.1, neighbor.2)
+                                        == block_type
+                                        && seen.insert(neighbor)
+                                    {
+                                        stack.push(neighbor);
+                                    }
+                                }
+                            }
+                            // Two veins can touch, but never grow into large masses
+                            assert!(
+                                cluster_size <= 2 * ORE_VEINS[0].max_size,
+                                "cluster of {}",
+                                cluster_size
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        let coal = counts[&BlockType::Coal];
+        let iron = counts[&BlockType::Iron];
+        let gold = counts[&BlockType::Gold];
+        assert!(
+            coal > iron && iron > gold && gold > 0,
+            "{} {} {}",
+            coal,
+            iron,
+            gold
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use crate::biome::BiomeManager;
+use crate::blocks::BlockType;
+use crate::chunk::{ChunkBlocks, CHUNK_SIZE, WORLD_HEIGHT};
+use crate::rng::{position_rng, salt};
+use crate::terrain::Terrain;
+use rand::Rng;
+
+/// Hook for custom terrain features (craters, pillars, ore styles, ...).
+///
+/// Registered modifiers run in order on every generated chunk, after base terrain is
+/// generated and before structures are placed. They must be `Send + Sync` because chunks generate in parallel.
+pub trait TerrainModifier: Send + Sync {
+    /// Edit a chunk's blocks. `origin_x`/`origin_z` are the world coordinates of the
+    /// chunk's local (0, 0) column; `terrain` gives access to heights and biomes.
+    fn modify(
+        &self,
+        chunk_blocks: &mut ChunkBlocks,
+        origin_x: i32,
+        origin_z: i32,
+        terrain: &Terrain,
+        biome_manager: &BiomeManager,
+    );
+}
+
+/// Example modifier that scatters small stone boulders on the surface
+pub struct BoulderModifier {
+    seed: u32,
+    /// Probability that a chunk gets a boulder
+    chance: f32,
+}
+
+impl BoulderModifier {
+    pub fn new(seed: u32) -> Self {
+        Self { seed, chance: 0.3 }
+    }
+}
+
+impl TerrainModifier for BoulderModifier {
+    fn modify(
+        &self,
+        chunk_blocks: &mut ChunkBlocks,
+        origin_x: i32,
+        origin_z: i32,
+        terrain: &Terrain,
+        biome_manager: &BiomeManager,
+    ) {
+        let mut rng = position_rng(origin_x, origin_z, salt::BOULDER, self.seed);
+        if rng.gen::<f32>() >= self.chance {
+            return;
+        }
+
+        let radius: i32 = rng.gen_range(1..=2);
+        // Keep the whole boulder inside this chunk so it never gets cut at a border
+        let center_x = rng.gen_range(radius..CHUNK_SIZE as i32 - radius);
+        let center_z = rng.gen_range(radius..CHUNK_SIZE as i32 - radius);
+        let center_y =
+            terrain.height_at(origin_x + center_x, origin_z + center_z, biome_manager) as i32;
+
+        for dx in -radius..=radius {
+            for dy in -radius..=radius {
+                for dz in -radius..=radius {
+                    if dx * dx + dy * dy + dz * dz > radius * radius {
+                        continue;
+                    }
+
+                    let y = center_y + dy;
+                    if y < 0 || y >= WORLD_HEIGHT as i32 {
+                        continue;
+                    }
+
+                    let block_type = if rng.gen::<f32>() < 0.5 {
+                        BlockType::Stone
+                    } else {
+                        BlockType::Cobblestone
+                    };
+                    chunk_blocks.set(
+                        (center_x + dx) as usize,
+                        y as usize,
+                        (center_z + dz) as usize,
+                        block_type,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blocks::BlockRegistry;
+    use crate::chunk::{ChunkGenerator, ChunkPos};
+
+    /// Puts a glass pillar at the chunk's origin column
+    struct PillarModifier;
+
+    impl TerrainModifier for PillarModifier {
+        fn modify(
+            &self,
+            chunk_blocks: &mut ChunkBlocks,
+            _origin_x: i32,
+            _origin_z: i32,
+            _terrain: &Terrain,
+            _biome_manager: &BiomeManager,
+        ) {
+            for y in 200..210 {
+                chunk_blocks.set(0, y, 0, BlockType::Glass);
+            }
+        }
+    }
+
+    #[test]
+    fn test_modifier_edits_appear_in_generated_blocks() {
+        let mut generator = ChunkGenerator::new(7777);
+        generator.register_modifier(Box::new(PillarModifier));
+
+        let (_, chunk_blocks) = generator.generate_chunk(
+            ChunkPos { x: 3, z: -2 },
+            &Terrain::new(42),
+            &BiomeManager::new(),
+            &BlockRegistry::new(),
+        );
+
+        for y in 200..210 {
+            assert_eq!(chunk_blocks.get(0, y, 0), BlockType::Glass);
+        }
+        assert_eq!(chunk_blocks.get(0, 210, 0), BlockType::Air);
+    }
+}
@@ -0,0 +1,290 @@
+use crate::ui::pixels_to_ndc;
+use crate::ui_atlas::{glyph_uv, UiAtlas, GLYPH_CELL_SIZE};
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+/// Most glyph quads drawn at once, counting the shadow of each character
+const MAX_QUADS: usize = 2048;
+/// Horizontal distance between characters and vertical distance between lines, in pixels.
+/// Glyphs only fill part of their cell, so cells overlap a little.
+const CHAR_ADVANCE: f32 = 12.0;
+const LINE_HEIGHT: f32 = 18.0;
+/// Offset of the drop shadow that keeps text readable on bright terrain
+const SHADOW_OFFSET: f32 = 2.0;
+
+const TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const SHADOW_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.75];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct TextVertex {
+    pub position: [f32; 2],
+    pub tex_coords: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl TextVertex {
+    fn desc<'a>() -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TextVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
+        }
+    }
+}
+
+/// Screen-space text drawn with the bitmap font of the UI atlas, e.g. the debug overlay
+pub struct TextRenderer {
+    render_pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    num_indices: u32,
+}
+
+impl TextRenderer {
+    pub fn new(
+        device: &wgpu::Device,
+        surface_format: wgpu::TextureFormat,
+        ui_atlas: &UiAtlas,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Text Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("text_renderer.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("text_bind_group_layout"),
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&ui_atlas.view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&ui_atlas.sampler),
+                },
+            ],
+            label: Some("text_bind_group"),
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Text Pipeline Layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[TextVertex::desc()],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: wgpu::TextureFormat::Depth32Float,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always, // Overlay everything
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+        });
+
+        // Room for the longest text; `set_text` rewrites the start of it
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Text Vertex Buffer"),
+            size: (MAX_QUADS * 4 * std::mem::size_of::<TextVertex>()) as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let indices: Vec<u16> = (0..MAX_QUADS as u16)
+            .flat_map(|quad| [0, 1, 2, 0, 2, 3].map(|i| quad * 4 + i))
+            .collect();
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Text Index Buffer"),
+            contents: bytemuck::cast_slice(&indices),
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Self {
+            render_pipeline,
+            bind_group,
+            vertex_buffer,
+            index_buffer,
+            num_indices: 0,
+        }
+    }
+
+    /// Replace the text with `text`, its top-left corner at `position` in pixels.
+    /// Text past the renderer's capacity is cut off.
+    pub fn set_text(
+        &mut self,
+        queue: &wgpu::Queue,
+        text: &str,
+        position: [f32; 2],
+        window_width: u32,
+        window_height: u32,
+    ) {
+        let mut vertices = create_text_vertices(text, position, window_width, window_height);
+        vertices.truncate(MAX_QUADS * 4);
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        self.num_indices = (vertices.len() / 4 * 6) as u32;
+    }
+
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.num_indices == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+    }
+}
+
+/// One quad per visible character, each preceded by its shadow. Lines are split at
+/// '\n' and spaces only move the pen.
+pub fn create_text_vertices(
+    text: &str,
+    position: [f32; 2],
+    window_width: u32,
+    window_height: u32,
+) -> Vec<TextVertex> {
+    let cell = GLYPH_CELL_SIZE as f32;
+    let mut vertices = Vec::new();
+
+    for (line, characters) in text.lines().enumerate() {
+        let top = position[1] + line as f32 * LINE_HEIGHT;
+        for (column, c) in characters.chars().enumerate() {
+            if c == ' ' {
+                continue;
+            }
+            let left = position[0] + column as f32 * CHAR_ADVANCE;
+            let [u_min, v_min, u_max, v_max] = glyph_uv(c);
+
+            for (offset, color) in [(SHADOW_OFFSET, SHADOW_COLOR), (0.0, TEXT_COLOR)] {
+                let (x, y) = (left + offset, top + offset);
+                vertices.extend(
+                    [
+                        (x, y + cell, u_min, v_max),
+                        (x + cell, y + cell, u_max, v_max),
+                        (x + cell, y, u_max, v_min),
+                        (x, y, u_min, v_min),
+                    ]
+                    .map(|(px, py, u, v)| TextVertex {
+                        position: pixels_to_ndc(px, py, window_width, window_height),
+                        tex_coords: [u, v],
+                        color,
+                    }),
+                );
+            }
+        }
+    }
+
+    vertices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_is_laid_out_in_rows_of_glyphs() {
+        let (width, height) = (800, 600);
+        let vertices = create_text_vertices("A B\nC", [10.0, 20.0], width, height);
+
+        // Three visible characters with a shadow each; the space only advances
+        assert_eq!(vertices.len(), 3 * 2 * 4);
+        let top_left = |quad: usize| vertices[quad * 4 + 3];
+
+        // Shadow first, then the glyph itself at the requested position
+        assert_eq!(top_left(0).color, SHADOW_COLOR);
+        assert_eq!(
+            top_left(0).position,
+            pixels_to_ndc(12.0, 22.0, width, height)
+        );
+        assert_eq!(top_left(1).color, TEXT_COLOR);
+        assert_eq!(
+            top_left(1).position,
+            pixels_to_ndc(10.0, 20.0, width, height)
+        );
+        assert_eq!(top_left(1).tex_coords, glyph_uv('A')[..2]);
+
+        // 'B' two columns over, 'C' at the start of the next line
+        assert_eq!(
+            top_left(3).position,
+            pixels_to_ndc(10.0 + 2.0 * CHAR_ADVANCE, 20.0, width, height)
+        );
+        assert_eq!(
+            top_left(5).position,
+            pixels_to_ndc(10.0, 20.0 + LINE_HEIGHT, width, height)
+        );
+        assert_eq!(top_left(5).tex_coords, glyph_uv('c')[..2]);
+    }
+}
@@ -0,0 +1,33 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+    @location(2) color: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var ui_atlas: texture_2d<f32>;
+
+@group(0) @binding(1)
+var ui_sampler: sampler;
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    // Already in NDC, laid out in pixels on the CPU
+    out.clip_position = vec4<f32>(model.position, 0.0, 1.0);
+    out.tex_coords = model.tex_coords;
+    out.color = model.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Generated glyphs are white, so the vertex color tints them
+    return textureSample(ui_atlas, ui_sampler, in.tex_coords) * in.color;
+}
@@ -1,47 +1,105 @@
-use crate::texture_parser;
+use crate::blocks::{texture_id_filename, TextureId};
+use crate::texture_parser::{self, ParsedTexture};
+use bytemuck::{Pod, Zeroable};
+use std::collections::HashMap;
+use wgpu::util::DeviceExt;
+
+/// Default size of one block texture in the atlas, in pixels. `graphics.texture_size` in
+/// settings.toml raises it (e.g. to 32) for higher resolution texture packs; textures of
+/// any other size are scaled to fit.
+pub const ATLAS_TILE_SIZE: u32 = 16;
+/// Largest tile size accepted from the settings
+pub const MAX_TILE_SIZE: u32 = 256;
+
+/// `size` if it can be used as the atlas tile size: a power of two (so mips stay inside
+/// their tiles) no larger than `MAX_TILE_SIZE`
+pub fn validate_tile_size(size: u32) -> Result<u32, String> {
+    if size.is_power_of_two() && size <= MAX_TILE_SIZE {
+        Ok(size)
+    } else {
+        Err(format!(
+            "texture_size {} is not a power of two up to {}, using {}",
+            size, MAX_TILE_SIZE, ATLAS_TILE_SIZE
+        ))
+    }
+}
+
+/// Tiles along each side of a square atlas holding `texture_count` textures, rounded
+/// up to a power of two
+pub fn tiles_per_row(texture_count: usize) -> u32 {
+    let mut tiles = 1;
+    while tiles * tiles < texture_count {
+        tiles *= 2;
+    }
+    tiles as u32
+}
+
+/// Atlas layout for the shader
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct AtlasUniform {
+    tiles_per_row: u32,
+    _padding: [u32; 3], // 16-byte alignment
+}
 
 pub struct TextureAtlas {
     pub texture: wgpu::Texture,
     pub view: wgpu::TextureView,
     pub sampler: wgpu::Sampler,
     pub bind_group: wgpu::BindGroup,
+    /// Tiles along each side of the atlas; `TextureId` n sits at column n % this, row n / this
+    pub tiles_per_row: u32,
+    _uniform_buffer: wgpu::Buffer,
 }
 
 impl TextureAtlas {
+    /// Build the atlas from the files in `textures/` with `tile_size` pixel tiles. Missing
+    /// textures are drawn in a flat color, textures of another size are scaled and an
+    /// invalid tile size falls back to the default; all are listed in the returned summary
+    /// instead of failing.
     pub fn new(
         device: &wgpu::Device,
         queue: &wgpu::Queue,
         bind_group_layout: &wgpu::BindGroupLayout,
-    ) -> Self {
-        // Create a 4x4 texture atlas with loaded block textures
-        // Each texture is 16x16 pixels for a total of 64x64 atlas
-        let atlas_size = 64u32;
-        let tile_size = 16u32;
-
-        // Load textures from .texture files
-        let loaded_textures = texture_parser::load_all_textures().unwrap_or_else(|e| {
-            eprintln!("Failed to load textures: {}", e);
-            std::collections::HashMap::new()
+        anisotropy_clamp: u16,
+        tile_size: u32,
+    ) -> (Self, Option<String>) {
+        let mut problems = Vec::new();
+        let tile_size = validate_tile_size(tile_size).unwrap_or_else(|e| {
+            problems.push(e);
+            ATLAS_TILE_SIZE
         });
 
-        // Create the atlas data
-        let mut atlas_data = vec![0u8; (atlas_size * atlas_size * 4) as usize]; // RGBA
-
-        // Fill the atlas with loaded textures
-        for tile_y in 0..4 {
-            for tile_x in 0..4 {
-                let texture_id = tile_y * 4 + tile_x;
-                copy_texture_to_atlas(
-                    &mut atlas_data,
-                    atlas_size,
-                    tile_x * tile_size,
-                    tile_y * tile_size,
-                    tile_size,
-                    texture_id,
-                    &loaded_textures,
-                );
+        // Create a square grid just big enough for every block texture
+        // Each texture is tile_size pixels square (8x8 tiles make a 128x128 atlas at 16)
+        let tiles_per_row = tiles_per_row(TextureId::ALL.len());
+        let atlas_size = tiles_per_row * tile_size;
+        // Halve down to one texel per tile. Each level is downsampled within its tiles, so
+        // halving stops once a tile side is odd and would mix neighbors.
+        let mip_level_count = tile_size.trailing_zeros() + 1;
+
+        // Load textures from .texture files
+        let loaded_textures = match texture_parser::load_all_textures(tile_size) {
+            Ok((textures, scaling_warnings)) => {
+                problems.extend(scaling_warnings);
+                textures
             }
+            Err(e) => {
+                problems.push(e);
+                HashMap::new()
+            }
+        };
+
+        let (atlas_data, missing) = build_atlas_data(tiles_per_row, tile_size, &loaded_textures);
+        if !missing.is_empty() {
+            problems.push(format!(
+                "{} of {} textures missing, drawn in flat colors: {}",
+                missing.len(),
+                TextureId::ALL.len(),
+                missing.join(", ")
+            ));
         }
+        let summary = (!problems.is_empty()).then(|| problems.join("; "));
 
         let texture = device.create_texture(&wgpu::TextureDescriptor {
             size: wgpu::Extent3d {
@@ -49,7 +107,7 @@ impl TextureAtlas {
                 height: atlas_size,
                 depth_or_array_layers: 1,
             },
-            mip_level_count: 1,
+            mip_level_count,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
             format: wgpu::TextureFormat::Rgba8UnormSrgb,
@@ -58,36 +116,76 @@ impl TextureAtlas {
             view_formats: &[],
         });
 
-        queue.write_texture(
-            wgpu::ImageCopyTexture {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            &atlas_data,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(atlas_size * 4),
-                rows_per_image: Some(atlas_size),
-            },
-            wgpu::Extent3d {
-                width: atlas_size,
-                height: atlas_size,
-                depth_or_array_layers: 1,
-            },
-        );
+        let mut level_data = atlas_data;
+        let mut level_size = atlas_size;
+        for mip_level in 0..mip_level_count {
+            if mip_level > 0 {
+                level_data = downsample(&level_data, level_size);
+                level_size /= 2;
+            }
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &texture,
+                    mip_level,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &level_data,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(level_size * 4),
+                    rows_per_image: Some(level_size),
+                },
+                wgpu::Extent3d {
+                    width: level_size,
+                    height: level_size,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
 
         let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
 
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Nearest, // Pixel-perfect for Minecraft style
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            ..Default::default()
+        // Anisotropic filtering only helps when there are mips to choose from
+        let anisotropy_clamp = if mip_level_count > 1 {
+            anisotropy_clamp.clamp(1, 16)
+        } else {
+            1
+        };
+
+        let sampler = if anisotropy_clamp > 1 {
+            // wgpu requires linear filtering for every stage when anisotropy is enabled,
+            // so close-up textures get blurred too
+            device.create_sampler(&wgpu::SamplerDescriptor {
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Linear,
+                anisotropy_clamp,
+                ..Default::default()
+            })
+        } else {
+            device.create_sampler(&wgpu::SamplerDescriptor {
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Nearest, // Pixel-perfect for Minecraft style
+                // Blend into the mips at a distance so far-away blocks don't shimmer
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            })
+        };
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Texture Atlas Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[AtlasUniform {
+                tiles_per_row,
+                _padding: [0; 3],
+            }]),
+            usage: wgpu::BufferUsages::UNIFORM,
         });
 
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
@@ -101,80 +199,169 @@ impl TextureAtlas {
                     binding: 1,
                     resource: wgpu::BindingResource::Sampler(&sampler),
                 },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
             ],
             label: Some("Texture Atlas Bind Group"),
         });
 
-        Self {
+        let atlas = Self {
             texture,
             view,
             sampler,
             bind_group,
+            tiles_per_row,
+            _uniform_buffer: uniform_buffer,
+        };
+        (atlas, summary)
+    }
+}
+
+/// Next mip level of a square RGBA atlas `size` pixels across: each pixel averages a 2x2
+/// block of the level above. With even tile sizes those blocks never straddle two tiles.
+fn downsample(data: &[u8], size: u32) -> Vec<u8> {
+    let half = size / 2;
+    let mut result = vec![0u8; (half * half * 4) as usize];
+    for y in 0..half {
+        for x in 0..half {
+            let sources = [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(dx, dy)| {
+                let index = (((y * 2 + dy) * size + x * 2 + dx) * 4) as usize;
+                &data[index..index + 4]
+            });
+
+            // Average in linear light, weighting color by alpha so the hidden color of
+            // transparent pixels (leaf gaps, glass) doesn't darken the edges
+            let mut color = [0.0f32; 3];
+            let mut alpha = 0.0f32;
+            for pixel in sources {
+                let weight = pixel[3] as f32 / 255.0;
+                for channel in 0..3 {
+                    color[channel] += srgb_to_linear(pixel[channel]) * weight;
+                }
+                alpha += weight;
+            }
+
+            let index = ((y * half + x) * 4) as usize;
+            if alpha > 0.0 {
+                for channel in 0..3 {
+                    result[index + channel] = linear_to_srgb(color[channel] / alpha);
+                }
+            }
+            result[index + 3] = (alpha / 4.0 * 255.0).round() as u8;
+        }
+    }
+    result
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let value = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// RGBA pixels of the top mip level with every texture in its tile, along with the file
+/// names of the textures that weren't loaded. Tiles past the last texture stay empty.
+fn build_atlas_data(
+    tiles_per_row: u32,
+    tile_size: u32,
+    loaded_textures: &HashMap<String, ParsedTexture>,
+) -> (Vec<u8>, Vec<&'static str>) {
+    let atlas_size = tiles_per_row * tile_size;
+    let mut atlas_data = vec![0u8; (atlas_size * atlas_size * 4) as usize];
+    let mut missing = Vec::new();
+    for texture_id in TextureId::ALL {
+        let index = texture_id as u32;
+        let found = copy_texture_to_atlas(
+            &mut atlas_data,
+            atlas_size,
+            index % tiles_per_row * tile_size,
+            index / tiles_per_row * tile_size,
+            tile_size,
+            texture_id,
+            loaded_textures,
+        );
+        if !found {
+            missing.push(texture_id_filename(texture_id));
         }
     }
+    (atlas_data, missing)
+}
+
+/// Stand-in color for a texture whose file is missing, close enough to the real one that
+/// the world stays readable
+fn fallback_color(texture_id: TextureId) -> [u8; 4] {
+    match texture_id {
+        TextureId::Stone => [128, 128, 128, 255],
+        TextureId::Dirt => [139, 90, 43, 255],
+        TextureId::GrassTop => [95, 159, 53, 255],
+        TextureId::GrassSide => [120, 110, 50, 255],
+        TextureId::Sand => [219, 207, 163, 255],
+        TextureId::Water => [74, 143, 200, 176],
+        TextureId::WoodTop => [160, 130, 80, 255],
+        TextureId::WoodSide => [102, 81, 51, 255],
+        TextureId::Leaves => [60, 120, 40, 255],
+        TextureId::Snow => [240, 245, 250, 255],
+        TextureId::Bedrock => [50, 50, 50, 255],
+        TextureId::Planks => [180, 144, 90, 255],
+        TextureId::Cobblestone => [110, 110, 110, 255],
+        TextureId::Glass => [200, 225, 235, 80],
+        TextureId::CoalOre => [90, 90, 90, 255],
+        TextureId::IronOre => [150, 130, 115, 255],
+        TextureId::GoldOre => [170, 150, 80, 255],
+        TextureId::Glowstone => [230, 190, 110, 255],
+    }
 }
 
-// Copy loaded textures to atlas positions
+/// Copy a loaded texture into its atlas tile, or fill the tile with its fallback color.
+/// Returns whether the texture was loaded.
 fn copy_texture_to_atlas(
     atlas_data: &mut [u8],
     atlas_width: u32,
     start_x: u32,
     start_y: u32,
     size: u32,
-    texture_id: u32,
-    loaded_textures: &std::collections::HashMap<String, texture_parser::ParsedTexture>,
-) {
-    // Map texture IDs to texture file names
-    let texture_name = match texture_id {
-        0 => "stone",        // Stone
-        1 => "dirt",         // Dirt
-        2 => "grass_top",    // Grass Top
-        3 => "grass_side",   // Grass Side
-        4 => "sand",         // Sand
-        5 => "water",        // Water
-        6 => "wood_top",     // Wood Top
-        7 => "wood_side",    // Wood Side
-        8 => "leaves",       // Leaves
-        9 => "snow",         // Snow
-        10 => "bedrock",     // Bedrock
-        11 => "planks",      // Planks
-        12 => "cobblestone", // Cobblestone
-        13 => "glass",       // Glass
-        _ => "stone",        // Default to stone
-    };
+    texture_id: TextureId,
+    loaded_textures: &HashMap<String, ParsedTexture>,
+) -> bool {
+    let texture_name = texture_id_filename(texture_id);
 
     // Get the loaded texture or use a fallback
     if let Some(texture) = loaded_textures.get(texture_name) {
-        // Copy texture data to atlas
+        // Copy texture data to atlas. Loading already scaled it to the tile size, this
+        // only guards against a texture that slipped through at another size.
         for y in 0..size {
             for x in 0..size {
                 let atlas_x = start_x + x;
                 let atlas_y = start_y + y;
                 let atlas_index = ((atlas_y * atlas_width + atlas_x) * 4) as usize;
 
-                if x < texture.width && y < texture.height {
-                    let texture_index = ((y * texture.width + x) * 4) as usize;
-
-                    if atlas_index + 3 < atlas_data.len()
-                        && texture_index + 3 < texture.pixels.len()
-                    {
-                        atlas_data[atlas_index] = texture.pixels[texture_index]; // R
-                        atlas_data[atlas_index + 1] = texture.pixels[texture_index + 1]; // G
-                        atlas_data[atlas_index + 2] = texture.pixels[texture_index + 2]; // B
-                        atlas_data[atlas_index + 3] = texture.pixels[texture_index + 3];
-                        // A
-                    }
+                let texture_x = x * texture.width / size;
+                let texture_y = y * texture.height / size;
+                let texture_index = ((texture_y * texture.width + texture_x) * 4) as usize;
+
+                if atlas_index + 3 < atlas_data.len() && texture_index + 3 < texture.pixels.len() {
+                    atlas_data[atlas_index..atlas_index + 4]
+                        .copy_from_slice(&texture.pixels[texture_index..texture_index + 4]);
                 }
             }
         }
+        true
     } else {
-        // Fallback: generate a simple colored pattern if texture not found
-        let (r, g, b, a) = match texture_id {
-            0 => (128, 128, 128, 255), // Stone - gray
-            1 => (139, 90, 43, 255),   // Dirt - brown
-            _ => (255, 0, 255, 255),   // Magenta for missing textures
-        };
-
+        let color = fallback_color(texture_id);
         for y in 0..size {
             for x in 0..size {
                 let atlas_x = start_x + x;
@@ -182,17 +369,121 @@ fn copy_texture_to_atlas(
                 let atlas_index = ((atlas_y * atlas_width + atlas_x) * 4) as usize;
 
                 if atlas_index + 3 < atlas_data.len() {
-                    atlas_data[atlas_index] = r;
-                    atlas_data[atlas_index + 1] = g;
-                    atlas_data[atlas_index + 2] = b;
-                    atlas_data[atlas_index + 3] = a; // Use proper alpha value
+                    atlas_data[atlas_index..atlas_index + 4].copy_from_slice(&color);
                 }
             }
         }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_32px_texture_copies_fully_into_32px_tile() {
+        let size = 32u32;
+        let pixels: Vec<u8> = (0..size * size)
+            .flat_map(|i| [(i % 256) as u8, (i / 256) as u8, 7, 255])
+            .collect();
+        let mut loaded_textures = HashMap::new();
+        loaded_textures.insert(
+            "dirt".to_string(),
+            ParsedTexture {
+                name: "Dirt".to_string(),
+                width: size,
+                height: size,
+                pixels: pixels.clone(),
+            },
+        );
+
+        // 2x2 tiles of 32px; dirt (id 1) goes in the top-right tile
+        let atlas_width = size * 2;
+        let mut atlas_data = vec![0u8; (atlas_width * atlas_width * 4) as usize];
+        copy_texture_to_atlas(
+            &mut atlas_data,
+            atlas_width,
+            size,
+            0,
+            size,
+            TextureId::Dirt,
+            &loaded_textures,
+        );
+
+        for y in 0..size {
+            let row_start = ((y * atlas_width + size) * 4) as usize;
+            let texture_row_start = (y * size * 4) as usize;
+            assert_eq!(
+                &atlas_data[row_start..row_start + (size * 4) as usize],
+                &pixels[texture_row_start..texture_row_start + (size * 4) as usize]
+            );
+        }
+    }
+
+    #[test]
+    fn test_atlas_grid_grows_past_16_textures() {
+        assert_eq!(tiles_per_row(1), 1);
+        assert_eq!(tiles_per_row(16), 4);
+        assert_eq!(tiles_per_row(17), 8);
+        assert_eq!(tiles_per_row(65), 16);
+        assert!(tiles_per_row(TextureId::ALL.len()).pow(2) as usize >= TextureId::ALL.len());
+        // Tiles are placed by discriminant, so ALL must list them in order
+        for (index, texture_id) in TextureId::ALL.into_iter().enumerate() {
+            assert_eq!(texture_id as usize, index);
+        }
+    }
 
-        eprintln!(
-            "Warning: Texture '{}' not found, using fallback color",
-            texture_name
+    #[test]
+    fn test_missing_textures_are_listed_and_filled() {
+        let mut loaded_textures = HashMap::new();
+        loaded_textures.insert(
+            "stone".to_string(),
+            ParsedTexture {
+                name: "Stone".to_string(),
+                width: 1,
+                height: 1,
+                pixels: vec![1, 2, 3, 255],
+            },
         );
+
+        let tiles_per_row = tiles_per_row(TextureId::ALL.len());
+        let (atlas_data, missing) = build_atlas_data(tiles_per_row, 2, &loaded_textures);
+        assert_eq!(missing.len(), TextureId::ALL.len() - 1);
+        assert!(!missing.contains(&"stone"));
+        assert!(missing.contains(&"gold_ore"));
+
+        assert_eq!(&atlas_data[0..4], &[1, 2, 3, 255]);
+        // Dirt is the second tile in the top row
+        assert_eq!(&atlas_data[8..12], &fallback_color(TextureId::Dirt));
+    }
+
+    #[test]
+    fn test_tile_size_must_be_a_power_of_two() {
+        assert_eq!(validate_tile_size(32), Ok(32));
+        assert!(validate_tile_size(24).is_err());
+        assert!(validate_tile_size(0).is_err());
+        assert!(validate_tile_size(MAX_TILE_SIZE * 2).is_err());
+    }
+
+    #[test]
+    fn test_mips_average_within_each_tile() {
+        // Two 2x2 tiles side by side: opaque white, and half-transparent black beside
+        // fully transparent pixels with a red color that must not show through
+        let white = [255, 255, 255, 255];
+        let dark = [0, 0, 0, 255];
+        let hidden_red = [255, 0, 0, 0];
+        let rows = [
+            [white, white, dark, hidden_red],
+            [white, white, hidden_red, dark],
+            [white, white, white, white],
+            [white, white, white, white],
+        ];
+        let data: Vec<u8> = rows.iter().flatten().flatten().copied().collect();
+
+        let mip = downsample(&data, 4);
+        assert_eq!(&mip[0..4], &[255, 255, 255, 255]);
+        assert_eq!(&mip[4..8], &[0, 0, 0, 128]);
+        assert_eq!(mip.len(), 2 * 2 * 4);
     }
 }
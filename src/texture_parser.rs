@@ -1,5 +1,5 @@
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs;
 use std::path::Path;
 use toml;
@@ -21,7 +21,7 @@ struct TextureInfo {
 
 #[derive(Debug, Deserialize)]
 struct PixelData {
-    data: String,
+    data: toml::Spanned<String>,
 }
 
 /// Represents a parsed texture with RGBA pixel data
@@ -33,6 +33,40 @@ pub struct ParsedTexture {
     pub pixels: Vec<u8>, // RGBA format
 }
 
+impl ParsedTexture {
+    /// Copy of the texture at a different size, nearest-neighbor so pixel art stays sharp
+    pub fn scaled_to(&self, width: u32, height: u32) -> ParsedTexture {
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let source_x = x * self.width / width;
+                let source_y = y * self.height / height;
+                let index = ((source_y * self.width + source_x) * 4) as usize;
+                pixels.extend_from_slice(&self.pixels[index..index + 4]);
+            }
+        }
+        ParsedTexture {
+            name: self.name.clone(),
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+/// Scale a block texture to the atlas tile size. Returns a warning describing the
+/// mismatch when it had to be scaled.
+fn fit_to_tile(texture: ParsedTexture, tile_size: u32) -> (ParsedTexture, Option<String>) {
+    if texture.width == tile_size && texture.height == tile_size {
+        return (texture, None);
+    }
+    let warning = format!(
+        "texture is {}x{} but atlas tiles are {}x{}, scaling it to fit",
+        texture.width, texture.height, tile_size, tile_size
+    );
+    (texture.scaled_to(tile_size, tile_size), Some(warning))
+}
+
 /// Color palette entry
 #[derive(Debug, Clone)]
 struct PaletteEntry {
@@ -42,13 +76,30 @@ struct PaletteEntry {
     a: u8,
 }
 
-/// Parses a single .toml texture file
+/// Parses a single .toml texture file.
+/// Errors are prefixed with the file path; recoverable problems are printed as warnings.
 pub fn parse_texture_file<P: AsRef<Path>>(path: P) -> Result<ParsedTexture, String> {
-    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let path = path.as_ref();
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("{}: Failed to read file: {}", path.display(), e))?;
+
+    let (texture, warnings) =
+        parse_texture_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+    for warning in warnings {
+        eprintln!("Warning: {}: {}", path.display(), warning);
+    }
+
+    Ok(texture)
+}
+
+/// Parses texture TOML content. Returns the texture along with warnings about
+/// problems that were worked around (bad palette colors, unknown pixel characters).
+fn parse_texture_str(content: &str) -> Result<(ParsedTexture, Vec<String>), String> {
+    let mut warnings = Vec::new();
 
     // Parse TOML content
     let texture_toml: TextureToml =
-        toml::from_str(&content).map_err(|e| format!("Failed to parse TOML: {}", e))?;
+        toml::from_str(content).map_err(|e| format!("Failed to parse TOML: {}", e))?;
 
     let width = texture_toml.texture.size[0];
     let height = texture_toml.texture.size[1];
@@ -57,56 +108,45 @@ pub fn parse_texture_file<P: AsRef<Path>>(path: P) -> Result<ParsedTexture, Stri
     // Build palette from TOML
     let mut palette: HashMap<char, PaletteEntry> = HashMap::new();
     for (key_str, color_str) in texture_toml.palette {
-        if let Some(key_char) = key_str.chars().next() {
-            if color_str == "transparent" {
-                palette.insert(
-                    key_char,
-                    PaletteEntry {
-                        r: 0,
-                        g: 0,
-                        b: 0,
-                        a: 0,
-                    },
-                );
-            } else if color_str.starts_with('#') {
-                let hex = &color_str[1..];
-                match hex.len() {
-                    6 => {
-                        // #rrggbb format
-                        if let (Ok(r), Ok(g), Ok(b)) = (
-                            u8::from_str_radix(&hex[0..2], 16),
-                            u8::from_str_radix(&hex[2..4], 16),
-                            u8::from_str_radix(&hex[4..6], 16),
-                        ) {
-                            palette.insert(key_char, PaletteEntry { r, g, b, a: 255 });
-                        }
-                    }
-                    8 => {
-                        // #rrggbbaa format
-                        if let (Ok(r), Ok(g), Ok(b), Ok(a)) = (
-                            u8::from_str_radix(&hex[0..2], 16),
-                            u8::from_str_radix(&hex[2..4], 16),
-                            u8::from_str_radix(&hex[4..6], 16),
-                            u8::from_str_radix(&hex[6..8], 16),
-                        ) {
-                            palette.insert(key_char, PaletteEntry { r, g, b, a });
-                        }
-                    }
-                    _ => {
-                        // Invalid hex color length, skip
-                    }
-                }
+        let Some(key_char) = key_str.chars().next() else {
+            warnings.push(format!(
+                "palette entry with an empty key (color '{}') skipped",
+                color_str
+            ));
+            continue;
+        };
+
+        match parse_palette_color(&color_str) {
+            Some(entry) => {
+                palette.insert(key_char, entry);
             }
+            None => warnings.push(format!(
+                "palette key '{}' has invalid color '{}', expected #rrggbb, #rrggbbaa or \"transparent\"",
+                key_str, color_str
+            )),
         }
     }
 
-    // Parse pixel data from multi-line string
-    let pixel_lines: Vec<&str> = texture_toml
+    // Line in the file where the pixel data starts, so row errors can point at it.
+    // A newline right after the opening quotes isn't part of the string.
+    let data_span = texture_toml.pixels.data.span();
+    let opening_line = content[..data_span.start].matches('\n').count() + 1;
+    let after_quotes = content[data_span.start..].trim_start_matches(['\'', '"']);
+    let first_data_line = if after_quotes.starts_with('\n') || after_quotes.starts_with("\r\n") {
+        opening_line + 1
+    } else {
+        opening_line
+    };
+
+    // Parse pixel data from multi-line string, remembering each row's line number
+    let pixel_lines: Vec<(usize, &str)> = texture_toml
         .pixels
         .data
+        .get_ref()
         .lines()
-        .map(|line| line.trim())
-        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(index, line)| (first_data_line + index, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
         .collect();
 
     // Validate dimensions
@@ -116,7 +156,8 @@ pub fn parse_texture_file<P: AsRef<Path>>(path: P) -> Result<ParsedTexture, Stri
 
     if pixel_lines.len() != height as usize {
         return Err(format!(
-            "Expected {} pixel rows, found {}",
+            "line {}: Expected {} pixel rows, found {}",
+            first_data_line,
             height,
             pixel_lines.len()
         ));
@@ -124,12 +165,14 @@ pub fn parse_texture_file<P: AsRef<Path>>(path: P) -> Result<ParsedTexture, Stri
 
     // Convert pixel characters to RGBA data
     let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    let mut unknown_chars = BTreeSet::new();
 
-    for (row_index, line) in pixel_lines.iter().enumerate() {
+    for (row_index, (line_number, line)) in pixel_lines.iter().enumerate() {
         let chars: Vec<char> = line.chars().collect();
         if chars.len() != width as usize {
             return Err(format!(
-                "Row {} has {} characters, expected {}",
+                "line {}: Row {} has {} characters, expected {}",
+                line_number,
                 row_index,
                 chars.len(),
                 width
@@ -137,12 +180,10 @@ pub fn parse_texture_file<P: AsRef<Path>>(path: P) -> Result<ParsedTexture, Stri
         }
 
         for &ch in &chars {
-            let color = palette.get(&ch).unwrap_or(&PaletteEntry {
-                r: 255,
-                g: 0,
-                b: 255,
-                a: 255,
-            }); // Magenta for missing
+            let color = palette.get(&ch).unwrap_or_else(|| {
+                unknown_chars.insert(ch);
+                &MISSING_COLOR
+            });
             pixels.push(color.r);
             pixels.push(color.g);
             pixels.push(color.b);
@@ -150,21 +191,113 @@ pub fn parse_texture_file<P: AsRef<Path>>(path: P) -> Result<ParsedTexture, Stri
         }
     }
 
-    Ok(ParsedTexture {
-        name,
-        width,
-        height,
-        pixels,
-    })
+    if !unknown_chars.is_empty() {
+        let listed: Vec<String> = unknown_chars.iter().map(|ch| format!("'{}'", ch)).collect();
+        warnings.push(format!(
+            "pixel characters with no palette entry (drawn magenta): {}",
+            listed.join(", ")
+        ));
+    }
+
+    Ok((
+        ParsedTexture {
+            name,
+            width,
+            height,
+            pixels,
+        },
+        warnings,
+    ))
+}
+
+/// Magenta for pixels whose character has no palette entry
+const MISSING_COLOR: PaletteEntry = PaletteEntry {
+    r: 255,
+    g: 0,
+    b: 255,
+    a: 255,
+};
+
+/// Parse a palette color: "transparent", "#rrggbb" or "#rrggbbaa"
+fn parse_palette_color(color_str: &str) -> Option<PaletteEntry> {
+    if color_str == "transparent" {
+        return Some(PaletteEntry {
+            r: 0,
+            g: 0,
+            b: 0,
+            a: 0,
+        });
+    }
+
+    let hex = color_str.strip_prefix('#')?;
+    if !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+
+    match hex.len() {
+        // #rrggbb format
+        6 => Some(PaletteEntry {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+            a: 255,
+        }),
+        // #rrggbbaa format
+        8 => Some(PaletteEntry {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+            a: channel(6)?,
+        }),
+        _ => None,
+    }
 }
 
-/// Load all texture files from the textures directory
-pub fn load_all_textures() -> Result<HashMap<String, ParsedTexture>, String> {
+/// Block textures with a warning for each one that had to be scaled
+pub type LoadedTextures = (HashMap<String, ParsedTexture>, Vec<String>);
+
+/// Load all block textures from the textures directory, scaled to `tile_size`
+pub fn load_all_textures(tile_size: u32) -> Result<LoadedTextures, String> {
+    load_block_textures_from_dir("textures", tile_size)
+}
+
+/// Load every .toml texture in a directory like `load_textures_from_dir`, scaling any
+/// that aren't `tile_size` pixels square. Each of those gets a warning naming the file,
+/// returned for the caller to report.
+pub fn load_block_textures_from_dir<P: AsRef<Path>>(
+    dir: P,
+    tile_size: u32,
+) -> Result<LoadedTextures, String> {
+    let dir = dir.as_ref();
+    let mut warnings = Vec::new();
+    let textures = load_textures_from_dir(dir)?
+        .into_iter()
+        .map(|(texture_name, texture)| {
+            let (texture, warning) = fit_to_tile(texture, tile_size);
+            if let Some(warning) = warning {
+                let path = dir.join(format!("{}.toml", texture_name));
+                warnings.push(format!("{}: {}", path.display(), warning));
+            }
+            (texture_name, texture)
+        })
+        .collect();
+    warnings.sort();
+    Ok((textures, warnings))
+}
+
+/// Load every .toml texture in a directory, keyed by file stem
+pub fn load_textures_from_dir<P: AsRef<Path>>(
+    dir: P,
+) -> Result<HashMap<String, ParsedTexture>, String> {
     let mut textures = HashMap::new();
 
-    let textures_dir = Path::new("textures");
+    let textures_dir = dir.as_ref();
     if !textures_dir.exists() {
-        return Err("Textures directory not found".to_string());
+        return Err(format!(
+            "Textures directory {} not found",
+            textures_dir.display()
+        ));
     }
 
     let entries = fs::read_dir(textures_dir)
@@ -217,4 +350,99 @@ pixels:
         // We'd need to create a temporary file for this test
         // For now, this demonstrates the expected functionality
     }
+
+    const TEXTURE: &str = r##"[texture]
+name = "Test"
+description = "Test texture"
+size = [2, 2]
+
+[palette]
+"." = "#FF0000"
+"#" = "#00GG00"
+
+[pixels]
+data = '''
+.#
+x.
+'''
+"##;
+
+    #[test]
+    fn test_bad_palette_and_unknown_chars_warn_but_still_load() {
+        let (texture, warnings) = parse_texture_str(TEXTURE).unwrap();
+
+        assert_eq!(texture.pixels.len(), 2 * 2 * 4);
+        assert_eq!(&texture.pixels[0..4], &[255, 0, 0, 255]);
+        // Both the bad color and the unknown char fall back to magenta
+        assert_eq!(&texture.pixels[4..8], &[255, 0, 255, 255]);
+        assert_eq!(&texture.pixels[8..12], &[255, 0, 255, 255]);
+
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("'#'") && warnings[0].contains("#00GG00"));
+        assert!(warnings[1].contains("'#'") && warnings[1].contains("'x'"));
+    }
+
+    /// Texture TOML `size` pixels square, with a different color in every column
+    fn striped_texture(size: u32) -> String {
+        let keys: Vec<char> = "0123456789".chars().take(size as usize).collect();
+        let palette: String = keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| format!("\"{}\" = \"#{:02X}0000\"\n", key, i * 20))
+            .collect();
+        let row: String = keys.iter().collect();
+        let rows = vec![row; size as usize].join("\n");
+        format!(
+            "[texture]\nname = \"Stripes\"\ndescription = \"\"\nsize = [{size}, {size}]\n\n[palette]\n{palette}\n[pixels]\ndata = '''\n{rows}\n'''\n"
+        )
+    }
+
+    #[test]
+    fn test_textures_are_scaled_to_the_tile_size() {
+        let red_of_column = |texture: &ParsedTexture, x: u32| texture.pixels[(x * 4) as usize];
+
+        let (small, _) = parse_texture_str(&striped_texture(2)).unwrap();
+        let (small, warning) = fit_to_tile(small, 4);
+        assert!(warning.unwrap().contains("2x2 but atlas tiles are 4x4"));
+        assert_eq!(
+            (small.width, small.height, small.pixels.len()),
+            (4, 4, 4 * 4 * 4)
+        );
+        // Each source column covers two columns
+        let columns: Vec<u8> = (0..4).map(|x| red_of_column(&small, x)).collect();
+        assert_eq!(columns, vec![0, 0, 20, 20]);
+
+        let (large, _) = parse_texture_str(&striped_texture(8)).unwrap();
+        let (large, warning) = fit_to_tile(large, 4);
+        assert!(warning.unwrap().contains("8x8"));
+        let columns: Vec<u8> = (0..4).map(|x| red_of_column(&large, x)).collect();
+        assert_eq!(columns, vec![0, 40, 80, 120]);
+
+        let (exact, _) = parse_texture_str(&striped_texture(4)).unwrap();
+        assert!(fit_to_tile(exact, 4).1.is_none());
+    }
+
+    #[test]
+    fn test_scaling_warnings_are_returned_per_file() {
+        let dir = std::env::temp_dir().join(format!("rustcraft_textures_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("small.toml"), striped_texture(2)).unwrap();
+        fs::write(dir.join("exact.toml"), striped_texture(4)).unwrap();
+
+        let result = load_block_textures_from_dir(&dir, 4);
+        fs::remove_dir_all(&dir).unwrap();
+        let (textures, warnings) = result.unwrap();
+
+        assert_eq!(textures.len(), 2);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("small.toml: texture is 2x2"));
+    }
+
+    #[test]
+    fn test_row_length_error_reports_file_line() {
+        let content = TEXTURE.replace("x.\n", "x..\n");
+        let err = parse_texture_str(&content).unwrap_err();
+
+        assert!(err.starts_with("line 13:"), "{}", err);
+    }
 }
@@ -0,0 +1,172 @@
+use crate::blocks::{BlockRegistry, BlockType};
+use std::time::Duration;
+
+/// Seconds to break one unit of hardness by hand
+const SECONDS_PER_HARDNESS: f32 = 0.5;
+/// Speed factor for using a tool on a block it isn't meant for
+const WRONG_TOOL_MULTIPLIER: f32 = 0.75;
+
+/// Family of tools, matched against a block's preferred tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolKind {
+    Pickaxe,
+    Axe,
+    Shovel,
+}
+
+/// Tool the player is holding. Tools have no durability yet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tool {
+    #[default]
+    Hand,
+    WoodPickaxe,
+    StonePickaxe,
+    WoodAxe,
+    StoneAxe,
+    WoodShovel,
+    StoneShovel,
+}
+
+impl Tool {
+    const ALL: [Tool; 7] = [
+        Tool::Hand,
+        Tool::WoodPickaxe,
+        Tool::StonePickaxe,
+        Tool::WoodAxe,
+        Tool::StoneAxe,
+        Tool::WoodShovel,
+        Tool::StoneShovel,
+    ];
+
+    /// The next tool in the cycle, wrapping back to the hand
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&tool| tool == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Hand => "Hand",
+            Tool::WoodPickaxe => "Wooden Pickaxe",
+            Tool::StonePickaxe => "Stone Pickaxe",
+            Tool::WoodAxe => "Wooden Axe",
+            Tool::StoneAxe => "Stone Axe",
+            Tool::WoodShovel => "Wooden Shovel",
+            Tool::StoneShovel => "Stone Shovel",
+        }
+    }
+
+    fn kind(self) -> Option<ToolKind> {
+        match self {
+            Tool::Hand => None,
+            Tool::WoodPickaxe | Tool::StonePickaxe => Some(ToolKind::Pickaxe),
+            Tool::WoodAxe | Tool::StoneAxe => Some(ToolKind::Axe),
+            Tool::WoodShovel | Tool::StoneShovel => Some(ToolKind::Shovel),
+        }
+    }
+
+    /// Speed-up when used on a block it's meant for
+    fn tier_speed(self) -> f32 {
+        match self {
+            Tool::Hand => 1.0,
+            Tool::WoodPickaxe | Tool::WoodAxe | Tool::WoodShovel => 2.0,
+            Tool::StonePickaxe | Tool::StoneAxe | Tool::StoneShovel => 4.0,
+        }
+    }
+}
+
+/// How much faster (> 1) or slower (< 1) a tool mines a block than the bare hand
+pub fn mining_multiplier(tool: Tool, block_type: BlockType, registry: &BlockRegistry) -> f32 {
+    let Some(kind) = tool.kind() else {
+        return 1.0;
+    };
+
+    match registry.preferred_tool(block_type) {
+        Some(preferred) if preferred == kind => tool.tier_speed(),
+        Some(_) => WRONG_TOOL_MULTIPLIER,
+        None => 1.0,
+    }
+}
+
+/// Time to break a block with a tool: hardness-based hand time divided by the multiplier
+pub fn break_time(tool: Tool, block_type: BlockType, registry: &BlockRegistry) -> Duration {
+    let seconds = registry.hardness(block_type) * SECONDS_PER_HARDNESS
+        / mining_multiplier(tool, block_type, registry);
+    Duration::from_secs_f32(seconds)
+}
+
+/// Mining progress on the targeted block while the break button is held
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BreakProgress {
+    target: Option<[i32; 3]>,
+    elapsed: Duration,
+    break_time: Duration,
+}
+
+impl BreakProgress {
+    /// Mine `target` for another `dt`. Switching to a different block starts over.
+    /// Returns true once the block has been mined for its whole `break_time`.
+    pub fn advance(&mut self, target: [i32; 3], dt: Duration, break_time: Duration) -> bool {
+        if self.target != Some(target) {
+            self.target = Some(target);
+            self.elapsed = Duration::ZERO;
+        }
+        self.elapsed += dt;
+        self.break_time = break_time;
+        self.elapsed >= break_time
+    }
+
+    /// Forget the current block, e.g. when the button is released
+    pub fn reset(&mut self) {
+        self.target = None;
+        self.elapsed = Duration::ZERO;
+    }
+
+    /// How far along breaking `block_pos` is, from 0.0 to 1.0
+    pub fn fraction(&self, block_pos: [i32; 3]) -> f32 {
+        if self.target != Some(block_pos) || self.break_time.is_zero() {
+            return 0.0;
+        }
+        (self.elapsed.as_secs_f32() / self.break_time.as_secs_f32()).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pickaxe_breaks_stone_faster_than_hand() {
+        let registry = BlockRegistry::new();
+
+        let by_hand = break_time(Tool::Hand, BlockType::Stone, &registry);
+        let by_pickaxe = break_time(Tool::WoodPickaxe, BlockType::Stone, &registry);
+        let by_shovel = break_time(Tool::WoodShovel, BlockType::Stone, &registry);
+
+        assert!(by_pickaxe < by_hand);
+        assert!(by_shovel > by_hand);
+        assert!(break_time(Tool::StonePickaxe, BlockType::Stone, &registry) < by_pickaxe);
+    }
+
+    #[test]
+    fn test_break_progress_restarts_on_a_new_target() {
+        let step = Duration::from_millis(100);
+        let break_time = Duration::from_millis(250);
+        let mut progress = BreakProgress::default();
+
+        assert!(!progress.advance([0, 5, 0], step, break_time));
+        assert!(!progress.advance([0, 5, 0], step, break_time));
+        assert!((progress.fraction([0, 5, 0]) - 0.8).abs() < 1e-6);
+
+        // Looking away loses the progress
+        assert!(!progress.advance([1, 5, 0], step, break_time));
+        assert_eq!(progress.fraction([0, 5, 0]), 0.0);
+        assert!(!progress.advance([1, 5, 0], step, break_time));
+        assert!(progress.advance([1, 5, 0], step, break_time));
+
+        progress.reset();
+        assert_eq!(progress.fraction([1, 5, 0]), 0.0);
+        // Blocks without hardness break at once
+        assert!(progress.advance([1, 5, 0], Duration::ZERO, Duration::ZERO));
+    }
+}
@@ -0,0 +1,33 @@
+/// Convert a pixel position (origin top-left, Y down) to NDC (-1..1, Y up).
+///
+/// UI is laid out in pixels and converted here so elements keep a fixed pixel size
+/// and their proportions at any aspect ratio. Renderers rebuild their geometry from
+/// `State::resize_ui` whenever the window size changes.
+pub fn pixels_to_ndc(px: f32, py: f32, win_w: u32, win_h: u32) -> [f32; 2] {
+    [px * 2.0 / win_w as f32 - 1.0, 1.0 - py * 2.0 / win_h as f32]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pixels_to_ndc_at_super_ultrawide_aspect() {
+        // 32:9
+        let (win_w, win_h) = (3840, 1080);
+
+        assert_eq!(pixels_to_ndc(0.0, 0.0, win_w, win_h), [-1.0, 1.0]);
+        assert_eq!(pixels_to_ndc(1920.0, 540.0, win_w, win_h), [0.0, 0.0]);
+        assert_eq!(pixels_to_ndc(3840.0, 1080.0, win_w, win_h), [1.0, -1.0]);
+
+        // A 100px square stays square on screen: its NDC extents differ by the aspect ratio
+        let top_left = pixels_to_ndc(1870.0, 490.0, win_w, win_h);
+        let bottom_right = pixels_to_ndc(1970.0, 590.0, win_w, win_h);
+        let ndc_w = bottom_right[0] - top_left[0];
+        let ndc_h = top_left[1] - bottom_right[1];
+
+        assert!((ndc_w * win_w as f32 / 2.0 - 100.0).abs() < 1e-3);
+        assert!((ndc_h * win_h as f32 / 2.0 - 100.0).abs() < 1e-3);
+        assert!((ndc_h / ndc_w - 32.0 / 9.0).abs() < 1e-4);
+    }
+}
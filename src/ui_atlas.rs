@@ -0,0 +1,390 @@
+use crate::texture_parser::{self, ParsedTexture};
+use std::collections::HashMap;
+
+/// Width of the UI atlas in pixels
+pub const UI_ATLAS_WIDTH: u32 = 256;
+/// Height of the UI atlas in pixels
+pub const UI_ATLAS_HEIGHT: u32 = 128;
+/// Size of one glyph cell in the font sheet, in pixels
+pub const GLYPH_CELL_SIZE: u32 = 16;
+/// Size of one UI sprite (slot frames), in pixels
+pub const SPRITE_SIZE: u32 = 32;
+
+/// Directory with optional .toml overrides for the generated UI art
+const UI_DIR: &str = "ui";
+
+// The font sheet covers ASCII 32 (space) to 95 (underscore), 16 glyphs per row.
+// Lowercase letters reuse the uppercase glyphs.
+const FIRST_GLYPH: u8 = b' ';
+const GLYPH_COUNT: u32 = 64;
+const GLYPHS_PER_ROW: u32 = UI_ATLAS_WIDTH / GLYPH_CELL_SIZE;
+const FONT_SHEET_HEIGHT: u32 = GLYPH_COUNT / GLYPHS_PER_ROW * GLYPH_CELL_SIZE;
+
+// Built-in glyphs are 5x7 bitmaps drawn at 2x, so they fill 10x14 of the 16px cell
+const GLYPH_SCALE: u32 = 2;
+const GLYPH_OFFSET: [u32; 2] = [3, 1];
+
+/// Slot border width in sprite pixels
+const FRAME_BORDER: u32 = 2;
+
+/// 5x7 bitmap font, one byte per row with bit 4 as the leftmost pixel
+#[rustfmt::skip]
+const FONT_5X7: [[u8; 7]; GLYPH_COUNT as usize] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // space
+    [0x04, 0x04, 0x04, 0x04, 0x04, 0x00, 0x04], // !
+    [0x0A, 0x0A, 0x0A, 0x00, 0x00, 0x00, 0x00], // "
+    [0x0A, 0x0A, 0x1F, 0x0A, 0x1F, 0x0A, 0x0A], // #
+    [0x04, 0x0F, 0x14, 0x0E, 0x05, 0x1E, 0x04], // $
+    [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03], // %
+    [0x0C, 0x12, 0x14, 0x08, 0x15, 0x12, 0x0D], // &
+    [0x0C, 0x04, 0x08, 0x00, 0x00, 0x00, 0x00], // '
+    [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02], // (
+    [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08], // )
+    [0x00, 0x04, 0x15, 0x0E, 0x15, 0x04, 0x00], // *
+    [0x00, 0x04, 0x04, 0x1F, 0x04, 0x04, 0x00], // +
+    [0x00, 0x00, 0x00, 0x00, 0x0C, 0x04, 0x08], // ,
+    [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00], // -
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C], // .
+    [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00], // /
+    [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E], // 0
+    [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E], // 1
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F], // 2
+    [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E], // 3
+    [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02], // 4
+    [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E], // 5
+    [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E], // 6
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08], // 7
+    [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E], // 8
+    [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C], // 9
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00], // :
+    [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x04, 0x08], // ;
+    [0x02, 0x04, 0x08, 0x10, 0x08, 0x04, 0x02], // <
+    [0x00, 0x00, 0x1F, 0x00, 0x1F, 0x00, 0x00], // =
+    [0x08, 0x04, 0x02, 0x01, 0x02, 0x04, 0x08], // >
+    [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // ?
+    [0x0E, 0x11, 0x01, 0x0D, 0x15, 0x15, 0x0E], // @
+    [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11], // A
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // B
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // C
+    [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C], // D
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // E
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // F
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // G
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // H
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // I
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // J
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // K
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // L
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // M
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // N
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // O
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // P
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // Q
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // R
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // S
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // T
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // U
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // V
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // W
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // X
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // Y
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // Z
+    [0x0E, 0x08, 0x08, 0x08, 0x08, 0x08, 0x0E], // [
+    [0x00, 0x10, 0x08, 0x04, 0x02, 0x01, 0x00], // \
+    [0x0E, 0x02, 0x02, 0x02, 0x02, 0x02, 0x0E], // ]
+    [0x04, 0x0A, 0x11, 0x00, 0x00, 0x00, 0x00], // ^
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F], // _
+];
+
+/// Non-glyph art in the UI atlas, laid out in a row below the font sheet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UiSprite {
+    SlotFrame,
+    SlotFrameSelected,
+}
+
+impl UiSprite {
+    pub const ALL: [UiSprite; 2] = [UiSprite::SlotFrame, UiSprite::SlotFrameSelected];
+
+    /// File stem of the override texture in `ui/`
+    pub fn name(self) -> &'static str {
+        match self {
+            UiSprite::SlotFrame => "slot_frame",
+            UiSprite::SlotFrameSelected => "slot_frame_selected",
+        }
+    }
+
+    /// Top-left pixel of the sprite in the atlas
+    fn origin(self) -> [u32; 2] {
+        [self as u32 * SPRITE_SIZE, FONT_SHEET_HEIGHT]
+    }
+}
+
+/// Atlas UV rectangle of a glyph as [u_min, v_min, u_max, v_max].
+/// Lowercase letters map to uppercase and unsupported characters to '?'.
+pub fn glyph_uv(c: char) -> [f32; 4] {
+    let index = glyph_index(c);
+    let x = index % GLYPHS_PER_ROW * GLYPH_CELL_SIZE;
+    let y = index / GLYPHS_PER_ROW * GLYPH_CELL_SIZE;
+    pixel_rect_to_uv(x, y, GLYPH_CELL_SIZE)
+}
+
+/// Atlas UV rectangle of a sprite as [u_min, v_min, u_max, v_max]
+pub fn sprite_uv(sprite: UiSprite) -> [f32; 4] {
+    let [x, y] = sprite.origin();
+    pixel_rect_to_uv(x, y, SPRITE_SIZE)
+}
+
+fn glyph_index(c: char) -> u32 {
+    let c = c.to_ascii_uppercase();
+    let code = c as u32;
+    let first = FIRST_GLYPH as u32;
+    if (first..first + GLYPH_COUNT).contains(&code) {
+        code - first
+    } else {
+        b'?' as u32 - first
+    }
+}
+
+fn pixel_rect_to_uv(x: u32, y: u32, size: u32) -> [f32; 4] {
+    let w = UI_ATLAS_WIDTH as f32;
+    let h = UI_ATLAS_HEIGHT as f32;
+    [
+        x as f32 / w,
+        y as f32 / h,
+        (x + size) as f32 / w,
+        (y + size) as f32 / h,
+    ]
+}
+
+/// Dedicated texture for UI art: a bitmap font sheet plus slot frame sprites.
+///
+/// Everything is generated procedurally, so it works without art assets. Any of
+/// `ui/font.toml`, `ui/slot_frame.toml` and `ui/slot_frame_selected.toml` (same format
+/// as block textures) replaces the generated art.
+pub struct UiAtlas {
+    pub texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    pub sampler: wgpu::Sampler,
+}
+
+impl UiAtlas {
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Self {
+        // The ui/ directory is optional, so a missing one isn't worth a warning
+        let overrides = texture_parser::load_textures_from_dir(UI_DIR).unwrap_or_default();
+        let atlas_data = build_ui_atlas_data(&overrides);
+
+        let size = wgpu::Extent3d {
+            width: UI_ATLAS_WIDTH,
+            height: UI_ATLAS_HEIGHT,
+            depth_or_array_layers: 1,
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            label: Some("UI Atlas"),
+            view_formats: &[],
+        });
+
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &atlas_data,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(UI_ATLAS_WIDTH * 4),
+                rows_per_image: Some(UI_ATLAS_HEIGHT),
+            },
+            size,
+        );
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest, // Keep glyph edges crisp
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        Self {
+            texture,
+            view,
+            sampler,
+        }
+    }
+}
+
+/// Build the RGBA pixels of the UI atlas, using `overrides` (keyed by name) where present
+fn build_ui_atlas_data(overrides: &HashMap<String, ParsedTexture>) -> Vec<u8> {
+    let mut atlas_data = vec![0u8; (UI_ATLAS_WIDTH * UI_ATLAS_HEIGHT * 4) as usize];
+
+    if let Some(font) = overrides.get("font") {
+        blit_scaled(
+            &mut atlas_data,
+            [0, 0],
+            [UI_ATLAS_WIDTH, FONT_SHEET_HEIGHT],
+            font,
+        );
+    } else {
+        draw_font_sheet(&mut atlas_data);
+    }
+
+    for sprite in UiSprite::ALL {
+        let origin = sprite.origin();
+        if let Some(texture) = overrides.get(sprite.name()) {
+            blit_scaled(&mut atlas_data, origin, [SPRITE_SIZE, SPRITE_SIZE], texture);
+        } else {
+            draw_sprite(&mut atlas_data, origin, sprite);
+        }
+    }
+
+    atlas_data
+}
+
+fn set_pixel(atlas_data: &mut [u8], x: u32, y: u32, rgba: [u8; 4]) {
+    let index = ((y * UI_ATLAS_WIDTH + x) * 4) as usize;
+    atlas_data[index..index + 4].copy_from_slice(&rgba);
+}
+
+fn draw_font_sheet(atlas_data: &mut [u8]) {
+    for (index, rows) in FONT_5X7.iter().enumerate() {
+        let cell_x = index as u32 % GLYPHS_PER_ROW * GLYPH_CELL_SIZE;
+        let cell_y = index as u32 / GLYPHS_PER_ROW * GLYPH_CELL_SIZE;
+
+        for (row, bits) in rows.iter().enumerate() {
+            for column in 0..5 {
+                if bits & (0x10 >> column) == 0 {
+                    continue;
+                }
+                // White glyphs on transparent, so text can be tinted in the shader
+                for sy in 0..GLYPH_SCALE {
+                    for sx in 0..GLYPH_SCALE {
+                        set_pixel(
+                            atlas_data,
+                            cell_x + GLYPH_OFFSET[0] + column * GLYPH_SCALE + sx,
+                            cell_y + GLYPH_OFFSET[1] + row as u32 * GLYPH_SCALE + sy,
+                            [255, 255, 255, 255],
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn draw_sprite(atlas_data: &mut [u8], origin: [u32; 2], sprite: UiSprite) {
+    for y in 0..SPRITE_SIZE {
+        for x in 0..SPRITE_SIZE {
+            let on_border = x < FRAME_BORDER
+                || y < FRAME_BORDER
+                || x >= SPRITE_SIZE - FRAME_BORDER
+                || y >= SPRITE_SIZE - FRAME_BORDER;
+
+            let rgba = match sprite {
+                UiSprite::SlotFrame if on_border => [102, 102, 102, 178],
+                UiSprite::SlotFrameSelected if on_border => [255, 255, 255, 230],
+                _ => continue,
+            };
+            set_pixel(atlas_data, origin[0] + x, origin[1] + y, rgba);
+        }
+    }
+}
+
+// Nearest-neighbor copy of a texture into a region of the atlas
+fn blit_scaled(atlas_data: &mut [u8], origin: [u32; 2], size: [u32; 2], texture: &ParsedTexture) {
+    if texture.width != size[0] || texture.height != size[1] {
+        eprintln!(
+            "Warning: UI texture '{}' is {}x{} but its atlas slot is {}x{}, scaling it to fit",
+            texture.name, texture.width, texture.height, size[0], size[1]
+        );
+    }
+
+    for y in 0..size[1] {
+        for x in 0..size[0] {
+            let texture_x = x * texture.width / size[0];
+            let texture_y = y * texture.height / size[1];
+            let texture_index = ((texture_y * texture.width + texture_x) * 4) as usize;
+            if texture_index + 3 < texture.pixels.len() {
+                let mut rgba = [0u8; 4];
+                rgba.copy_from_slice(&texture.pixels[texture_index..texture_index + 4]);
+                set_pixel(atlas_data, origin[0] + x, origin[1] + y, rgba);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alpha_at(atlas_data: &[u8], x: u32, y: u32) -> u8 {
+        atlas_data[((y * UI_ATLAS_WIDTH + x) * 4 + 3) as usize]
+    }
+
+    #[test]
+    fn test_generated_atlas_has_glyphs_and_frames() {
+        let atlas_data = build_ui_atlas_data(&HashMap::new());
+
+        // Lowercase shares the uppercase glyph; unknown characters fall back to '?'
+        assert_eq!(glyph_uv('a'), glyph_uv('A'));
+        assert_eq!(glyph_uv('~'), glyph_uv('?'));
+        assert_ne!(glyph_uv('A'), glyph_uv('B'));
+
+        // 'I' has a full-width top bar and a hollow corner below it
+        let [u, v, _, _] = glyph_uv('I');
+        let cell_x = (u * UI_ATLAS_WIDTH as f32) as u32;
+        let cell_y = (v * UI_ATLAS_HEIGHT as f32) as u32;
+        let pixel = |column: u32, row: u32| {
+            alpha_at(
+                &atlas_data,
+                cell_x + GLYPH_OFFSET[0] + column * GLYPH_SCALE,
+                cell_y + GLYPH_OFFSET[1] + row * GLYPH_SCALE,
+            )
+        };
+        assert_eq!(pixel(2, 0), 255);
+        assert_eq!(pixel(2, 3), 255);
+        assert_eq!(pixel(0, 3), 0);
+
+        // Frames are opaque at the edge and see-through in the middle
+        let [x, y] = UiSprite::SlotFrame.origin();
+        assert!(alpha_at(&atlas_data, x, y) > 0);
+        assert_eq!(
+            alpha_at(&atlas_data, x + SPRITE_SIZE / 2, y + SPRITE_SIZE / 2),
+            0
+        );
+        let [u_min, v_min, u_max, v_max] = sprite_uv(UiSprite::SlotFrameSelected);
+        assert!(u_min < u_max && v_min < v_max && v_max <= 1.0);
+    }
+
+    #[test]
+    fn test_override_replaces_generated_sprite() {
+        let mut overrides = HashMap::new();
+        overrides.insert(
+            "slot_frame".to_string(),
+            ParsedTexture {
+                name: "Slot Frame".to_string(),
+                width: 1,
+                height: 1,
+                pixels: vec![10, 20, 30, 40],
+            },
+        );
+
+        let atlas_data = build_ui_atlas_data(&overrides);
+        let [x, y] = UiSprite::SlotFrame.origin();
+        let index = (((y + 5) * UI_ATLAS_WIDTH + x + 7) * 4) as usize;
+        assert_eq!(&atlas_data[index..index + 4], &[10, 20, 30, 40]);
+    }
+}
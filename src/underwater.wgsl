@@ -0,0 +1,28 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+}
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 0.0, 1.0);
+    return out;
+}
+
+struct UnderwaterUniform {
+    // rgb tint, alpha is how strongly it covers the scene
+    tint: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> underwater: UnderwaterUniform;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return underwater.tint;
+}
@@ -0,0 +1,37 @@
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+}
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position, 0.0, 1.0);
+    out.tex_coords = model.tex_coords;
+    return out;
+}
+
+struct VignetteUniform {
+    strength: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> vignette: VignetteUniform;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // 0 at the center, ~0.7 at the middle of each edge, 1 in the corners.
+    // Working in UV space stretches the falloff with the window, so it fits any size.
+    let offset = (in.tex_coords - vec2<f32>(0.5, 0.5)) * 2.0;
+    let dist = length(offset) / sqrt(2.0);
+
+    // Start late so the center of the screen and the hotbar stay mostly untouched
+    let darkness = smoothstep(0.55, 1.0, dist) * vignette.strength;
+
+    return vec4<f32>(0.0, 0.0, 0.0, darkness);
+}
@@ -7,6 +7,14 @@ pub struct Vertex {
     pub tex_coords: [f32; 2],
     pub normal: [f32; 3],
     pub texture_id: u32,
+    /// Ambient occlusion at this corner, 1.0 when nothing blocks it
+    pub ao: f32,
+    /// Lowest brightness of the face, from the block's `emission`
+    pub emission: f32,
+    /// Block light reaching the face from glowing blocks nearby, 0.0-1.0
+    pub block_light: f32,
+    /// Share of the sunlight reaching the face past the blocks above it, 0.0-1.0
+    pub skylight: f32,
 }
 
 impl Vertex {
@@ -43,6 +51,49 @@ impl Vertex {
                     shader_location: 3,
                     format: wgpu::VertexFormat::Uint32,
                 },
+                // Ambient occlusion
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 3]>()
+                        + std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<[f32; 3]>()
+                        + std::mem::size_of::<u32>())
+                        as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                // Emission
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 3]>()
+                        + std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<[f32; 3]>()
+                        + std::mem::size_of::<u32>()
+                        + std::mem::size_of::<f32>())
+                        as wgpu::BufferAddress,
+                    shader_location: 5,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                // Block light
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 3]>()
+                        + std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<[f32; 3]>()
+                        + std::mem::size_of::<u32>()
+                        + std::mem::size_of::<f32>() * 2)
+                        as wgpu::BufferAddress,
+                    shader_location: 6,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                // Skylight
+                wgpu::VertexAttribute {
+                    offset: (std::mem::size_of::<[f32; 3]>()
+                        + std::mem::size_of::<[f32; 2]>()
+                        + std::mem::size_of::<[f32; 3]>()
+                        + std::mem::size_of::<u32>()
+                        + std::mem::size_of::<f32>() * 3)
+                        as wgpu::BufferAddress,
+                    shader_location: 7,
+                    format: wgpu::VertexFormat::Float32,
+                },
             ],
         }
     }
@@ -62,24 +113,40 @@ pub fn create_cube_vertices_minecraft(
             tex_coords: [0.0, 1.0],
             normal: [0.0, 0.0, 1.0],
             texture_id: texture_ids.front,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x + 1.0, y, z + 1.0],
             tex_coords: [1.0, 1.0],
             normal: [0.0, 0.0, 1.0],
             texture_id: texture_ids.front,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x + 1.0, y + 1.0, z + 1.0],
             tex_coords: [1.0, 0.0],
             normal: [0.0, 0.0, 1.0],
             texture_id: texture_ids.front,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x, y + 1.0, z + 1.0],
             tex_coords: [0.0, 0.0],
             normal: [0.0, 0.0, 1.0],
             texture_id: texture_ids.front,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         // Back face (normal: -Z)
         Vertex {
@@ -87,24 +154,40 @@ pub fn create_cube_vertices_minecraft(
             tex_coords: [0.0, 1.0],
             normal: [0.0, 0.0, -1.0],
             texture_id: texture_ids.back,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x, y, z],
             tex_coords: [1.0, 1.0],
             normal: [0.0, 0.0, -1.0],
             texture_id: texture_ids.back,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x, y + 1.0, z],
             tex_coords: [1.0, 0.0],
             normal: [0.0, 0.0, -1.0],
             texture_id: texture_ids.back,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x + 1.0, y + 1.0, z],
             tex_coords: [0.0, 0.0],
             normal: [0.0, 0.0, -1.0],
             texture_id: texture_ids.back,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         // Left face (normal: -X)
         Vertex {
@@ -112,24 +195,40 @@ pub fn create_cube_vertices_minecraft(
             tex_coords: [0.0, 1.0],
             normal: [-1.0, 0.0, 0.0],
             texture_id: texture_ids.left,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x, y, z + 1.0],
             tex_coords: [1.0, 1.0],
             normal: [-1.0, 0.0, 0.0],
             texture_id: texture_ids.left,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x, y + 1.0, z + 1.0],
             tex_coords: [1.0, 0.0],
             normal: [-1.0, 0.0, 0.0],
             texture_id: texture_ids.left,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x, y + 1.0, z],
             tex_coords: [0.0, 0.0],
             normal: [-1.0, 0.0, 0.0],
             texture_id: texture_ids.left,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         // Right face (normal: +X)
         Vertex {
@@ -137,24 +236,40 @@ pub fn create_cube_vertices_minecraft(
             tex_coords: [0.0, 1.0],
             normal: [1.0, 0.0, 0.0],
             texture_id: texture_ids.right,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x + 1.0, y, z],
             tex_coords: [1.0, 1.0],
             normal: [1.0, 0.0, 0.0],
             texture_id: texture_ids.right,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x + 1.0, y + 1.0, z],
             tex_coords: [1.0, 0.0],
             normal: [1.0, 0.0, 0.0],
             texture_id: texture_ids.right,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x + 1.0, y + 1.0, z + 1.0],
             tex_coords: [0.0, 0.0],
             normal: [1.0, 0.0, 0.0],
             texture_id: texture_ids.right,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         // Top face (normal: +Y)
         Vertex {
@@ -162,24 +277,40 @@ pub fn create_cube_vertices_minecraft(
             tex_coords: [0.0, 0.0],
             normal: [0.0, 1.0, 0.0],
             texture_id: texture_ids.top,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x + 1.0, y + 1.0, z + 1.0],
             tex_coords: [1.0, 0.0],
             normal: [0.0, 1.0, 0.0],
             texture_id: texture_ids.top,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x + 1.0, y + 1.0, z],
             tex_coords: [1.0, 1.0],
             normal: [0.0, 1.0, 0.0],
             texture_id: texture_ids.top,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x, y + 1.0, z],
             tex_coords: [0.0, 1.0],
             normal: [0.0, 1.0, 0.0],
             texture_id: texture_ids.top,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         // Bottom face (normal: -Y)
         Vertex {
@@ -187,24 +318,40 @@ pub fn create_cube_vertices_minecraft(
             tex_coords: [0.0, 0.0],
             normal: [0.0, -1.0, 0.0],
             texture_id: texture_ids.bottom,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x + 1.0, y, z],
             tex_coords: [1.0, 0.0],
             normal: [0.0, -1.0, 0.0],
             texture_id: texture_ids.bottom,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x + 1.0, y, z + 1.0],
             tex_coords: [1.0, 1.0],
             normal: [0.0, -1.0, 0.0],
             texture_id: texture_ids.bottom,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
         Vertex {
             position: [x, y, z + 1.0],
             tex_coords: [0.0, 1.0],
             normal: [0.0, -1.0, 0.0],
             texture_id: texture_ids.bottom,
+            ao: 1.0,
+            emission: 0.0,
+            block_light: 0.0,
+            skylight: 1.0,
         },
     ]
 }
@@ -244,13 +391,172 @@ impl FaceTextures {
     }
 }
 
-// Generate only specific faces for optimization with proper UV mapping
+/// Deterministic quarter-turn texture rotation (0-3) for a block position,
+/// used to break up visible tiling on large areas of the same block
+pub fn texture_rotation_at(world_x: i32, world_y: i32, world_z: i32) -> u8 {
+    let mut h = (world_x as u32).wrapping_mul(0x9E37_79B1)
+        ^ (world_y as u32).wrapping_mul(0x85EB_CA77)
+        ^ (world_z as u32).wrapping_mul(0xC2B2_AE3D);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2C1B_3C6D);
+    h ^= h >> 12;
+    (h >> 30) as u8
+}
+
+/// Brightness for each ambient occlusion level, from a corner boxed in on both edges (0)
+/// to a fully open corner (3)
+const AO_FACTORS: [f32; 4] = [0.5, 0.7, 0.85, 1.0];
+
+/// Most a corner is darkened by `wide_ambient_occlusion`, with every block in the ring two
+/// steps out solid
+const WIDE_AO_STRENGTH: f32 = 0.25;
+
+/// Unit offset of each face's normal, in the face order of `create_cube_vertices_selective`
+pub const FACE_NORMALS: [[i32; 3]; 6] = [
+    [0, 0, 1],
+    [0, 0, -1],
+    [-1, 0, 0],
+    [1, 0, 0],
+    [0, 1, 0],
+    [0, -1, 0],
+];
+
+/// Corners of each face relative to the block's minimum corner, in vertex order
+const FACE_CORNERS: [[[i32; 3]; 4]; 6] = [
+    [[0, 0, 1], [1, 0, 1], [1, 1, 1], [0, 1, 1]],
+    [[1, 0, 0], [0, 0, 0], [0, 1, 0], [1, 1, 0]],
+    [[0, 0, 0], [0, 0, 1], [0, 1, 1], [0, 1, 0]],
+    [[1, 0, 1], [1, 0, 0], [1, 1, 0], [1, 1, 1]],
+    [[0, 1, 1], [1, 1, 1], [1, 1, 0], [0, 1, 0]],
+    [[0, 0, 0], [1, 0, 0], [1, 0, 1], [0, 0, 1]],
+];
+
+/// Ambient occlusion level (0-3) of each corner of a face, in vertex order.
+/// `is_solid(dx, dy, dz)` tells whether the block at that offset from this one blocks
+/// light. Each corner looks at the two blocks along its edges and the one diagonal to
+/// it, all in the layer in front of the face.
+pub fn face_ao_levels(face: usize, is_solid: impl Fn(i32, i32, i32) -> bool) -> [u8; 4] {
+    let normal = FACE_NORMALS[face];
+    let mut tangent_axes = (0..3).filter(|&axis| normal[axis] == 0);
+    let (axis_a, axis_b) = (tangent_axes.next().unwrap(), tangent_axes.next().unwrap());
+    let solid = |[dx, dy, dz]: [i32; 3]| is_solid(dx, dy, dz);
+
+    FACE_CORNERS[face].map(|corner| {
+        // Step from the block in front of the face toward the corner along each axis
+        let step = |axis: usize| if corner[axis] == 1 { 1 } else { -1 };
+        let mut side_a = normal;
+        side_a[axis_a] += step(axis_a);
+        let mut side_b = normal;
+        side_b[axis_b] += step(axis_b);
+        let mut diagonal = side_a;
+        diagonal[axis_b] += step(axis_b);
+
+        match (solid(side_a), solid(side_b)) {
+            // Boxed in on both edges, so the diagonal can't brighten the corner
+            (true, true) => 0,
+            (a, b) => 3 - a as u8 - b as u8 - solid(diagonal) as u8,
+        }
+    })
+}
+
+/// Brightness for an ambient occlusion level from `face_ao_levels`
+pub fn ao_factor(level: u8) -> f32 {
+    AO_FACTORS[level.min(3) as usize]
+}
+
+/// Darken the corners of faces built at `origin` further by the solid blocks in a wider
+/// ring around each one: the 12 cells in front of the face that surround the 2x2 checked
+/// by `face_ao_levels`, diagonals two blocks out included. This gives softer contact
+/// shadows, but reads four times as many blocks. Run it before `smooth_normals`, which
+/// replaces the face normals it uses to tell the faces apart. `is_solid` is the same
+/// lookup as for `create_cube_vertices_selective`.
+pub fn wide_ambient_occlusion(
+    vertices: &mut [Vertex],
+    origin: [f32; 3],
+    is_solid: impl Fn(i32, i32, i32) -> bool,
+) {
+    for vertex in vertices {
+        let normal = vertex.normal.map(|c| c.round() as i32);
+        let corner = [0, 1, 2].map(|axis| (vertex.position[axis] - origin[axis]).round() as i32);
+        let mut tangent_axes = (0..3).filter(|&axis| normal[axis] == 0);
+        let (axis_a, axis_b) = (tangent_axes.next().unwrap(), tangent_axes.next().unwrap());
+        // Toward the corner along each axis, as in `face_ao_levels`
+        let step = |axis: usize| if corner[axis] == 1 { 1 } else { -1 };
+        let (step_a, step_b) = (step(axis_a), step(axis_b));
+
+        let mut solid_cells = 0;
+        for offset_a in [-step_a, 0, step_a, 2 * step_a] {
+            for offset_b in [-step_b, 0, step_b, 2 * step_b] {
+                let inner =
+                    (offset_a == 0 || offset_a == step_a) && (offset_b == 0 || offset_b == step_b);
+                if inner {
+                    continue;
+                }
+                let mut cell = normal;
+                cell[axis_a] += offset_a;
+                cell[axis_b] += offset_b;
+                if is_solid(cell[0], cell[1], cell[2]) {
+                    solid_cells += 1;
+                }
+            }
+        }
+        vertex.ao *= 1.0 - WIDE_AO_STRENGTH * solid_cells as f32 / 12.0;
+    }
+}
+
+/// Replace the flat normals of faces built at `origin` with ones averaged over the open
+/// space around each corner. Every face meeting at a grid point then shares its normal,
+/// so lighting rounds off over hills and edges instead of stepping. Corners with open
+/// space all around (a flat plain) keep the face normal. `is_solid` is the same lookup
+/// as for `create_cube_vertices_selective`.
+pub fn smooth_normals(
+    vertices: &mut [Vertex],
+    origin: [f32; 3],
+    is_solid: impl Fn(i32, i32, i32) -> bool,
+) {
+    for vertex in vertices {
+        let corner = [0, 1, 2].map(|axis| (vertex.position[axis] - origin[axis]).round() as i32);
+
+        // Point from the corner into each of the 8 blocks around it that light can pass
+        let mut sum = [0.0f32; 3];
+        for cell in 0..8 {
+            let offset = [0, 1, 2].map(|axis| corner[axis] - 1 + (cell >> axis & 1));
+            if offset == [0, 0, 0] || is_solid(offset[0], offset[1], offset[2]) {
+                continue;
+            }
+            for axis in 0..3 {
+                sum[axis] += if offset[axis] == corner[axis] {
+                    0.5
+                } else {
+                    -0.5
+                };
+            }
+        }
+
+        let length = sum.iter().map(|c| c * c).sum::<f32>().sqrt();
+        let facing = (0..3)
+            .map(|axis| sum[axis] * vertex.normal[axis])
+            .sum::<f32>();
+        // Keep the flat normal where the open space doesn't point out of the face
+        if length > 1e-4 && facing > 0.0 {
+            vertex.normal = sum.map(|c| c / length);
+        }
+    }
+}
+
+// Generate only specific faces for optimization with proper UV mapping.
+// `texture_rotation` turns every face's texture by that many quarter turns.
+// UVs are tile-local 0..1; shader.wgsl wraps them and insets them from the tile edges.
+// `is_solid(dx, dy, dz)` reports neighbors that shade the corners (see `face_ao_levels`);
+// pass `|_, _, _| false` for a block floating on its own.
 pub fn create_cube_vertices_selective(
     x: f32,
     y: f32,
     z: f32,
     texture_ids: &FaceTextures,
     faces_to_render: &[usize],
+    texture_rotation: u8,
+    is_solid: impl Fn(i32, i32, i32) -> bool,
 ) -> Vec<Vertex> {
     let mut vertices = Vec::new();
 
@@ -327,13 +633,31 @@ pub fn create_cube_vertices_selective(
     for &face_index in faces_to_render {
         if face_index < face_definitions.len() {
             let (vertex_data, normal, texture_id) = &face_definitions[face_index];
+            let ao_levels = face_ao_levels(face_index, &is_solid);
+
+            // Quads are split along the 0-2 diagonal. Start from corner 1 instead when
+            // that splits along the brighter diagonal, so the occlusion interpolates
+            // evenly instead of showing a crease.
+            let first = if ao_levels[0] + ao_levels[2] < ao_levels[1] + ao_levels[3] {
+                1
+            } else {
+                0
+            };
 
-            for &(position, tex_coords) in vertex_data {
+            for i in (first..first + 4).map(|i| i % 4) {
+                let (position, _) = vertex_data[i];
+                // UVs run around the quad's corners, so shifting them rotates the texture
+                // while staying inside the tile
+                let (_, tex_coords) = vertex_data[(i + texture_rotation as usize) % 4];
                 vertices.push(Vertex {
                     position,
                     tex_coords,
                     normal: *normal,
                     texture_id: *texture_id,
+                    ao: ao_factor(ao_levels[i]),
+                    emission: 0.0,
+                    block_light: 0.0,
+                    skylight: 1.0,
                 });
             }
         }
@@ -378,3 +702,166 @@ pub fn create_cube_indices() -> Vec<u16> {
         20, 21, 22, 22, 23, 20,
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_texture_rotation_is_deterministic_and_varies_by_position() {
+        let a = texture_rotation_at(10, 20, 30);
+        let b = texture_rotation_at(11, 20, 30);
+
+        assert_eq!(a, texture_rotation_at(10, 20, 30));
+        assert_eq!(b, texture_rotation_at(11, 20, 30));
+        assert_ne!(a, b);
+        assert!(a < 4 && b < 4);
+    }
+
+    #[test]
+    fn test_rotated_uvs_stay_within_tile() {
+        let textures = FaceTextures::all_same(0);
+        let plain =
+            create_cube_vertices_selective(0.0, 0.0, 0.0, &textures, &[4], 0, |_, _, _| false);
+        let rotated =
+            create_cube_vertices_selective(0.0, 0.0, 0.0, &textures, &[4], 1, |_, _, _| false);
+
+        for (i, vertex) in rotated.iter().enumerate() {
+            assert_eq!(vertex.position, plain[i].position);
+            assert_eq!(vertex.tex_coords, plain[(i + 1) % 4].tex_coords);
+            assert!(vertex
+                .tex_coords
+                .iter()
+                .all(|&uv| (0.0..=1.0).contains(&uv)));
+        }
+    }
+
+    #[test]
+    fn test_uvs_stay_within_inset_tile_bounds() {
+        use crate::blocks::TextureId;
+        use crate::texture_atlas::{tiles_per_row, ATLAS_TILE_SIZE};
+
+        let tiles_per_row = tiles_per_row(TextureId::ALL.len());
+        let atlas_size = (tiles_per_row * ATLAS_TILE_SIZE) as f32;
+        let half_texel = 0.5 / atlas_size;
+        let tile_size = 1.0 / tiles_per_row as f32;
+
+        // Same mapping as get_atlas_coords in shader.wgsl
+        let inset = 0.5 / ATLAS_TILE_SIZE as f32;
+        let atlas_coord =
+            |tile: f32, uv: f32| (tile + inset + uv.fract() * (1.0 - 2.0 * inset)) * tile_size;
+
+        for texture_id in [0, 5, 13] {
+            let textures = FaceTextures::all_same(texture_id);
+            let vertices = create_cube_vertices_selective(
+                0.0,
+                0.0,
+                0.0,
+                &textures,
+                &[0, 1, 2, 3, 4, 5],
+                3,
+                |_, _, _| false,
+            );
+
+            let tile_x = (texture_id % tiles_per_row) as f32;
+            let tile_y = (texture_id / tiles_per_row) as f32;
+            // Vertex UVs, plus UVs past 1.0 as found inside greedy-meshed quads
+            let uvs = vertices
+                .iter()
+                .map(|vertex| vertex.tex_coords)
+                .chain([[2.5, 7.999], [15.0, 3.25]]);
+            for [u, v] in uvs {
+                let atlas_x = atlas_coord(tile_x, u);
+                let atlas_y = atlas_coord(tile_y, v);
+
+                let min_x = tile_x * tile_size + half_texel;
+                let min_y = tile_y * tile_size + half_texel;
+                assert!(
+                    atlas_x >= min_x - 1e-6
+                        && atlas_x <= min_x + tile_size - 2.0 * half_texel + 1e-6
+                );
+                assert!(
+                    atlas_y >= min_y - 1e-6
+                        && atlas_y <= min_y + tile_size - 2.0 * half_texel + 1e-6
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_corners_against_walls_are_darker() {
+        let textures = FaceTextures::all_same(0);
+        let all_faces = [0, 1, 2, 3, 4, 5];
+        let vertices =
+            create_cube_vertices_selective(0.0, 0.0, 0.0, &textures, &all_faces, 0, |_, _, _| {
+                false
+            });
+        for (face, corners) in vertices.chunks(4).enumerate() {
+            for (vertex, corner) in corners.iter().zip(FACE_CORNERS[face]) {
+                assert_eq!(vertex.position, corner.map(|c| c as f32));
+                assert_eq!(vertex.ao, 1.0);
+            }
+        }
+
+        // Top face of a block at the foot of a wall running along +X
+        let wall = |dx: i32, dy: i32, _dz: i32| dx == 1 && dy >= 0;
+        let top = create_cube_vertices_selective(0.0, 0.0, 0.0, &textures, &[4], 0, wall);
+        for vertex in &top {
+            let expected = if vertex.position[0] == 1.0 {
+                ao_factor(1)
+            } else {
+                1.0
+            };
+            assert_eq!(vertex.ao, expected);
+        }
+
+        // In an inside corner, the corner between both walls is darkest
+        let corner = |dx: i32, dy: i32, dz: i32| dy == 1 && (dx == 1 || dz == 1);
+        assert_eq!(face_ao_levels(4, corner), [1, 0, 1, 3]);
+    }
+
+    #[test]
+    fn test_wide_ao_reaches_walls_two_blocks_out() {
+        let textures = FaceTextures::all_same(0);
+        // Top face of a block with a wall two blocks away along +X
+        let wall = |dx: i32, dy: i32, _dz: i32| dx == 2 && dy >= 0;
+        let mut top = create_cube_vertices_selective(0.0, 0.0, 0.0, &textures, &[4], 0, wall);
+        assert!(top.iter().all(|vertex| vertex.ao == 1.0));
+
+        wide_ambient_occlusion(&mut top, [0.0; 3], wall);
+        for vertex in &top {
+            if vertex.position[0] == 1.0 {
+                // Four of the twelve ring cells in front of the corner are wall
+                assert_eq!(vertex.ao, 1.0 - WIDE_AO_STRENGTH * 4.0 / 12.0);
+            } else {
+                assert_eq!(vertex.ao, 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_smooth_normals_round_off_ledges_only() {
+        let textures = FaceTextures::all_same(0);
+        let mut top_and_side =
+            create_cube_vertices_selective(0.0, 0.0, 0.0, &textures, &[3, 4], 0, |_, _, _| false);
+
+        // Ground at this height toward -X, dropping away toward +X
+        let ledge = |dx: i32, dy: i32, _dz: i32| dy <= 0 && dx <= 0;
+        smooth_normals(&mut top_and_side, [0.0; 3], ledge);
+
+        for vertex in &top_and_side {
+            let [x, y, _] = vertex.position;
+            if x == 0.0 {
+                // Inner corners of the top face are on the plain
+                assert_eq!(vertex.normal, [0.0, 1.0, 0.0]);
+            } else if y == 1.0 {
+                // The rim leans out over the drop, the same for the top and the side
+                assert!(vertex.normal[0] > 0.1 && vertex.normal[1] > 0.1);
+                assert_eq!(vertex.normal[2], 0.0);
+            }
+        }
+        let rim = |v: &&Vertex| v.position == [1.0, 1.0, 0.0];
+        let mut rim_normals = top_and_side.iter().filter(rim).map(|v| v.normal);
+        assert_eq!(rim_normals.next(), rim_normals.next());
+    }
+}
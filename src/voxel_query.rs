@@ -0,0 +1,80 @@
+use crate::blocks::{BlockRegistry, BlockType};
+use crate::chunk::WORLD_HEIGHT;
+use std::collections::HashMap;
+
+/// Read access to the blocks of a world. Physics and raycasting only need this, so
+/// they can run against a small in-memory `BlockMap` as well as the full `World`.
+pub trait VoxelQuery {
+    /// Block at a world position. Air outside the world and in chunks that aren't loaded.
+    fn get_block(&self, x: i32, y: i32, z: i32) -> BlockType;
+
+    /// Whether the block at a position stops movement and rays (water doesn't)
+    fn is_solid(&self, x: i32, y: i32, z: i32) -> bool;
+}
+
+/// Y of the first free cell above the highest solid block in a column, or None when
+/// the column has no solid block (e.g. its chunk isn't loaded)
+pub fn find_ground_level(voxels: &dyn VoxelQuery, x: i32, z: i32) -> Option<i32> {
+    (0..WORLD_HEIGHT as i32)
+        .rev()
+        .find(|&y| voxels.is_solid(x, y, z))
+        .map(|y| y + 1)
+}
+
+/// Blocks stored in a plain map, with solidity from a block registry. Everything not
+/// set is air.
+#[derive(Default)]
+pub struct BlockMap {
+    blocks: HashMap<[i32; 3], BlockType>,
+    registry: BlockRegistry,
+}
+
+impl BlockMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, z: i32, block_type: BlockType) {
+        self.blocks.insert([x, y, z], block_type);
+    }
+
+    /// Fill the box between two corners, inclusive
+    pub fn fill(&mut self, min: [i32; 3], max: [i32; 3], block_type: BlockType) {
+        for x in min[0]..=max[0] {
+            for y in min[1]..=max[1] {
+                for z in min[2]..=max[2] {
+                    self.set(x, y, z, block_type);
+                }
+            }
+        }
+    }
+}
+
+impl VoxelQuery for BlockMap {
+    fn get_block(&self, x: i32, y: i32, z: i32) -> BlockType {
+        self.blocks
+            .get(&[x, y, z])
+            .copied()
+            .unwrap_or(BlockType::Air)
+    }
+
+    fn is_solid(&self, x: i32, y: i32, z: i32) -> bool {
+        self.registry.is_solid(self.get_block(x, y, z))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ground_level_skips_water_and_gaps() {
+        let mut map = BlockMap::new();
+        map.fill([0, 0, 0], [0, 5, 0], BlockType::Stone);
+        map.set(0, 9, 0, BlockType::Dirt);
+        map.set(0, 10, 0, BlockType::Water);
+
+        assert_eq!(find_ground_level(&map, 0, 0), Some(10));
+        assert_eq!(find_ground_level(&map, 1, 0), None);
+    }
+}
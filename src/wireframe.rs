@@ -1,10 +1,17 @@
 use bytemuck::{Pod, Zeroable};
+use cgmath::Vector3;
 use wgpu::util::DeviceExt;
 
+/// Faint white fill over the face the crosshair is on
+const FACE_HIGHLIGHT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.2];
+/// How far the face highlight floats off the block, on top of the depth bias
+const FACE_HIGHLIGHT_OFFSET: f32 = 0.002;
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 pub struct WireframeVertex {
     pub position: [f32; 3],
+    pub color: [f32; 4],
 }
 
 impl WireframeVertex {
@@ -12,20 +19,31 @@ impl WireframeVertex {
         wgpu::VertexBufferLayout {
             array_stride: std::mem::size_of::<WireframeVertex>() as wgpu::BufferAddress,
             step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[wgpu::VertexAttribute {
-                offset: 0,
-                shader_location: 0,
-                format: wgpu::VertexFormat::Float32x3,
-            }],
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
+            ],
         }
     }
 }
 
+/// Outline of the selected block, plus a translucent quad on the face the ray hit
 pub struct WireframeRenderer {
     render_pipeline: wgpu::RenderPipeline,
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
     num_indices: u32,
+    face_pipeline: wgpu::RenderPipeline,
+    face_vertex_buffer: wgpu::Buffer,
+    face_index_buffer: wgpu::Buffer,
 }
 
 impl WireframeRenderer {
@@ -35,7 +53,7 @@ impl WireframeRenderer {
         camera_bind_group_layout: &wgpu::BindGroupLayout,
     ) -> Self {
         // Create wireframe cube vertices (just corners)
-        let vertices = create_wireframe_cube_vertices(0.0, 0.0, 0.0);
+        let vertices = create_wireframe_cube_vertices(0.0, 0.0, 0.0, 0.0);
         let indices = create_wireframe_cube_indices();
 
         let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
@@ -62,49 +80,73 @@ impl WireframeRenderer {
                 push_constant_ranges: &[],
             });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Wireframe Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[WireframeVertex::desc()],
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: surface_format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::LineList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None, // Don't cull wireframe
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
-                depth_write_enabled: false, // Don't write to depth for wireframe
-                depth_compare: wgpu::CompareFunction::LessEqual, // Render wireframe with depth testing
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState {
-                    constant: -100, // Pull wireframe forward to avoid z-fighting
-                    slope_scale: -1.0,
-                    clamp: 0.0,
+        // Edges as lines, the face highlight as triangles; both pulled toward the camera
+        let create_pipeline = |label, topology| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&render_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: "vs_main",
+                    buffers: &[WireframeVertex::desc()],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: "fs_main",
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: surface_format,
+                        blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                }),
+                primitive: wgpu::PrimitiveState {
+                    topology,
+                    strip_index_format: None,
+                    front_face: wgpu::FrontFace::Ccw,
+                    cull_mode: None, // Don't cull wireframe
+                    polygon_mode: wgpu::PolygonMode::Fill,
+                    unclipped_depth: false,
+                    conservative: false,
                 },
-            }),
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview: None,
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled: false, // Don't write to depth for wireframe
+                    depth_compare: wgpu::CompareFunction::LessEqual, // Render wireframe with depth testing
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState {
+                        constant: -100, // Pull wireframe forward to avoid z-fighting
+                        slope_scale: -1.0,
+                        clamp: 0.0,
+                    },
+                }),
+                multisample: wgpu::MultisampleState {
+                    count: 1,
+                    mask: !0,
+                    alpha_to_coverage_enabled: false,
+                },
+                multiview: None,
+            })
+        };
+        let render_pipeline =
+            create_pipeline("Wireframe Pipeline", wgpu::PrimitiveTopology::LineList);
+        let face_pipeline = create_pipeline(
+            "Face Highlight Pipeline",
+            wgpu::PrimitiveTopology::TriangleList,
+        );
+
+        let face_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Face Highlight Vertex Buffer"),
+            contents: bytemuck::cast_slice(&create_face_highlight_vertices(
+                [0, 0, 0],
+                Vector3::new(0.0, 0.0, 0.0),
+            )),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let face_index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Face Highlight Index Buffer"),
+            contents: bytemuck::cast_slice(&[0u16, 1, 2, 0, 2, 3]),
+            usage: wgpu::BufferUsages::INDEX,
         });
 
         Self {
@@ -112,19 +154,45 @@ impl WireframeRenderer {
             vertex_buffer,
             index_buffer,
             num_indices: indices.len() as u32,
+            face_pipeline,
+            face_vertex_buffer,
+            face_index_buffer,
         }
     }
 
-    pub fn update_position(&self, queue: &wgpu::Queue, x: f32, y: f32, z: f32) {
-        let vertices = create_wireframe_cube_vertices(x, y, z);
+    /// Move the outline to a block. `break_progress` (0-1) fades it from red to white
+    /// while the block is being mined.
+    pub fn update_position(
+        &self,
+        queue: &wgpu::Queue,
+        x: f32,
+        y: f32,
+        z: f32,
+        break_progress: f32,
+    ) {
+        let vertices = create_wireframe_cube_vertices(x, y, z, break_progress);
         queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
     }
 
+    /// Move the face highlight to the side of `block_pos` facing along `face_normal`, the
+    /// side a new block would be placed against
+    pub fn update_face(&self, queue: &wgpu::Queue, block_pos: [i32; 3], face_normal: Vector3<f32>) {
+        let vertices = create_face_highlight_vertices(block_pos, face_normal);
+        queue.write_buffer(&self.face_vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+    }
+
+    /// Draw the face highlight, then the outline over it
     pub fn render<'a>(
         &'a self,
         render_pass: &mut wgpu::RenderPass<'a>,
         camera_bind_group: &'a wgpu::BindGroup,
     ) {
+        render_pass.set_pipeline(&self.face_pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.face_vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.face_index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        render_pass.draw_indexed(0..6, 0, 0..1);
+
         render_pass.set_pipeline(&self.render_pipeline);
         render_pass.set_bind_group(0, camera_bind_group, &[]);
         render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
@@ -133,38 +201,88 @@ impl WireframeRenderer {
     }
 }
 
-fn create_wireframe_cube_vertices(x: f32, y: f32, z: f32) -> Vec<WireframeVertex> {
+fn create_wireframe_cube_vertices(
+    x: f32,
+    y: f32,
+    z: f32,
+    break_progress: f32,
+) -> Vec<WireframeVertex> {
     let offset = 0.05; // Larger offset for better visibility
+
+    // Bright red for high contrast against terrain, turning white as the block cracks
+    let t = break_progress.clamp(0.0, 1.0);
+    let color = [1.0, 0.2 + 0.8 * t, 0.2 + 0.8 * t, 1.0];
     vec![
         // Bottom face corners
         WireframeVertex {
             position: [x - offset, y - offset, z - offset],
+            color,
         }, // 0
         WireframeVertex {
             position: [x + 1.0 + offset, y - offset, z - offset],
+            color,
         }, // 1
         WireframeVertex {
             position: [x + 1.0 + offset, y - offset, z + 1.0 + offset],
+            color,
         }, // 2
         WireframeVertex {
             position: [x - offset, y - offset, z + 1.0 + offset],
+            color,
         }, // 3
         // Top face corners
         WireframeVertex {
             position: [x - offset, y + 1.0 + offset, z - offset],
+            color,
         }, // 4
         WireframeVertex {
             position: [x + 1.0 + offset, y + 1.0 + offset, z - offset],
+            color,
         }, // 5
         WireframeVertex {
             position: [x + 1.0 + offset, y + 1.0 + offset, z + 1.0 + offset],
+            color,
         }, // 6
         WireframeVertex {
             position: [x - offset, y + 1.0 + offset, z + 1.0 + offset],
+            color,
         }, // 7
     ]
 }
 
+/// Quad covering one face of the block at `block_pos`, just off its surface. A zero
+/// normal (the ray started inside the block) gives a quad with no area, so nothing shows.
+fn create_face_highlight_vertices(
+    block_pos: [i32; 3],
+    face_normal: Vector3<f32>,
+) -> [WireframeVertex; 4] {
+    let normal = [face_normal.x, face_normal.y, face_normal.z];
+    let origin = block_pos.map(|coordinate| coordinate as f32);
+    let Some(axis) = (0..3).find(|&axis| normal[axis] != 0.0) else {
+        return [WireframeVertex {
+            position: origin,
+            color: FACE_HIGHLIGHT_COLOR,
+        }; 4];
+    };
+    let (u_axis, v_axis) = ((axis + 1) % 3, (axis + 2) % 3);
+    let plane = if normal[axis] > 0.0 {
+        1.0 + FACE_HIGHLIGHT_OFFSET
+    } else {
+        -FACE_HIGHLIGHT_OFFSET
+    };
+
+    [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].map(|(u, v)| {
+        let mut position = origin;
+        position[axis] += plane;
+        position[u_axis] += u;
+        position[v_axis] += v;
+        WireframeVertex {
+            position,
+            color: FACE_HIGHLIGHT_COLOR,
+        }
+    })
+}
+
 fn create_wireframe_cube_indices() -> Vec<u16> {
     vec![
         // Bottom face edges
@@ -173,3 +291,32 @@ fn create_wireframe_cube_indices() -> Vec<u16> {
         0, 4, 1, 5, 2, 6, 3, 7,
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_face_highlight_covers_the_hit_face() {
+        let quad = create_face_highlight_vertices([2, 5, -3], Vector3::new(0.0, 0.0, -1.0));
+        for vertex in &quad {
+            assert_eq!(vertex.position[2], -3.0 - FACE_HIGHLIGHT_OFFSET);
+        }
+        let corners: Vec<[f32; 2]> = quad
+            .iter()
+            .map(|vertex| [vertex.position[0], vertex.position[1]])
+            .collect();
+        assert_eq!(corners, [[2.0, 5.0], [3.0, 5.0], [3.0, 6.0], [2.0, 6.0]]);
+
+        let top = create_face_highlight_vertices([0, 0, 0], Vector3::new(0.0, 1.0, 0.0));
+        assert!(top
+            .iter()
+            .all(|vertex| vertex.position[1] == 1.0 + FACE_HIGHLIGHT_OFFSET));
+
+        // No face when the ray starts inside the block
+        let inside = create_face_highlight_vertices([1, 1, 1], Vector3::new(0.0, 0.0, 0.0));
+        assert!(inside
+            .iter()
+            .all(|vertex| vertex.position == inside[0].position));
+    }
+}
@@ -7,21 +7,24 @@ var<uniform> camera: CameraUniform;
 
 struct VertexInput {
     @location(0) position: vec3<f32>,
+    @location(1) color: vec4<f32>,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
 }
 
 @vertex
 fn vs_main(model: VertexInput) -> VertexOutput {
     var out: VertexOutput;
     out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
+    out.color = model.color;
     return out;
 }
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
-    // Bright red wireframe for high contrast against terrain
-    return vec4<f32>(1.0, 0.2, 0.2, 1.0);
+    // Outline red fading to white with the break progress, or the faint face highlight
+    return in.color;
 }
\ No newline at end of file
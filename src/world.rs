@@ -1,14 +1,68 @@
+use crate::aabb::Aabb;
 use crate::biome::BiomeManager;
-use crate::blocks::BlockType;
+use crate::block_light::{BlockLight, LightSource};
+use crate::blocks::{BlockRegistry, BlockType};
 use crate::chunk::{
-    Chunk, ChunkBlocks, ChunkData, ChunkGenerator, ChunkPos, CHUNK_SIZE, WORLD_HEIGHT,
+    border_column_heights, mesh_chunk_blocks, predicted_terrain_block, visible_faces, Chunk,
+    ChunkBlocks, ChunkData, ChunkGenerator, ChunkPos, MeshOptions, CHUNK_SIZE, WORLD_HEIGHT,
 };
-use crate::terrain::Terrain;
-use crate::voxel::{create_cube_indices_selective, create_cube_vertices_selective};
+use crate::frustum::Frustum;
+use crate::rng::{derive_seed, salt};
+use crate::structure_file::FileStructure;
+use crate::terrain::{Terrain, TerrainKind};
+use crate::terrain_modifier::TerrainModifier;
+use crate::voxel::{create_cube_vertices_selective, FACE_NORMALS};
+use crate::voxel_query::VoxelQuery;
 use cgmath::Point3;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
 
 const RENDER_DISTANCE: i32 = 4;
+/// Generator seeds of `World::new`; `World::with_seed` derives its own
+const TERRAIN_SEED: u32 = 42;
+const STRUCTURE_SEED: u32 = 7777;
+/// Chunk meshes turned into GPU buffers per `update` unless changed with
+/// `set_max_chunk_uploads_per_frame`
+pub const MAX_CHUNK_UPLOADS_PER_FRAME: usize = 2;
+/// How often falling blocks such as sand drop by one block
+const FALL_STEP_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Player edits in one chunk, keyed by chunk-local (x, z, y)
+type BlockEdits = HashMap<(u8, u8, u16), BlockType>;
+
+/// Predicted terrain fill height of columns around a chunk, keyed by world (x, z)
+type BorderHeights = HashMap<(i32, i32), usize>;
+
+/// Snapshot of world counters, e.g. for the debug overlay
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WorldStats {
+    /// Chunks generated since startup, including ones that were unloaded again
+    pub chunks_generated: u64,
+    pub chunks_loaded: usize,
+    /// Vertices across all loaded chunk meshes
+    pub total_vertices: u64,
+    pub blocks_placed: u64,
+    pub blocks_broken: u64,
+    pub average_generation_time: Duration,
+    /// Estimated GPU memory held by chunk vertex and index buffers
+    pub gpu_memory_bytes: u64,
+    /// Render distance in chunks, lower than the default when the memory budget is tight
+    pub render_distance: i32,
+}
+
+/// A block in the world together with its material, e.g. for the debug overlay
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockDescription {
+    pub block_type: BlockType,
+    pub name: &'static str,
+    pub hardness: f32,
+    pub is_solid: bool,
+    pub is_transparent: bool,
+    pub emission: f32,
+}
 
 pub struct World {
     chunks: HashMap<ChunkPos, Chunk>,
@@ -16,12 +70,72 @@ pub struct World {
     chunk_generator: ChunkGenerator,
     // Cache the actual block data for each chunk - this is the single source of truth
     chunk_blocks: HashMap<ChunkPos, ChunkBlocks>,
+    // Light spread from glowing blocks, for every chunk in `chunk_blocks`
+    block_light: HashMap<ChunkPos, BlockLight>,
+    // Predicted terrain fill height of the columns just outside each chunk in
+    // `chunk_blocks`, so border faces against a neighbor that isn't loaded yet can be culled
+    border_heights: HashMap<ChunkPos, BorderHeights>,
+    // Placed and broken blocks, kept when chunks unload and re-applied when they regenerate
+    block_edits: HashMap<ChunkPos, BlockEdits>,
+    // World (x, z) columns touched by an edit that may hold unsupported falling blocks
+    falling_columns: HashSet<(i32, i32)>,
+    last_fall_step: Instant,
+    // Holds falling blocks still, e.g. in photo mode; chunks keep loading and rendering
+    simulation_paused: bool,
+    block_registry: BlockRegistry,
+    chunks_generated: u64,
+    blocks_placed: u64,
+    blocks_broken: u64,
+    total_generation_time: Duration,
+    // Cap on chunks generated per `update`, so loading can be spread over several frames
+    max_chunks_per_update: Option<usize>,
+    // Meshes built but not uploaded yet, and how many of them `update` uploads
+    pending_meshes: HashMap<ChunkPos, ChunkData>,
+    // Chunks whose meshes edits made stale, re-meshed once each at the end of `update`
+    dirty_meshes: HashSet<ChunkPos>,
+    max_chunk_uploads_per_frame: Option<usize>,
+    // Optional cap on chunk mesh GPU memory; `render_distance` is whatever fits in it
+    memory_budget: Option<u64>,
+    // Distance asked for with `set_render_distance`, and the one in effect after the budget
+    target_render_distance: i32,
+    render_distance: i32,
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl World {
     pub fn new() -> Self {
-        let terrain = Terrain::new(42);
-        let chunk_generator = ChunkGenerator::new(7777);
+        Self::with_block_registry(BlockRegistry::new())
+    }
+
+    /// Create a world generated from `seed`. The terrain and structure seeds are both
+    /// derived from it with `rng::derive_seed`, so the same seed always gives the same
+    /// terrain and the same structures in the same places. Terrain modifiers such as the
+    /// boulders take the terrain seed (`Terrain::seed`), so they follow it too.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_generator_seeds(
+            BlockRegistry::new(),
+            derive_seed(seed, salt::TERRAIN_SEED),
+            derive_seed(seed, salt::STRUCTURE_SEED),
+        )
+    }
+
+    /// Create a world that uses its own set of block materials
+    pub fn with_block_registry(block_registry: BlockRegistry) -> Self {
+        Self::with_generator_seeds(block_registry, TERRAIN_SEED, STRUCTURE_SEED)
+    }
+
+    fn with_generator_seeds(
+        block_registry: BlockRegistry,
+        terrain_seed: u32,
+        structure_seed: u32,
+    ) -> Self {
+        let terrain = Terrain::new(terrain_seed);
+        let chunk_generator = ChunkGenerator::new(structure_seed);
         let chunks = HashMap::new();
 
         Self {
@@ -29,6 +143,24 @@ impl World {
             terrain,
             chunk_generator,
             chunk_blocks: HashMap::new(),
+            block_light: HashMap::new(),
+            border_heights: HashMap::new(),
+            block_edits: HashMap::new(),
+            falling_columns: HashSet::new(),
+            last_fall_step: Instant::now(),
+            simulation_paused: false,
+            block_registry,
+            chunks_generated: 0,
+            blocks_placed: 0,
+            blocks_broken: 0,
+            total_generation_time: Duration::ZERO,
+            max_chunks_per_update: None,
+            pending_meshes: HashMap::new(),
+            dirty_meshes: HashSet::new(),
+            max_chunk_uploads_per_frame: Some(MAX_CHUNK_UPLOADS_PER_FRAME),
+            memory_budget: None,
+            target_render_distance: RENDER_DISTANCE,
+            render_distance: RENDER_DISTANCE,
         }
     }
 
@@ -41,72 +173,345 @@ impl World {
         let camera_chunk_x = (camera_pos.x / CHUNK_SIZE as f32).floor() as i32;
         let camera_chunk_z = (camera_pos.z / CHUNK_SIZE as f32).floor() as i32;
 
-        // Collect all chunk positions that need generation
-        let mut chunks_to_generate = Vec::new();
-        for dx in -RENDER_DISTANCE..=RENDER_DISTANCE {
-            for dz in -RENDER_DISTANCE..=RENDER_DISTANCE {
-                let chunk_pos = ChunkPos {
-                    x: camera_chunk_x + dx,
-                    z: camera_chunk_z + dz,
-                };
+        let chunks_to_generate = self.missing_chunks(camera_chunk_x, camera_chunk_z);
+
+        // Generate chunk blocks in parallel
+        if !chunks_to_generate.is_empty() {
+            use rayon::prelude::*;
+            let chunk_block_results: Vec<(ChunkPos, ChunkBlocks, BlockLight, Duration)> =
+                chunks_to_generate
+                    .into_par_iter()
+                    .map(|chunk_pos| {
+                        let start = Instant::now();
+                        let mut block_array = self.chunk_generator.generate_chunk_blocks(
+                            chunk_pos,
+                            &self.terrain,
+                            biome_manager,
+                        );
+                        self.apply_block_edits(chunk_pos, &mut block_array);
+                        let block_light =
+                            BlockLight::new(chunk_pos, &block_array, &self.block_registry);
+                        (chunk_pos, block_array, block_light, start.elapsed())
+                    })
+                    .collect();
+            let border_heights: Vec<(ChunkPos, BorderHeights)> = chunk_block_results
+                .par_iter()
+                .map(|(chunk_pos, ..)| {
+                    let heights = border_column_heights(*chunk_pos, &self.terrain, biome_manager);
+                    (*chunk_pos, heights)
+                })
+                .collect();
+            self.border_heights.extend(border_heights);
 
-                if !self.chunks.contains_key(&chunk_pos) {
-                    chunks_to_generate.push(chunk_pos);
+            // New chunks hide faces on the borders of chunks that are already loaded,
+            // so mesh those neighbors again along with the new chunks
+            let mut chunks_to_mesh = HashSet::new();
+            let mut chunks_to_relight = HashSet::new();
+            for (chunk_pos, block_array, block_light, generation_time) in chunk_block_results {
+                self.chunks_generated += 1;
+                self.total_generation_time += generation_time;
+                self.chunk_blocks.insert(chunk_pos, block_array);
+                self.block_light.insert(chunk_pos, block_light);
+                chunks_to_mesh.insert(chunk_pos);
+                chunks_to_relight.extend(chunk_neighborhood(chunk_pos));
+                for (dx, dz) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    let neighbor_pos = ChunkPos {
+                        x: chunk_pos.x + dx,
+                        z: chunk_pos.z + dz,
+                    };
+                    if self.chunks.contains_key(&neighbor_pos)
+                        || self.pending_meshes.contains_key(&neighbor_pos)
+                    {
+                        chunks_to_mesh.insert(neighbor_pos);
+                    }
                 }
             }
-        }
 
-        // Generate chunk data in parallel
-        if !chunks_to_generate.is_empty() {
-            // Generate chunks in parallel
-            use rayon::prelude::*;
-            let chunk_data_results: Vec<(ChunkPos, ChunkData, ChunkBlocks)> = chunks_to_generate
+            // Light spreads between the new chunks and the ones around them, so loaded
+            // chunks that end up lit differently need new meshes too
+            for chunk_pos in self.relight_chunks(chunks_to_relight) {
+                if self.chunks.contains_key(&chunk_pos)
+                    || self.pending_meshes.contains_key(&chunk_pos)
+                {
+                    chunks_to_mesh.insert(chunk_pos);
+                }
+            }
+
+            // Mesh in parallel once every new chunk's blocks are in place. A new mesh
+            // replaces one still waiting for upload.
+            let meshes: Vec<(ChunkPos, ChunkData)> = chunks_to_mesh
                 .into_par_iter()
                 .map(|chunk_pos| {
-                    let (chunk_data, block_array) = self.chunk_generator.generate_chunk(
-                        chunk_pos,
-                        &self.terrain,
-                        biome_manager,
-                    );
-                    (chunk_pos, chunk_data, block_array)
+                    let mesh_data =
+                        self.generate_mesh_from_blocks(chunk_pos, &self.chunk_blocks[&chunk_pos]);
+                    (chunk_pos, mesh_data)
                 })
                 .collect();
+            self.pending_meshes.extend(meshes);
+        }
 
-            // Create GPU buffers on main thread and insert chunks
-            for (chunk_pos, chunk_data, block_array) in chunk_data_results {
-                let chunk = Chunk::from_data(chunk_data, device);
-                self.chunks.insert(chunk_pos, chunk);
-                self.chunk_blocks.insert(chunk_pos, block_array);
+        // Create GPU buffers on main thread, a few per frame so a burst of new chunks
+        // doesn't spike the frame time
+        for (chunk_pos, mesh_data) in self.next_uploads((camera_chunk_x, camera_chunk_z)) {
+            self.chunks
+                .insert(chunk_pos, Chunk::from_data(mesh_data, device));
+        }
+
+        // Fit the render distance to the memory budget: over it, the farthest rings of
+        // chunks go; once memory frees up, they come back up to the requested distance
+        if let Some(budget) = self.memory_budget {
+            let chunk_bytes = self
+                .chunks
+                .iter()
+                .map(|(chunk_pos, chunk)| (*chunk_pos, chunk.gpu_bytes()));
+            let distance = distance_within_budget(
+                chunk_bytes,
+                (camera_chunk_x, camera_chunk_z),
+                budget,
+                self.target_render_distance,
+            );
+            if distance < self.render_distance {
+                println!(
+                    "Chunk meshes exceed the {} MB GPU memory budget, render distance reduced to {}",
+                    budget / (1024 * 1024),
+                    distance
+                );
+            } else if distance > self.render_distance {
+                println!(
+                    "Chunk meshes fit the {} MB GPU memory budget again, render distance raised to {}",
+                    budget / (1024 * 1024),
+                    distance
+                );
             }
+            self.render_distance = distance;
         }
 
         // Remove distant chunks
+        let render_distance = self.render_distance;
         let chunks_to_remove: Vec<ChunkPos> = self
             .chunks
             .keys()
+            .chain(self.pending_meshes.keys())
             .filter(|&pos| {
                 let dx = pos.x - camera_chunk_x;
                 let dz = pos.z - camera_chunk_z;
-                dx.abs() > RENDER_DISTANCE || dz.abs() > RENDER_DISTANCE
+                dx.abs() > render_distance || dz.abs() > render_distance
             })
             .copied()
             .collect();
 
         for chunk_pos in chunks_to_remove {
-            self.chunks.remove(&chunk_pos);
-            self.chunk_blocks.remove(&chunk_pos);
+            self.unload_chunk(chunk_pos);
+        }
+
+        if !self.simulation_paused
+            && !self.falling_columns.is_empty()
+            && self.last_fall_step.elapsed() >= FALL_STEP_INTERVAL
+        {
+            self.last_fall_step = Instant::now();
+            let dirty_chunks = self.step_falling_blocks();
+            let relit = self.relight_around(dirty_chunks.clone());
+            self.dirty_meshes.extend(dirty_chunks);
+            self.dirty_meshes.extend(relit);
+        }
+
+        self.update_dirty_meshes(device);
+    }
+
+    /// Move every falling block with air beneath it in the queued columns down by one,
+    /// without touching meshes. Columns where nothing moved leave the queue.
+    /// Returns the chunks (plus neighbors) whose meshes are stale.
+    fn step_falling_blocks(&mut self) -> HashSet<ChunkPos> {
+        let mut dirty_chunks = HashSet::new();
+
+        let columns: Vec<(i32, i32)> = self.falling_columns.drain().collect();
+        for (world_x, world_z) in columns {
+            let chunk_pos = ChunkPos {
+                x: world_x.div_euclid(CHUNK_SIZE as i32),
+                z: world_z.div_euclid(CHUNK_SIZE as i32),
+            };
+            let Some(chunk_blocks) = self.chunk_blocks.get_mut(&chunk_pos) else {
+                continue; // Chunk unloaded, the column stays as it was saved
+            };
+            let block_x = world_x.rem_euclid(CHUNK_SIZE as i32) as usize;
+            let block_z = world_z.rem_euclid(CHUNK_SIZE as i32) as usize;
+
+            // Bottom up, so a whole stack drops together
+            let mut moved = false;
+            for y in 1..WORLD_HEIGHT {
+                let block_type = chunk_blocks.get(block_x, y, block_z);
+                if !self.block_registry.falls(block_type)
+                    || chunk_blocks.get(block_x, y - 1, block_z) != BlockType::Air
+                {
+                    continue;
+                }
+
+                chunk_blocks.set(block_x, y - 1, block_z, block_type);
+                chunk_blocks.set(block_x, y, block_z, BlockType::Air);
+                for (y, block_type) in [(y - 1, block_type), (y, BlockType::Air)] {
+                    record_edit(
+                        &mut self.block_edits,
+                        chunk_pos,
+                        (block_x, y, block_z),
+                        block_type,
+                    );
+                }
+                moved = true;
+            }
+
+            if moved {
+                self.falling_columns.insert((world_x, world_z));
+                dirty_chunks.insert(chunk_pos);
+                dirty_chunks.extend([(-1, 0), (1, 0), (0, -1), (0, 1)].map(|(dx, dz)| ChunkPos {
+                    x: chunk_pos.x + dx,
+                    z: chunk_pos.z + dz,
+                }));
+            }
+        }
+
+        dirty_chunks
+    }
+
+    /// Drop a chunk's mesh and blocks. Its edits stay in `block_edits`.
+    fn unload_chunk(&mut self, chunk_pos: ChunkPos) {
+        self.chunks.remove(&chunk_pos);
+        self.pending_meshes.remove(&chunk_pos);
+        self.chunk_blocks.remove(&chunk_pos);
+        self.block_light.remove(&chunk_pos);
+        self.border_heights.remove(&chunk_pos);
+    }
+
+    /// Take the waiting meshes to upload this frame, nearest to the camera first
+    fn next_uploads(&mut self, camera_chunk: (i32, i32)) -> Vec<(ChunkPos, ChunkData)> {
+        let mut positions: Vec<ChunkPos> = self.pending_meshes.keys().copied().collect();
+        sort_nearest_first(&mut positions, |pos| *pos, camera_chunk);
+        if let Some(max) = self.max_chunk_uploads_per_frame {
+            positions.truncate(max);
+        }
+        positions
+            .into_iter()
+            .filter_map(|pos| Some((pos, self.pending_meshes.remove(&pos)?)))
+            .collect()
+    }
+
+    /// Re-apply the player's edits to freshly generated blocks
+    fn apply_block_edits(&self, chunk_pos: ChunkPos, chunk_blocks: &mut ChunkBlocks) {
+        let Some(edits) = self.block_edits.get(&chunk_pos) else {
+            return;
+        };
+        for (&(x, z, y), &block_type) in edits {
+            chunk_blocks.set(x as usize, y as usize, z as usize, block_type);
         }
     }
 
-    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
-        for chunk in self.chunks.values() {
-            render_pass.set_vertex_buffer(0, chunk.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(chunk.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-            render_pass.draw_indexed(0..chunk.num_indices, 0, 0..1);
+    /// Chunks within render distance that aren't generated yet, nearest first and
+    /// limited to `max_chunks_per_update`
+    fn missing_chunks(&self, camera_chunk_x: i32, camera_chunk_z: i32) -> Vec<ChunkPos> {
+        let mut missing = Vec::new();
+        let render_distance = self.render_distance;
+        for dx in -render_distance..=render_distance {
+            for dz in -render_distance..=render_distance {
+                let chunk_pos = ChunkPos {
+                    x: camera_chunk_x + dx,
+                    z: camera_chunk_z + dz,
+                };
+
+                if !self.chunks.contains_key(&chunk_pos)
+                    && !self.pending_meshes.contains_key(&chunk_pos)
+                {
+                    missing.push(chunk_pos);
+                }
+            }
+        }
+
+        // Nearest first, so the chunks around and straight ahead of the player load
+        // before the corners of the view
+        sort_nearest_first(&mut missing, |pos| *pos, (camera_chunk_x, camera_chunk_z));
+        if let Some(max) = self.max_chunks_per_update {
+            missing.truncate(max);
         }
+        missing
+    }
+
+    /// Limit how many chunks each `update` generates. `None` generates everything missing at once.
+    pub fn set_max_chunks_per_update(&mut self, max: Option<usize>) {
+        self.max_chunks_per_update = max;
+    }
+
+    /// Freeze or resume the world's simulation (falling blocks). Chunks around the camera
+    /// still load, mesh and unload while it's paused.
+    pub fn set_simulation_paused(&mut self, paused: bool) {
+        self.simulation_paused = paused;
+    }
+
+    /// Limit how many chunk meshes each `update` uploads to the GPU; the rest wait for
+    /// later frames. `None` uploads everything as soon as it's meshed.
+    pub fn set_max_chunk_uploads_per_frame(&mut self, max: Option<usize>) {
+        self.max_chunk_uploads_per_frame = max;
+    }
+
+    /// Cap the GPU memory used by chunk meshes. Every `update` fits the render distance
+    /// to it: going over unloads the farthest chunks, and the distance grows back, up to
+    /// the one asked for, as memory frees up. `None` removes the cap.
+    pub fn set_memory_budget(&mut self, budget_bytes: Option<u64>) {
+        self.memory_budget = budget_bytes;
+        self.render_distance = self.target_render_distance;
+    }
+
+    /// Change how many chunks around the camera are kept loaded. The next `update`
+    /// generates the new ring or drops chunks that are now too far away.
+    pub fn set_render_distance(&mut self, render_distance: i32) {
+        self.target_render_distance = render_distance.max(0);
+        self.render_distance = self.target_render_distance;
+    }
+
+    /// Render distance in effect, possibly reduced by the memory budget
+    pub fn render_distance(&self) -> i32 {
+        self.render_distance
+    }
+
+    /// Fraction (0-1) of the chunks within render distance of the camera that are generated
+    pub fn load_progress(&self, camera_pos: Point3<f32>) -> f32 {
+        let camera_chunk_x = (camera_pos.x / CHUNK_SIZE as f32).floor() as i32;
+        let camera_chunk_z = (camera_pos.z / CHUNK_SIZE as f32).floor() as i32;
+        let render_distance = self.render_distance;
+        let total = (render_distance * 2 + 1).pow(2) as usize;
+
+        let loaded = (-render_distance..=render_distance)
+            .flat_map(|dx| (-render_distance..=render_distance).map(move |dz| (dx, dz)))
+            .filter(|&(dx, dz)| {
+                self.chunks.contains_key(&ChunkPos {
+                    x: camera_chunk_x + dx,
+                    z: camera_chunk_z + dz,
+                })
+            })
+            .count();
+        loaded as f32 / total as f32
+    }
+
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>, camera_pos: Point3<f32>) {
+        draw_chunks(render_pass, self.chunks.iter().collect(), camera_pos);
+    }
+
+    /// Draw only the chunks inside the camera frustum. Returns how many were drawn.
+    pub fn render_culled<'a>(
+        &'a self,
+        render_pass: &mut wgpu::RenderPass<'a>,
+        frustum: &Frustum,
+        camera_pos: Point3<f32>,
+    ) -> usize {
+        let visible: Vec<_> = self
+            .chunks
+            .iter()
+            .filter(|(chunk_pos, _)| frustum.intersects_aabb(&chunk_aabb(**chunk_pos)))
+            .collect();
+        let drawn = visible.len();
+        draw_chunks(render_pass, visible, camera_pos);
+        drawn
     }
 
-    /// Check if there's a solid block at the given world position
+    /// Check if there's a solid block at the given world position.
+    /// Uses the registry's `is_solid`, so e.g. water doesn't block movement or rays.
     pub fn is_block_solid(&self, world_x: i32, world_y: i32, world_z: i32) -> bool {
         // Check if Y is within valid range
         if world_y < 0 || world_y >= WORLD_HEIGHT as i32 {
@@ -129,7 +534,8 @@ impl World {
             let block_y = world_y as usize;
 
             // Use cached block data - this is the single source of truth
-            chunk_blocks[block_x][block_z][block_y] != BlockType::Air
+            let block_type = chunk_blocks.get(block_x, block_y, block_z);
+            self.block_registry.is_solid(block_type)
         } else {
             false // Chunk not loaded
         }
@@ -158,35 +564,33 @@ impl World {
             let block_y = world_y as usize;
 
             // Use cached block data - this is the single source of truth
-            Some(chunk_blocks[block_x][block_z][block_y])
+            Some(chunk_blocks.get(block_x, block_y, block_z))
         } else {
             None // Chunk not loaded
         }
     }
 
-    /// Remove a block at the given world position and update the mesh
+    /// Remove a block at the given world position. The affected meshes are rebuilt at the
+    /// end of the next `update`.
     /// Returns the type of block that was removed, or None if no block was removed
-    pub fn remove_block(
-        &mut self,
-        world_x: i32,
-        world_y: i32,
-        world_z: i32,
-        device: &wgpu::Device,
-    ) -> Option<BlockType> {
+    pub fn remove_block(&mut self, world_x: i32, world_y: i32, world_z: i32) -> Option<BlockType> {
+        let block_type = self.remove_block_data(world_x, world_y, world_z)?;
+        self.mark_edited(world_x, world_z);
+        Some(block_type)
+    }
+
+    /// Remove a block from the block data only, without touching any mesh
+    /// Returns the type of block that was removed, or None if no block was removed
+    fn remove_block_data(&mut self, world_x: i32, world_y: i32, world_z: i32) -> Option<BlockType> {
         // Check if block exists before trying to remove it
-        if !self.is_block_solid(world_x, world_y, world_z) {
+        let block_type = self.get_block_type(world_x, world_y, world_z)?;
+        if block_type == BlockType::Air {
             return None;
         }
 
-        // Get the block type before removing it
-        let block_type = self.get_block_type(world_x, world_y, world_z);
-
-        // Convert world coordinates to chunk coordinates
-        let chunk_x = world_x.div_euclid(CHUNK_SIZE as i32);
-        let chunk_z = world_z.div_euclid(CHUNK_SIZE as i32);
         let chunk_pos = ChunkPos {
-            x: chunk_x,
-            z: chunk_z,
+            x: world_x.div_euclid(CHUNK_SIZE as i32),
+            z: world_z.div_euclid(CHUNK_SIZE as i32),
         };
 
         // Get chunk-relative coordinates
@@ -195,59 +599,46 @@ impl World {
         let block_y = world_y as usize;
 
         // Update the block directly in chunk_blocks
-        if let Some(chunk_blocks) = self.chunk_blocks.get_mut(&chunk_pos) {
-            chunk_blocks[block_x][block_z][block_y] = BlockType::Air;
-
-            // Update mesh for this chunk (much faster than full regeneration)
-            self.update_chunk_mesh(chunk_pos, device);
-        }
+        let chunk_blocks = self.chunk_blocks.get_mut(&chunk_pos)?;
+        chunk_blocks.set(block_x, block_y, block_z, BlockType::Air);
+        record_edit(
+            &mut self.block_edits,
+            chunk_pos,
+            (block_x, block_y, block_z),
+            BlockType::Air,
+        );
+        // Whatever rested on this block may fall now
+        self.falling_columns.insert((world_x, world_z));
+        self.blocks_broken += 1;
 
-        // Check if block is at chunk boundary and regenerate neighboring chunks if needed
-        let local_x = world_x.rem_euclid(CHUNK_SIZE as i32);
-        let local_z = world_z.rem_euclid(CHUNK_SIZE as i32);
+        Some(block_type)
+    }
 
-        // Check each direction for chunk boundaries
-        if local_x == 0 {
-            let neighbor_pos = ChunkPos {
-                x: chunk_x - 1,
-                z: chunk_z,
-            };
-            self.update_chunk_mesh(neighbor_pos, device);
-        }
-        if local_x == CHUNK_SIZE as i32 - 1 {
-            let neighbor_pos = ChunkPos {
-                x: chunk_x + 1,
-                z: chunk_z,
-            };
-            self.update_chunk_mesh(neighbor_pos, device);
-        }
-        if local_z == 0 {
-            let neighbor_pos = ChunkPos {
-                x: chunk_x,
-                z: chunk_z - 1,
-            };
-            self.update_chunk_mesh(neighbor_pos, device);
-        }
-        if local_z == CHUNK_SIZE as i32 - 1 {
-            let neighbor_pos = ChunkPos {
-                x: chunk_x,
-                z: chunk_z + 1,
-            };
-            self.update_chunk_mesh(neighbor_pos, device);
+    /// Add a block at the given world position. The affected meshes are rebuilt at the
+    /// end of the next `update`.
+    /// Returns true if the block was successfully added
+    pub fn add_block(
+        &mut self,
+        world_x: i32,
+        world_y: i32,
+        world_z: i32,
+        block_type: BlockType,
+    ) -> bool {
+        if !self.add_block_data(world_x, world_y, world_z, block_type) {
+            return false;
         }
-
-        block_type
+        self.mark_edited(world_x, world_z);
+        true
     }
 
-    /// Add a block at the given world position and regenerate the affected chunk
+    /// Add a block to the block data only, without touching any mesh
     /// Returns true if the block was successfully added
-    pub fn add_block(
+    fn add_block_data(
         &mut self,
         world_x: i32,
         world_y: i32,
         world_z: i32,
         block_type: BlockType,
-        device: &wgpu::Device,
     ) -> bool {
         // Check if Y is within valid range
         if world_y < 0 || world_y >= WORLD_HEIGHT as i32 {
@@ -265,11 +656,9 @@ impl World {
         );
 
         // Convert world coordinates to chunk coordinates
-        let chunk_x = world_x.div_euclid(CHUNK_SIZE as i32);
-        let chunk_z = world_z.div_euclid(CHUNK_SIZE as i32);
         let chunk_pos = ChunkPos {
-            x: chunk_x,
-            z: chunk_z,
+            x: world_x.div_euclid(CHUNK_SIZE as i32),
+            z: world_z.div_euclid(CHUNK_SIZE as i32),
         };
 
         // Get chunk-relative coordinates
@@ -278,94 +667,193 @@ impl World {
         let block_y = world_y as usize;
 
         // Update the block directly in chunk_blocks
-        if let Some(chunk_blocks) = self.chunk_blocks.get_mut(&chunk_pos) {
-            chunk_blocks[block_x][block_z][block_y] = block_type;
-
-            // Update mesh for this chunk (much faster than full regeneration)
-            self.update_chunk_mesh(chunk_pos, device);
-        } else {
+        let Some(chunk_blocks) = self.chunk_blocks.get_mut(&chunk_pos) else {
             return false; // Chunk not loaded
-        }
+        };
+        chunk_blocks.set(block_x, block_y, block_z, block_type);
+        record_edit(
+            &mut self.block_edits,
+            chunk_pos,
+            (block_x, block_y, block_z),
+            block_type,
+        );
+        self.falling_columns.insert((world_x, world_z));
+        self.blocks_placed += 1;
 
-        // Check if block is at chunk boundary and regenerate neighboring chunks if needed
-        let local_x = world_x.rem_euclid(CHUNK_SIZE as i32);
-        let local_z = world_z.rem_euclid(CHUNK_SIZE as i32);
+        true
+    }
 
-        // Update neighboring chunks at boundaries
-        self.update_boundary_chunks(chunk_x, chunk_z, local_x, local_z, device);
+    /// Remove every block inside the box spanned by two corners (inclusive). Each affected
+    /// chunk is re-meshed once, at the end of the next `update`. The bottom layer is the
+    /// world's floor and is never cleared.
+    /// Returns the removed blocks, e.g. for undo or drops.
+    pub fn break_region(
+        &mut self,
+        corner_a: [i32; 3],
+        corner_b: [i32; 3],
+    ) -> Vec<([i32; 3], BlockType)> {
+        let (removed, dirty_chunks) = self.clear_region_blocks(corner_a, corner_b);
+        let relit = self.relight_around(dirty_chunks.clone());
+        self.dirty_meshes.extend(dirty_chunks);
+        self.dirty_meshes.extend(relit);
 
-        true
+        removed
     }
 
-    fn update_boundary_chunks(
+    /// Set every non-air block in the box to air without touching meshes.
+    /// Returns the removed blocks and the chunks (plus neighbors) whose meshes are stale.
+    fn clear_region_blocks(
         &mut self,
-        chunk_x: i32,
-        chunk_z: i32,
-        local_x: i32,
-        local_z: i32,
-        device: &wgpu::Device,
-    ) {
-        // Check each direction for chunk boundaries
-        if local_x == 0 {
-            let neighbor_pos = ChunkPos {
-                x: chunk_x - 1,
-                z: chunk_z,
-            };
-            self.update_chunk_mesh(neighbor_pos, device);
-        }
-        if local_x == CHUNK_SIZE as i32 - 1 {
-            let neighbor_pos = ChunkPos {
-                x: chunk_x + 1,
-                z: chunk_z,
-            };
-            self.update_chunk_mesh(neighbor_pos, device);
-        }
-        if local_z == 0 {
-            let neighbor_pos = ChunkPos {
-                x: chunk_x,
-                z: chunk_z - 1,
-            };
-            self.update_chunk_mesh(neighbor_pos, device);
-        }
-        if local_z == CHUNK_SIZE as i32 - 1 {
-            let neighbor_pos = ChunkPos {
-                x: chunk_x,
-                z: chunk_z + 1,
-            };
-            self.update_chunk_mesh(neighbor_pos, device);
-        }
+        corner_a: [i32; 3],
+        corner_b: [i32; 3],
+    ) -> (Vec<([i32; 3], BlockType)>, HashSet<ChunkPos>) {
+        let min = [
+            corner_a[0].min(corner_b[0]),
+            // Keep the floor at y = 0 so the player can't dig out of the world
+            corner_a[1].min(corner_b[1]).max(1),
+            corner_a[2].min(corner_b[2]),
+        ];
+        let max = [
+            corner_a[0].max(corner_b[0]),
+            corner_a[1].max(corner_b[1]).min(WORLD_HEIGHT as i32 - 1),
+            corner_a[2].max(corner_b[2]),
+        ];
 
-        // Check corners (block at corner of chunk affects 3 neighboring chunks)
-        if local_x == 0 && local_z == 0 {
-            let neighbor_pos = ChunkPos {
-                x: chunk_x - 1,
-                z: chunk_z - 1,
-            };
-            self.update_chunk_mesh(neighbor_pos, device);
-        }
-        if local_x == 0 && local_z == CHUNK_SIZE as i32 - 1 {
-            let neighbor_pos = ChunkPos {
-                x: chunk_x - 1,
-                z: chunk_z + 1,
-            };
-            self.update_chunk_mesh(neighbor_pos, device);
-        }
-        if local_x == CHUNK_SIZE as i32 - 1 && local_z == 0 {
-            let neighbor_pos = ChunkPos {
-                x: chunk_x + 1,
-                z: chunk_z - 1,
-            };
-            self.update_chunk_mesh(neighbor_pos, device);
+        let mut removed = Vec::new();
+        let mut dirty_chunks = HashSet::new();
+
+        for world_x in min[0]..=max[0] {
+            for world_z in min[2]..=max[2] {
+                let chunk_pos = ChunkPos {
+                    x: world_x.div_euclid(CHUNK_SIZE as i32),
+                    z: world_z.div_euclid(CHUNK_SIZE as i32),
+                };
+                let Some(chunk_blocks) = self.chunk_blocks.get_mut(&chunk_pos) else {
+                    continue; // Chunk not loaded
+                };
+
+                let block_x = world_x.rem_euclid(CHUNK_SIZE as i32) as usize;
+                let block_z = world_z.rem_euclid(CHUNK_SIZE as i32) as usize;
+
+                for world_y in min[1]..=max[1] {
+                    let block_type = chunk_blocks.get(block_x, world_y as usize, block_z);
+                    if block_type == BlockType::Air {
+                        continue;
+                    }
+
+                    chunk_blocks.set(block_x, world_y as usize, block_z, BlockType::Air);
+                    record_edit(
+                        &mut self.block_edits,
+                        chunk_pos,
+                        (block_x, world_y as usize, block_z),
+                        BlockType::Air,
+                    );
+                    removed.push(([world_x, world_y, world_z], block_type));
+                    dirty_chunks.insert(chunk_pos);
+                    self.falling_columns.insert((world_x, world_z));
+                }
+            }
         }
-        if local_x == CHUNK_SIZE as i32 - 1 && local_z == CHUNK_SIZE as i32 - 1 {
-            let neighbor_pos = ChunkPos {
-                x: chunk_x + 1,
-                z: chunk_z + 1,
-            };
-            self.update_chunk_mesh(neighbor_pos, device);
+
+        // Neighbors may have faces that were hidden by the removed blocks
+        let neighbors: Vec<ChunkPos> = dirty_chunks
+            .iter()
+            .flat_map(|pos| {
+                [(-1, 0), (1, 0), (0, -1), (0, 1)].map(|(dx, dz)| ChunkPos {
+                    x: pos.x + dx,
+                    z: pos.z + dz,
+                })
+            })
+            .collect();
+        dirty_chunks.extend(neighbors);
+        self.blocks_broken += removed.len() as u64;
+
+        (removed, dirty_chunks)
+    }
+
+    /// Relight around a single edited block and queue the meshes it made stale: its own
+    /// chunk's, those of neighbors sharing the border it sits on, and any whose light changed
+    fn mark_edited(&mut self, world_x: i32, world_z: i32) {
+        let chunk_pos = ChunkPos {
+            x: world_x.div_euclid(CHUNK_SIZE as i32),
+            z: world_z.div_euclid(CHUNK_SIZE as i32),
+        };
+        let relit = self.relight_around([chunk_pos]);
+        self.dirty_meshes.insert(chunk_pos);
+        self.dirty_meshes.extend(boundary_neighbors(
+            chunk_pos,
+            world_x.rem_euclid(CHUNK_SIZE as i32),
+            world_z.rem_euclid(CHUNK_SIZE as i32),
+        ));
+        self.dirty_meshes.extend(relit);
+    }
+
+    /// Re-mesh every chunk queued in `dirty_meshes` once, no matter how many edits touched
+    /// it, and upload the meshes right away
+    fn update_dirty_meshes(&mut self, device: &wgpu::Device) {
+        for (chunk_pos, mesh_data) in self.mesh_dirty_chunks() {
+            self.chunks
+                .insert(chunk_pos, Chunk::from_data(mesh_data, device));
+            // Newer than anything still waiting for upload
+            self.pending_meshes.remove(&chunk_pos);
         }
     }
 
+    /// Empty `dirty_meshes`, meshing each chunk in it in parallel with the world's mesh
+    /// options. Chunks unloaded since are dropped.
+    fn mesh_dirty_chunks(&mut self) -> Vec<(ChunkPos, ChunkData)> {
+        use rayon::prelude::*;
+
+        let dirty_chunks: Vec<ChunkPos> = self
+            .dirty_meshes
+            .drain()
+            .filter(|chunk_pos| self.chunk_blocks.contains_key(chunk_pos))
+            .collect();
+        dirty_chunks
+            .into_par_iter()
+            .map(|chunk_pos| {
+                let mesh_data =
+                    self.generate_mesh_from_blocks(chunk_pos, &self.chunk_blocks[&chunk_pos]);
+                (chunk_pos, mesh_data)
+            })
+            .collect()
+    }
+
+    /// Re-mesh every loaded chunk once with `MeshOptions::high_quality_ao`, e.g. before
+    /// taking screenshots. The meshes are uploaded over the next frames like new chunks.
+    /// It doesn't stick: chunks that are edited or loaded later get realtime AO again.
+    pub fn bake_high_quality_ao(&mut self) {
+        use rayon::prelude::*;
+        let start = Instant::now();
+
+        let mesh_options = MeshOptions {
+            high_quality_ao: true,
+            ..self.mesh_options()
+        };
+        let meshes: Vec<(ChunkPos, ChunkData)> = self
+            .chunk_blocks
+            .par_iter()
+            .map(|(&chunk_pos, chunk_blocks)| {
+                let mesh_data = mesh_chunk_blocks(
+                    chunk_pos,
+                    chunk_blocks,
+                    &self.block_registry,
+                    mesh_options,
+                    |world_x, world_y, world_z| self.get_block_type(world_x, world_y, world_z),
+                    |world_x, world_y, world_z| self.block_light_at(world_x, world_y, world_z),
+                );
+                (chunk_pos, mesh_data)
+            })
+            .collect();
+
+        println!(
+            "Baked high quality ambient occlusion into {} chunks in {:.2?}",
+            meshes.len(),
+            start.elapsed()
+        );
+        self.pending_meshes.extend(meshes);
+    }
+
     /// Update chunk mesh from existing block data (no terrain regeneration)
     fn update_chunk_mesh(&mut self, chunk_pos: ChunkPos, device: &wgpu::Device) {
         // Get the existing chunk block data
@@ -374,120 +862,1549 @@ impl World {
             let mesh_data = self.generate_mesh_from_blocks(chunk_pos, chunk_blocks);
             let new_chunk = Chunk::from_data(mesh_data, device);
             self.chunks.insert(chunk_pos, new_chunk);
+            // Newer than anything still waiting for upload
+            self.pending_meshes.remove(&chunk_pos);
         }
     }
 
-    /// Generate mesh from existing block data
-    fn generate_mesh_from_blocks(
-        &self,
-        chunk_pos: ChunkPos,
-        chunk_blocks: &ChunkBlocks,
-    ) -> ChunkData {
-        let mut vertices = Vec::new();
-        let mut indices: Vec<u32> = Vec::new();
-        let registry = crate::blocks::get_block_registry();
-
-        // Generate vertices with face culling (same logic as before)
-        for x in 0..CHUNK_SIZE {
-            for z in 0..CHUNK_SIZE {
-                for y in 0..WORLD_HEIGHT {
-                    let block_type = chunk_blocks[x][z][y];
+    /// Look for light sources again in chunks whose blocks changed, then recompute the
+    /// light of every loaded chunk it could have changed: those chunks and the ones
+    /// around them. Returns the chunks whose light changed.
+    fn relight_around(
+        &mut self,
+        edited_chunks: impl IntoIterator<Item = ChunkPos>,
+    ) -> HashSet<ChunkPos> {
+        let mut chunks_to_relight = HashSet::new();
+        for chunk_pos in edited_chunks {
+            let Some(chunk_blocks) = self.chunk_blocks.get(&chunk_pos) else {
+                continue; // Chunk not loaded
+            };
+            match self.block_light.get_mut(&chunk_pos) {
+                Some(block_light) => {
+                    block_light.refresh_sources(chunk_pos, chunk_blocks, &self.block_registry)
+                }
+                None => {
+                    let block_light =
+                        BlockLight::new(chunk_pos, chunk_blocks, &self.block_registry);
+                    self.block_light.insert(chunk_pos, block_light);
+                }
+            }
+            chunks_to_relight.extend(chunk_neighborhood(chunk_pos));
+        }
+        self.relight_chunks(chunks_to_relight)
+    }
 
-                    // Skip air blocks
-                    if block_type == BlockType::Air {
-                        continue;
-                    }
+    /// Recompute the block light of the given chunks in parallel from the light sources
+    /// around them. Chunks that aren't loaded are skipped.
+    /// Returns the chunks whose light changed.
+    fn relight_chunks(&mut self, chunk_positions: HashSet<ChunkPos>) -> HashSet<ChunkPos> {
+        use rayon::prelude::*;
 
-                    let world_x = (chunk_pos.x * CHUNK_SIZE as i32 + x as i32) as f32;
-                    let world_z = (chunk_pos.z * CHUNK_SIZE as i32 + z as i32) as f32;
-
-                    // Check each face for culling
-                    let mut faces_to_render = Vec::new();
-
-                    // Check each direction for adjacent blocks
-                    let directions = [
-                        (0, 0, 1),  // Front (+Z)
-                        (0, 0, -1), // Back (-Z)
-                        (-1, 0, 0), // Left (-X)
-                        (1, 0, 0),  // Right (+X)
-                        (0, 1, 0),  // Top (+Y)
-                        (0, -1, 0), // Bottom (-Y)
-                    ];
-
-                    for (i, &(dx, dy, dz)) in directions.iter().enumerate() {
-                        let adj_x = x as i32 + dx;
-                        let adj_y = y as i32 + dy;
-                        let adj_z = z as i32 + dz;
-
-                        let should_render_face = if adj_x < 0
-                            || adj_x >= CHUNK_SIZE as i32
-                            || adj_z < 0
-                            || adj_z >= CHUNK_SIZE as i32
-                            || adj_y < 0
-                            || adj_y >= WORLD_HEIGHT as i32
-                        {
-                            // Face is at chunk boundary, check if there's a block in the neighboring position
-                            if adj_y < 0 || adj_y >= WORLD_HEIGHT as i32 {
-                                // Out of world bounds vertically, always render
-                                true
-                            } else {
-                                // Check the actual world position for a block
-                                let world_adj_x = world_x as i32 + dx;
-                                let world_adj_z = world_z as i32 + dz;
-                                let world_adj_y = y as i32 + dy;
-                                !self.is_block_solid(world_adj_x, world_adj_y, world_adj_z)
-                            }
-                        } else {
-                            // Check if adjacent block is air (render face) or solid (cull face)
-                            let adj_block =
-                                chunk_blocks[adj_x as usize][adj_z as usize][adj_y as usize];
-                            adj_block == BlockType::Air
-                        };
-
-                        if should_render_face {
-                            faces_to_render.push(i);
-                        }
-                    }
+        // Gather every chunk's sources before taking any light out of the map
+        let sources: Vec<(ChunkPos, Vec<LightSource>)> = chunk_positions
+            .into_iter()
+            .filter(|chunk_pos| self.block_light.contains_key(chunk_pos))
+            .map(|chunk_pos| (chunk_pos, self.light_sources_near(chunk_pos)))
+            .collect();
+        let mut lights: Vec<(ChunkPos, BlockLight, Vec<LightSource>)> = sources
+            .into_iter()
+            .filter_map(|(chunk_pos, sources)| {
+                Some((chunk_pos, self.block_light.remove(&chunk_pos)?, sources))
+            })
+            .collect();
 
-                    // Only generate vertices for visible faces
-                    if !faces_to_render.is_empty() {
-                        let textures = registry.get_textures(block_type);
-
-                        let vertex_offset = vertices.len() as u32;
-                        let cube_vertices = create_cube_vertices_selective(
-                            world_x,
-                            y as f32,
-                            world_z,
-                            &textures,
-                            &faces_to_render,
-                        );
-                        vertices.extend(cube_vertices);
+        let changed: Vec<bool> = lights
+            .par_iter_mut()
+            .map(|(chunk_pos, block_light, sources)| {
+                block_light.propagate(*chunk_pos, sources, &self.block_registry, |x, y, z| {
+                    self.get_block_type(x, y, z)
+                })
+            })
+            .collect();
 
-                        let cube_indices =
-                            create_cube_indices_selective(&faces_to_render, vertex_offset);
-                        indices.extend(cube_indices);
-                    }
-                }
+        let mut relit = HashSet::new();
+        for ((chunk_pos, block_light, _), changed) in lights.into_iter().zip(changed) {
+            self.block_light.insert(chunk_pos, block_light);
+            if changed {
+                relit.insert(chunk_pos);
             }
         }
-
-        ChunkData { vertices, indices }
+        relit
     }
 
-    /// Get all currently loaded chunk positions for debug rendering
-    pub fn get_loaded_chunk_positions(&self) -> Vec<ChunkPos> {
-        self.chunks.keys().copied().collect()
+    /// Light sources in and around a chunk that are bright enough to reach into it
+    fn light_sources_near(&self, chunk_pos: ChunkPos) -> Vec<LightSource> {
+        let min_x = chunk_pos.x * CHUNK_SIZE as i32;
+        let min_z = chunk_pos.z * CHUNK_SIZE as i32;
+        let max_x = min_x + CHUNK_SIZE as i32 - 1;
+        let max_z = min_z + CHUNK_SIZE as i32 - 1;
+
+        chunk_neighborhood(chunk_pos)
+            .into_iter()
+            .filter_map(|neighbor_pos| self.block_light.get(&neighbor_pos))
+            .flat_map(|block_light| block_light.sources())
+            .filter(|source| {
+                let [x, _, z] = source.position;
+                let distance_x = (min_x - x).max(x - max_x).max(0);
+                let distance_z = (min_z - z).max(z - max_z).max(0);
+                distance_x + distance_z < source.level as i32
+            })
+            .copied()
+            .collect()
     }
 
-    pub fn get_terrain(&self) -> &Terrain {
+    /// Block light level at a world position. 0 outside the world and in chunks that
+    /// aren't loaded.
+    pub fn block_light_at(&self, world_x: i32, world_y: i32, world_z: i32) -> u8 {
+        if !(0..WORLD_HEIGHT as i32).contains(&world_y) {
+            return 0;
+        }
+        let chunk_pos = ChunkPos {
+            x: world_x.div_euclid(CHUNK_SIZE as i32),
+            z: world_z.div_euclid(CHUNK_SIZE as i32),
+        };
+        self.block_light.get(&chunk_pos).map_or(0, |block_light| {
+            block_light.get(
+                world_x.rem_euclid(CHUNK_SIZE as i32) as usize,
+                world_y as usize,
+                world_z.rem_euclid(CHUNK_SIZE as i32) as usize,
+            )
+        })
+    }
+
+    /// Generate mesh from existing block data, culling faces against loaded neighbors and
+    /// the predicted terrain of unloaded ones
+    fn generate_mesh_from_blocks(
+        &self,
+        chunk_pos: ChunkPos,
+        chunk_blocks: &ChunkBlocks,
+    ) -> ChunkData {
+        mesh_chunk_blocks(
+            chunk_pos,
+            chunk_blocks,
+            &self.block_registry,
+            self.chunk_generator.mesh_options(),
+            |world_x, world_y, world_z| {
+                self.get_block_type(world_x, world_y, world_z)
+                    .or_else(|| self.predicted_border_block(chunk_pos, world_x, world_y, world_z))
+            },
+            |world_x, world_y, world_z| self.block_light_at(world_x, world_y, world_z),
+        )
+    }
+
+    /// Block just outside `chunk_pos` predicted from the terrain height map, for a
+    /// neighbor that isn't loaded. None (keep the face) where there's no prediction or the
+    /// neighbor has player edits the height map doesn't know about. Once the neighbor
+    /// loads, the chunk is re-meshed against its real blocks.
+    fn predicted_border_block(
+        &self,
+        chunk_pos: ChunkPos,
+        world_x: i32,
+        world_y: i32,
+        world_z: i32,
+    ) -> Option<BlockType> {
+        let neighbor_pos = ChunkPos {
+            x: world_x.div_euclid(CHUNK_SIZE as i32),
+            z: world_z.div_euclid(CHUNK_SIZE as i32),
+        };
+        if !(0..WORLD_HEIGHT as i32).contains(&world_y)
+            || self.block_edits.contains_key(&neighbor_pos)
+        {
+            return None;
+        }
+        let filled_height = *self
+            .border_heights
+            .get(&chunk_pos)?
+            .get(&(world_x, world_z))?;
+        Some(predicted_terrain_block(
+            filled_height,
+            self.terrain.sea_level(),
+            world_y,
+        ))
+    }
+
+    /// Get all currently loaded chunk positions for debug rendering
+    pub fn get_loaded_chunk_positions(&self) -> Vec<ChunkPos> {
+        self.chunks.keys().copied().collect()
+    }
+
+    pub fn get_terrain(&self) -> &Terrain {
         &self.terrain
     }
 
-    /// Clear all loaded chunks to force regeneration with new biome configs
+    /// Generate a chunk's blocks without creating GPU buffers, so the world can be used
+    /// headless (tools, tests, servers). Chunks loaded this way have no mesh and aren't drawn.
+    ///
+    /// ```
+    /// use rustcraft::{BiomeManager, BlockType, ChunkPos, World};
+    ///
+    /// let mut world = World::new();
+    /// world.load_chunk_blocks(ChunkPos { x: 0, z: 0 }, &BiomeManager::new());
+    ///
+    /// assert!(world.is_block_solid(0, 0, 0));
+    /// assert_eq!(world.get_block_type(0, 250, 0), Some(BlockType::Air));
+    /// ```
+    pub fn load_chunk_blocks(&mut self, chunk_pos: ChunkPos, biome_manager: &BiomeManager) {
+        let start = Instant::now();
+        let mut block_array =
+            self.chunk_generator
+                .generate_chunk_blocks(chunk_pos, &self.terrain, biome_manager);
+        self.apply_block_edits(chunk_pos, &mut block_array);
+        self.chunks_generated += 1;
+        self.total_generation_time += start.elapsed();
+        self.chunk_blocks.insert(chunk_pos, block_array);
+        self.border_heights.insert(
+            chunk_pos,
+            border_column_heights(chunk_pos, &self.terrain, biome_manager),
+        );
+        self.relight_around([chunk_pos]);
+    }
+
+    pub fn mesh_options(&self) -> MeshOptions {
+        self.chunk_generator.mesh_options()
+    }
+
+    /// Change how chunk meshes are built. Loaded chunks are dropped so they rebuild.
+    pub fn set_mesh_options(&mut self, mesh_options: MeshOptions) {
+        self.chunk_generator.set_mesh_options(mesh_options);
+        self.clear_all_chunks();
+    }
+
+    /// The block at a world position and its material. None outside the world and in
+    /// chunks that aren't loaded.
+    pub fn describe_position(
+        &self,
+        world_x: i32,
+        world_y: i32,
+        world_z: i32,
+    ) -> Option<BlockDescription> {
+        let block_type = self.get_block_type(world_x, world_y, world_z)?;
+        let material = self.block_registry.get_material(block_type)?;
+        Some(BlockDescription {
+            block_type,
+            name: material.name,
+            hardness: material.hardness,
+            is_solid: material.is_solid,
+            is_transparent: material.is_transparent,
+            emission: material.emission,
+        })
+    }
+
+    /// Block materials used by this world
+    pub fn block_registry(&self) -> &BlockRegistry {
+        &self.block_registry
+    }
+
+    /// Switch the terrain generator, e.g. to superflat. Loaded chunks are dropped
+    /// so everything regenerates with the new terrain.
+    pub fn set_terrain_kind(&mut self, kind: TerrainKind) {
+        let sea_level = self.terrain.sea_level();
+        self.terrain = Terrain::with_kind(self.terrain.seed(), kind);
+        self.terrain.set_sea_level(sea_level);
+        self.clear_all_chunks();
+    }
+
+    /// Change the height water fills up to. Loaded chunks are dropped so they regenerate.
+    pub fn set_sea_level(&mut self, sea_level: usize) {
+        self.terrain.set_sea_level(sea_level);
+        self.clear_all_chunks();
+    }
+
+    /// Place a structure loaded from a file in chunks generated from now on
+    pub fn register_structure(&mut self, structure: FileStructure) {
+        self.chunk_generator.register_structure(structure);
+    }
+
+    /// Register a custom terrain feature for chunks generated from now on
+    pub fn register_terrain_modifier(&mut self, modifier: Box<dyn TerrainModifier>) {
+        self.chunk_generator.register_modifier(modifier);
+    }
+
+    /// Drop and rebuild a single chunk from the generators, discarding any edits in it
+    pub fn regenerate_chunk(
+        &mut self,
+        chunk_pos: ChunkPos,
+        device: &wgpu::Device,
+        biome_manager: &BiomeManager,
+    ) {
+        let start = Instant::now();
+
+        self.block_edits.remove(&chunk_pos);
+        let block_array =
+            self.chunk_generator
+                .generate_chunk_blocks(chunk_pos, &self.terrain, biome_manager);
+        self.chunks_generated += 1;
+        self.total_generation_time += start.elapsed();
+        self.chunk_blocks.insert(chunk_pos, block_array);
+        self.border_heights.insert(
+            chunk_pos,
+            border_column_heights(chunk_pos, &self.terrain, biome_manager),
+        );
+        let mut chunks_to_mesh = self.relight_around([chunk_pos]);
+        chunks_to_mesh.insert(chunk_pos);
+
+        // Neighbors may have faces that were hidden by (or exposed to) the old blocks
+        for (dx, dz) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            chunks_to_mesh.insert(ChunkPos {
+                x: chunk_pos.x + dx,
+                z: chunk_pos.z + dz,
+            });
+        }
+        for chunk_pos in chunks_to_mesh {
+            self.update_chunk_mesh(chunk_pos, device);
+        }
+
+        println!(
+            "Regenerated chunk ({}, {}) in {:.2?}",
+            chunk_pos.x,
+            chunk_pos.z,
+            start.elapsed()
+        );
+    }
+
+    /// Re-run structure generation on every loaded chunk while keeping its terrain, for
+    /// iterating on structures without paying for terrain generation
+    pub fn regenerate_structures(&mut self, device: &wgpu::Device, biome_manager: &BiomeManager) {
+        let start = Instant::now();
+
+        let chunk_positions = self.regenerate_structure_blocks(biome_manager);
+        self.relight_around(chunk_positions.iter().copied());
+        for chunk_pos in &chunk_positions {
+            self.update_chunk_mesh(*chunk_pos, device);
+        }
+
+        println!(
+            "Regenerated structures in {} chunks in {:.2?}",
+            chunk_positions.len(),
+            start.elapsed()
+        );
+    }
+
+    /// Swap structure blocks in every loaded chunk without touching meshes. Blocks the
+    /// player edited stay as they are.
+    fn regenerate_structure_blocks(&mut self, biome_manager: &BiomeManager) -> Vec<ChunkPos> {
+        let mut all_chunk_blocks = std::mem::take(&mut self.chunk_blocks);
+        for (chunk_pos, chunk_blocks) in all_chunk_blocks.iter_mut() {
+            self.chunk_generator.regenerate_structures(
+                *chunk_pos,
+                chunk_blocks,
+                &self.terrain,
+                biome_manager,
+            );
+            self.apply_block_edits(*chunk_pos, chunk_blocks);
+        }
+        self.chunk_blocks = all_chunk_blocks;
+        self.chunk_blocks.keys().copied().collect()
+    }
+
+    /// Current counters for generation, meshes and block edits
+    pub fn stats(&self) -> WorldStats {
+        let average_generation_time = if self.chunks_generated > 0 {
+            self.total_generation_time / self.chunks_generated as u32
+        } else {
+            Duration::ZERO
+        };
+
+        WorldStats {
+            chunks_generated: self.chunks_generated,
+            chunks_loaded: self.chunks.len(),
+            total_vertices: self
+                .chunks
+                .values()
+                .map(|chunk| chunk.num_vertices as u64)
+                .sum(),
+            blocks_placed: self.blocks_placed,
+            blocks_broken: self.blocks_broken,
+            average_generation_time,
+            gpu_memory_bytes: self.chunks.values().map(Chunk::gpu_bytes).sum(),
+            render_distance: self.render_distance,
+        }
+    }
+
+    /// Clear all loaded chunks to force regeneration with new biome configs.
+    /// Player edits are kept and re-applied as the chunks come back.
     pub fn clear_all_chunks(&mut self) {
         let chunk_count = self.chunks.len();
         self.chunks.clear();
+        self.pending_meshes.clear();
         self.chunk_blocks.clear();
+        self.block_light.clear();
+        self.border_heights.clear();
         println!("Cleared {} chunks for regeneration", chunk_count);
     }
+
+    /// Regenerate the terrain of every loaded chunk in place, e.g. with new biome configs.
+    /// Player edits are re-applied. The new meshes replace the old ones right away, past
+    /// the per-frame upload cap, so what's drawn always matches the blocks the player
+    /// collides with and the world never vanishes while it rebuilds.
+    pub fn regenerate_loaded_chunks(
+        &mut self,
+        device: &wgpu::Device,
+        biome_manager: &BiomeManager,
+    ) {
+        use rayon::prelude::*;
+        let start = Instant::now();
+
+        let chunk_positions: Vec<ChunkPos> = self.chunk_blocks.keys().copied().collect();
+        let chunk_block_results: Vec<(ChunkPos, ChunkBlocks, BlockLight, Duration)> =
+            chunk_positions
+                .par_iter()
+                .map(|&chunk_pos| {
+                    let start = Instant::now();
+                    let mut block_array = self.chunk_generator.generate_chunk_blocks(
+                        chunk_pos,
+                        &self.terrain,
+                        biome_manager,
+                    );
+                    self.apply_block_edits(chunk_pos, &mut block_array);
+                    let block_light =
+                        BlockLight::new(chunk_pos, &block_array, &self.block_registry);
+                    (chunk_pos, block_array, block_light, start.elapsed())
+                })
+                .collect();
+        for (chunk_pos, block_array, block_light, generation_time) in chunk_block_results {
+            self.chunks_generated += 1;
+            self.total_generation_time += generation_time;
+            self.chunk_blocks.insert(chunk_pos, block_array);
+            self.block_light.insert(chunk_pos, block_light);
+        }
+        // New biome configs move the terrain of the neighbors too
+        let border_heights: Vec<(ChunkPos, BorderHeights)> = chunk_positions
+            .par_iter()
+            .map(|&chunk_pos| {
+                let heights = border_column_heights(chunk_pos, &self.terrain, biome_manager);
+                (chunk_pos, heights)
+            })
+            .collect();
+        self.border_heights.extend(border_heights);
+        self.relight_chunks(chunk_positions.iter().copied().collect());
+
+        let meshes: Vec<(ChunkPos, ChunkData)> = chunk_positions
+            .par_iter()
+            .map(|&chunk_pos| {
+                let mesh_data =
+                    self.generate_mesh_from_blocks(chunk_pos, &self.chunk_blocks[&chunk_pos]);
+                (chunk_pos, mesh_data)
+            })
+            .collect();
+        for (chunk_pos, mesh_data) in meshes {
+            // Any mesh still waiting for upload was built from the old terrain
+            self.pending_meshes.remove(&chunk_pos);
+            self.chunks
+                .insert(chunk_pos, Chunk::from_data(mesh_data, device));
+        }
+
+        println!(
+            "Regenerated {} chunks in place in {:.2?}",
+            chunk_positions.len(),
+            start.elapsed()
+        );
+    }
+
+    /// Write the loaded chunks between two corners (inclusive) to a Wavefront OBJ file,
+    /// with a .mtl file next to it holding one flat-colored material per block type.
+    /// Hidden faces are culled the same way as in the chunk meshes.
+    pub fn export_obj(
+        &self,
+        path: &Path,
+        region: (ChunkPos, ChunkPos),
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mtl_path = path.with_extension("mtl");
+        let mtl_name = mtl_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or("export path has no file name")?;
+        let (obj, mtl) = self.obj_contents(region, mtl_name);
+        fs::write(path, obj)?;
+        fs::write(&mtl_path, mtl)?;
+        Ok(())
+    }
+
+    /// Text of the .obj and .mtl files for `export_obj`
+    fn obj_contents(&self, region: (ChunkPos, ChunkPos), mtl_name: &str) -> (String, String) {
+        let (a, b) = region;
+        let mut obj = format!("# Exported from rustcraft\nmtllib {}\n", mtl_name);
+        // One normal per face direction, in face order
+        for [x, y, z] in FACE_NORMALS {
+            writeln!(obj, "vn {} {} {}", x, y, z).unwrap();
+        }
+
+        // Vertices are written as blocks are visited; faces are grouped per material
+        let mut faces: HashMap<BlockType, String> = HashMap::new();
+        let mut vertex_count = 0;
+        for chunk_x in a.x.min(b.x)..=a.x.max(b.x) {
+            for chunk_z in a.z.min(b.z)..=a.z.max(b.z) {
+                let chunk_pos = ChunkPos {
+                    x: chunk_x,
+                    z: chunk_z,
+                };
+                let Some(chunk_blocks) = self.chunk_blocks.get(&chunk_pos) else {
+                    continue;
+                };
+
+                for x in 0..CHUNK_SIZE {
+                    for z in 0..CHUNK_SIZE {
+                        for y in 0..WORLD_HEIGHT {
+                            let block_type = chunk_blocks.get(x, y, z);
+                            if block_type == BlockType::Air {
+                                continue;
+                            }
+                            let world_x = chunk_x * CHUNK_SIZE as i32 + x as i32;
+                            let world_y = y as i32;
+                            let world_z = chunk_z * CHUNK_SIZE as i32 + z as i32;
+
+                            // Culled the same way as in the chunk meshes
+                            let faces_to_render =
+                                visible_faces(block_type, &self.block_registry, |dx, dy, dz| {
+                                    self.get_block_type(world_x + dx, world_y + dy, world_z + dz)
+                                });
+                            if faces_to_render.is_empty() {
+                                continue;
+                            }
+
+                            let vertices = create_cube_vertices_selective(
+                                world_x as f32,
+                                world_y as f32,
+                                world_z as f32,
+                                &self.block_registry.get_textures(block_type),
+                                &faces_to_render,
+                                0,
+                                |_, _, _| false,
+                            );
+                            for vertex in &vertices {
+                                let [vx, vy, vz] = vertex.position;
+                                writeln!(obj, "v {} {} {}", vx, vy, vz).unwrap();
+                            }
+                            let block_faces = faces.entry(block_type).or_default();
+                            for (i, face) in faces_to_render.iter().enumerate() {
+                                // OBJ indices start at 1
+                                let first = vertex_count + i * 4 + 1;
+                                let normal = face + 1;
+                                writeln!(
+                                    block_faces,
+                                    "f {}//{} {}//{} {}//{} {}//{}",
+                                    first,
+                                    normal,
+                                    first + 1,
+                                    normal,
+                                    first + 2,
+                                    normal,
+                                    first + 3,
+                                    normal
+                                )
+                                .unwrap();
+                            }
+                            vertex_count += vertices.len();
+                        }
+                    }
+                }
+            }
+        }
+
+        // Sort by name so exports of the same region are identical. OBJ and MTL split
+        // statements on whitespace, so names like "Coal Ore" become "Coal_Ore".
+        let mut materials: Vec<(String, BlockType, String)> = faces
+            .into_iter()
+            .map(|(block_type, block_faces)| {
+                let name = self
+                    .block_name(block_type)
+                    .replace(char::is_whitespace, "_");
+                (name, block_type, block_faces)
+            })
+            .collect();
+        materials.sort_by(|(a, _, _), (b, _, _)| a.cmp(b));
+
+        let mut mtl = String::from("# Exported from rustcraft\n");
+        for (name, block_type, block_faces) in materials {
+            write!(obj, "usemtl {}\n{}", name, block_faces).unwrap();
+
+            let [r, g, b] = block_color(block_type);
+            writeln!(mtl, "\nnewmtl {}\nKd {} {} {}", name, r, g, b).unwrap();
+            if self.block_registry.is_transparent(block_type) {
+                writeln!(mtl, "d 0.6").unwrap();
+            }
+        }
+        (obj, mtl)
+    }
+
+    fn block_name(&self, block_type: BlockType) -> &'static str {
+        self.block_registry
+            .get_material(block_type)
+            .map_or("Unknown", |material| material.name)
+    }
+}
+
+/// Rough average color of a block's texture, for materials in exported models
+fn block_color(block_type: BlockType) -> [f32; 3] {
+    match block_type {
+        BlockType::Air => [1.0, 1.0, 1.0],
+        BlockType::Stone => [0.5, 0.5, 0.5],
+        BlockType::Dirt => [0.45, 0.3, 0.2],
+        BlockType::Grass => [0.35, 0.6, 0.25],
+        BlockType::Sand => [0.86, 0.8, 0.55],
+        BlockType::Water => [0.2, 0.4, 0.8],
+        BlockType::Wood => [0.4, 0.3, 0.18],
+        BlockType::Leaves => [0.2, 0.45, 0.15],
+        BlockType::Snow => [0.95, 0.95, 0.97],
+        BlockType::Planks => [0.65, 0.5, 0.3],
+        BlockType::Cobblestone => [0.42, 0.42, 0.42],
+        BlockType::Glass => [0.8, 0.9, 0.95],
+        BlockType::Coal => [0.3, 0.3, 0.3],
+        BlockType::Iron => [0.6, 0.55, 0.5],
+        BlockType::Gold => [0.75, 0.65, 0.3],
+        BlockType::Glowstone => [0.9, 0.75, 0.45],
+    }
+}
+
+/// Draw the opaque part of every chunk, then the transparent parts from the farthest
+/// chunk to the nearest so water and glass blend over whatever is behind them
+fn draw_chunks<'a>(
+    render_pass: &mut wgpu::RenderPass<'a>,
+    mut chunks: Vec<(&ChunkPos, &'a Chunk)>,
+    camera_pos: Point3<f32>,
+) {
+    for (_, chunk) in &chunks {
+        if chunk.num_opaque_indices == 0 {
+            continue;
+        }
+        render_pass.set_vertex_buffer(0, chunk.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(chunk.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..chunk.num_opaque_indices, 0, 0..1);
+    }
+
+    let distance_squared = |chunk_pos: &ChunkPos| {
+        let half = CHUNK_SIZE as f32 / 2.0;
+        let center_x = (chunk_pos.x * CHUNK_SIZE as i32) as f32 + half;
+        let center_z = (chunk_pos.z * CHUNK_SIZE as i32) as f32 + half;
+        (center_x - camera_pos.x).powi(2) + (center_z - camera_pos.z).powi(2)
+    };
+    chunks.retain(|(_, chunk)| chunk.num_transparent_indices > 0);
+    chunks.sort_by(|(a, _), (b, _)| distance_squared(b).total_cmp(&distance_squared(a)));
+    for (_, chunk) in chunks {
+        let start = chunk.num_opaque_indices;
+        render_pass.set_vertex_buffer(0, chunk.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(chunk.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(start..start + chunk.num_transparent_indices, 0, 0..1);
+    }
+}
+
+/// World-space box around a whole chunk column
+fn chunk_aabb(chunk_pos: ChunkPos) -> Aabb {
+    let min_x = (chunk_pos.x * CHUNK_SIZE as i32) as f32;
+    let min_z = (chunk_pos.z * CHUNK_SIZE as i32) as f32;
+    Aabb {
+        min: Point3::new(min_x, 0.0, min_z),
+        max: Point3::new(
+            min_x + CHUNK_SIZE as f32,
+            WORLD_HEIGHT as f32,
+            min_z + CHUNK_SIZE as f32,
+        ),
+    }
+}
+
+/// Sort by squared distance from the camera's chunk, nearest first. The sort is stable,
+/// so chunks at the same distance keep their order.
+fn sort_nearest_first<T>(
+    items: &mut [T],
+    chunk_pos: impl Fn(&T) -> ChunkPos,
+    (camera_chunk_x, camera_chunk_z): (i32, i32),
+) {
+    items.sort_by_key(|item| {
+        let pos = chunk_pos(item);
+        let dx = pos.x - camera_chunk_x;
+        let dz = pos.z - camera_chunk_z;
+        dx * dx + dz * dz
+    });
+}
+
+/// Remember that a chunk-local block was set by the player
+fn record_edit(
+    block_edits: &mut HashMap<ChunkPos, BlockEdits>,
+    chunk_pos: ChunkPos,
+    (x, y, z): (usize, usize, usize),
+    block_type: BlockType,
+) {
+    block_edits
+        .entry(chunk_pos)
+        .or_default()
+        .insert((x as u8, z as u8, y as u16), block_type);
+}
+
+/// Largest render distance (in rings of chunks around the camera, up to `max_distance`)
+/// whose chunks fit in `budget` bytes. Chunks that aren't loaded are counted at the
+/// average size of the loaded ones. The camera's own chunk is always kept.
+fn distance_within_budget(
+    chunk_bytes: impl Iterator<Item = (ChunkPos, u64)>,
+    camera_chunk: (i32, i32),
+    budget: u64,
+    max_distance: i32,
+) -> i32 {
+    let mut ring_bytes = vec![0u64; max_distance as usize + 1];
+    let mut ring_chunks = vec![0u64; max_distance as usize + 1];
+    let (mut loaded_bytes, mut loaded_chunks) = (0, 0);
+    for (chunk_pos, bytes) in chunk_bytes {
+        let ring = (chunk_pos.x - camera_chunk.0)
+            .abs()
+            .max((chunk_pos.z - camera_chunk.1).abs());
+        if ring <= max_distance {
+            ring_bytes[ring as usize] += bytes;
+            ring_chunks[ring as usize] += 1;
+        }
+        loaded_bytes += bytes;
+        loaded_chunks += 1;
+    }
+    let average_bytes = loaded_bytes.checked_div(loaded_chunks).unwrap_or(0);
+
+    let mut total = ring_bytes[0];
+    for ring in 1..=max_distance {
+        let missing_chunks = (8 * ring as u64).saturating_sub(ring_chunks[ring as usize]);
+        total += ring_bytes[ring as usize] + missing_chunks * average_bytes;
+        if total > budget {
+            return ring - 1;
+        }
+    }
+    max_distance
+}
+
+/// Neighbors whose meshes can see a block at chunk-local (`local_x`, `local_z`) of
+/// `chunk_pos`: one across each border the block touches, plus the diagonal one when it
+/// sits in a corner. Blocks away from the borders only affect their own chunk.
+fn boundary_neighbors(chunk_pos: ChunkPos, local_x: i32, local_z: i32) -> Vec<ChunkPos> {
+    let side = |local: i32| match local {
+        0 => -1,
+        local if local == CHUNK_SIZE as i32 - 1 => 1,
+        _ => 0,
+    };
+    let (dx, dz) = (side(local_x), side(local_z));
+
+    let mut offsets = Vec::new();
+    if dx != 0 {
+        offsets.push((dx, 0));
+    }
+    if dz != 0 {
+        offsets.push((0, dz));
+    }
+    if dx != 0 && dz != 0 {
+        offsets.push((dx, dz));
+    }
+    offsets
+        .into_iter()
+        .map(|(dx, dz)| ChunkPos {
+            x: chunk_pos.x + dx,
+            z: chunk_pos.z + dz,
+        })
+        .collect()
+}
+
+/// A chunk and the eight around it. Block light fades out within a chunk's width, so
+/// these are the only chunks whose light sources can reach into it.
+fn chunk_neighborhood(chunk_pos: ChunkPos) -> [ChunkPos; 9] {
+    let mut neighborhood = [chunk_pos; 9];
+    for (i, neighbor_pos) in neighborhood.iter_mut().enumerate() {
+        neighbor_pos.x += i as i32 % 3 - 1;
+        neighbor_pos.z += i as i32 / 3 - 1;
+    }
+    neighborhood
+}
+
+impl VoxelQuery for World {
+    fn get_block(&self, x: i32, y: i32, z: i32) -> BlockType {
+        self.get_block_type(x, y, z).unwrap_or(BlockType::Air)
+    }
+
+    fn is_solid(&self, x: i32, y: i32, z: i32) -> bool {
+        self.is_block_solid(x, y, z)
+    }
+}
+
+#[cfg(test)]
+impl World {
+    /// Write a block straight into the block cache, creating an empty chunk if needed
+    pub fn set_block_for_test(
+        &mut self,
+        world_x: i32,
+        world_y: i32,
+        world_z: i32,
+        block_type: BlockType,
+    ) {
+        let chunk_pos = ChunkPos {
+            x: world_x.div_euclid(CHUNK_SIZE as i32),
+            z: world_z.div_euclid(CHUNK_SIZE as i32),
+        };
+        let chunk_blocks = self.chunk_blocks.entry(chunk_pos).or_default();
+        let block_x = world_x.rem_euclid(CHUNK_SIZE as i32) as usize;
+        let block_z = world_z.rem_euclid(CHUNK_SIZE as i32) as usize;
+        chunk_blocks.set(block_x, world_y as usize, block_z, block_type);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::voxel_query::find_ground_level;
+
+    /// The shipped biome configs with every biome 20 blocks higher, loaded from a file the
+    /// way F5 does
+    fn raised_biome_manager(name: &str) -> BiomeManager {
+        let mut configs: toml::Table =
+            toml::from_str(&fs::read_to_string("biome.toml").unwrap()).unwrap();
+        for (_, config) in configs.iter_mut() {
+            let base_height = &mut config["base_height"];
+            *base_height = toml::Value::Integer(base_height.as_integer().unwrap() + 20);
+        }
+        let path = std::env::temp_dir().join(format!("rustcraft_biome_{}.toml", name));
+        fs::write(&path, toml::to_string(&configs).unwrap()).unwrap();
+        let mut biome_manager = BiomeManager::new();
+        biome_manager.reload_from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        biome_manager
+    }
+
+    #[test]
+    fn test_reloaded_biome_config_shapes_regenerated_chunks() {
+        let mut world = World::new();
+        let chunk_pos = ChunkPos { x: 0, z: 0 };
+        world.load_chunk_blocks(
+            chunk_pos,
+            &BiomeManager::load_from_file("biome.toml").unwrap(),
+        );
+        let ground = |world: &World| find_ground_level(world, 5, 5).unwrap();
+        let before = ground(&world);
+
+        world.clear_all_chunks();
+        world.load_chunk_blocks(chunk_pos, &raised_biome_manager("reload"));
+        assert!(ground(&world) >= before + 15);
+    }
+
+    #[test]
+    fn test_glowing_block_lights_across_chunk_borders() {
+        let mut world = World::new();
+        world.set_terrain_kind(TerrainKind::SuperFlat {
+            layers: vec![(BlockType::Stone, 4)],
+        });
+        let biome_manager = BiomeManager::new();
+        let (chunk, next_chunk) = (ChunkPos { x: 0, z: 0 }, ChunkPos { x: 1, z: 0 });
+        world.load_chunk_blocks(chunk, &biome_manager);
+        world.load_chunk_blocks(next_chunk, &biome_manager);
+
+        // Glowstone on the ground two blocks from the next chunk
+        assert!(world.add_block_data(14, 4, 5, BlockType::Glowstone));
+        assert_eq!(
+            world.relight_around([chunk]),
+            HashSet::from([chunk, next_chunk])
+        );
+        assert_eq!(world.block_light_at(14, 4, 5), 15);
+        assert_eq!(world.block_light_at(14, 5, 5), 14);
+        assert_eq!(world.block_light_at(17, 4, 5), 12);
+        assert_eq!(world.block_light_at(14, 3, 5), 0);
+
+        // The ground next door is lit by the level of the air above it
+        let mesh = world.generate_mesh_from_blocks(next_chunk, &world.chunk_blocks[&next_chunk]);
+        let ground_top = mesh
+            .vertices
+            .chunks(4)
+            .find(|face| {
+                face.iter().all(|v| {
+                    v.normal == [0.0, 1.0, 0.0]
+                        && (17.0..=18.0).contains(&v.position[0])
+                        && (5.0..=6.0).contains(&v.position[2])
+                })
+            })
+            .unwrap();
+        assert!(ground_top.iter().all(|v| v.block_light == 12.0 / 15.0));
+
+        // Walled in, the light no longer reaches the next chunk
+        for [x, y, z] in [[13, 4, 5], [15, 4, 5], [14, 4, 4], [14, 4, 6], [14, 5, 5]] {
+            assert!(world.add_block_data(x, y, z, BlockType::Stone));
+        }
+        assert!(world.relight_around([chunk]).contains(&next_chunk));
+        assert_eq!(world.block_light_at(17, 4, 5), 0);
+
+        // Without the glowstone the inside goes dark too
+        world.remove_block_data(14, 4, 5).unwrap();
+        assert_eq!(world.relight_around([chunk]), HashSet::from([chunk]));
+        assert_eq!(world.block_light_at(14, 4, 5), 0);
+    }
+
+    /// Device on the first adapter available, which may be a software one. None on
+    /// machines without any.
+    fn test_device() -> Option<wgpu::Device> {
+        let instance = wgpu::Instance::default();
+        let adapter = pollster::block_on(instance.request_adapter(&Default::default()))?;
+        let (device, _queue) =
+            pollster::block_on(adapter.request_device(&Default::default(), None)).ok()?;
+        Some(device)
+    }
+
+    #[test]
+    fn test_in_place_regeneration_keeps_edits_and_swaps_meshes_at_once() {
+        let Some(device) = test_device() else {
+            eprintln!("No graphics adapter, skipping");
+            return;
+        };
+        let mut world = World::new();
+        let chunk_pos = ChunkPos { x: 0, z: 0 };
+        world.load_chunk_blocks(
+            chunk_pos,
+            &BiomeManager::load_from_file("biome.toml").unwrap(),
+        );
+        let ground = find_ground_level(&world, 5, 5).unwrap();
+        let neighbor_ground = find_ground_level(&world, 6, 6).unwrap();
+        world.remove_block_data(5, ground - 1, 5).unwrap();
+        world.update_chunk_mesh(chunk_pos, &device);
+        let old_buffer = world.chunks[&chunk_pos].vertex_buffer.global_id();
+
+        world.regenerate_loaded_chunks(&device, &raised_biome_manager("in_place"));
+
+        // New terrain with the player's hole still in it
+        assert!(find_ground_level(&world, 6, 6).unwrap() >= neighbor_ground + 15);
+        assert_eq!(world.get_block_type(5, ground - 1, 5), Some(BlockType::Air));
+        assert_eq!(world.stats().chunks_generated, 2);
+        // Its mesh is already on the GPU, not waiting behind the upload cap
+        assert!(world.pending_meshes.is_empty());
+        assert_ne!(
+            world.chunks[&chunk_pos].vertex_buffer.global_id(),
+            old_buffer
+        );
+    }
+
+    #[test]
+    fn test_describe_position_matches_terrain_sample() {
+        let mut world = World::new();
+        let biome_manager = BiomeManager::new();
+        world.load_chunk_blocks(ChunkPos { x: 0, z: 0 }, &biome_manager);
+
+        let sample = world.terrain.sample(3, 4, &biome_manager);
+        let surface = world
+            .describe_position(3, sample.height as i32 - 1, 4)
+            .unwrap();
+        assert_eq!(surface.block_type, sample.surface_block);
+        assert!(surface.is_solid);
+
+        let sky = world.describe_position(3, 200, 4).unwrap();
+        assert_eq!((sky.name, sky.is_solid), ("Air", false));
+        assert_eq!(world.describe_position(3, -1, 4), None);
+        assert_eq!(world.describe_position(3, 10, CHUNK_SIZE as i32), None);
+    }
+
+    #[test]
+    fn test_obj_export_culls_hidden_faces() {
+        let mut world = World::new();
+        // Two stone blocks side by side across a chunk border, glass on top of one
+        world.set_block_for_test(-1, 10, 0, BlockType::Stone);
+        world.set_block_for_test(0, 10, 0, BlockType::Stone);
+        world.set_block_for_test(0, 11, 0, BlockType::Glass);
+        // And a lone ore block, whose name has a space in it
+        world.set_block_for_test(-5, 20, 5, BlockType::Coal);
+
+        let region = (ChunkPos { x: -1, z: 0 }, ChunkPos { x: 0, z: 0 });
+        let (obj, mtl) = world.obj_contents(region, "export.mtl");
+
+        let count = |prefix: &str| obj.lines().filter(|l| l.starts_with(prefix)).count();
+        // 5 faces on each block: the stones hide each other and the stone hides the
+        // bottom of the glass, but the stone's top shows through the glass
+        assert_eq!(count("f "), 15 + 6);
+        assert_eq!(count("v "), (15 + 6) * 4);
+        assert_eq!(count("vn "), 6);
+        assert!(obj.starts_with("# Exported from rustcraft\nmtllib export.mtl\nvn 0 0 1\n"));
+        assert!(obj.contains("usemtl Glass\n") && obj.contains("usemtl Stone\n"));
+        assert!(obj.contains("usemtl Coal_Ore\n"));
+        assert!(mtl.contains("newmtl Stone\nKd 0.5 0.5 0.5\n"));
+        assert!(mtl.contains("newmtl Glass\n"));
+        assert!(mtl.contains("newmtl Coal_Ore\n"));
+
+        // Only the chunk at x = 0
+        let (obj, _) = world.obj_contents((region.1, region.1), "export.mtl");
+        assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 10);
+    }
+
+    #[test]
+    fn test_same_seed_gives_the_same_world() {
+        let biome_manager = BiomeManager::new();
+        let a = World::with_seed(123_456_789);
+        let b = World::with_seed(123_456_789);
+        let other = World::with_seed(987_654_321);
+
+        let samples: Vec<(i32, i32)> = (-5..5).map(|i| (i * 97 - 13, i * 61 + 7)).collect();
+        let heights = |world: &World| -> Vec<usize> {
+            samples
+                .iter()
+                .map(|&(x, z)| world.terrain.height_at(x, z, &biome_manager))
+                .collect()
+        };
+        assert_eq!(heights(&a), heights(&b));
+        assert_ne!(heights(&a), heights(&other));
+
+        // Structures land in the same places too
+        let chunk_pos = ChunkPos { x: 1, z: -2 };
+        let blocks = |world: &World| {
+            world
+                .chunk_generator
+                .generate_chunk_blocks(chunk_pos, &world.terrain, &biome_manager)
+        };
+        let (blocks_a, blocks_b) = (blocks(&a), blocks(&b));
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                for y in 0..WORLD_HEIGHT {
+                    assert_eq!(blocks_a.get(x, y, z), blocks_b.get(x, y, z));
+                    assert_eq!(
+                        blocks_a.is_structure_block(x, y, z),
+                        blocks_b.is_structure_block(x, y, z)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_chunks_ahead_load_before_the_sides() {
+        // Walking from chunk (0, 0) to (0, 1) with render distance 2 uncovers the row at
+        // z = 3, listed here in raster order
+        let mut row: Vec<ChunkPos> = (-2..=2).map(|x| ChunkPos { x, z: 3 }).collect();
+        sort_nearest_first(&mut row, |pos| *pos, (0, 1));
+        let xs: Vec<i32> = row.iter().map(|pos| pos.x).collect();
+        assert_eq!(xs, vec![0, -1, 1, -2, 2]);
+
+        // Everything missing around a fresh world starts at the camera's own chunk
+        let mut world = World::new();
+        world.set_render_distance(2);
+        world.set_max_chunks_per_update(Some(5));
+        let missing = world.missing_chunks(4, -7);
+        assert_eq!(missing[0], ChunkPos { x: 4, z: -7 });
+        assert!(missing[1..]
+            .iter()
+            .all(|pos| (pos.x - 4).abs() + (pos.z + 7).abs() == 1));
+    }
+
+    #[test]
+    fn test_uploads_are_capped_and_nearest_first() {
+        let mut world = World::new();
+        for x in [5, -1, 3, 0, 2] {
+            world.pending_meshes.insert(
+                ChunkPos { x, z: 0 },
+                ChunkData {
+                    vertices: Vec::new(),
+                    indices: Vec::new(),
+                    num_opaque_indices: 0,
+                },
+            );
+        }
+        let uploaded_xs = |uploads: Vec<(ChunkPos, ChunkData)>| -> Vec<i32> {
+            uploads.iter().map(|(pos, _)| pos.x).collect()
+        };
+
+        let first = world.next_uploads((0, 0));
+        assert_eq!(first.len(), MAX_CHUNK_UPLOADS_PER_FRAME);
+        let mut first = uploaded_xs(first);
+        first.sort();
+        assert_eq!(first, vec![-1, 0]);
+        assert_eq!(world.pending_meshes.len(), 3);
+        // Queued chunks aren't generated again
+        assert!(!world
+            .missing_chunks(0, 0)
+            .contains(&ChunkPos { x: 3, z: 0 }));
+
+        world.set_max_chunk_uploads_per_frame(None);
+        assert_eq!(uploaded_xs(world.next_uploads((0, 0))), vec![2, 3, 5]);
+        assert!(world.pending_meshes.is_empty());
+    }
+
+    #[test]
+    fn test_water_is_not_solid_for_collision() {
+        let mut world = World::new();
+        world.set_block_for_test(0, 10, 0, BlockType::Water);
+
+        assert!(!world.is_block_solid(0, 10, 0));
+        // Still visible to targeting
+        assert_eq!(world.get_block_type(0, 10, 0), Some(BlockType::Water));
+    }
+
+    #[test]
+    fn test_stone_is_solid_for_collision() {
+        let mut world = World::new();
+        world.set_block_for_test(0, 10, 0, BlockType::Stone);
+
+        assert!(world.is_block_solid(0, 10, 0));
+        assert!(!world.is_block_solid(0, 11, 0));
+    }
+
+    #[test]
+    fn test_clear_region_removes_blocks_and_reports_them() {
+        let mut world = World::new();
+        // Region straddles the boundary between chunks (-1, 0) and (0, 0)
+        world.set_block_for_test(-1, 10, 3, BlockType::Stone);
+        world.set_block_for_test(0, 10, 3, BlockType::Dirt);
+        world.set_block_for_test(1, 11, 4, BlockType::Planks);
+        // Just outside the region
+        world.set_block_for_test(2, 10, 3, BlockType::Glass);
+
+        let (mut removed, dirty_chunks) = world.clear_region_blocks([1, 11, 4], [-1, 10, 3]);
+        removed.sort_by_key(|(pos, _)| *pos);
+
+        assert_eq!(
+            removed,
+            vec![
+                ([-1, 10, 3], BlockType::Stone),
+                ([0, 10, 3], BlockType::Dirt),
+                ([1, 11, 4], BlockType::Planks),
+            ]
+        );
+        for (pos, _) in &removed {
+            assert_eq!(
+                world.get_block_type(pos[0], pos[1], pos[2]),
+                Some(BlockType::Air)
+            );
+        }
+        assert_eq!(world.get_block_type(2, 10, 3), Some(BlockType::Glass));
+        assert!(dirty_chunks.contains(&ChunkPos { x: -1, z: 0 }));
+        assert!(dirty_chunks.contains(&ChunkPos { x: 0, z: 0 }));
+    }
+
+    #[test]
+    fn test_break_region_leaves_the_bottom_layer() {
+        let mut world = World::new();
+        world.set_block_for_test(2, 0, 2, BlockType::Stone);
+        world.set_block_for_test(2, 1, 2, BlockType::Stone);
+
+        let removed = world.break_region([0, -5, 0], [4, 5, 4]);
+
+        assert_eq!(removed, vec![([2, 1, 2], BlockType::Stone)]);
+        assert_eq!(world.get_block_type(2, 0, 2), Some(BlockType::Stone));
+    }
+
+    #[test]
+    fn test_placing_and_breaking_update_stats() {
+        let mut world = World::new();
+        world.set_block_for_test(0, 10, 0, BlockType::Stone);
+
+        assert!(world.add_block_data(0, 11, 0, BlockType::Planks));
+        // Occupied, so nothing is placed
+        assert!(!world.add_block_data(0, 10, 0, BlockType::Planks));
+        assert_eq!(world.remove_block_data(0, 10, 0), Some(BlockType::Stone));
+        // Already air, so nothing is broken
+        assert_eq!(world.remove_block_data(0, 10, 0), None);
+
+        let stats = world.stats();
+        assert_eq!(stats.blocks_placed, 1);
+        assert_eq!(stats.blocks_broken, 1);
+        assert_eq!(stats.chunks_generated, 0);
+    }
+
+    #[test]
+    fn test_paused_simulation_holds_falling_blocks() {
+        let Some(device) = test_device() else {
+            eprintln!("No graphics adapter, skipping");
+            return;
+        };
+        let biome_manager = BiomeManager::new();
+        let camera_pos = Point3::new(3.5, 50.0, 3.5);
+        let mut world = World::new();
+        world.set_render_distance(0);
+        world.set_block_for_test(3, 10, 3, BlockType::Stone);
+        world.set_block_for_test(3, 11, 3, BlockType::Sand);
+        world.update_chunk_mesh(ChunkPos { x: 0, z: 0 }, &device);
+        assert_eq!(world.remove_block(3, 10, 3), Some(BlockType::Stone));
+
+        world.set_simulation_paused(true);
+        world.last_fall_step = Instant::now() - FALL_STEP_INTERVAL;
+        world.update(camera_pos, &device, &biome_manager);
+        assert_eq!(world.get_block_type(3, 11, 3), Some(BlockType::Sand));
+
+        world.set_simulation_paused(false);
+        world.update(camera_pos, &device, &biome_manager);
+        assert_eq!(world.get_block_type(3, 10, 3), Some(BlockType::Sand));
+        assert_eq!(world.get_block_type(3, 11, 3), Some(BlockType::Air));
+    }
+
+    #[test]
+    fn test_sand_tower_collapses_when_its_base_is_broken() {
+        let mut world = World::new();
+        world.set_block_for_test(15, 9, 4, BlockType::Stone);
+        world.set_block_for_test(15, 10, 4, BlockType::Dirt);
+        for y in 11..15 {
+            world.set_block_for_test(15, y, 4, BlockType::Sand);
+        }
+
+        assert_eq!(world.remove_block_data(15, 10, 4), Some(BlockType::Dirt));
+
+        // Each step drops the whole stack by one; the column sits on a chunk border
+        let dirty_chunks = world.step_falling_blocks();
+        assert!(dirty_chunks.contains(&ChunkPos { x: 0, z: 0 }));
+        assert!(dirty_chunks.contains(&ChunkPos { x: 1, z: 0 }));
+        assert_eq!(world.get_block_type(15, 10, 4), Some(BlockType::Sand));
+        assert_eq!(world.get_block_type(15, 14, 4), Some(BlockType::Air));
+
+        // Now it rests on the stone, so nothing is left to do
+        assert!(world.step_falling_blocks().is_empty());
+        assert!(world.falling_columns.is_empty());
+        for y in 10..14 {
+            assert_eq!(world.get_block_type(15, y, 4), Some(BlockType::Sand));
+        }
+        assert_eq!(world.get_block_type(15, 14, 4), Some(BlockType::Air));
+    }
+
+    #[test]
+    fn test_edits_survive_chunk_unload() {
+        let biome_manager = BiomeManager::new();
+        let mut world = World::new();
+        let chunk_pos = ChunkPos { x: 0, z: 0 };
+        world.load_chunk_blocks(chunk_pos, &biome_manager);
+
+        assert!(world.remove_block_data(3, 0, 5).is_some());
+        assert!(world.add_block_data(3, 200, 5, BlockType::Glass));
+
+        world.unload_chunk(chunk_pos);
+        assert_eq!(world.get_block_type(3, 0, 5), None);
+        world.load_chunk_blocks(chunk_pos, &biome_manager);
+
+        assert_eq!(world.get_block_type(3, 0, 5), Some(BlockType::Air));
+        assert_eq!(world.get_block_type(3, 200, 5), Some(BlockType::Glass));
+    }
+
+    #[test]
+    fn test_edits_only_queue_chunks_sharing_their_border() {
+        let biome_manager = BiomeManager::new();
+        let mut world = World::new();
+        for x in -1..=1 {
+            for z in -1..=1 {
+                world.load_chunk_blocks(ChunkPos { x, z }, &biome_manager);
+            }
+        }
+
+        // Inside the chunk: nothing else can see it
+        assert!(world.add_block(5, 200, 5, BlockType::Stone));
+        assert_eq!(world.dirty_meshes, HashSet::from([ChunkPos { x: 0, z: 0 }]));
+
+        // Edits queued in the same frame coalesce, one per chunk
+        assert!(world.add_block(6, 200, 5, BlockType::Stone));
+        assert!(world.remove_block(0, 0, 5).is_some());
+        assert!(world.add_block(15, 200, 15, BlockType::Stone));
+        let expected = [(0, 0), (-1, 0), (1, 0), (0, 1), (1, 1)].map(|(x, z)| ChunkPos { x, z });
+        assert_eq!(world.dirty_meshes, HashSet::from(expected));
+    }
+
+    #[test]
+    fn test_edited_chunks_go_back_to_realtime_ao_after_a_bake() {
+        let mut world = World::new();
+        let chunk_pos = ChunkPos { x: 0, z: 0 };
+        for x in 0..CHUNK_SIZE as i32 {
+            for z in 0..CHUNK_SIZE as i32 {
+                world.set_block_for_test(x, 0, z, BlockType::Stone);
+            }
+            world.set_block_for_test(8, 1, x, BlockType::Stone);
+        }
+        // Brightest top-face corner at (7, 1, 5). The wall at x = 8 only reaches it through
+        // the block at x = 6, two steps away.
+        let corner_ao = |mesh: &ChunkData| {
+            mesh.vertices
+                .iter()
+                .filter(|vertex| vertex.position == [7.0, 1.0, 5.0] && vertex.normal[1] == 1.0)
+                .map(|vertex| vertex.ao)
+                .fold(0.0, f32::max)
+        };
+
+        world.bake_high_quality_ao();
+        let baked = world.pending_meshes.remove(&chunk_pos).unwrap();
+        assert!(corner_ao(&baked) < 1.0);
+
+        assert!(world.add_block(3, 1, 3, BlockType::Stone));
+        let remeshed = world.mesh_dirty_chunks();
+        let (_, realtime) = remeshed.iter().find(|(pos, _)| *pos == chunk_pos).unwrap();
+        assert_eq!(corner_ao(realtime), 1.0);
+    }
+
+    #[test]
+    fn test_regenerating_structures_keeps_terrain() {
+        let biome_manager = BiomeManager::new();
+        let mut world = World::new();
+        // Load a patch big enough to contain at least one structure
+        for x in -2..2 {
+            for z in -2..2 {
+                world.load_chunk_blocks(ChunkPos { x, z }, &biome_manager);
+            }
+        }
+
+        let before: HashMap<ChunkPos, ChunkBlocks> = world
+            .chunk_blocks
+            .iter()
+            .map(|(chunk_pos, chunk_blocks)| (*chunk_pos, chunk_blocks.clone()))
+            .collect();
+
+        // Knock out a structure block; regenerating should put it back
+        let (edited_pos, edited) = before
+            .iter()
+            .find_map(|(chunk_pos, chunk_blocks)| {
+                (0..CHUNK_SIZE)
+                    .flat_map(|x| {
+                        (0..WORLD_HEIGHT).flat_map(move |y| (0..CHUNK_SIZE).map(move |z| (x, y, z)))
+                    })
+                    .find(|&(x, y, z)| chunk_blocks.is_structure_block(x, y, z))
+                    .map(|local| (*chunk_pos, local))
+            })
+            .expect("no structures generated in the test area");
+        world.chunk_blocks.get_mut(&edited_pos).unwrap().set(
+            edited.0,
+            edited.1,
+            edited.2,
+            BlockType::Air,
+        );
+
+        world.regenerate_structure_blocks(&biome_manager);
+
+        for (chunk_pos, old_blocks) in &before {
+            let new_blocks = &world.chunk_blocks[chunk_pos];
+            for x in 0..CHUNK_SIZE {
+                for y in 0..WORLD_HEIGHT {
+                    for z in 0..CHUNK_SIZE {
+                        assert_eq!(new_blocks.get(x, y, z), old_blocks.get(x, y, z));
+                        assert_eq!(
+                            new_blocks.is_structure_block(x, y, z),
+                            old_blocks.is_structure_block(x, y, z)
+                        );
+                    }
+                }
+            }
+
+            // With structures stripped, what's left is the untouched terrain
+            let mut old_terrain = old_blocks.clone();
+            let mut new_terrain = new_blocks.clone();
+            old_terrain.clear_structure_blocks();
+            new_terrain.clear_structure_blocks();
+            for x in 0..CHUNK_SIZE {
+                for y in 0..WORLD_HEIGHT {
+                    for z in 0..CHUNK_SIZE {
+                        assert_eq!(new_terrain.get(x, y, z), old_terrain.get(x, y, z));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Lays glass on the first free block of every column, where structures also stand
+    struct GlassCarpetModifier;
+
+    impl TerrainModifier for GlassCarpetModifier {
+        fn modify(
+            &self,
+            chunk_blocks: &mut ChunkBlocks,
+            origin_x: i32,
+            origin_z: i32,
+            terrain: &Terrain,
+            biome_manager: &BiomeManager,
+        ) {
+            for x in 0..CHUNK_SIZE {
+                for z in 0..CHUNK_SIZE {
+                    let y = terrain.filled_height_at(
+                        origin_x + x as i32,
+                        origin_z + z as i32,
+                        biome_manager,
+                    );
+                    if y < WORLD_HEIGHT {
+                        chunk_blocks.set(x, y, z, BlockType::Glass);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_regenerating_structures_keeps_modifier_blocks_and_edits() {
+        let biome_manager = BiomeManager::new();
+        let mut world = World::new();
+        world.register_terrain_modifier(Box::new(GlassCarpetModifier));
+        for x in -2..2 {
+            for z in -2..2 {
+                world.load_chunk_blocks(ChunkPos { x, z }, &biome_manager);
+            }
+        }
+
+        // The player breaks a block of some structure
+        let (edited_pos, edited) = world
+            .chunk_blocks
+            .iter()
+            .find_map(|(chunk_pos, chunk_blocks)| {
+                (0..CHUNK_SIZE)
+                    .flat_map(|x| {
+                        (0..WORLD_HEIGHT).flat_map(move |y| (0..CHUNK_SIZE).map(move |z| (x, y, z)))
+                    })
+                    .find(|&(x, y, z)| {
+                        chunk_blocks.is_structure_block(x, y, z)
+                            && chunk_blocks.get(x, y, z) != BlockType::Air
+                    })
+                    .map(|local| (*chunk_pos, local))
+            })
+            .expect("no structures generated in the test area");
+        let world_x = edited_pos.x * CHUNK_SIZE as i32 + edited.0 as i32;
+        let world_z = edited_pos.z * CHUNK_SIZE as i32 + edited.2 as i32;
+        assert!(world
+            .remove_block(world_x, edited.1 as i32, world_z)
+            .is_some());
+        let before: HashMap<ChunkPos, ChunkBlocks> = world
+            .chunk_blocks
+            .iter()
+            .map(|(chunk_pos, chunk_blocks)| (*chunk_pos, chunk_blocks.clone()))
+            .collect();
+
+        world.regenerate_structure_blocks(&biome_manager);
+
+        // Glass under the structures comes back with them, and the broken block stays broken
+        for (chunk_pos, old_blocks) in &before {
+            let new_blocks = &world.chunk_blocks[chunk_pos];
+            for x in 0..CHUNK_SIZE {
+                for y in 0..WORLD_HEIGHT {
+                    for z in 0..CHUNK_SIZE {
+                        assert_eq!(new_blocks.get(x, y, z), old_blocks.get(x, y, z));
+                    }
+                }
+            }
+        }
+        assert_eq!(
+            world.get_block_type(world_x, edited.1 as i32, world_z),
+            Some(BlockType::Air)
+        );
+    }
+
+    #[test]
+    fn test_faces_between_loaded_chunks_are_culled() {
+        let mut world = World::new();
+        // Two stones touching across the border between chunks (0, 0) and (1, 0)
+        world.set_block_for_test(15, 10, 0, BlockType::Stone);
+        world.set_block_for_test(16, 10, 0, BlockType::Stone);
+        let chunk_pos = ChunkPos { x: 0, z: 0 };
+
+        let mesh = world.generate_mesh_from_blocks(chunk_pos, &world.chunk_blocks[&chunk_pos]);
+        // Five faces of four vertices; the one against the neighbor is hidden
+        assert_eq!(mesh.vertices.len(), 5 * 4);
+
+        // The border face stays while the neighbor isn't loaded
+        world.chunk_blocks.remove(&ChunkPos { x: 1, z: 0 });
+        let mesh = world.generate_mesh_from_blocks(chunk_pos, &world.chunk_blocks[&chunk_pos]);
+        assert_eq!(mesh.vertices.len(), 6 * 4);
+    }
+
+    #[test]
+    fn test_border_faces_against_unloaded_terrain_are_culled() {
+        let biome_manager = BiomeManager::new();
+        let mut world = World::new();
+        let chunk_pos = ChunkPos { x: 0, z: 0 };
+        world.load_chunk_blocks(chunk_pos, &biome_manager);
+        let border_faces = |world: &World| {
+            let mesh = world.generate_mesh_from_blocks(chunk_pos, &world.chunk_blocks[&chunk_pos]);
+            mesh.vertices
+                .iter()
+                .filter(|vertex| vertex.normal == [-1.0, 0.0, 0.0] && vertex.position[0] == 0.0)
+                .map(|vertex| vertex.position[1])
+                .collect::<Vec<f32>>()
+        };
+
+        // Only faces reaching above the neighbor's predicted terrain are left on the -X side
+        let predicted = border_faces(&world);
+        let lowest_ground = (0..CHUNK_SIZE as i32)
+            .map(|z| world.border_heights[&chunk_pos][&(-1, z)])
+            .min()
+            .unwrap();
+        assert!(lowest_ground > 0);
+        assert!(predicted.iter().all(|&y| y >= lowest_ground as f32 - 1.0));
+
+        // Edits in the neighbor may have dug into it, so it is treated as open again
+        world
+            .block_edits
+            .insert(ChunkPos { x: -1, z: 0 }, BlockEdits::default());
+        let with_edits = border_faces(&world);
+        assert!(with_edits.contains(&0.0));
+        assert!(with_edits.len() > predicted.len());
+    }
+
+    #[test]
+    fn test_render_distance_changes_missing_chunks() {
+        let mut world = World::new();
+        world.set_render_distance(2);
+        assert_eq!(world.missing_chunks(0, 0).len(), 25);
+
+        world.set_render_distance(6);
+        assert_eq!(world.missing_chunks(0, 0).len(), 13 * 13);
+        assert_eq!(world.render_distance(), 6);
+
+        // A memory budget starts again from the requested distance, not the default
+        world.set_memory_budget(Some(1024));
+        assert_eq!(world.render_distance(), 6);
+    }
+
+    #[test]
+    fn test_chunk_budget_generates_nearest_chunks_first() {
+        let mut world = World::new();
+        world.set_max_chunks_per_update(Some(9));
+
+        let missing = world.missing_chunks(5, -3);
+
+        assert_eq!(missing.len(), 9);
+        assert_eq!(missing[0], ChunkPos { x: 5, z: -3 });
+        // The first batch is the ring right around the camera
+        for chunk_pos in &missing {
+            assert!((chunk_pos.x - 5).abs() <= 1 && (chunk_pos.z + 3).abs() <= 1);
+        }
+        assert_eq!(world.load_progress(Point3::new(85.0, 70.0, -40.0)), 0.0);
+    }
+
+    #[test]
+    fn test_memory_budget_drops_farthest_chunks_first() {
+        // 100 bytes per chunk in a 5x5 area around chunk (2, 2)
+        let chunk_bytes = (0..5).flat_map(|x| (0..5).map(move |z| (ChunkPos { x, z }, 100)));
+
+        // Everything fits
+        assert_eq!(
+            distance_within_budget(chunk_bytes.clone(), (2, 2), 2500, 2),
+            2
+        );
+        // Rings that aren't loaded yet count at the average chunk size
+        assert_eq!(
+            distance_within_budget(chunk_bytes.clone(), (2, 2), 4900, 4),
+            3
+        );
+        // The inner 3x3 fits but the outer ring doesn't, so it goes first
+        assert_eq!(
+            distance_within_budget(chunk_bytes.clone(), (2, 2), 950, 4),
+            1
+        );
+        // Even a budget smaller than one chunk keeps the camera's chunk
+        assert_eq!(distance_within_budget(chunk_bytes, (2, 2), 10, 4), 0);
+    }
+
+    #[test]
+    fn test_render_distance_follows_the_memory_budget() {
+        let Some(device) = test_device() else {
+            eprintln!("No graphics adapter, skipping");
+            return;
+        };
+        let biome_manager = BiomeManager::new();
+        let camera_pos = Point3::new(8.0, 100.0, 8.0);
+        let mut world = World::new();
+        world.set_render_distance(2);
+        world.set_max_chunk_uploads_per_frame(None);
+        world.update(camera_pos, &device, &biome_manager);
+        assert_eq!(world.chunks.len(), 25);
+        let inner_bytes: u64 = world
+            .chunks
+            .iter()
+            .filter(|(pos, _)| pos.x.abs() <= 1 && pos.z.abs() <= 1)
+            .map(|(_, chunk)| chunk.gpu_bytes())
+            .sum();
+
+        // Room for the inner 3x3 only: the outer ring is unloaded
+        world.set_memory_budget(Some(inner_bytes));
+        world.update(camera_pos, &device, &biome_manager);
+        assert_eq!(world.render_distance(), 1);
+        assert_eq!(world.chunks.len(), 9);
+        assert!(world
+            .chunks
+            .keys()
+            .all(|pos| pos.x.abs() <= 1 && pos.z.abs() <= 1));
+
+        // Once there's room again the distance recovers, but not past the one asked for
+        world.memory_budget = Some(inner_bytes * 100);
+        world.update(camera_pos, &device, &biome_manager);
+        assert_eq!(world.render_distance(), 2);
+        world.update(camera_pos, &device, &biome_manager);
+        assert_eq!(world.chunks.len(), 25);
+    }
 }